@@ -0,0 +1,278 @@
+use fugue_bytes::endian::Endian;
+
+use crate::ArchitectureDef;
+
+// ELF e_machine values
+const EM_SPARC: u16 = 2;
+const EM_386: u16 = 3;
+const EM_68K: u16 = 4;
+const EM_MIPS: u16 = 8;
+const EM_MIPS_RS3_LE: u16 = 10;
+const EM_PPC: u16 = 20;
+const EM_PPC64: u16 = 21;
+const EM_ARM: u16 = 40;
+const EM_SH: u16 = 42;
+const EM_SPARCV9: u16 = 43;
+const EM_X86_64: u16 = 62;
+const EM_AVR: u16 = 83;
+const EM_AARCH64: u16 = 183;
+const EM_RISCV: u16 = 243;
+
+const EF_MIPS_MICROMIPS: u32 = 0x0200_0000;
+const EF_MIPS_ARCH: u32 = 0xf000_0000;
+const EF_MIPS_ARCH_32R6: u32 = 0x9000_0000;
+const EF_MIPS_ARCH_64R6: u32 = 0xa000_0000;
+
+// PE/COFF machine values
+const IMAGE_FILE_MACHINE_I386: u16 = 0x014c;
+const IMAGE_FILE_MACHINE_R4000: u16 = 0x0166;
+const IMAGE_FILE_MACHINE_ARM: u16 = 0x01c0;
+const IMAGE_FILE_MACHINE_THUMB: u16 = 0x01c2;
+const IMAGE_FILE_MACHINE_ARMNT: u16 = 0x01c4;
+const IMAGE_FILE_MACHINE_POWERPC: u16 = 0x01f0;
+const IMAGE_FILE_MACHINE_RISCV32: u16 = 0x5032;
+const IMAGE_FILE_MACHINE_RISCV64: u16 = 0x5064;
+const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+const IMAGE_FILE_MACHINE_ARM64: u16 = 0xaa64;
+
+// Mach-O cputype values
+const CPU_ARCH_ABI64: u32 = 0x0100_0000;
+const CPU_ARCH_ABI64_32: u32 = 0x0200_0000;
+const CPU_TYPE_X86: u32 = 7;
+const CPU_TYPE_ARM: u32 = 12;
+const CPU_TYPE_POWERPC: u32 = 18;
+
+const FAT_MAGIC: u32 = 0xcafe_babe;
+// Java class files share the fat Mach-O magic; their version field is
+// always much larger than any plausible number of slices.
+const FAT_MAX_SLICES: u32 = 32;
+
+/// Proposes architectures for the binary image `bytes` based on its ELF,
+/// PE, or Mach-O headers. Candidates are ordered from most to least
+/// likely; for fat Mach-O images, each slice contributes candidates in the
+/// order the slices are stored. The variants of the returned definitions
+/// are hints, and are best resolved against the available languages, e.g.,
+/// using `LanguageDB::find`.
+///
+/// Returns an empty vector if the format or machine is not recognised.
+pub fn detect(bytes: &[u8]) -> Vec<ArchitectureDef> {
+    let mut candidates = if bytes.starts_with(b"\x7fELF") {
+        detect_elf(bytes)
+    } else if bytes.starts_with(b"MZ") {
+        detect_pe(bytes)
+    } else {
+        detect_macho(bytes)
+    };
+
+    let mut seen = Vec::with_capacity(candidates.len());
+    candidates.retain(|def| {
+        if seen.contains(def) {
+            false
+        } else {
+            seen.push(def.clone());
+            true
+        }
+    });
+    candidates
+}
+
+fn read_u16(bytes: &[u8], offset: usize, endian: Endian) -> Option<u16> {
+    let raw = bytes.get(offset..offset.checked_add(2)?)?.try_into().ok()?;
+    Some(match endian {
+        Endian::Big => u16::from_be_bytes(raw),
+        Endian::Little => u16::from_le_bytes(raw),
+    })
+}
+
+fn read_u32(bytes: &[u8], offset: usize, endian: Endian) -> Option<u32> {
+    let raw = bytes.get(offset..offset.checked_add(4)?)?.try_into().ok()?;
+    Some(match endian {
+        Endian::Big => u32::from_be_bytes(raw),
+        Endian::Little => u32::from_le_bytes(raw),
+    })
+}
+
+fn def(processor: &str, endian: Endian, bits: usize, variant: &str) -> ArchitectureDef {
+    ArchitectureDef::new(processor, endian, bits, variant)
+}
+
+fn detect_elf(bytes: &[u8]) -> Vec<ArchitectureDef> {
+    let class = match bytes.get(4) {
+        Some(1) => 32,
+        Some(2) => 64,
+        _ => return Vec::new(),
+    };
+
+    let endian = match bytes.get(5) {
+        Some(1) => Endian::Little,
+        Some(2) => Endian::Big,
+        _ => return Vec::new(),
+    };
+
+    let machine = if let Some(machine) = read_u16(bytes, 18, endian) {
+        machine
+    } else {
+        return Vec::new();
+    };
+
+    let flags = read_u32(bytes, if class == 32 { 36 } else { 48 }, endian).unwrap_or(0);
+
+    elf_machine(machine, endian, class, flags)
+}
+
+pub(crate) fn elf_machine(
+    machine: u16,
+    endian: Endian,
+    class: usize,
+    flags: u32,
+) -> Vec<ArchitectureDef> {
+    match machine {
+        EM_386 => vec![def("x86", endian, 32, "default")],
+        EM_X86_64 => vec![
+            def("x86", endian, 64, "default"),
+            def("x86", endian, 32, "default"),
+        ],
+        EM_ARM => vec![
+            def("ARM", endian, 32, "v8"),
+            def("ARM", endian, 32, "v7"),
+        ],
+        EM_AARCH64 => vec![def("AARCH64", endian, 64, "v8A")],
+        EM_MIPS | EM_MIPS_RS3_LE => {
+            let variant = if flags & EF_MIPS_MICROMIPS != 0 {
+                "micro"
+            } else if matches!(flags & EF_MIPS_ARCH, EF_MIPS_ARCH_32R6 | EF_MIPS_ARCH_64R6) {
+                "R6"
+            } else {
+                "default"
+            };
+            vec![def("MIPS", endian, class, variant)]
+        }
+        EM_PPC => vec![def("PowerPC", endian, 32, "default")],
+        EM_PPC64 => vec![def("PowerPC", endian, 64, "default")],
+        EM_SPARC => vec![def("sparc", endian, 32, "default")],
+        EM_SPARCV9 => vec![def("sparc", endian, 64, "default")],
+        EM_68K => vec![def("68000", endian, 32, "default")],
+        EM_SH => vec![def("SuperH4", endian, 32, "default")],
+        EM_AVR => vec![def("avr8", endian, 16, "default")],
+        EM_RISCV => {
+            let variant = if class == 32 { "RV32GC" } else { "RV64GC" };
+            vec![def("RISCV", endian, class, variant)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn detect_pe(bytes: &[u8]) -> Vec<ArchitectureDef> {
+    let machine = read_u32(bytes, 0x3c, Endian::Little)
+        .map(|offset| offset as usize)
+        .filter(|&offset| bytes.get(offset..offset + 4) == Some(b"PE\0\0"))
+        .and_then(|offset| read_u16(bytes, offset + 4, Endian::Little));
+
+    let le = Endian::Little;
+    match machine {
+        Some(IMAGE_FILE_MACHINE_I386) => vec![def("x86", le, 32, "default")],
+        Some(IMAGE_FILE_MACHINE_AMD64) => vec![def("x86", le, 64, "default")],
+        Some(IMAGE_FILE_MACHINE_ARM) => vec![def("ARM", le, 32, "v8"), def("ARM", le, 32, "v7")],
+        Some(IMAGE_FILE_MACHINE_THUMB | IMAGE_FILE_MACHINE_ARMNT) => {
+            vec![def("ARM", le, 32, "v8T"), def("ARM", le, 32, "v8")]
+        }
+        Some(IMAGE_FILE_MACHINE_ARM64) => vec![def("AARCH64", le, 64, "v8A")],
+        Some(IMAGE_FILE_MACHINE_R4000) => vec![def("MIPS", le, 32, "default")],
+        Some(IMAGE_FILE_MACHINE_POWERPC) => vec![def("PowerPC", le, 32, "default")],
+        Some(IMAGE_FILE_MACHINE_RISCV32) => vec![def("RISCV", le, 32, "RV32GC")],
+        Some(IMAGE_FILE_MACHINE_RISCV64) => vec![def("RISCV", le, 64, "RV64GC")],
+        _ => Vec::new(),
+    }
+}
+
+fn detect_macho(bytes: &[u8]) -> Vec<ArchitectureDef> {
+    if let Some(FAT_MAGIC) = read_u32(bytes, 0, Endian::Big) {
+        let count = read_u32(bytes, 4, Endian::Big).unwrap_or(0);
+        if count == 0 || count > FAT_MAX_SLICES {
+            return Vec::new();
+        }
+
+        return (0..count as usize)
+            .filter_map(|i| read_u32(bytes, 8 + i * 20, Endian::Big))
+            .flat_map(|cputype| {
+                // slices do not record their byte order; only PowerPC
+                // images are big-endian
+                let endian = if cputype & !CPU_ARCH_ABI64 == CPU_TYPE_POWERPC {
+                    Endian::Big
+                } else {
+                    Endian::Little
+                };
+                macho_cputype(cputype, endian)
+            })
+            .collect();
+    }
+
+    let endian = match read_u32(bytes, 0, Endian::Big) {
+        Some(0xfeed_face | 0xfeed_facf) => Endian::Big,
+        Some(0xcefa_edfe | 0xcffa_edfe) => Endian::Little,
+        _ => return Vec::new(),
+    };
+
+    read_u32(bytes, 4, endian)
+        .map(|cputype| macho_cputype(cputype, endian))
+        .unwrap_or_default()
+}
+
+fn macho_cputype(cputype: u32, endian: Endian) -> Vec<ArchitectureDef> {
+    match cputype {
+        CPU_TYPE_X86 => vec![def("x86", endian, 32, "default")],
+        t if t == CPU_TYPE_X86 | CPU_ARCH_ABI64 => vec![def("x86", endian, 64, "default")],
+        CPU_TYPE_ARM => vec![def("ARM", endian, 32, "v8"), def("ARM", endian, 32, "v7")],
+        t if t == CPU_TYPE_ARM | CPU_ARCH_ABI64 => vec![def("AARCH64", endian, 64, "v8A")],
+        t if t == CPU_TYPE_ARM | CPU_ARCH_ABI64_32 => vec![def("AARCH64", endian, 32, "ilp32")],
+        CPU_TYPE_POWERPC => vec![def("PowerPC", endian, 32, "default")],
+        t if t == CPU_TYPE_POWERPC | CPU_ARCH_ABI64 => vec![def("PowerPC", endian, 64, "default")],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_detect_headers() {
+        let mut elf = vec![0u8; 64];
+        elf[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        elf[18..20].copy_from_slice(&EM_X86_64.to_le_bytes());
+        assert_eq!(detect(&elf)[0], "x86:LE:64:default".parse().unwrap());
+
+        let mut mips = vec![0u8; 52];
+        mips[..6].copy_from_slice(b"\x7fELF\x01\x02");
+        mips[18..20].copy_from_slice(&EM_MIPS.to_be_bytes());
+        mips[36..40].copy_from_slice(&EF_MIPS_ARCH_32R6.to_be_bytes());
+        assert_eq!(detect(&mips), vec!["MIPS:BE:32:R6".parse().unwrap()]);
+
+        let mut pe = vec![0u8; 0x80];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+        pe[0x44..0x46].copy_from_slice(&IMAGE_FILE_MACHINE_ARM64.to_le_bytes());
+        assert_eq!(detect(&pe), vec!["AARCH64:LE:64:v8A".parse().unwrap()]);
+
+        let mut fat = vec![0u8; 48];
+        fat[..8].copy_from_slice(&[0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 2]);
+        fat[8..12].copy_from_slice(&(CPU_TYPE_X86 | CPU_ARCH_ABI64).to_be_bytes());
+        fat[28..32].copy_from_slice(&(CPU_TYPE_ARM | CPU_ARCH_ABI64).to_be_bytes());
+        assert_eq!(
+            detect(&fat),
+            vec![
+                "x86:LE:64:default".parse().unwrap(),
+                "AARCH64:LE:64:v8A".parse().unwrap(),
+            ]
+        );
+
+        let mut watch = vec![0u8; 28];
+        watch[..4].copy_from_slice(&[0xce, 0xfa, 0xed, 0xfe]);
+        watch[4..8].copy_from_slice(&(CPU_TYPE_ARM | CPU_ARCH_ABI64_32).to_le_bytes());
+        assert_eq!(detect(&watch), vec!["AARCH64:LE:32:ilp32".parse().unwrap()]);
+
+        assert!(detect(b"\xca\xfe\xba\xbe\x00\x00\x00\x34").is_empty());
+        assert!(detect(b"#!/bin/sh").is_empty());
+    }
+}
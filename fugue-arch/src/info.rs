@@ -0,0 +1,167 @@
+use crate::ArchitectureDef;
+
+/// How floating-point arithmetic is usually performed on an architecture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FloatSupport {
+    Hardware,
+    Software,
+    Unknown,
+}
+
+/// Practical attributes of an architecture that are not captured by its
+/// definition; see [`ArchitectureDef::info`]. The attributes are hints
+/// based on the processor and variant, and the language's specifications
+/// should be preferred where they are available.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArchInfo {
+    address_bits: usize,
+    word_bits: usize,
+    delay_slots: bool,
+    float: FloatSupport,
+    default_context: Vec<(&'static str, u32)>,
+}
+
+impl ArchInfo {
+    /// The size of a pointer.
+    pub fn address_bits(&self) -> usize {
+        self.address_bits
+    }
+
+    /// The size of a general-purpose register.
+    pub fn word_bits(&self) -> usize {
+        self.word_bits
+    }
+
+    pub fn has_delay_slots(&self) -> bool {
+        self.delay_slots
+    }
+
+    pub fn float(&self) -> FloatSupport {
+        self.float
+    }
+
+    /// The context variables to set when decoding code at an entry point,
+    /// e.g., to select the default ISA mode.
+    pub fn default_context(&self) -> &[(&'static str, u32)] {
+        &self.default_context
+    }
+}
+
+impl ArchitectureDef {
+    /// Looks up practical attributes of the architecture.
+    pub fn info(&self) -> ArchInfo {
+        let processor = self.processor();
+        let variant = self.variant();
+        let is = |name: &str| processor.eq_ignore_ascii_case(name);
+
+        let mut info = ArchInfo {
+            address_bits: self.bits(),
+            word_bits: self.bits(),
+            delay_slots: false,
+            float: FloatSupport::Unknown,
+            default_context: Vec::new(),
+        };
+
+        if is("x86") || is("PowerPC") {
+            info.float = FloatSupport::Hardware;
+        } else if is("AARCH64") {
+            info.float = FloatSupport::Hardware;
+            // ILP32 code uses 32-bit pointers, but all 64 bits of each
+            // register
+            if variant.eq_ignore_ascii_case("ilp32") {
+                info.address_bits = 32;
+                info.word_bits = 64;
+            }
+        } else if is("ARM") {
+            let cortex = variant.eq_ignore_ascii_case("Cortex");
+
+            info.float = if cortex {
+                FloatSupport::Unknown
+            } else if variant.starts_with("v7") || variant.starts_with("v8") {
+                FloatSupport::Hardware
+            } else {
+                FloatSupport::Software
+            };
+
+            // M-profile processors only execute Thumb code
+            info.default_context = vec![("TMode", cortex as u32)];
+        } else if is("MIPS") {
+            info.delay_slots = true;
+            info.float = FloatSupport::Hardware;
+            if variant.eq_ignore_ascii_case("micro") {
+                info.default_context = vec![("ISA_MODE", 1)];
+            } else if variant.eq_ignore_ascii_case("64-32addr") {
+                info.address_bits = 32;
+            }
+        } else if is("sparc") || is("pa-risc") {
+            info.delay_slots = true;
+            info.float = FloatSupport::Hardware;
+        } else if is("SuperH") || is("SuperH4") {
+            info.delay_slots = true;
+            info.float = if is("SuperH4") || variant.eq_ignore_ascii_case("SH-2A") {
+                FloatSupport::Hardware
+            } else {
+                FloatSupport::Software
+            };
+        } else if is("RISCV") {
+            // the F and D extensions (or G, which implies them) provide
+            // hardware floating point
+            let extensions = variant.get(4..).unwrap_or_default();
+            info.float = if extensions.contains(['G', 'F', 'D', 'g', 'f', 'd']) {
+                FloatSupport::Hardware
+            } else {
+                FloatSupport::Software
+            };
+        } else if is("avr8") || is("6502") || is("65C02") || is("8051") {
+            info.word_bits = 8;
+            info.float = FloatSupport::Software;
+        } else if is("TI_MSP430") || is("TI_MSP430X") {
+            info.word_bits = 16;
+            info.float = FloatSupport::Software;
+            if is("TI_MSP430X") {
+                info.address_bits = 20;
+            }
+        }
+
+        info
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ArchDefParseError;
+
+    #[test]
+    fn test_lookup_info() -> Result<(), ArchDefParseError> {
+        let mips = "MIPS:BE:32:default".parse::<ArchitectureDef>()?.info();
+        assert!(mips.has_delay_slots());
+        assert_eq!(mips.word_bits(), 32);
+
+        let cortex = "ARM:LE:32:Cortex".parse::<ArchitectureDef>()?.info();
+        assert!(!cortex.has_delay_slots());
+        assert_eq!(cortex.default_context(), &[("TMode", 1)]);
+
+        let avr = "avr8:LE:16:default".parse::<ArchitectureDef>()?.info();
+        assert_eq!(avr.address_bits(), 16);
+        assert_eq!(avr.word_bits(), 8);
+        assert_eq!(avr.float(), FloatSupport::Software);
+
+        let rv = "RISCV:LE:32:RV32IMAC".parse::<ArchitectureDef>()?.info();
+        assert_eq!(rv.float(), FloatSupport::Software);
+
+        let ilp32 = "AARCH64:LE:32:ilp32".parse::<ArchitectureDef>()?.info();
+        assert_eq!(ilp32.address_bits(), 32);
+        assert_eq!(ilp32.word_bits(), 64);
+
+        let sh4 = "SuperH4:LE:32:default".parse::<ArchitectureDef>()?.info();
+        assert!(sh4.has_delay_slots());
+        assert_eq!(sh4.float(), FloatSupport::Hardware);
+
+        let sh2 = "SuperH:BE:32:SH-2".parse::<ArchitectureDef>()?.info();
+        assert!(sh2.has_delay_slots());
+        assert_eq!(sh2.float(), FloatSupport::Software);
+
+        Ok(())
+    }
+}
@@ -5,6 +5,15 @@ use std::str::FromStr;
 
 use thiserror::Error;
 
+pub mod detect;
+pub use detect::detect;
+
+pub mod info;
+pub use info::{ArchInfo, FloatSupport};
+
+pub mod registry;
+pub use registry::resolve;
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct ArchitectureDef {
@@ -26,6 +35,8 @@ pub enum ArchDefParseError {
     ParseVariant,
     #[error("could not parse architecture definition: incorrect format")]
     ParseFormat,
+    #[error("unknown architecture `{0}`")]
+    UnknownArchitecture(String),
 }
 
 impl FromStr for ArchitectureDef {
@@ -104,4 +115,16 @@ impl ArchitectureDef {
     pub fn variant(&self) -> &str {
         &self.variant
     }
+
+    /// The SLEIGH language identifier for the definition, e.g.,
+    /// `x86:LE:64:default`.
+    pub fn language_id(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.processor,
+            if self.endian.is_big() { "BE" } else { "LE" },
+            self.bits,
+            self.variant,
+        )
+    }
 }
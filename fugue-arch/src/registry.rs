@@ -0,0 +1,152 @@
+use fugue_bytes::endian::Endian;
+
+use crate::{ArchDefParseError, ArchitectureDef};
+
+// Common names for architectures, as used by compilers and other tools,
+// mapped to SLEIGH language identifiers; names are compared without regard
+// to case.
+const ALIASES: &[(&str, &str)] = &[
+    ("x86_64", "x86:LE:64:default"),
+    ("x86-64", "x86:LE:64:default"),
+    ("amd64", "x86:LE:64:default"),
+    ("x64", "x86:LE:64:default"),
+    ("x86", "x86:LE:32:default"),
+    ("i386", "x86:LE:32:default"),
+    ("i486", "x86:LE:32:default"),
+    ("i586", "x86:LE:32:default"),
+    ("i686", "x86:LE:32:default"),
+    ("ia32", "x86:LE:32:default"),
+    ("aarch64", "AARCH64:LE:64:v8A"),
+    ("arm64", "AARCH64:LE:64:v8A"),
+    ("aarch64_be", "AARCH64:BE:64:v8A"),
+    ("arm", "ARM:LE:32:v7"),
+    ("armel", "ARM:LE:32:v7"),
+    ("armeb", "ARM:BE:32:v7"),
+    ("armv4t", "ARM:LE:32:v4T"),
+    ("armv5", "ARM:LE:32:v5"),
+    ("armv5t", "ARM:LE:32:v5T"),
+    ("armv5te", "ARM:LE:32:v5T"),
+    ("armv6", "ARM:LE:32:v6"),
+    ("armv7", "ARM:LE:32:v7"),
+    ("armv7a", "ARM:LE:32:v7"),
+    ("armv7l", "ARM:LE:32:v7"),
+    ("armv7r", "ARM:LE:32:v7"),
+    ("armv8", "ARM:LE:32:v8"),
+    ("armv8a", "ARM:LE:32:v8"),
+    ("thumb", "ARM:LE:32:v8T"),
+    ("armv6m", "ARM:LE:32:Cortex"),
+    ("armv7m", "ARM:LE:32:Cortex"),
+    ("armv7em", "ARM:LE:32:Cortex"),
+    ("armv8m", "ARM:LE:32:Cortex"),
+    ("thumbv6m", "ARM:LE:32:Cortex"),
+    ("thumbv7m", "ARM:LE:32:Cortex"),
+    ("thumbv7em", "ARM:LE:32:Cortex"),
+    ("thumbv8m.base", "ARM:LE:32:Cortex"),
+    ("thumbv8m.main", "ARM:LE:32:Cortex"),
+    ("cortex-m", "ARM:LE:32:Cortex"),
+    ("mips", "MIPS:BE:32:default"),
+    ("mipsel", "MIPS:LE:32:default"),
+    ("mips64", "MIPS:BE:64:default"),
+    ("mips64el", "MIPS:LE:64:default"),
+    ("mipsisa32r6", "MIPS:BE:32:R6"),
+    ("mipsisa32r6el", "MIPS:LE:32:R6"),
+    ("powerpc", "PowerPC:BE:32:default"),
+    ("ppc", "PowerPC:BE:32:default"),
+    ("powerpcle", "PowerPC:LE:32:default"),
+    ("ppcle", "PowerPC:LE:32:default"),
+    ("powerpc64", "PowerPC:BE:64:default"),
+    ("ppc64", "PowerPC:BE:64:default"),
+    ("powerpc64le", "PowerPC:LE:64:default"),
+    ("ppc64le", "PowerPC:LE:64:default"),
+    ("riscv32", "RISCV:LE:32:RV32GC"),
+    ("riscv64", "RISCV:LE:64:RV64GC"),
+    ("sparc", "sparc:BE:32:default"),
+    ("sparc64", "sparc:BE:64:default"),
+    ("sparcv9", "sparc:BE:64:default"),
+    ("m68k", "68000:BE:32:default"),
+    ("avr", "avr8:LE:16:default"),
+    ("msp430", "TI_MSP430:LE:16:default"),
+    ("sh4", "SuperH4:LE:32:default"),
+    ("6502", "6502:LE:16:default"),
+    ("8051", "8051:BE:16:default"),
+];
+
+/// Resolves `name` to an architecture definition. `name` may be a SLEIGH
+/// language identifier (e.g., `ARM:LE:32:v7`), a common name for an
+/// architecture (e.g., `amd64` or `armv7em`), or a target triple (e.g.,
+/// `thumbv7em-none-eabi`), whose architecture component is resolved as a
+/// common name. As with [`detect`](crate::detect), variants are hints,
+/// and are best resolved against the available languages, e.g., using
+/// `LanguageDB::find`.
+pub fn resolve(name: &str) -> Result<ArchitectureDef, ArchDefParseError> {
+    if name.contains(':') {
+        return name.parse();
+    }
+
+    alias(name)
+        .or_else(|| name.split('-').next().and_then(alias))
+        .or_else(|| {
+            // RISC-V triples encode the extensions after the base ISA,
+            // e.g., riscv32imac
+            let arch = name.split('-').next()?;
+            ["riscv32", "riscv64"]
+                .into_iter()
+                .find(|base| {
+                    arch.get(..base.len())
+                        .map(|prefix| prefix.eq_ignore_ascii_case(base))
+                        .unwrap_or(false)
+                })
+                .and_then(alias)
+        })
+        .ok_or_else(|| ArchDefParseError::UnknownArchitecture(name.to_owned()))
+}
+
+/// Proposes architectures for the ELF `e_machine` value `machine`, for an
+/// image of the given endian and class (i.e., 32 or 64 bits). Candidates
+/// are ordered from most to least likely.
+pub fn from_elf_machine(machine: u16, endian: Endian, bits: usize) -> Vec<ArchitectureDef> {
+    crate::detect::elf_machine(machine, endian, bits, 0)
+}
+
+fn alias(name: &str) -> Option<ArchitectureDef> {
+    ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map(|(_, id)| id.parse().expect("valid language identifier"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve_names() -> Result<(), ArchDefParseError> {
+        assert_eq!(resolve("armv7em")?, resolve("thumbv7em-none-eabi")?);
+        assert_eq!(
+            resolve("thumbv7em-none-eabi")?.language_id(),
+            "ARM:LE:32:Cortex"
+        );
+        assert_eq!(
+            resolve("x86_64-pc-linux")?.language_id(),
+            "x86:LE:64:default"
+        );
+        assert_eq!(resolve("X86-64")?.language_id(), "x86:LE:64:default");
+        assert_eq!(
+            resolve("riscv32imac-unknown-none-elf")?.language_id(),
+            "RISCV:LE:32:RV32GC"
+        );
+        assert_eq!(resolve("MIPS:BE:32:R6")?.language_id(), "MIPS:BE:32:R6");
+
+        assert!(matches!(
+            resolve("z80-unknown-none"),
+            Err(ArchDefParseError::UnknownArchitecture(_))
+        ));
+
+        assert_eq!(
+            from_elf_machine(40, Endian::Little, 32)[0],
+            "ARM:LE:32:v8".parse()?
+        );
+
+        Ok(())
+    }
+}
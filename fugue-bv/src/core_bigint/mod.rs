@@ -12,6 +12,8 @@ use rug::integer::Order as BVOrder;
 use rug::Integer as BigInt;
 
 use crate::error::{ParseError, TryFromBitVecError};
+use crate::repr::impl_serde;
+use crate::saturating::impl_saturating;
 
 mod mask_table;
 use mask_table::lookup_mask;
@@ -19,12 +21,19 @@ use mask_table::lookup_mask;
 pub const MAX_BITS: Option<u32> = Some(2048);
 
 /// BitVec(value, mask, is_signed, number of bits)
-#[derive(Debug, Clone, Hash, serde::Deserialize, serde::Serialize)]
+///
+/// Values are always boxed; with the `bigint` feature, the exported
+/// [`BitVec`](crate::core_mixed::BitVec) holds values of at most 64 bits
+/// inline and only uses this representation for wider values.
+#[derive(Debug, Clone, Hash)]
 pub struct BitVec(
     pub(crate) Box<BigInt>,
     pub(crate) u32, // [nbits|sign]
 );
 
+impl_serde!(BitVec);
+impl_saturating!(BitVec);
+
 impl fmt::Display for BitVec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}:{}", *self.0, self.bits())
@@ -1181,6 +1190,66 @@ impl BitVec {
     }
 }
 
+impl BitVec {
+    /// Divides by `rhs`, returning the quotient and remainder computed by
+    /// `/` and `%` respectively.
+    pub fn div_rem(&self, rhs: &Self) -> (BitVec, BitVec) {
+        if self.bits() != rhs.bits() {
+            panic!(
+                "cannot use `div_rem` with bit vector of size {} and bit vector of size {}",
+                self.bits(),
+                rhs.bits()
+            )
+        }
+        let lneg = self.is_negative();
+        let rneg = rhs.is_negative();
+        let size = lookup_mask(self.bits());
+
+        let l = if lneg { *(-self).0 } else { (*self.0).clone() };
+        let r = if rneg { *(-rhs).0 } else { (*rhs.0).clone() };
+
+        let (q, m) = l.div_rem(r);
+        let q = BitVec::from_bigint_with(q, size);
+        let m = BitVec::from_bigint_with(m, size);
+
+        (if lneg != rneg { -q } else { q }, if lneg { -m } else { m })
+    }
+
+    /// Multiplies by `rhs`, returning the low and high halves of the
+    /// double-width product; the multiplication is signed if either
+    /// operand is signed.
+    pub fn widening_mul(&self, rhs: &Self) -> (BitVec, BitVec) {
+        if self.bits() != rhs.bits() {
+            panic!(
+                "cannot use `widening_mul` with bit vector of size {} and bit vector of size {}",
+                self.bits(),
+                rhs.bits()
+            )
+        }
+        let bits = self.bits();
+        let signed = self.is_signed() || rhs.is_signed();
+
+        let value = |bv: &Self| {
+            if signed && bv.msb() {
+                &*bv.0 - (BigInt::from(1) << bits as u32)
+            } else {
+                (*bv.0).clone()
+            }
+        };
+
+        let product = value(self) * value(rhs);
+
+        let high = Self::from_bigint(BigInt::from(&product >> bits as u32), bits);
+        let low = Self::from_bigint(product, bits);
+
+        if signed {
+            (low.signed(), high.signed())
+        } else {
+            (low, high)
+        }
+    }
+}
+
 impl Sub for BitVec {
     type Output = Self;
 
@@ -2092,4 +2161,27 @@ mod test {
         );
         assert_eq!(std::mem::size_of::<BitVec>(), 16);
     }
+
+    #[test]
+    fn test_widening_mul() {
+        let v1 = BitVec::from_u128(u128::MAX, 128);
+        let (low, high) = v1.widening_mul(&v1);
+        assert_eq!(low, BitVec::one(128));
+        assert_eq!(high, BitVec::from_u128(u128::MAX - 1, 128));
+
+        let v2 = BitVec::from_i64(-3, 64).signed();
+        let v3 = BitVec::from_i64(i64::MAX, 64).signed();
+        let product = -3i128 * i64::MAX as i128;
+        let (low, high) = v2.widening_mul(&v3);
+        assert_eq!(low, BitVec::from_u64(product as u64, 64));
+        assert_eq!(high, BitVec::from_u64((product >> 64) as u64, 64));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let v1 = BitVec::from_i32(-7, 32).signed();
+        let v2 = BitVec::from_i32(2, 32).signed();
+        assert_eq!(v1.div_rem(&v2), (&v1 / &v2, &v1 % &v2));
+        assert_eq!(v2.div_rem(&v1), (&v2 / &v1, &v2 % &v1));
+    }
 }
@@ -11,11 +11,13 @@ use fugue_bytes::Order;
 use rug::Integer as BigInt;
 
 use crate::error::{ParseError, TryFromBitVecError};
+use crate::repr::impl_serde;
+use crate::saturating::impl_saturating;
 use crate::{core_bigint, core_u64};
 
 pub const MAX_BITS: Option<u32> = core_bigint::MAX_BITS;
 
-#[derive(Debug, Clone, Hash, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, Hash)]
 pub enum BitVec {
     N(core_u64::BitVec),
     U(core_bigint::BitVec),
@@ -152,6 +154,9 @@ impl From<core_bigint::BitVec> for BitVec {
     }
 }
 
+impl_serde!(BitVec);
+impl_saturating!(BitVec);
+
 impl fmt::Display for BitVec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fold_map!(self, |slf| fmt::Display::fmt(slf, f))
@@ -182,6 +187,15 @@ impl FromStr for BitVec {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (cst, sz) = s.rsplit_once(':').ok_or(ParseError::InvalidFormat)?;
 
+        let (small, radix) = match cst.strip_prefix("0x") {
+            Some(cstv) => (cstv, 16),
+            None => (cst, 10),
+        };
+
+        if let Some(bv) = Self::from_str_small(small, sz, radix) {
+            return Ok(bv);
+        }
+
         let val = if let Some(cstv) = cst.strip_prefix("0x") {
             BigInt::from_str_radix(cstv, 16)
         } else {
@@ -198,19 +212,35 @@ impl FromStr for BitVec {
 impl BitVec {
     pub fn from_str_radix(s: &str, radix: u32) -> Result<Self, ParseError> {
         let (cst, sz) = s.rsplit_once(':').ok_or(ParseError::InvalidFormat)?;
+
+        if let Some(bv) = Self::from_str_small(cst, sz, radix) {
+            return Ok(bv);
+        }
+
         let val =
             BigInt::from_str_radix(cst, radix as i32).map_err(|_| ParseError::InvalidConst)?;
 
         let bits = usize::from_str(sz).map_err(|_| ParseError::InvalidSize)?;
         Ok(Self::from_bigint(val, bits))
     }
+
+    /// Parses constants of at most 64 bits without going via a `BigInt`;
+    /// `None` if the constant is wider, or does not fit in a `u64`, in
+    /// which case the caller should fall back to the general path.
+    fn from_str_small(cst: &str, sz: &str, radix: u32) -> Option<Self> {
+        let bits = usize::from_str(sz).ok().filter(|bits| *bits <= 64)?;
+        let val = u64::from_str_radix(cst, radix).ok()?;
+        Some(Self::N(core_u64::BitVec::from_uint(val, bits)))
+    }
 }
 
 impl BitVec {
+    /// Values of at most 64 bits are stored inline; they are truncated
+    /// directly from `v` rather than by first constructing (and boxing) a
+    /// `core_bigint::BitVec`.
     pub fn from_bigint(v: BigInt, bits: usize) -> Self {
         if bits <= 64 {
-            let v = core_bigint::BitVec::from_bigint(v, bits).to_u64().unwrap();
-            Self::N(core_u64::BitVec::from_uint(v, bits))
+            Self::N(core_u64::BitVec::from_uint(v.to_u64_wrapping(), bits))
         } else {
             Self::U(core_bigint::BitVec::from_bigint(v, bits))
         }
@@ -220,10 +250,7 @@ impl BitVec {
     pub(crate) fn from_bigint_with(v: BigInt, mask: &'static BigInt) -> Self {
         let bits = mask.count_ones().unwrap() as usize;
         if bits <= 64 {
-            let v = core_bigint::BitVec::from_bigint_with(v, mask)
-                .to_u64()
-                .unwrap();
-            Self::N(core_u64::BitVec::from_uint(v, bits))
+            Self::N(core_u64::BitVec::from_uint(v.to_u64_wrapping(), bits))
         } else {
             Self::U(core_bigint::BitVec::from_bigint_with(v, mask))
         }
@@ -784,6 +811,41 @@ impl BitVec {
     }
 }
 
+impl BitVec {
+    /// Divides by `rhs`, returning the quotient and remainder computed by
+    /// `/` and `%` respectively.
+    pub fn div_rem(&self, rhs: &Self) -> (BitVec, BitVec) {
+        match (self, rhs) {
+            (Self::N(m), Self::N(n)) => {
+                let (q, r) = m.div_rem(n);
+                (Self::N(q), Self::N(r))
+            }
+            (Self::U(m), Self::U(n)) => {
+                let (q, r) = m.div_rem(n);
+                (Self::U(q), Self::U(r))
+            }
+            _ => panic!("cannot apply operation to operands with different bit sizes"),
+        }
+    }
+
+    /// Multiplies by `rhs`, returning the low and high halves of the
+    /// double-width product; the multiplication is signed if either
+    /// operand is signed.
+    pub fn widening_mul(&self, rhs: &Self) -> (BitVec, BitVec) {
+        match (self, rhs) {
+            (Self::N(m), Self::N(n)) => {
+                let (l, h) = m.widening_mul(n);
+                (Self::N(l), Self::N(h))
+            }
+            (Self::U(m), Self::U(n)) => {
+                let (l, h) = m.widening_mul(n);
+                (Self::U(l), Self::U(h))
+            }
+            _ => panic!("cannot apply operation to operands with different bit sizes"),
+        }
+    }
+}
+
 impl Sub for BitVec {
     type Output = Self;
 
@@ -1367,6 +1429,35 @@ mod test {
     fn test_parse() -> Result<(), ParseError> {
         assert_eq!("0x100:129".parse::<BitVec>()?.bits(), 129);
         assert_eq!("0x100:0".parse::<BitVec>()?.bits(), 0);
+        assert_eq!("0x1ff:8".parse::<BitVec>()?, BitVec::from_u64(0xff, 8));
+        assert_eq!("-1:8".parse::<BitVec>()?, BitVec::from_u64(0xff, 8));
+        assert_eq!(
+            "0x10000000000000001:64".parse::<BitVec>()?,
+            BitVec::from_u64(1, 64)
+        );
         Ok(())
     }
+
+    #[test]
+    fn test_widening_mul() {
+        let v1 = BitVec::from_u128(u128::MAX, 128);
+        let (low, high) = v1.widening_mul(&v1);
+        assert_eq!(low, BitVec::one(128));
+        assert_eq!(high, BitVec::from_u128(u128::MAX - 1, 128));
+
+        let v2 = BitVec::from_i64(-3, 64).signed();
+        let v3 = BitVec::from_i64(i64::MAX, 64).signed();
+        let product = -3i128 * i64::MAX as i128;
+        let (low, high) = v2.widening_mul(&v3);
+        assert_eq!(low, BitVec::from_u64(product as u64, 64));
+        assert_eq!(high, BitVec::from_u64((product >> 64) as u64, 64));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let v1 = BitVec::from_i32(-7, 32).signed();
+        let v2 = BitVec::from_i32(2, 32).signed();
+        assert_eq!(v1.div_rem(&v2), (&v1 / &v2, &v1 % &v2));
+        assert_eq!(v2.div_rem(&v1), (&v2 / &v1, &v2 % &v1));
+    }
 }
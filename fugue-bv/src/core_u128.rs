@@ -11,12 +11,17 @@ use std::ops::{
 use std::str::FromStr;
 
 use crate::error::{ParseError, TryFromBitVecError};
+use crate::repr::impl_serde;
+use crate::saturating::impl_saturating;
 
 pub const MAX_BITS: Option<u32> = Some(128);
 
-#[derive(Debug, Clone, Hash, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, Hash)]
 pub struct BitVec(pub(crate) u128, pub(crate) u32);
 
+impl_serde!(BitVec);
+impl_saturating!(BitVec);
+
 impl fmt::Display for BitVec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}:{}", self.0, self.bits())
@@ -1171,6 +1176,102 @@ impl BitVec {
     }
 }
 
+impl BitVec {
+    /// Divides by `rhs`, returning the quotient and remainder computed by
+    /// `/` and `%` respectively.
+    pub fn div_rem(&self, rhs: &Self) -> (BitVec, BitVec) {
+        if self.bits() != rhs.bits() {
+            panic!(
+                "cannot use `div_rem` with bit vector of size {} and bit vector of size {}",
+                self.bits(),
+                rhs.bits()
+            )
+        }
+        let lneg = self.is_negative();
+        let rneg = rhs.is_negative();
+        let size = self.mask_bits();
+
+        let l = if lneg { (-self).0 } else { self.0 };
+        let r = if rneg { (-rhs).0 } else { rhs.0 };
+
+        let q = BitVec::from_uint_with(l / r, size);
+        let m = BitVec::from_uint_with(l % r, size);
+
+        (if lneg != rneg { -q } else { q }, if lneg { -m } else { m })
+    }
+
+    /// Multiplies by `rhs`, returning the low and high halves of the
+    /// double-width product; the multiplication is signed if either
+    /// operand is signed.
+    pub fn widening_mul(&self, rhs: &Self) -> (BitVec, BitVec) {
+        if self.bits() != rhs.bits() {
+            panic!(
+                "cannot use `widening_mul` with bit vector of size {} and bit vector of size {}",
+                self.bits(),
+                rhs.bits()
+            )
+        }
+        let bits = self.bits();
+        let signed = self.is_signed() || rhs.is_signed();
+
+        let (l, r) = if signed {
+            let shift = 128 - bits as u32;
+            (
+                (self.0.wrapping_shl(shift) as i128).wrapping_shr(shift) as u128,
+                (rhs.0.wrapping_shl(shift) as i128).wrapping_shr(shift) as u128,
+            )
+        } else {
+            (self.0, rhs.0)
+        };
+
+        let (low, mut high) = mul_wide(l, r);
+
+        // correct the high half of the unsigned product of the operands'
+        // two's complement representations
+        if signed {
+            if (l as i128) < 0 {
+                high = high.wrapping_sub(r);
+            }
+            if (r as i128) < 0 {
+                high = high.wrapping_sub(l);
+            }
+        }
+
+        let high = if bits == 128 {
+            high
+        } else {
+            (low >> bits) | (high << (128 - bits))
+        };
+
+        let low = Self::from_uint(low, bits);
+        let high = Self::from_uint(high, bits);
+
+        if signed {
+            (low.signed(), high.signed())
+        } else {
+            (low, high)
+        }
+    }
+}
+
+/// The full 256-bit product of `l` and `r`, as its low and high halves.
+fn mul_wide(l: u128, r: u128) -> (u128, u128) {
+    let (l0, l1) = (l as u64 as u128, l >> 64);
+    let (r0, r1) = (r as u64 as u128, r >> 64);
+
+    let p00 = l0 * r0;
+    let p01 = l0 * r1;
+    let p10 = l1 * r0;
+    let p11 = l1 * r1;
+
+    let mid = (p00 >> 64) + (p01 as u64 as u128) + (p10 as u64 as u128);
+
+    let low = (p00 as u64 as u128) | (mid << 64);
+    let high = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+
+    (low, high)
+}
+
 impl Sub for BitVec {
     type Output = Self;
 
@@ -2071,4 +2172,27 @@ mod test {
 
         assert_eq!(v3.signed_borrow(&v4), false);
     }
+
+    #[test]
+    fn test_widening_mul() {
+        let v1 = BitVec::from_u128(u128::MAX, 128);
+        let (low, high) = v1.widening_mul(&v1);
+        assert_eq!(low, BitVec::one(128));
+        assert_eq!(high, BitVec::from_u128(u128::MAX - 1, 128));
+
+        let v2 = BitVec::from_i64(-3, 64).signed();
+        let v3 = BitVec::from_i64(i64::MAX, 64).signed();
+        let product = -3i128 * i64::MAX as i128;
+        let (low, high) = v2.widening_mul(&v3);
+        assert_eq!(low, BitVec::from_u64(product as u64, 64));
+        assert_eq!(high, BitVec::from_u64((product >> 64) as u64, 64));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let v1 = BitVec::from_i32(-7, 32).signed();
+        let v2 = BitVec::from_i32(2, 32).signed();
+        assert_eq!(v1.div_rem(&v2), (&v1 / &v2, &v1 % &v2));
+        assert_eq!(v2.div_rem(&v1), (&v2 / &v1, &v2 % &v1));
+    }
 }
@@ -12,15 +12,20 @@ use std::ops::{
 use std::str::FromStr;
 
 use crate::error::{ParseError, TryFromBitVecError};
+use crate::repr::impl_serde;
+use crate::saturating::impl_saturating;
 
 pub const MAX_BITS: Option<u32> = Some(64);
 
-#[derive(Debug, Clone, Hash, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, Hash)]
 pub struct BitVec(
     pub(crate) u64,
     pub(crate) u32, // [nbits|sign]
 );
 
+impl_serde!(BitVec);
+impl_saturating!(BitVec);
+
 impl fmt::Display for BitVec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}:{}", self.0, self.bits())
@@ -1180,6 +1185,64 @@ impl BitVec {
     }
 }
 
+impl BitVec {
+    /// Divides by `rhs`, returning the quotient and remainder computed by
+    /// `/` and `%` respectively.
+    pub fn div_rem(&self, rhs: &Self) -> (BitVec, BitVec) {
+        if self.bits() != rhs.bits() {
+            panic!(
+                "cannot use `div_rem` with bit vector of size {} and bit vector of size {}",
+                self.bits(),
+                rhs.bits()
+            )
+        }
+        let lneg = self.is_negative();
+        let rneg = rhs.is_negative();
+        let size = self.mask_bits();
+
+        let l = if lneg { (-self).0 } else { self.0 };
+        let r = if rneg { (-rhs).0 } else { rhs.0 };
+
+        let q = BitVec::from_uint_with(l / r, size);
+        let m = BitVec::from_uint_with(l % r, size);
+
+        (if lneg != rneg { -q } else { q }, if lneg { -m } else { m })
+    }
+
+    /// Multiplies by `rhs`, returning the low and high halves of the
+    /// double-width product; the multiplication is signed if either
+    /// operand is signed.
+    pub fn widening_mul(&self, rhs: &Self) -> (BitVec, BitVec) {
+        if self.bits() != rhs.bits() {
+            panic!(
+                "cannot use `widening_mul` with bit vector of size {} and bit vector of size {}",
+                self.bits(),
+                rhs.bits()
+            )
+        }
+        let bits = self.bits();
+        let signed = self.is_signed() || rhs.is_signed();
+
+        let product = if signed {
+            let shift = 64 - bits as u32;
+            let l = (self.0.wrapping_shl(shift) as i64).wrapping_shr(shift);
+            let r = (rhs.0.wrapping_shl(shift) as i64).wrapping_shr(shift);
+            (l as i128 * r as i128) as u128
+        } else {
+            self.0 as u128 * rhs.0 as u128
+        };
+
+        let low = Self::from_uint(product as u64, bits);
+        let high = Self::from_uint((product >> bits) as u64, bits);
+
+        if signed {
+            (low.signed(), high.signed())
+        } else {
+            (low, high)
+        }
+    }
+}
+
 impl Sub for BitVec {
     type Output = Self;
 
@@ -2100,4 +2163,26 @@ mod test {
 
         assert_eq!(v3.signed_borrow(&v4), false);
     }
+
+    #[test]
+    fn test_widening_mul() {
+        let v1 = BitVec::from_u64(u64::MAX, 64);
+        let (low, high) = v1.widening_mul(&v1);
+        assert_eq!(low, BitVec::one(64));
+        assert_eq!(high, BitVec::from_u64(u64::MAX - 1, 64));
+
+        let v2 = BitVec::from_i8(-3, 8).signed();
+        let v3 = BitVec::from_i8(100, 8).signed();
+        let (low, high) = v2.widening_mul(&v3);
+        assert_eq!(low, BitVec::from_u16(0xfed4 & 0xff, 8));
+        assert_eq!(high, BitVec::from_u16(0xfed4 >> 8, 8));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let v1 = BitVec::from_i32(-7, 32).signed();
+        let v2 = BitVec::from_i32(2, 32).signed();
+        assert_eq!(v1.div_rem(&v2), (&v1 / &v2, &v1 % &v2));
+        assert_eq!(v2.div_rem(&v1), (&v2 / &v1, &v2 % &v1));
+    }
 }
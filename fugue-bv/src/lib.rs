@@ -7,11 +7,28 @@ pub mod core_u64;
 pub mod core_u128;
 pub mod error;
 
+mod repr;
+mod saturating;
+
+pub mod traits;
+pub use traits::BitVector;
+
+// Each backend is always available under its own name; the backend
+// exported as `BitVec` is selected by feature, preferring the widest when
+// more than one is enabled.
+pub type BitVec64 = core_u64::BitVec;
+pub type BitVec128 = core_u128::BitVec;
 #[cfg(feature = "bigint")]
-pub use self::core_mixed::*;
+pub type BitVecBig = core_mixed::BitVec;
 
-#[cfg(feature = "fixed-u64")]
-pub use self::core_u64::*;
+#[cfg(feature = "bigint")]
+pub use self::core_mixed::*;
 
-#[cfg(feature = "fixed-u128")]
+#[cfg(all(feature = "fixed-u128", not(feature = "bigint")))]
 pub use self::core_u128::*;
+
+#[cfg(all(
+    feature = "fixed-u64",
+    not(any(feature = "bigint", feature = "fixed-u128"))
+))]
+pub use self::core_u64::*;
@@ -0,0 +1,123 @@
+//! The serialised representation of bit-vectors, shared by all backends.
+//!
+//! A bit-vector is serialised as a structure named `BitVec` with three
+//! fields: `bytes`, its value as `ceil(bits / 8)` little-endian bytes;
+//! `bits`, its size in bits; and `signed`, its signedness. A value
+//! serialised using one backend can therefore be deserialised using any
+//! other backend that supports its size.
+
+use serde::de::{Error, Unexpected};
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename = "BitVec")]
+pub(crate) struct Repr {
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) bits: u32,
+    pub(crate) signed: bool,
+}
+
+impl Repr {
+    pub(crate) fn byte_len(bits: usize) -> usize {
+        bits.div_ceil(8)
+    }
+
+    pub(crate) fn validate<E: Error>(&self, max_bits: Option<u32>) -> Result<(), E> {
+        if max_bits.map(|max| self.bits > max).unwrap_or(false) {
+            return Err(E::invalid_value(
+                Unexpected::Unsigned(self.bits as u64),
+                &"a bit size supported by the backend",
+            ));
+        }
+
+        let expected = Self::byte_len(self.bits as usize);
+        if self.bytes.len() != expected {
+            return Err(E::invalid_length(
+                self.bytes.len(),
+                &format!("{} bytes", expected).as_str(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! impl_serde {
+    ($bv:ty) => {
+        impl serde::Serialize for $bv {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                let mut bytes = vec![0u8; $crate::repr::Repr::byte_len(self.bits())];
+                self.to_le_bytes(&mut bytes);
+
+                serde::Serialize::serialize(
+                    &$crate::repr::Repr {
+                        bytes,
+                        bits: self.bits() as u32,
+                        signed: self.is_signed(),
+                    },
+                    serializer,
+                )
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $bv {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let repr = <$crate::repr::Repr as serde::Deserialize>::deserialize(deserializer)?;
+                repr.validate(MAX_BITS)?;
+
+                let bv = if repr.bits == 0 {
+                    Self::zero(0)
+                } else {
+                    Self::from_le_bytes(&repr.bytes).unsigned_cast(repr.bits as usize)
+                };
+
+                Ok(if repr.signed { bv.signed() } else { bv })
+            }
+        }
+    };
+}
+
+pub(crate) use impl_serde;
+
+#[cfg(test)]
+mod test {
+    use crate::{core_u128, core_u64};
+
+    #[test]
+    fn test_interoperable() -> Result<(), bincode::Error> {
+        let v = core_u64::BitVec::from_i32(-2, 24).signed();
+        let bytes = bincode::serialize(&v)?;
+
+        let w = bincode::deserialize::<core_u128::BitVec>(&bytes)?;
+        assert_eq!(w, core_u128::BitVec::from_i32(-2, 24).signed());
+        assert!(w.is_signed());
+
+        #[cfg(feature = "bigint")]
+        {
+            let w = bincode::deserialize::<crate::core_bigint::BitVec>(&bytes)?;
+            assert_eq!(w, crate::core_bigint::BitVec::from_i32(-2, 24).signed());
+
+            let w = bincode::deserialize::<crate::core_mixed::BitVec>(&bytes)?;
+            assert_eq!(w, crate::core_mixed::BitVec::from_i32(-2, 24).signed());
+        }
+
+        let v = core_u128::BitVec::from_u128(1 << 100, 101);
+        let bytes = bincode::serialize(&v)?;
+
+        assert!(bincode::deserialize::<core_u64::BitVec>(&bytes).is_err());
+
+        #[cfg(feature = "bigint")]
+        {
+            let w = bincode::deserialize::<crate::core_mixed::BitVec>(&bytes)?;
+            assert_eq!(w.bits(), 101);
+            assert_eq!(bincode::serialize(&w)?, bytes);
+        }
+
+        Ok(())
+    }
+}
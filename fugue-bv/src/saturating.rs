@@ -0,0 +1,185 @@
+//! Saturating arithmetic, shared by all backends.
+//!
+//! Overflow is detected using each backend's wrapping operations, rather
+//! than by widening, so that operations on vectors of a backend's maximum
+//! size also saturate.
+
+macro_rules! impl_saturating {
+    ($bv:ident) => {
+        impl $bv {
+            fn with_sign_of(self, other: &Self) -> Self {
+                if other.is_signed() {
+                    self.signed()
+                } else {
+                    self.unsigned()
+                }
+            }
+
+            fn saturate(&self, negative: bool) -> Self {
+                if negative {
+                    Self::min_value_with(self.bits(), true)
+                } else {
+                    Self::max_value_with(self.bits(), true)
+                }
+                .with_sign_of(self)
+            }
+
+            fn check_saturating_operands(&self, rhs: &Self, op: &str) {
+                if self.bits() != rhs.bits() {
+                    panic!(
+                        "cannot use `{}` with bit vector of size {} and bit vector of size {}",
+                        op,
+                        self.bits(),
+                        rhs.bits()
+                    )
+                }
+            }
+
+            /// Adds `rhs`, saturating at the bounds of the vector's size;
+            /// the addition is signed if either operand is signed.
+            pub fn saturating_add(&self, rhs: &Self) -> Self {
+                if self.is_signed() || rhs.is_signed() {
+                    self.signed_saturating_add(rhs)
+                } else {
+                    self.check_saturating_operands(rhs, "saturating_add");
+                    let r = self + rhs;
+                    if r < *self {
+                        Self::max_value_with(self.bits(), false)
+                    } else {
+                        r
+                    }
+                }
+            }
+
+            /// Adds `rhs`, treating both operands as signed.
+            pub fn signed_saturating_add(&self, rhs: &Self) -> Self {
+                self.check_saturating_operands(rhs, "signed_saturating_add");
+                if self.signed_carry(rhs) {
+                    self.saturate(self.msb())
+                } else {
+                    (self + rhs).with_sign_of(self)
+                }
+            }
+
+            /// Subtracts `rhs`, saturating at the bounds of the vector's
+            /// size; the subtraction is signed if either operand is signed.
+            pub fn saturating_sub(&self, rhs: &Self) -> Self {
+                if self.is_signed() || rhs.is_signed() {
+                    self.signed_saturating_sub(rhs)
+                } else {
+                    self.check_saturating_operands(rhs, "saturating_sub");
+                    if *self < *rhs {
+                        Self::zero(self.bits())
+                    } else {
+                        self - rhs
+                    }
+                }
+            }
+
+            /// Subtracts `rhs`, treating both operands as signed.
+            pub fn signed_saturating_sub(&self, rhs: &Self) -> Self {
+                self.check_saturating_operands(rhs, "signed_saturating_sub");
+                if self.signed_borrow(rhs) {
+                    self.saturate(self.msb())
+                } else {
+                    (self - rhs).with_sign_of(self)
+                }
+            }
+
+            /// Multiplies by `rhs`, saturating at the bounds of the
+            /// vector's size; the multiplication is signed if either
+            /// operand is signed.
+            pub fn saturating_mul(&self, rhs: &Self) -> Self {
+                if self.is_signed() || rhs.is_signed() {
+                    self.signed_saturating_mul(rhs)
+                } else {
+                    self.check_saturating_operands(rhs, "saturating_mul");
+                    let r = self * rhs;
+                    if !self.is_zero() && &r / self != *rhs {
+                        Self::max_value_with(self.bits(), false)
+                    } else {
+                        r
+                    }
+                }
+            }
+
+            /// Multiplies by `rhs`, treating both operands as signed.
+            pub fn signed_saturating_mul(&self, rhs: &Self) -> Self {
+                self.check_saturating_operands(rhs, "signed_saturating_mul");
+
+                let r = self * rhs;
+                let min = Self::min_value_with(self.bits(), true);
+
+                // -1 * MIN wraps to MIN, which division does not detect
+                let overflow = !self.is_zero()
+                    && (r.signed_div(self) != *rhs
+                        || (*self == -Self::one(self.bits()) && *rhs == min));
+
+                if overflow {
+                    self.saturate(self.msb() != rhs.msb())
+                } else {
+                    r.with_sign_of(self)
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use impl_saturating;
+
+#[cfg(test)]
+mod test {
+    use crate::{BitVec128, BitVec64};
+
+    #[test]
+    fn test_saturating() {
+        let max = BitVec64::from_u8(0xff, 8);
+
+        assert_eq!(max.saturating_add(&BitVec64::one(8)), max);
+        assert_eq!(BitVec64::one(8).saturating_sub(&max), BitVec64::zero(8));
+        assert_eq!(
+            BitVec64::from_u8(0x10, 8).saturating_mul(&BitVec64::from_u8(0x10, 8)),
+            max
+        );
+        assert_eq!(
+            BitVec64::from_u8(0x0f, 8).saturating_mul(&BitVec64::from_u8(0x11, 8)),
+            max
+        );
+
+        let smax = BitVec64::from_i8(i8::MAX, 8).signed();
+        let smin = BitVec64::from_i8(i8::MIN, 8).signed();
+        let one = BitVec64::one(8).signed();
+
+        assert_eq!(smax.saturating_add(&one), smax);
+        assert_eq!(smin.saturating_sub(&one), smin);
+        assert_eq!(smin.saturating_add(&one), BitVec64::from_i8(-127, 8));
+        assert_eq!((-&one).saturating_mul(&smin), smax);
+        assert_eq!(smin.saturating_mul(&(-&one)), smax);
+        assert_eq!(
+            smax.saturating_mul(&BitVec64::from_i8(-2, 8).signed()),
+            smin
+        );
+        assert!(smax.saturating_add(&one).is_signed());
+
+        // unsigned operands treated as signed
+        let max = BitVec64::from_u64(u64::MAX >> 1, 64);
+        assert_eq!(
+            max.signed_saturating_add(&BitVec64::one(64)),
+            BitVec64::from_u64(u64::MAX >> 1, 64)
+        );
+
+        let max = BitVec128::max_value_with(128, false);
+        assert_eq!(max.saturating_add(&BitVec128::one(128)), max);
+
+        #[cfg(feature = "bigint")]
+        {
+            let max = crate::BitVecBig::max_value_with(256, true);
+            let one = crate::BitVecBig::one(256).signed();
+            assert_eq!(max.saturating_add(&one), max);
+            assert_eq!(
+                (-&max).saturating_sub(&one).saturating_sub(&one),
+                crate::BitVecBig::min_value_with(256, true)
+            );
+        }
+    }
+}
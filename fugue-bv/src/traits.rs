@@ -0,0 +1,174 @@
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub};
+use std::str::FromStr;
+
+/// The operations common to all bit-vector backends, allowing code to be
+/// written independently of the backend used.
+pub trait BitVector:
+    Clone
+    + fmt::Debug
+    + fmt::Display
+    + FromStr
+    + Hash
+    + Ord
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + Neg<Output = Self>
+    + Not<Output = Self>
+    + serde::Serialize
+    + serde::de::DeserializeOwned
+{
+    /// The largest size supported by the backend, if any.
+    const MAX_BITS: Option<u32>;
+
+    fn zero(bits: usize) -> Self;
+    fn one(bits: usize) -> Self;
+
+    fn from_u64(value: u64, bits: usize) -> Self;
+    fn to_u64(&self) -> Option<u64>;
+
+    /// Creates a bit-vector from `buf` with a size of `8 * buf.len()`
+    /// bits.
+    fn from_le_bytes(buf: &[u8]) -> Self;
+
+    /// Writes the bit-vector to `buf`, which must be exactly large enough
+    /// to hold it.
+    fn to_le_bytes(&self, buf: &mut [u8]);
+
+    fn bits(&self) -> usize;
+
+    fn is_zero(&self) -> bool;
+    fn is_signed(&self) -> bool;
+
+    fn signed(self) -> Self;
+    fn unsigned(self) -> Self;
+
+    /// Resizes the bit-vector to `bits` bits, sign-extending it if it is
+    /// signed.
+    fn cast(self, bits: usize) -> Self;
+
+    /// Converts the bit-vector to another backend, or `None` if that
+    /// backend does not support its size.
+    fn convert<T: BitVector>(&self) -> Option<T> {
+        let bits = self.bits();
+        if T::MAX_BITS.map(|max| bits > max as usize).unwrap_or(false) {
+            return None;
+        }
+
+        let bv = if bits == 0 {
+            T::zero(0)
+        } else {
+            let mut bytes = vec![0u8; bits.div_ceil(8)];
+            self.to_le_bytes(&mut bytes);
+            T::from_le_bytes(&bytes).cast(bits)
+        };
+
+        Some(if self.is_signed() { bv.signed() } else { bv })
+    }
+}
+
+macro_rules! impl_bitvector {
+    ($module:ident) => {
+        impl BitVector for crate::$module::BitVec {
+            const MAX_BITS: Option<u32> = crate::$module::MAX_BITS;
+
+            fn zero(bits: usize) -> Self {
+                Self::zero(bits)
+            }
+
+            fn one(bits: usize) -> Self {
+                Self::one(bits)
+            }
+
+            fn from_u64(value: u64, bits: usize) -> Self {
+                Self::from_u64(value, bits)
+            }
+
+            fn to_u64(&self) -> Option<u64> {
+                self.to_u64()
+            }
+
+            fn from_le_bytes(buf: &[u8]) -> Self {
+                Self::from_le_bytes(buf)
+            }
+
+            fn to_le_bytes(&self, buf: &mut [u8]) {
+                self.to_le_bytes(buf)
+            }
+
+            fn bits(&self) -> usize {
+                self.bits()
+            }
+
+            fn is_zero(&self) -> bool {
+                self.is_zero()
+            }
+
+            fn is_signed(&self) -> bool {
+                self.is_signed()
+            }
+
+            fn signed(self) -> Self {
+                self.signed()
+            }
+
+            fn unsigned(self) -> Self {
+                self.unsigned()
+            }
+
+            fn cast(self, bits: usize) -> Self {
+                self.cast(bits)
+            }
+        }
+    };
+}
+
+impl_bitvector!(core_u64);
+impl_bitvector!(core_u128);
+
+#[cfg(feature = "bigint")]
+impl_bitvector!(core_bigint);
+#[cfg(feature = "bigint")]
+impl_bitvector!(core_mixed);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{BitVec128, BitVec64};
+
+    fn sum<B: BitVector>(values: &[u64], bits: usize) -> B {
+        values
+            .iter()
+            .fold(B::zero(bits), |acc, v| acc + B::from_u64(*v, bits))
+    }
+
+    #[test]
+    fn test_convert() {
+        let v = sum::<BitVec64>(&[0xff, 0x01], 8);
+        assert!(v.is_zero());
+
+        let v = BitVec64::from_i64(-2, 32).signed();
+        let w = v.convert::<BitVec128>().unwrap();
+
+        assert_eq!(w, BitVec128::from_i64(-2, 32).signed());
+        assert_eq!(w.cast(64), BitVec128::from_i64(-2, 64).signed());
+
+        let v = BitVec128::from_u128(1 << 100, 128);
+        assert!(v.convert::<BitVec64>().is_none());
+
+        #[cfg(feature = "bigint")]
+        {
+            let w = v.convert::<crate::BitVecBig>().unwrap();
+            assert_eq!(w.convert::<BitVec128>(), Some(v));
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::Function;
+use crate::Id;
+
+type Results = HashMap<(u64, TypeId), Arc<dyn Any + Send + Sync>>;
+
+/// Results derived from a database's functions by analyses, e.g., CFGs
+/// or hashes, cached within the [`Database`](crate::Database). Results are
+/// keyed by function and by type, so each analysis should use a distinct
+/// result type; the cache may be shared between threads.
+#[derive(Default)]
+pub struct Analyses {
+    results: RwLock<Results>,
+}
+
+impl Analyses {
+    fn read(&self) -> RwLockReadGuard<'_, Results> {
+        self.results.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Results> {
+        self.results.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn key<T: Any>(function: &Id<Function>) -> (u64, TypeId) {
+        (function.value(), TypeId::of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    pub fn get<T>(&self, function: Id<Function>) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        let result = self.read().get(&Self::key::<T>(&function))?.clone();
+        Some(result.downcast::<T>().expect("result of the keyed type"))
+    }
+
+    /// Caches `result` for `function`, returning the result it replaces.
+    pub fn insert<T>(&self, function: Id<Function>, result: T) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        let previous = self
+            .write()
+            .insert(Self::key::<T>(&function), Arc::new(result))?;
+        Some(previous.downcast::<T>().expect("result of the keyed type"))
+    }
+
+    /// Returns the result cached for `function`, or computes and caches it
+    /// using `f`. The cache is not locked while `f` runs, hence threads
+    /// may race to compute the same result; the first to finish is kept.
+    pub fn get_or_insert_with<T, F>(&self, function: Id<Function>, f: F) -> Arc<T>
+    where
+        T: Any + Send + Sync,
+        F: FnOnce() -> T,
+    {
+        match self.get_or_try_insert_with(function, || Ok::<_, Infallible>(f())) {
+            Ok(result) => result,
+            Err(e) => match e {},
+        }
+    }
+
+    /// As [`Analyses::get_or_insert_with`], for analyses that may fail;
+    /// failures are not cached.
+    pub fn get_or_try_insert_with<T, E, F>(&self, function: Id<Function>, f: F) -> Result<Arc<T>, E>
+    where
+        T: Any + Send + Sync,
+        F: FnOnce() -> Result<T, E>,
+    {
+        let key = Self::key::<T>(&function);
+        if let Some(result) = self.read().get(&key).cloned() {
+            return Ok(result.downcast::<T>().expect("result of the keyed type"));
+        }
+
+        let result: Arc<dyn Any + Send + Sync> = Arc::new(f()?);
+        let result = self.write().entry(key).or_insert(result).clone();
+
+        Ok(result.downcast::<T>().expect("result of the keyed type"))
+    }
+
+    pub fn remove<T>(&self, function: Id<Function>) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        let result = self.write().remove(&Self::key::<T>(&function))?;
+        Some(result.downcast::<T>().expect("result of the keyed type"))
+    }
+
+    /// Removes all results cached for `function`.
+    pub fn invalidate(&self, function: Id<Function>) {
+        let function = function.value();
+        self.write().retain(|(id, _), _| *id != function);
+    }
+
+    pub fn clear(&self) {
+        self.write().clear();
+    }
+}
+
+impl Clone for Analyses {
+    fn clone(&self) -> Self {
+        Self {
+            results: RwLock::new(self.read().clone()),
+        }
+    }
+}
+
+impl fmt::Debug for Analyses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Analyses")
+            .field("results", &self.len())
+            .finish()
+    }
+}
+
+/// A value that may be read and updated from multiple threads; comparison
+/// and hashing are by the value it holds.
+#[derive(Default)]
+pub(crate) struct Shared<T>(RwLock<T>);
+
+impl<T> Shared<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self(RwLock::new(value))
+    }
+
+    pub(crate) fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.0.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.0.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Clone> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.read().clone())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.read().fmt(f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Shared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other) || *self.read() == *other.read()
+    }
+}
+
+impl<T: Eq> Eq for Shared<T> {}
+
+impl<T: Hash> Hash for Shared<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.read().hash(state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn id(index: usize) -> Id<Function<'static>> {
+        Id::from(index)
+    }
+
+    #[test]
+    fn test_analyses() {
+        let analyses = Analyses::default();
+
+        assert_eq!(*analyses.get_or_insert_with(id(0), || 42u64), 42);
+        assert_eq!(*analyses.get_or_insert_with(id(0), || 0u64), 42);
+        assert!(analyses.get::<u32>(id(0)).is_none());
+
+        analyses.insert(id(1), String::from("g"));
+        assert_eq!(analyses.len(), 2);
+
+        let failed = analyses.get_or_try_insert_with(id(0), || Err::<u32, _>("failed"));
+        assert_eq!(failed, Err("failed"));
+        assert!(analyses.get::<u32>(id(0)).is_none());
+
+        let shared = Arc::new(analyses);
+        let handles = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || *shared.get_or_insert_with(id(1), || 1u8))
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 1);
+        }
+
+        shared.invalidate(id(1));
+        assert!(shared.get::<String>(id(1)).is_none());
+        assert_eq!(shared.len(), 1);
+    }
+}
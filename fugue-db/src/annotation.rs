@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::ops::RangeBounds;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Colour {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl Colour {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    pub const fn from_rgb(rgb: u32) -> Self {
+        Self::new((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
+
+    pub const fn to_rgb(&self) -> u32 {
+        (self.r as u32) << 16 | (self.g as u32) << 8 | self.b as u32
+    }
+
+    pub const fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub const fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub const fn b(&self) -> u8 {
+        self.b
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Annotation<'a> {
+    Comment(&'a str),
+    Label(&'a str),
+    Bookmark(&'a str),
+    Colour(Colour),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+/// Analyst annotations keyed by address. Each address holds at most one
+/// annotation of each kind.
+pub struct Annotations {
+    comments: BTreeMap<u64, String>,
+    labels: BTreeMap<u64, String>,
+    bookmarks: BTreeMap<u64, String>,
+    colours: BTreeMap<u64, Colour>,
+}
+
+impl Annotations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.comments.is_empty()
+            && self.labels.is_empty()
+            && self.bookmarks.is_empty()
+            && self.colours.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.comments.clear();
+        self.labels.clear();
+        self.bookmarks.clear();
+        self.colours.clear();
+    }
+
+    pub fn comment(&self, address: u64) -> Option<&str> {
+        self.comments.get(&address).map(String::as_str)
+    }
+
+    pub fn set_comment<S: Into<String>>(&mut self, address: u64, comment: S) -> Option<String> {
+        self.comments.insert(address, comment.into())
+    }
+
+    pub fn remove_comment(&mut self, address: u64) -> Option<String> {
+        self.comments.remove(&address)
+    }
+
+    pub fn comments(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.comments.iter().map(|(a, c)| (*a, c.as_str()))
+    }
+
+    pub fn label(&self, address: u64) -> Option<&str> {
+        self.labels.get(&address).map(String::as_str)
+    }
+
+    pub fn set_label<S: Into<String>>(&mut self, address: u64, label: S) -> Option<String> {
+        self.labels.insert(address, label.into())
+    }
+
+    pub fn remove_label(&mut self, address: u64) -> Option<String> {
+        self.labels.remove(&address)
+    }
+
+    pub fn labels(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.labels.iter().map(|(a, l)| (*a, l.as_str()))
+    }
+
+    pub fn bookmark(&self, address: u64) -> Option<&str> {
+        self.bookmarks.get(&address).map(String::as_str)
+    }
+
+    pub fn set_bookmark<S: Into<String>>(&mut self, address: u64, description: S) -> Option<String> {
+        self.bookmarks.insert(address, description.into())
+    }
+
+    pub fn remove_bookmark(&mut self, address: u64) -> Option<String> {
+        self.bookmarks.remove(&address)
+    }
+
+    pub fn bookmarks(&self) -> impl Iterator<Item = (u64, &str)> {
+        self.bookmarks.iter().map(|(a, d)| (*a, d.as_str()))
+    }
+
+    pub fn colour(&self, address: u64) -> Option<Colour> {
+        self.colours.get(&address).copied()
+    }
+
+    pub fn set_colour(&mut self, address: u64, colour: Colour) -> Option<Colour> {
+        self.colours.insert(address, colour)
+    }
+
+    pub fn remove_colour(&mut self, address: u64) -> Option<Colour> {
+        self.colours.remove(&address)
+    }
+
+    pub fn colours(&self) -> impl Iterator<Item = (u64, Colour)> + '_ {
+        self.colours.iter().map(|(a, c)| (*a, *c))
+    }
+
+    /// Returns all annotations at `address`.
+    pub fn at(&self, address: u64) -> impl Iterator<Item = Annotation<'_>> {
+        self.range(address..=address).map(|(_, a)| a)
+    }
+
+    /// Returns all annotations within `range`, ordered by address.
+    pub fn range<R>(&self, range: R) -> impl Iterator<Item = (u64, Annotation<'_>)>
+    where
+        R: RangeBounds<u64> + Clone,
+    {
+        let mut annotations = self
+            .comments
+            .range(range.clone())
+            .map(|(a, c)| (*a, Annotation::Comment(c)))
+            .chain(
+                self.labels
+                    .range(range.clone())
+                    .map(|(a, l)| (*a, Annotation::Label(l))),
+            )
+            .chain(
+                self.bookmarks
+                    .range(range.clone())
+                    .map(|(a, d)| (*a, Annotation::Bookmark(d))),
+            )
+            .chain(
+                self.colours
+                    .range(range)
+                    .map(|(a, c)| (*a, Annotation::Colour(*c))),
+            )
+            .collect::<Vec<_>>();
+
+        annotations.sort_by_key(|(a, _)| *a);
+        annotations.into_iter()
+    }
+}
@@ -8,6 +8,11 @@ pub enum Imported {
     File(PathBuf),
 }
 
+/// Receives a backend's progress as `(done, total)` units of work (e.g.,
+/// functions exported); returns false if the import has been cancelled, in
+/// which case the backend should stop and return an error.
+pub type Checkpoint<'a> = dyn FnMut(u64, u64) -> bool + 'a;
+
 /// The Backend trait for adding support for new backend
 /// # Example
 /// ```
@@ -39,6 +44,18 @@ pub trait Backend {
     fn is_preferred_for(&self, path: &Url) -> Option<bool>;
 
     fn import(&self, program: &Url) -> Result<Imported, Self::Error>;
+
+    /// Imports `program` as [`Backend::import`], reporting progress to
+    /// `checkpoint` between units of work. Backends that cannot report
+    /// their progress need not implement this.
+    fn import_with(
+        &self,
+        program: &Url,
+        checkpoint: &mut Checkpoint,
+    ) -> Result<Imported, Self::Error> {
+        let _ = checkpoint;
+        self.import(program)
+    }
 }
 
 /// Wrapper for Backend 
@@ -69,6 +86,14 @@ where
     fn import(&self, program: &Url) -> Result<Imported, Self::Error> {
         self.0.import(program).map_err(|e| e.into())
     }
+
+    fn import_with(
+        &self,
+        program: &Url,
+        checkpoint: &mut Checkpoint,
+    ) -> Result<Imported, Self::Error> {
+        self.0.import_with(program, checkpoint).map_err(|e| e.into())
+    }
 }
 
 #[repr(transparent)]
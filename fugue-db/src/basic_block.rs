@@ -2,14 +2,19 @@ use fugue_ir::disassembly::{ContextDatabase, IRBuilderArena};
 use fugue_ir::PCode;
 use fugue_ir::Translator;
 use fugue_ir::il::Instruction;
+use fugue_ir::il::ecode::ECode;
 use iset::IntervalMap;
 
+use std::sync::OnceLock;
+
 use crate::ArchitectureDef;
 use crate::Id;
 use crate::IntraRef;
 use crate::Segment;
+use crate::SegmentBytes;
 
 use crate::error::Error;
+use crate::hash::{HashOptions, SemanticHasher};
 use crate::schema;
 
 use educe::Educe;
@@ -31,10 +36,27 @@ pub struct BasicBlock<'db> {
         Hash(ignore)
     )]
     translator: &'db Translator,
+    #[educe(
+        Debug(ignore),
+        PartialEq(ignore),
+        PartialOrd(ignore),
+        Ord(ignore),
+        Hash(ignore)
+    )]
+    pcode: OnceLock<Vec<PCode>>,
+    #[educe(
+        Debug(ignore),
+        PartialEq(ignore),
+        PartialOrd(ignore),
+        Ord(ignore),
+        Hash(ignore)
+    )]
+    ecode: OnceLock<Vec<ECode>>,
 }
 
 impl<'db> BasicBlock<'db> {
-    #[doc(hidden)]
+    /// The index of the block's architecture within the database's
+    /// architectures; see [`crate::Database::architecture`].
     pub fn architecture(&self) -> usize {
         self.architecture_id.index()
     }
@@ -59,20 +81,32 @@ impl<'db> BasicBlock<'db> {
         &self.predecessors
     }
 
+    /// The block's bytes, which must be held in memory.
+    ///
+    /// # Panics
+    ///
+    /// If its segment's contents are loaded lazily; see [`Segment::bytes`].
     pub fn bytes(&self) -> &'db [u8] {
         let offset = (self.address() - self.segment.address()) as usize;
         &self.segment.bytes()[offset..offset + self.len()]
     }
 
+    /// The block's bytes, reading them from the database file if its
+    /// segment's contents are loaded lazily.
+    pub fn try_bytes(&self) -> Result<SegmentBytes, Error> {
+        let offset = (self.address() - self.segment.address()) as usize;
+        Ok(self.segment.try_bytes()?.slice(offset..offset + self.len()))
+    }
+
     pub fn translate_with<F, O>(&self, context: &mut ContextDatabase, mut f: F) -> Result<Vec<O>, Error>
     where F: FnMut(&'db Translator, &mut ContextDatabase, u64, &[u8]) -> Result<(O, usize), Error> {
         let mut offset = 0;
         let mut outputs = Vec::with_capacity(16);
 
         let block_addr = self.address();
-        let bytes = self.bytes();
+        let bytes = self.try_bytes()?;
 
-        while offset < self.bytes().len() {
+        while offset < bytes.len() {
             let address = block_addr + offset as u64;
             let (output, length) = f(self.translator, context, address, &bytes[offset..])?;
 
@@ -112,6 +146,68 @@ impl<'db> BasicBlock<'db> {
         self.lift_with(&mut context)
     }
 
+    pub fn lift_ecode_with(&self, context: &mut ContextDatabase) -> Result<Vec<ECode>, Error> {
+        self.translate_with(context, |translator, context, address, bytes| {
+            let pcode = translator.lift_pcode(context, translator.address(address), bytes)
+                .map_err(|source| Error::Lifting { address, source })?;
+            let length = pcode.length();
+            let ecode = ECode::from_pcode(translator, pcode)
+                .map_err(|source| Error::Lifting { address, source: source.into() })?;
+            Ok((ecode, length))
+        })
+    }
+
+    pub fn lift_ecode(&self) -> Result<Vec<ECode>, Error> {
+        let mut context = self.translator.context_database();
+        self.lift_ecode_with(&mut context)
+    }
+
+    /// The block's instructions lifted to p-code using the translator for
+    /// its architecture. The instructions are lifted on first use and
+    /// cached, hence later calls do not lift the block again.
+    pub fn instructions(&self) -> Result<&[PCode], Error> {
+        if let Some(pcode) = self.pcode.get() {
+            return Ok(pcode)
+        }
+
+        let pcode = self.lift()?;
+        Ok(self.pcode.get_or_init(|| pcode))
+    }
+
+    /// The block's instructions lifted to ECode; see
+    /// [`BasicBlock::instructions`].
+    pub fn ecode(&self) -> Result<&[ECode], Error> {
+        if let Some(ecode) = self.ecode.get() {
+            return Ok(ecode)
+        }
+
+        let ecode = self.lift_ecode()?;
+        Ok(self.ecode.get_or_init(|| ecode))
+    }
+
+    pub(crate) fn translator(&self) -> &'db Translator {
+        self.translator
+    }
+
+    /// Computes a stable hash of the block's lifted IL; see [`HashOptions`]
+    /// for the default normalisation.
+    pub fn semantic_hash(&self) -> Result<u64, Error> {
+        self.semantic_hash_with(HashOptions::default())
+    }
+
+    pub fn semantic_hash_with(&self, options: HashOptions) -> Result<u64, Error> {
+        let mut hasher = SemanticHasher::new(options);
+        self.hash_into(&mut hasher)?;
+        Ok(hasher.finish())
+    }
+
+    pub(crate) fn hash_into(&self, hasher: &mut SemanticHasher) -> Result<(), Error> {
+        for pcode in self.instructions()? {
+            hasher.hash_pcode(pcode);
+        }
+        Ok(())
+    }
+
     pub(crate) fn from_reader(
         reader: schema::BasicBlock,
         segments: &'db IntervalMap<u64, Segment>,
@@ -146,7 +242,11 @@ impl<'db> BasicBlock<'db> {
                 .into_iter()
                 .map(IntraRef::from_reader)
                 .collect::<Result<Vec<_>, _>>()?,
-            translator: &translators[arch_index],
+            translator: translators
+                .get(arch_index)
+                .ok_or_else(|| Error::InvalidBlockArchitecture(address))?,
+            pcode: OnceLock::new(),
+            ecode: OnceLock::new(),
         })
     }
 
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::Error;
+
+// contents by file offset and length, with the tick of their last use
+type Entries = HashMap<(u64, usize), (Arc<[u8]>, u64)>;
+
+#[derive(Default)]
+struct CacheState {
+    budget: usize,
+    resident: usize,
+    tick: u64,
+    entries: Entries,
+}
+
+impl CacheState {
+    fn evict(&mut self, keep: Option<(u64, usize)>) {
+        while self.resident > self.budget {
+            let lru = self
+                .entries
+                .iter()
+                .filter(|(key, _)| Some(**key) != keep)
+                .min_by_key(|(_, (_, tick))| *tick)
+                .map(|(key, _)| *key);
+
+            if let Some(key) = lru {
+                let (bytes, _) = self.entries.remove(&key).unwrap();
+                self.resident -= bytes.len();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// Segment contents loaded on demand from a database file; see
+/// [`Database::from_file_lazy`](crate::Database::from_file_lazy). The
+/// most recently used contents are retained while they fit within the
+/// cache's budget (in bytes). Contents still referenced by a
+/// [`SegmentBytes`](crate::SegmentBytes) are not freed until released, so
+/// the budget bounds what the cache retains, rather than what is resident.
+pub struct SegmentCache {
+    path: PathBuf,
+    file: Mutex<File>,
+    state: Mutex<CacheState>,
+}
+
+impl SegmentCache {
+    pub(crate) fn new(file: File, path: PathBuf, budget: usize) -> Self {
+        Self {
+            path,
+            file: Mutex::new(file),
+            state: Mutex::new(CacheState {
+                budget,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The database file the contents are loaded from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn budget(&self) -> usize {
+        self.state().budget
+    }
+
+    /// Sets the cache's budget, evicting the least recently used contents
+    /// until the cache is within it.
+    pub fn set_budget(&self, budget: usize) {
+        let mut state = self.state();
+        state.budget = budget;
+        state.evict(None);
+    }
+
+    /// The number of bytes retained by the cache.
+    pub fn resident(&self) -> usize {
+        self.state().resident
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.entries.clear();
+        state.resident = 0;
+    }
+
+    pub(crate) fn load(&self, offset: u64, length: usize) -> Result<Arc<[u8]>, Error> {
+        let key = (offset, length);
+        {
+            let mut state = self.state();
+            state.tick += 1;
+
+            let tick = state.tick;
+            if let Some((bytes, last_used)) = state.entries.get_mut(&key) {
+                *last_used = tick;
+                return Ok(bytes.clone());
+            }
+        }
+
+        // the cache is not locked while reading, so other segments remain
+        // available; if another thread loads the same contents meanwhile,
+        // the first to finish is kept
+        let mut bytes = vec![0u8; length];
+        {
+            let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+            file.seek(SeekFrom::Start(offset))
+                .and_then(|_| file.read_exact(&mut bytes))
+                .map_err(Error::CannotReadFile)?;
+        }
+        let bytes = Arc::<[u8]>::from(bytes);
+
+        let mut state = self.state();
+        if length > state.budget {
+            return Ok(bytes);
+        }
+
+        state.tick += 1;
+
+        let tick = state.tick;
+        if let Some((bytes, last_used)) = state.entries.get_mut(&key) {
+            *last_used = tick;
+            return Ok(bytes.clone());
+        }
+
+        state.entries.insert(key, (bytes.clone(), tick));
+        state.resident += length;
+        state.evict(Some(key));
+
+        Ok(bytes)
+    }
+}
+
+impl fmt::Debug for SegmentCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state();
+        f.debug_struct("SegmentCache")
+            .field("path", &self.path)
+            .field("budget", &state.budget)
+            .field("resident", &state.resident)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_segment_cache() -> Result<(), Error> {
+        let mut file = tempfile::tempfile().map_err(Error::CannotWriteFile)?;
+        file.write_all(&(0..=255).collect::<Vec<u8>>())
+            .map_err(Error::CannotWriteFile)?;
+
+        let cache = SegmentCache::new(file, PathBuf::from("test.fdb"), 96);
+
+        assert_eq!(&*cache.load(0, 4)?, &[0, 1, 2, 3]);
+        assert_eq!(&*cache.load(64, 64)?, &(64..128).collect::<Vec<u8>>()[..]);
+        assert_eq!(cache.resident(), 68);
+
+        // evicts the contents at 64, which were used least recently
+        cache.load(0, 4)?;
+        cache.load(128, 32)?;
+        assert_eq!(cache.resident(), 36);
+
+        // too large to retain
+        assert_eq!(cache.load(0, 128)?.len(), 128);
+        assert_eq!(cache.resident(), 36);
+
+        cache.set_budget(32);
+        assert_eq!(cache.resident(), 32);
+
+        assert!(cache.load(250, 16).is_err());
+
+        Ok(())
+    }
+}
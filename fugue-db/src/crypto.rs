@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+
+use fugue_bytes::Endian;
+use fugue_ir::il::pcode::Operand;
+
+use crate::error::Error;
+use crate::hash::for_each_operand;
+use crate::Function;
+use crate::Segment;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CryptoMatchKind {
+    /// A table or sequence of initial values stored in a segment.
+    Table,
+    /// An immediate operand of an instruction.
+    Immediate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A known cryptographic or checksum constant located within a database.
+pub struct CryptoMatch {
+    address: u64,
+    name: &'static str,
+    kind: CryptoMatchKind,
+}
+
+impl CryptoMatch {
+    /// The start of a matched table, or the instruction referencing a
+    /// matched immediate.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn kind(&self) -> CryptoMatchKind {
+        self.kind
+    }
+}
+
+enum Pattern {
+    Bytes(&'static [u8]),
+    Words(&'static [u32]),
+    DoubleWords(&'static [u64]),
+}
+
+impl Pattern {
+    fn encode(&self, endian: Endian) -> Vec<u8> {
+        match self {
+            Self::Bytes(bytes) => bytes.to_vec(),
+            Self::Words(words) => words
+                .iter()
+                .flat_map(|w| match endian {
+                    Endian::Big => w.to_be_bytes(),
+                    Endian::Little => w.to_le_bytes(),
+                })
+                .collect(),
+            Self::DoubleWords(words) => words
+                .iter()
+                .flat_map(|w| match endian {
+                    Endian::Big => w.to_be_bytes(),
+                    Endian::Little => w.to_le_bytes(),
+                })
+                .collect(),
+        }
+    }
+}
+
+// Only a prefix of each table is matched; it is long enough to make false
+// positives unlikely.
+const TABLES: &[(&str, Pattern)] = &[
+    (
+        "AES S-box",
+        Pattern::Bytes(&[
+            0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7,
+            0xab, 0x76, 0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf,
+            0x9c, 0xa4, 0x72, 0xc0,
+        ]),
+    ),
+    (
+        "AES inverse S-box",
+        Pattern::Bytes(&[
+            0x52, 0x09, 0x6a, 0xd5, 0x30, 0x36, 0xa5, 0x38, 0xbf, 0x40, 0xa3, 0x9e, 0x81, 0xf3,
+            0xd7, 0xfb, 0x7c, 0xe3, 0x39, 0x82, 0x9b, 0x2f, 0xff, 0x87, 0x34, 0x8e, 0x43, 0x44,
+            0xc4, 0xde, 0xe9, 0xcb,
+        ]),
+    ),
+    (
+        "MD5 initial values",
+        Pattern::Words(&[0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476]),
+    ),
+    (
+        "MD5 sine table",
+        Pattern::Words(&[0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee]),
+    ),
+    (
+        "SHA-1 initial values",
+        Pattern::Words(&[0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0]),
+    ),
+    (
+        "SHA-224 initial values",
+        Pattern::Words(&[0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939]),
+    ),
+    (
+        "SHA-256 initial values",
+        Pattern::Words(&[
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ]),
+    ),
+    (
+        "SHA-256 round constants",
+        Pattern::Words(&[0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5]),
+    ),
+    (
+        "SHA-512 initial values",
+        Pattern::DoubleWords(&[
+            0x6a09e667f3bcc908,
+            0xbb67ae8584caa73b,
+            0x3c6ef372fe94f82b,
+            0xa54ff53a5f1d36f1,
+        ]),
+    ),
+    (
+        "Blowfish P-array",
+        Pattern::Words(&[0x243f6a88, 0x85a308d3, 0x13198a2e, 0x03707344]),
+    ),
+    (
+        "CRC-32 table",
+        Pattern::Words(&[0x00000000, 0x77073096, 0xee0e612c, 0x990951ba]),
+    ),
+    (
+        "CRC-32 table (non-reflected)",
+        Pattern::Words(&[0x00000000, 0x04c11db7, 0x09823b6e, 0x0d4326d9]),
+    ),
+    (
+        "CRC-32C table",
+        Pattern::Words(&[0x00000000, 0xf26b8303, 0xe13b70f7, 0x1350f3f4]),
+    ),
+];
+
+const IMMEDIATES: &[(u32, &str)] = &[
+    (0x04c11db7, "CRC-32 polynomial"),
+    (0x5a827999, "SHA-1 round constant"),
+    (0x67452301, "MD5/SHA-1 initial value"),
+    (0x6a09e667, "SHA-256 initial value"),
+    (0x6ed9eba1, "SHA-1 round constant"),
+    (0x82f63b78, "CRC-32C polynomial (reflected)"),
+    (0x8f1bbcdc, "SHA-1 round constant"),
+    (0x98badcfe, "MD5/SHA-1 initial value"),
+    (0x9e3779b9, "TEA/XTEA delta"),
+    (0xbb67ae85, "SHA-256 initial value"),
+    (0xc3d2e1f0, "SHA-1 initial value"),
+    (0xca62c1d6, "SHA-1 round constant"),
+    (0xd76aa478, "MD5 sine table"),
+    (0xedb88320, "CRC-32 polynomial (reflected)"),
+    (0xefcdab89, "MD5/SHA-1 initial value"),
+];
+
+const WIDE_IMMEDIATES: &[(u64, &str)] = &[
+    (0x6a09e667f3bcc908, "SHA-512 initial value"),
+    (0xbb67ae8584caa73b, "SHA-512 initial value"),
+    (0xcbbb9d5dc1059ed8, "SHA-384 initial value"),
+];
+
+/// Scans the contents of `segment` for known tables. Where more than one
+/// table matches at an address, e.g., the MD5 and SHA-1 initial values,
+/// only the longest is reported.
+pub fn scan_segment(segment: &Segment) -> Result<Vec<CryptoMatch>, Error> {
+    if segment.is_external() {
+        return Ok(Vec::new());
+    }
+
+    let bytes = segment.try_bytes()?;
+    let mut matches = BTreeMap::<u64, (usize, &'static str)>::new();
+
+    for (name, pattern) in TABLES {
+        let needle = pattern.encode(segment.endian());
+        if needle.len() > bytes.len() {
+            continue;
+        }
+
+        for (offset, window) in bytes.windows(needle.len()).enumerate() {
+            if window != needle.as_slice() {
+                continue;
+            }
+
+            let address = segment.address() + offset as u64;
+            let entry = matches.entry(address).or_insert((needle.len(), *name));
+            if needle.len() > entry.0 {
+                *entry = (needle.len(), *name);
+            }
+        }
+    }
+
+    Ok(matches
+        .into_iter()
+        .map(|(address, (_, name))| CryptoMatch {
+            address,
+            name,
+            kind: CryptoMatchKind::Table,
+        })
+        .collect())
+}
+
+/// Scans the lifted instructions of `function` for immediate operands
+/// that are known constants. 32-bit constants are also matched when zero-
+/// or sign-extended to 64 bits.
+pub fn scan_function(function: &Function) -> Result<Vec<CryptoMatch>, Error> {
+    let mut matches = Vec::new();
+
+    for block in function.blocks() {
+        if block.segment().is_external() {
+            continue;
+        }
+
+        for pcode in block.lift()? {
+            let address = pcode.address().offset();
+            let mut names = Vec::new();
+
+            for op in pcode.operations() {
+                for_each_operand(op, |operand| {
+                    if let Operand::Constant { value, size } = operand {
+                        if let Some(name) = immediate(*value, *size) {
+                            if !names.contains(&name) {
+                                names.push(name);
+                            }
+                        }
+                    }
+                });
+            }
+
+            matches.extend(names.into_iter().map(|name| CryptoMatch {
+                address,
+                name,
+                kind: CryptoMatchKind::Immediate,
+            }));
+        }
+    }
+
+    Ok(matches)
+}
+
+fn immediate(value: u64, size: usize) -> Option<&'static str> {
+    let narrow = match size {
+        4 => Some(value as u32),
+        8 if value >> 32 == 0 || value >> 32 == 0xffffffff => Some(value as u32),
+        _ => None,
+    };
+
+    if size == 8 {
+        if let Some((_, name)) = WIDE_IMMEDIATES.iter().find(|(v, _)| *v == value) {
+            return Some(*name);
+        }
+    }
+
+    let narrow = narrow?;
+    IMMEDIATES
+        .binary_search_by_key(&narrow, |(v, _)| *v)
+        .ok()
+        .map(|i| IMMEDIATES[i].1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_immediates_sorted() {
+        assert!(IMMEDIATES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_match_immediates() {
+        assert_eq!(immediate(0xedb88320, 4), Some("CRC-32 polynomial (reflected)"));
+        assert_eq!(immediate(0xffffffffca62c1d6, 8), Some("SHA-1 round constant"));
+        assert_eq!(immediate(0x6a09e667f3bcc908, 8), Some("SHA-512 initial value"));
+        assert_eq!(immediate(0x1234ca62c1d6, 8), None);
+        assert_eq!(immediate(0x9e37, 2), None);
+    }
+}
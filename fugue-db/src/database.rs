@@ -1,26 +1,46 @@
+// the constructors generated for `DatabaseImpl` take one argument per field
+#![allow(clippy::too_many_arguments)]
+
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{BufReader, Read, Write};
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLockReadGuard, RwLockWriteGuard};
 
 use fs_extra::file::{copy as copy_file, CopyOptions};
 
 use fugue_ir::LanguageDB;
 
 use fugue_ir::Translator;
+use fugue_ir::disassembly::ContextDatabase;
+use fugue_ir::il::dataflow::{Confidence, IndirectCallResolver, InferredPrototype};
 use iset::IntervalMap;
+use memmap2::Mmap;
 use unicase::UniCase;
 use url::Url;
 
+use crate::analysis::{Analyses, Shared};
+use crate::annotation::Annotations;
 use crate::architecture::{self, ArchitectureDef};
 use crate::BasicBlock;
+use crate::CryptoMatch;
 use crate::Function;
 use crate::Id;
+use crate::IndirectCall;
+use crate::InterRef;
 use crate::Metadata;
 use crate::Segment;
+use crate::SegmentBytes;
+use crate::SegmentCache;
+use crate::SymbolTable;
 
 use crate::backend::{Backend, DatabaseImporterBackend, Imported};
+use crate::crypto;
 use crate::error::Error;
+use crate::export::{self, ExportFormat};
+use crate::indirect::SegmentMemory;
 use crate::schema;
+use crate::trailer::Trailer;
 
 #[ouroboros::self_referencing(chain_hack)]
 #[derive(educe::Educe)]
@@ -34,6 +54,13 @@ pub struct DatabaseImpl {
     #[covariant]
     functions: Vec<Function<'this>>,
     metadata: Metadata,
+    annotations: Shared<Annotations>,
+    #[educe(Hash(ignore))]
+    symbols: SymbolTable,
+    #[educe(PartialEq(ignore), Eq(ignore), Hash(ignore))]
+    analyses: Analyses,
+    #[educe(PartialEq(ignore), Eq(ignore), Hash(ignore))]
+    segment_cache: Option<Arc<SegmentCache>>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
@@ -47,6 +74,10 @@ impl Default for DatabaseImpl {
             Box::new(IntervalMap::new()),
             |_, _| Vec::new(),
             Metadata::default(),
+            Shared::default(),
+            SymbolTable::default(),
+            Analyses::default(),
+            None,
         )
     }
 }
@@ -71,6 +102,77 @@ impl Database {
         self.0.borrow_translators().iter()
     }
 
+    /// The architecture at `index`, as returned by, e.g.,
+    /// [`BasicBlock::architecture`].
+    pub fn architecture(&self, index: usize) -> Option<&ArchitectureDef> {
+        self.translator(index).map(Translator::architecture)
+    }
+
+    pub fn architecture_index(&self, architecture: &ArchitectureDef) -> Option<usize> {
+        self.architectures().position(|arch| arch == architecture)
+    }
+
+    pub fn translator(&self, index: usize) -> Option<&Translator> {
+        self.0.borrow_translators().get(index)
+    }
+
+    pub fn translator_for(&self, architecture: &ArchitectureDef) -> Option<&Translator> {
+        self.translators().find(|t| t.architecture() == architecture)
+    }
+
+    /// The translator for the code at `address`: that of the block
+    /// containing it or, failing that, that of the segment containing it,
+    /// if all blocks within the segment share an architecture.
+    pub fn translator_at(&self, address: u64) -> Option<&Translator> {
+        if let Some(block) = self
+            .blocks()
+            .find(|b| b.address() <= address && address < b.address() + b.len() as u64)
+        {
+            return self.translator(block.architecture());
+        }
+
+        let segment = self
+            .segments()
+            .iter(address..address + 1)
+            .map(|(_, s)| s)
+            .next()?;
+
+        match &*self.segment_architectures(segment) {
+            [index] => self.translator(*index),
+            _ => None,
+        }
+    }
+
+    /// A fresh context database for decoding the code at `address`; see
+    /// [`Database::translator_at`].
+    pub fn context_database_at(&self, address: u64) -> Option<ContextDatabase> {
+        self.translator_at(address).map(Translator::context_database)
+    }
+
+    /// The distinct architectures of the blocks within `segment`, as
+    /// indices into the database's architectures; containers such as
+    /// Mach-O fat binaries, or firmware images for multiple cores, may
+    /// yield segments for each architecture.
+    pub fn segment_architectures(&self, segment: &Segment) -> Vec<usize> {
+        let segment = segment.id();
+        let mut architectures = self
+            .blocks()
+            .filter(|b| b.segment().id() == segment)
+            .map(BasicBlock::architecture)
+            .collect::<Vec<_>>();
+
+        architectures.sort_unstable();
+        architectures.dedup();
+        architectures
+    }
+
+    /// The functions whose entry blocks are of the architecture at `index`.
+    pub fn functions_for(&self, index: usize) -> impl Iterator<Item = &Function> {
+        self.functions()
+            .iter()
+            .filter(move |f| f.architecture() == Some(index))
+    }
+
     pub fn segments(&self) -> &IntervalMap<u64, Segment> {
         self.0.borrow_segments()
     }
@@ -138,28 +240,287 @@ impl Database {
             .sum()
     }
 
+    /// The blocks within the segment `name`, with their bytes; the
+    /// segment's contents are read once, if they are loaded lazily.
     pub fn blocks_in<S: AsRef<str>>(
         &self,
         name: S,
-    ) -> Option<impl Iterator<Item = (&BasicBlock, &[u8])>> {
-        let segment = self.segment(name)?.id();
-        Some(self.blocks().filter_map(move |b| {
-            if b.segment().id() == segment {
-                Some((b, b.bytes()))
+    ) -> Result<impl Iterator<Item = (&BasicBlock, SegmentBytes)>, Error> {
+        let name = name.as_ref();
+        let segment = self
+            .segment(name)
+            .ok_or_else(|| Error::NoSegment(name.to_owned()))?;
+
+        let id = segment.id();
+        let base = segment.address();
+        let bytes = segment.try_bytes()?;
+
+        Ok(self.blocks().filter_map(move |b| {
+            if b.segment().id() == id {
+                let offset = (b.address() - base) as usize;
+                Some((b, bytes.slice(offset..offset + b.len())))
             } else {
                 None
             }
         }))
     }
 
+    /// The database's symbols; when first imported, these are the names
+    /// of its functions. Changes to the symbols (e.g., renaming a function)
+    /// are saved by [`Database::to_file`].
+    pub fn symbols(&self) -> &SymbolTable {
+        self.0.borrow_symbols()
+    }
+
+    pub fn symbols_mut(&mut self) -> &mut SymbolTable {
+        self.0.with_symbols_mut(|symbols| symbols)
+    }
+
+    /// Proposes targets for the indirect calls within `function` by
+    /// emulating the code leading up to each call; loads are served from
+    /// the database's segments. Targets that start a known function are
+    /// linked to it; see [`Database::link_indirect_calls`] to record them
+    /// as calls.
+    pub fn resolve_indirect_calls(&self, function: &Function) -> Result<Vec<IndirectCall<'_>>, Error> {
+        let lifted = function.lift_ecode()?;
+        let graph = function.dataflow_graph(&lifted);
+
+        let resolver = IndirectCallResolver::new(SegmentMemory::new(self.segments()));
+
+        Ok(resolver
+            .resolve(&graph)
+            .into_iter()
+            .filter_map(|resolved| {
+                let point = resolved.point();
+                let mut index = point.index;
+                let insn = lifted[point.block].iter().find(|insn| {
+                    let found = index < insn.operations().len();
+                    if !found {
+                        index -= insn.operations().len();
+                    }
+                    found
+                })?;
+
+                let target_id = self
+                    .functions()
+                    .iter()
+                    .position(|f| f.address() == resolved.target())
+                    .map(Id::from);
+
+                Some(IndirectCall::new(
+                    insn.address().offset(),
+                    resolved.target(),
+                    target_id,
+                    resolved.confidence(),
+                ))
+            })
+            .collect())
+    }
+
+    /// Resolves the indirect calls within each function, as
+    /// [`Database::resolve_indirect_calls`], and records a call from the
+    /// function to each target resolved with at least `confidence` that
+    /// starts a known function; such targets then appear among the
+    /// function's references, and in call graphs, e.g., those used by
+    /// [`Database::inferred_prototype`]. Returns the number of calls
+    /// recorded; if any are, cached analysis results are cleared, as they
+    /// may depend upon the call graph.
+    pub fn link_indirect_calls(&mut self, confidence: Confidence) -> Result<usize, Error> {
+        let mut resolved = Vec::new();
+        for (index, function) in self.functions().iter().enumerate() {
+            for call in self.resolve_indirect_calls(function)? {
+                if call.confidence() < confidence {
+                    continue;
+                }
+                if let Some(target_id) = call.target_id() {
+                    resolved.push((index, call.address(), target_id.index()));
+                }
+            }
+        }
+
+        let added = self.0.with_functions_mut(|functions| {
+            let mut added = 0;
+            for (index, address, target) in resolved {
+                let reference = InterRef::new(address, Id::from(index), Id::from(target), true);
+                if functions[index].add_reference(reference) {
+                    added += 1;
+                }
+            }
+            added
+        });
+
+        if added != 0 {
+            self.analyses().clear();
+        }
+
+        Ok(added)
+    }
+
+    /// Infers the prototype of `function`, as
+    /// [`Function::inferred_prototype_with`], having first inferred those
+    /// of the functions it calls, transitively, so that calls are
+    /// summarised by their callees' prototypes. Calls within cycles of
+    /// recursive functions, and calls to functions whose prototypes cannot
+    /// be inferred, are treated as opaque. Prototypes are cached within
+    /// [`Database::analyses`].
+    pub fn inferred_prototype(&self, function: &Function) -> Result<Arc<InferredPrototype>, Error> {
+        let address = function.address();
+        let root = if let Some(index) = self.functions().iter().position(|f| f.address() == address) {
+            index
+        } else {
+            return self.infer_with_callees(function).map(Arc::new);
+        };
+
+        let analyses = self.analyses();
+        if let Some(prototype) = analyses.get::<InferredPrototype>(Id::from(root)) {
+            return Ok(prototype);
+        }
+
+        // visit the call graph depth-first, inferring each function's
+        // prototype after those of its callees
+        let mut visiting = HashSet::from([root]);
+        let mut failed = HashSet::new();
+        let mut stack = vec![(root, self.callees(root))];
+
+        while let Some((index, callees)) = stack.last_mut() {
+            if let Some(callee) = callees.pop() {
+                if !visiting.contains(&callee)
+                    && !failed.contains(&callee)
+                    && analyses.get::<InferredPrototype>(Id::from(callee)).is_none()
+                {
+                    visiting.insert(callee);
+                    stack.push((callee, self.callees(callee)));
+                }
+                continue;
+            }
+
+            let index = *index;
+            stack.pop();
+            visiting.remove(&index);
+
+            match self.infer_with_callees(&self.functions()[index]) {
+                Ok(prototype) => {
+                    analyses.insert(Id::from(index), prototype);
+                }
+                Err(e) if index == root => return Err(e),
+                Err(_) => {
+                    failed.insert(index);
+                }
+            }
+        }
+
+        Ok(analyses
+            .get::<InferredPrototype>(Id::from(root))
+            .expect("inferred prototype"))
+    }
+
+    /// The indices of the functions called by the function at `index`.
+    fn callees(&self, index: usize) -> Vec<usize> {
+        let mut callees = self.functions()[index]
+            .references()
+            .iter()
+            .filter(|r| r.is_call() && r.target_id().index() < self.functions().len())
+            .map(|r| r.target_id().index())
+            .collect::<Vec<_>>();
+
+        callees.sort_unstable();
+        callees.dedup();
+        callees
+    }
+
+    fn infer_with_callees(&self, function: &Function) -> Result<InferredPrototype, Error> {
+        let callees = function
+            .references()
+            .iter()
+            .filter(|r| r.is_call())
+            .filter_map(|r| {
+                let callee = self.functions().get(r.target_id().index())?;
+                let prototype = self.analyses().get::<InferredPrototype>(r.target_id())?;
+                Some((callee.address(), prototype))
+            })
+            .collect::<HashMap<_, _>>();
+
+        function.inferred_prototype_with(|address| callees.get(&address).map(|p| &**p))
+    }
+
+    /// Locates known cryptographic and checksum constants, both as tables
+    /// within segments and as immediates within functions.
+    pub fn crypto_constants(&self) -> Result<Vec<CryptoMatch>, Error> {
+        let mut matches = Vec::new();
+
+        for segment in self.segments().values(..) {
+            matches.extend(crypto::scan_segment(segment)?);
+        }
+
+        for function in self.functions() {
+            matches.extend(crypto::scan_function(function)?);
+        }
+
+        matches.sort();
+        Ok(matches)
+    }
+
+    /// Bookmarks each constant found by [`Database::crypto_constants`],
+    /// leaving existing bookmarks in place; returns the number of
+    /// bookmarks added.
+    pub fn annotate_crypto_constants(&mut self) -> Result<usize, Error> {
+        let matches = self.crypto_constants()?;
+        let annotations = self.annotations_mut();
+
+        let mut added = 0;
+        for m in matches {
+            if annotations.bookmark(m.address()).is_none() {
+                annotations.set_bookmark(m.address(), m.name());
+                added += 1;
+            }
+        }
+
+        Ok(added)
+    }
+
+    /// Writes the database's functions to `writer` in `format`, for use by
+    /// other tools; external functions are not exported.
+    pub fn export_functions<W: Write>(&self, format: ExportFormat, writer: W) -> Result<(), Error> {
+        export::export(self, format, writer)
+    }
+
+    pub fn export_functions_to<P: AsRef<Path>>(&self, format: ExportFormat, path: P) -> Result<(), Error> {
+        let file = File::create(path).map_err(Error::CannotWriteFile)?;
+        self.export_functions(format, BufWriter::new(file))
+    }
+
     pub fn metadata(&self) -> &Metadata {
         self.0.borrow_metadata()
     }
 
-    pub fn from_bytes(bytes: &[u8], language_db: &LanguageDB) -> Result<Self, Error> {
-        let reader = schema::root_as_project(&bytes).map_err(Error::Deserialisation)?;
+    /// User annotations; these are saved by [`Database::to_file`].
+    pub fn annotations(&self) -> RwLockReadGuard<'_, Annotations> {
+        self.0.borrow_annotations().read()
+    }
+
+    /// Locks the annotations for writing, e.g., from an analysis thread
+    /// holding a shared reference to the database.
+    pub fn annotations_write(&self) -> RwLockWriteGuard<'_, Annotations> {
+        self.0.borrow_annotations().write()
+    }
 
-        Self::from_reader(reader, language_db)
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        self.0.with_annotations_mut(|annotations| annotations.get_mut())
+    }
+
+    /// Results derived by analyses, cached by function; see [`Analyses`].
+    pub fn analyses(&self) -> &Analyses {
+        self.0.borrow_analyses()
+    }
+
+    /// The cache of segment contents, if the database was opened using
+    /// [`Database::from_file_lazy`].
+    pub fn segment_cache(&self) -> Option<&SegmentCache> {
+        self.0.borrow_segment_cache().as_deref()
+    }
+
+    pub fn from_bytes(bytes: &[u8], language_db: &LanguageDB) -> Result<Self, Error> {
+        Self::from_bytes_with(bytes, language_db, &mut |_| true)
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P, language_db: &LanguageDB) -> Result<Self, Error> {
@@ -173,9 +534,77 @@ impl Database {
         Self::from_bytes(&bytes, language_db)
     }
 
+    /// Reads a database from `path`, leaving the segments' contents in the
+    /// file until they are used; at most `budget` bytes of contents are
+    /// retained, see [`Database::segment_cache`]. The file is mapped while
+    /// the rest of the database is deserialised, so opening a database does
+    /// not require memory proportional to the size of its segments.
+    pub fn from_file_lazy<P: AsRef<Path>>(
+        path: P,
+        language_db: &LanguageDB,
+        budget: usize,
+    ) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(Error::CannotReadFile)?;
+
+        // SAFETY: the mapping is dropped once the database is deserialised,
+        // and the file is not expected to change while the database is open
+        let bytes = unsafe { Mmap::map(&file) }.map_err(Error::CannotReadFile)?;
+        let cache = Arc::new(SegmentCache::new(file, path.to_owned(), budget));
+
+        let (program, trailer) = Trailer::split(&bytes)?;
+        let reader = schema::root_as_project(program).map_err(Error::Deserialisation)?;
+
+        Self::from_reader(
+            reader,
+            trailer,
+            language_db,
+            Some((&bytes[..], cache)),
+            &mut |_| true,
+        )
+    }
+
+    /// Reads a database from `path` as [`Database::from_file`], reporting
+    /// progress to `progress` as each segment and function is deserialised;
+    /// the import is cancelled if `progress` returns false. The file is
+    /// mapped, rather than read into memory, so that only the deserialised
+    /// database is resident.
+    pub fn from_file_with<P, F>(
+        path: P,
+        language_db: &LanguageDB,
+        mut progress: F,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        F: FnMut(ImportProgress) -> bool,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(Error::CannotReadFile)?;
+
+        // SAFETY: the mapping is dropped once the database is deserialised,
+        // and the file is not expected to change while it is read
+        let bytes = unsafe { Mmap::map(&file) }.map_err(Error::CannotReadFile)?;
+
+        Self::from_bytes_with(&bytes, language_db, &mut progress)
+    }
+
+    fn from_bytes_with(
+        bytes: &[u8],
+        language_db: &LanguageDB,
+        progress: &mut dyn FnMut(ImportProgress) -> bool,
+    ) -> Result<Self, Error> {
+        let (bytes, trailer) = Trailer::split(bytes)?;
+        let reader = schema::root_as_project(bytes).map_err(Error::Deserialisation)?;
+
+        Self::from_reader(reader, trailer, language_db, None, progress)
+    }
+
     fn from_reader<'a>(
         database: schema::Project<'a>,
+        trailer: Option<Trailer>,
         language_db: &LanguageDB,
+        lazy: Option<(&'a [u8], Arc<SegmentCache>)>,
+        progress: &mut dyn FnMut(ImportProgress) -> bool,
     ) -> Result<Self, Error> {
         let metadata = Metadata::from_reader(
             database
@@ -201,37 +630,73 @@ impl Database {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
-        let mut segment_id = 0usize;
         let segments = database
             .segments()
-            .ok_or(Error::DeserialiseField("segments"))?
+            .ok_or(Error::DeserialiseField("segments"))?;
+        let total = segments.len();
+
+        let mut segment_id = 0usize;
+        let segments = segments
             .into_iter()
-            .filter_map(|r| match Segment::from_reader(Id::from(segment_id), &r) {
-                Ok(seg) if seg.len() != 0 => {
-                    segment_id += 1;
-                    Some(Ok((
-                        seg.address()..(seg.address() + seg.len() as u64),
-                        seg,
-                    )))
+            .enumerate()
+            .filter_map(|(done, r)| {
+                if !progress(ImportProgress::Segments { done, total }) {
+                    return Some(Err(Error::ImportCancelled));
+                }
+
+                match Segment::from_reader(
+                    Id::from(segment_id),
+                    &r,
+                    lazy.as_ref().map(|(bytes, cache)| (*bytes, cache)),
+                ) {
+                    Ok(seg) if seg.len() != 0 => {
+                        segment_id += 1;
+                        Some(Ok((
+                            seg.address()..(seg.address() + seg.len() as u64),
+                            seg,
+                        )))
+                    }
+                    Ok(_) => None,
+                    Err(e) => Some(Err(e)),
                 }
-                Ok(_) => None,
-                Err(e) => Some(Err(e)),
             })
             .collect::<Result<IntervalMap<_, _>, Error>>()?;
 
-        Ok(Self(DatabaseImpl::try_new(
+        let (symbols, annotations) = trailer
+            .map(|trailer| (Some(trailer.symbols), trailer.annotations))
+            .unwrap_or_default();
+
+        let mut db = Self(DatabaseImpl::try_new(
             Box::new(translators),
             Box::new(segments),
             |segments, translators| {
-                database
+                let functions = database
                     .functions()
-                    .ok_or(Error::DeserialiseField("functions"))?
+                    .ok_or(Error::DeserialiseField("functions"))?;
+                let total = functions.len();
+
+                functions
                     .into_iter()
-                    .map(|r| Function::from_reader(r, segments, translators))
+                    .enumerate()
+                    .map(|(done, r)| {
+                        if !progress(ImportProgress::Functions { done, total }) {
+                            return Err(Error::ImportCancelled);
+                        }
+                        Function::from_reader(r, segments, translators)
+                    })
                     .collect::<Result<Vec<_>, _>>()
             },
             metadata,
-        )?))
+            Shared::new(annotations),
+            SymbolTable::default(),
+            Analyses::default(),
+            lazy.map(|(_, cache)| cache),
+        )?);
+
+        let symbols = symbols.unwrap_or_else(|| SymbolTable::from_functions(db.functions()));
+        *db.symbols_mut() = symbols;
+
+        Ok(db)
     }
 
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
@@ -246,7 +711,11 @@ impl Database {
         file.write_all(builder.finished_data())
             .map_err(Error::CannotWriteFile)?;
 
-        Ok(())
+        let trailer = Trailer {
+            symbols: self.symbols().clone(),
+            annotations: self.annotations().clone(),
+        };
+        trailer.write(file)
     }
 
     pub(crate) fn to_builder<'a: 'b, 'b>(
@@ -286,12 +755,33 @@ impl Database {
     }
 }
 
+/// The progress of a database import, reported to the callback registered
+/// using [`DatabaseImporter::progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportProgress {
+    /// The named backend is importing the program.
+    Backend(&'static str),
+    /// The named backend has completed `done` of `total` units of work, as
+    /// reported to its [`Checkpoint`](crate::backend::Checkpoint); what a
+    /// unit is depends upon the backend.
+    BackendProgress { name: &'static str, done: u64, total: u64 },
+    /// `done` of the `total` segments of the imported database have been
+    /// deserialised.
+    Segments { done: usize, total: usize },
+    /// `done` of the `total` functions of the imported database have been
+    /// deserialised.
+    Functions { done: usize, total: usize },
+    /// The imported database is being written to the export path.
+    Exporting,
+}
+
 pub struct DatabaseImporter {
     program: Option<url::Url>,
     fdb_path: Option<PathBuf>,
     overwrite_fdb: bool,
     backend_pref: Option<String>,
     backends: Vec<DatabaseImporterBackend>,
+    progress: Option<Box<dyn Fn(ImportProgress) -> bool>>,
 }
 
 impl Default for DatabaseImporter {
@@ -302,6 +792,7 @@ impl Default for DatabaseImporter {
             overwrite_fdb: false,
             backend_pref: None,
             backends: Vec::default(),
+            progress: None,
         }
     }
 }
@@ -366,6 +857,37 @@ impl DatabaseImporter {
         self
     }
 
+    /// Reports the progress of imports to `progress`. If `progress` returns
+    /// false, the import is cancelled at the next opportunity and fails with
+    /// [`Error::ImportCancelled`].
+    pub fn progress<F>(&mut self, progress: F) -> &mut Self
+    where
+        F: Fn(ImportProgress) -> bool + 'static,
+    {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    fn proceed(&self, progress: ImportProgress) -> bool {
+        self.progress.as_ref().map(|f| f(progress)).unwrap_or(true)
+    }
+
+    fn report(&self, progress: ImportProgress) -> Result<(), Error> {
+        if self.proceed(progress) {
+            Ok(())
+        } else {
+            Err(Error::ImportCancelled)
+        }
+    }
+
+    fn read_database(&self, path: &Path, language_db: &LanguageDB) -> Result<Database, Error> {
+        if let Some(ref progress) = self.progress {
+            Database::from_file_with(path, language_db, progress)
+        } else {
+            Database::from_file(path, language_db)
+        }
+    }
+
     pub fn import(&self, language_db: &LanguageDB) -> Result<Database, Error> {
         let program = if let Some(ref program) = self.program {
             program.clone()
@@ -386,8 +908,10 @@ impl DatabaseImporter {
 
             // importing from an existing database
             if program.extension().map(|e| e == "fdb").unwrap_or(false) {
-                if let Ok(db) = Database::from_file(&program, language_db) {
-                    return Ok(db);
+                match self.read_database(&program, language_db) {
+                    Ok(db) => return Ok(db),
+                    Err(Error::ImportCancelled) => return Err(Error::ImportCancelled),
+                    Err(_) => (),
                 }
             };
         }
@@ -426,17 +950,30 @@ impl DatabaseImporter {
         let mut res = Err(Error::NoBackendsAvailable);
         for (_, backend) in backends {
             // log::debug!("Trying backend {}", backend.name());
-            res = backend.import(&program);
-            if res.is_ok() {
+            self.report(ImportProgress::Backend(backend.name()))?;
+
+            // the backend's error need not indicate that it was cancelled,
+            // so note it here
+            let mut cancelled = false;
+            res = backend.import_with(&program, &mut |done, total| {
+                let name = backend.name();
+                cancelled |= !self.proceed(ImportProgress::BackendProgress { name, done, total });
+                !cancelled
+            });
+
+            if cancelled {
+                return Err(Error::ImportCancelled);
+            } else if res.is_ok() {
                 break;
             }
         }
 
         match res {
             Ok(Imported::File(ref path)) => {
-                let db = Database::from_file(path, language_db)?;
+                let db = self.read_database(path, language_db)?;
                 if let Some(ref fdb_path) = self.fdb_path {
                     if path != fdb_path {
+                        self.report(ImportProgress::Exporting)?;
                         // copy it
                         copy_file(
                             path,
@@ -453,9 +990,18 @@ impl DatabaseImporter {
                 Ok(db)
             }
             Ok(Imported::Bytes(ref bytes)) => {
-                let db = Database::from_bytes(bytes, language_db)?;
+                let db = if let Some(ref progress) = self.progress {
+                    Database::from_bytes_with(bytes, language_db, &mut |p| progress(p))?
+                } else {
+                    Database::from_bytes(bytes, language_db)?
+                };
                 if let Some(ref fdb_path) = self.fdb_path {
-                    db.to_file(fdb_path)?;
+                    // write the imported bytes through, rather than
+                    // serialising a second copy of the database
+                    self.report(ImportProgress::Exporting)?;
+                    File::create(fdb_path)
+                        .and_then(|mut file| file.write_all(bytes))
+                        .map_err(Error::CannotWriteFile)?;
                 }
                 Ok(db)
             }
@@ -463,3 +1009,210 @@ impl DatabaseImporter {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::backend::Checkpoint;
+    use crate::{Colour, Symbol, SymbolSource};
+
+    // a project holding `count` segments, and no architectures or functions
+    fn project(count: u64) -> Vec<u8> {
+        let mut builder = flatbuffers::FlatBufferBuilder::new();
+
+        let mut segments = Vec::new();
+        for i in 0..count {
+            let name = builder.create_string(&format!("seg{}", i));
+            let bytes = builder.create_vector(&[0u8; 16]);
+
+            let mut sbuilder = schema::SegmentBuilder::new(&mut builder);
+            sbuilder.add_name(name);
+            sbuilder.add_address(0x1000 * (i + 1));
+            sbuilder.add_size_(16);
+            sbuilder.add_bytes(bytes);
+            segments.push(sbuilder.finish());
+        }
+
+        let svec = builder.create_vector(&segments);
+        let avec = builder.create_vector::<flatbuffers::WIPOffset<schema::Architecture>>(&[]);
+        let fvec = builder.create_vector::<flatbuffers::WIPOffset<schema::Function>>(&[]);
+        let meta = Metadata::default().to_builder(&mut builder).unwrap();
+
+        let mut pbuilder = schema::ProjectBuilder::new(&mut builder);
+        pbuilder.add_architectures(avec);
+        pbuilder.add_segments(svec);
+        pbuilder.add_functions(fvec);
+        pbuilder.add_metadata(meta);
+
+        let project = pbuilder.finish();
+        schema::finish_project_buffer(&mut builder, project);
+
+        builder.finished_data().to_vec()
+    }
+
+    struct Exporter {
+        steps: Rc<Cell<u64>>,
+    }
+
+    impl Backend for Exporter {
+        type Error = Error;
+
+        fn name(&self) -> &'static str {
+            "fugue-test"
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn is_preferred_for(&self, _: &Url) -> Option<bool> {
+            Some(true)
+        }
+
+        fn import(&self, program: &Url) -> Result<Imported, Self::Error> {
+            self.import_with(program, &mut |_, _| true)
+        }
+
+        fn import_with(&self, program: &Url, checkpoint: &mut Checkpoint) -> Result<Imported, Self::Error> {
+            for done in 0..10 {
+                if !checkpoint(done, 10) {
+                    return Err(Error::InvalidLocalImportUrl(program.clone()));
+                }
+                self.steps.set(done + 1);
+            }
+            Ok(Imported::Bytes(project(1)))
+        }
+    }
+
+    #[test]
+    fn test_symbols_round_trip() -> Result<(), Error> {
+        let dir = tempfile::tempdir().map_err(Error::CannotCreateTempDir)?;
+        let path = dir.path().join("symbols.fdb");
+
+        let mut db = Database::default();
+        let symbols = db.symbols_mut();
+        symbols.insert(Symbol::new("helper", 0x1000, SymbolSource::Importer));
+        symbols.insert(Symbol::new("helper", 0x2000, SymbolSource::Importer));
+        symbols.rename(0x3000, "entry");
+
+        db.to_file(&path)?;
+
+        let restored = Database::from_file(&path, &LanguageDB::default())?;
+        assert_eq!(restored.symbols(), db.symbols());
+        assert_eq!(restored.symbols().lookup_all("helper").count(), 2);
+        assert!(restored.symbols().lookup("entry").map(|s| s.source().is_user()).unwrap_or(false));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_annotations_round_trip() -> Result<(), Error> {
+        let dir = tempfile::tempdir().map_err(Error::CannotCreateTempDir)?;
+        let path = dir.path().join("annotations.fdb");
+
+        let mut db = Database::default();
+        let annotations = db.annotations_mut();
+        annotations.set_comment(0x1000, "checks the licence");
+        annotations.set_label(0x1010, "fail");
+        annotations.set_bookmark(0x1020, "CRC-32 table");
+        annotations.set_colour(0x1000, Colour::from_rgb(0xff8000));
+
+        db.to_file(&path)?;
+
+        let restored = Database::from_file(&path, &LanguageDB::default())?;
+        assert_eq!(*restored.annotations(), *db.annotations());
+        assert_eq!(restored.annotations().comment(0x1000), Some("checks the licence"));
+        assert_eq!(restored.annotations().colour(0x1000), Some(Colour::from_rgb(0xff8000)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_file_with_cancelled() -> Result<(), Error> {
+        let dir = tempfile::tempdir().map_err(Error::CannotCreateTempDir)?;
+        let path = dir.path().join("segments.fdb");
+        std::fs::write(&path, project(3)).map_err(Error::CannotWriteFile)?;
+
+        let mut reported = Vec::new();
+        let db = Database::from_file_with(&path, &LanguageDB::default(), |p| {
+            reported.push(p);
+            true
+        })?;
+
+        // the project has no functions to report
+        assert_eq!(db.segments().len(), 3);
+        assert_eq!(
+            reported,
+            (0..3)
+                .map(|done| ImportProgress::Segments { done, total: 3 })
+                .collect::<Vec<_>>()
+        );
+
+        let mut reported = 0;
+        let result = Database::from_file_with(&path, &LanguageDB::default(), |p| {
+            reported += 1;
+            p != ImportProgress::Segments { done: 1, total: 3 }
+        });
+
+        assert!(matches!(result, Err(Error::ImportCancelled)));
+        assert_eq!(reported, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lazy_segment_bytes() -> Result<(), Error> {
+        let dir = tempfile::tempdir().map_err(Error::CannotCreateTempDir)?;
+        let path = dir.path().join("segments.fdb");
+        std::fs::write(&path, project(2)).map_err(Error::CannotWriteFile)?;
+
+        let db = Database::from_file_lazy(&path, &LanguageDB::default(), 1024)?;
+
+        let segment = db.segment("seg1").unwrap();
+        assert!(segment.is_lazy());
+        assert_eq!(&segment.try_bytes()?[..], &[0u8; 16]);
+        assert!(std::panic::catch_unwind(|| segment.bytes().len()).is_err());
+
+        // the project has no functions, and so no blocks
+        assert_eq!(db.blocks_in("seg1")?.count(), 0);
+        assert!(matches!(db.blocks_in("seg2"), Err(Error::NoSegment(name)) if name == "seg2"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backend_cancelled() {
+        let first = Rc::new(Cell::new(0));
+        let second = Rc::new(Cell::new(0));
+        let reported = Rc::new(RefCell::new(Vec::new()));
+
+        let mut importer = DatabaseImporter::new("program.bin");
+        importer
+            .register_backend(Exporter { steps: first.clone() })
+            .register_backend(Exporter { steps: second.clone() });
+
+        let progress = reported.clone();
+        importer.progress(move |p| {
+            progress.borrow_mut().push(p);
+            !matches!(p, ImportProgress::BackendProgress { done: 3, .. })
+        });
+
+        // the backend stops at the checkpoint, and no other backend is tried
+        let result = importer.import(&LanguageDB::default());
+        assert!(matches!(result, Err(Error::ImportCancelled)));
+        assert_eq!(first.get(), 3);
+        assert_eq!(second.get(), 0);
+        assert_eq!(reported.borrow().first(), Some(&ImportProgress::Backend("fugue-test")));
+
+        // cancelled while deserialising the backend's output
+        importer.progress(|p| !matches!(p, ImportProgress::Segments { .. }));
+        assert!(matches!(importer.import(&LanguageDB::default()), Err(Error::ImportCancelled)));
+        assert_eq!(first.get(), 10);
+
+        importer.progress(|_| true);
+        assert_eq!(importer.import(&LanguageDB::default()).map(|db| db.segments().len()).ok(), Some(1));
+    }
+}
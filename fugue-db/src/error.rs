@@ -13,6 +13,8 @@ pub enum Error {
     CannotWriteFile(std::io::Error),
     #[error(transparent)]
     Deserialisation(flatbuffers::InvalidFlatbuffer),
+    #[error("cannot deserialise database trailer: {0}")]
+    DeserialiseTrailer(serde_json::Error),
     #[error("field `{0}` to deserialise is missing")]
     DeserialiseField(&'static str),
     #[error("export path `{}` for serialised database already exists", _0.display())]
@@ -23,10 +25,18 @@ pub enum Error {
     NoFunctionSegment(u64),
     #[error("block at {0:#x} has no corresponding segment")]
     NoBlockSegment(u64),
+    #[error("no segment named `{0}`")]
+    NoSegment(String),
+    #[error("function at {0:#x} has no calling convention to infer its prototype")]
+    NoCallingConvention(u64),
+    #[error("block at {0:#x} references an unknown architecture")]
+    InvalidBlockArchitecture(u64),
     #[error("no importer backends available")]
     NoBackendsAvailable,
     #[error("no URL specified for database import")]
     NoImportUrl,
+    #[error("database import cancelled")]
+    ImportCancelled,
     #[error("file not found at `{}`", _0.display())]
     FileNotFound(std::path::PathBuf),
     #[error("invalid local import URL `{0}`")]
@@ -0,0 +1,213 @@
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::error::Error;
+use crate::Database;
+use crate::Function;
+use crate::SegmentBytes;
+
+/// Formats for exporting a database's functions to other tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    /// An IDA FLAIR pattern file; use `sigmake` to compile it into a `.sig`
+    /// signature file.
+    Pat,
+    /// A symbol list for Ghidra's `ImportSymbolsScript.py`, with one
+    /// `name address f` line per function.
+    Ghidra,
+    /// A JSON object with a `functions` array, giving each function's name,
+    /// address, size, architecture, blocks and call targets.
+    Json,
+}
+
+#[derive(serde::Serialize)]
+struct ExportedFunctions<'a> {
+    functions: Vec<ExportedFunction<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct ExportedFunction<'a> {
+    name: &'a str,
+    address: u64,
+    size: usize,
+    architecture: Option<String>,
+    blocks: Vec<ExportedBlock>,
+    calls: Vec<u64>,
+}
+
+#[derive(serde::Serialize)]
+struct ExportedBlock {
+    address: u64,
+    size: usize,
+}
+
+// FLAIR patterns hold the first 32 bytes of a function, followed by a
+// checksum over (at most) the next 255
+const PAT_PREFIX: usize = 32;
+const PAT_CRC_MAX: usize = 255;
+
+pub(crate) fn export<W: Write>(database: &Database, format: ExportFormat, mut writer: W) -> Result<(), Error> {
+    let functions = database
+        .functions()
+        .iter()
+        .filter(|f| f.entry().map(|b| !b.segment().is_external()).unwrap_or(false))
+        .collect::<Vec<_>>();
+
+    match format {
+        ExportFormat::Pat => {
+            for function in functions {
+                writeln!(writer, "{}", pat_line(function.name(), &body(function)?))
+                    .map_err(Error::CannotWriteFile)?;
+            }
+            writeln!(writer, "---").map_err(Error::CannotWriteFile)?;
+        }
+        ExportFormat::Ghidra => {
+            for function in functions {
+                writeln!(writer, "{} {:x} f", function.name(), function.address())
+                    .map_err(Error::CannotWriteFile)?;
+            }
+        }
+        ExportFormat::Json => {
+            let exported = ExportedFunctions {
+                functions: functions
+                    .into_iter()
+                    .map(|function| Ok(ExportedFunction {
+                        name: function.name(),
+                        address: function.address(),
+                        size: body(function)?.len(),
+                        architecture: function
+                            .architecture()
+                            .and_then(|index| database.architecture(index))
+                            .map(|arch| arch.to_string()),
+                        blocks: function
+                            .blocks()
+                            .iter()
+                            .map(|b| ExportedBlock {
+                                address: b.address(),
+                                size: b.len(),
+                            })
+                            .collect(),
+                        calls: function
+                            .references()
+                            .iter()
+                            .filter(|r| r.is_call())
+                            .filter_map(|r| database.functions().get(r.target_id().index()))
+                            .map(Function::address)
+                            .collect(),
+                    }))
+                    .collect::<Result<Vec<_>, Error>>()?,
+            };
+
+            serde_json::to_writer_pretty(&mut writer, &exported)
+                .map_err(|e| Error::CannotWriteFile(e.into()))?;
+            writeln!(writer).map_err(Error::CannotWriteFile)?;
+        }
+    }
+
+    writer.flush().map_err(Error::CannotWriteFile)
+}
+
+/// The bytes of the blocks forming a contiguous range from the function's
+/// entry point; blocks beyond a gap (e.g., outlined cold paths) are not
+/// included.
+fn body(function: &Function) -> Result<SegmentBytes, Error> {
+    let segment = if let Some(block) = function.entry() {
+        block.segment()
+    } else {
+        return Ok(SegmentBytes::default())
+    };
+
+    let mut blocks = function.blocks().iter().collect::<Vec<_>>();
+    blocks.sort_by_key(|b| b.address());
+
+    let start = function.address();
+    let mut end = start;
+    for block in blocks {
+        if block.address() > end {
+            break
+        }
+        end = end.max(block.address() + block.len() as u64);
+    }
+
+    let bytes = segment.try_bytes()?;
+    let offset = (start - segment.address()) as usize;
+    let range = offset..offset + (end - start) as usize;
+
+    Ok(if range.end <= bytes.len() {
+        bytes.slice(range)
+    } else {
+        SegmentBytes::default()
+    })
+}
+
+/// Formats a FLAIR pattern for a function named `name`. The database does
+/// not record relocations, so no bytes are masked, and patterns will only
+/// match identical code.
+fn pat_line(name: &str, bytes: &[u8]) -> String {
+    let mut line = String::with_capacity(128);
+
+    for i in 0..PAT_PREFIX {
+        if let Some(byte) = bytes.get(i) {
+            write!(line, "{:02X}", byte).unwrap();
+        } else {
+            line.push_str("..");
+        }
+    }
+
+    let checked = bytes
+        .get(PAT_PREFIX..)
+        .map(|rest| &rest[..rest.len().min(PAT_CRC_MAX)])
+        .unwrap_or_default();
+
+    write!(
+        line,
+        " {:02X} {:04X} {:04X} :0000 {}",
+        checked.len(),
+        crc16(checked),
+        bytes.len(),
+        name,
+    )
+    .unwrap();
+
+    line
+}
+
+// CRC-16/X-25, byte-swapped as FLAIR expects
+fn crc16(bytes: &[u8]) -> u16 {
+    if bytes.is_empty() {
+        return 0
+    }
+
+    let mut crc = 0xffffu16;
+    for byte in bytes {
+        let mut byte = *byte;
+        for _ in 0..8 {
+            if (crc ^ byte as u16) & 1 != 0 {
+                crc = (crc >> 1) ^ 0x8408;
+            } else {
+                crc >>= 1;
+            }
+            byte >>= 1;
+        }
+    }
+
+    (!crc).swap_bytes()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pat_line() {
+        assert_eq!(crc16(b"123456789"), 0x6e90);
+
+        let short = pat_line("f", &[0x55, 0xc3]);
+        assert_eq!(short, format!("55C3{} 00 0000 0002 :0000 f", "..".repeat(30)));
+
+        let long = (0..40).collect::<Vec<u8>>();
+        let line = pat_line("g", &long);
+        assert!(line.starts_with("000102"));
+        assert!(line.ends_with(&format!(" 08 {:04X} 0028 :0000 g", crc16(&long[32..]))));
+    }
+}
@@ -1,4 +1,7 @@
 use fugue_ir::Translator;
+use fugue_bv::BitVec;
+use fugue_ir::il::dataflow::InferredPrototype;
+use fugue_ir::il::ecode::{ECode, Location, Var};
 use iset::IntervalMap;
 
 use crate::Id;
@@ -7,6 +10,8 @@ use crate::InterRef;
 use crate::Segment;
 
 use crate::error::Error;
+use crate::graph::BlockGraph;
+use crate::hash::{HashOptions, SemanticHasher};
 use crate::schema;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -36,6 +41,30 @@ impl<'db> Function<'db> {
         &self.blocks
     }
 
+    /// The function's control-flow graph; nodes are indices into
+    /// [`Function::blocks`], and hold no statements.
+    pub fn graph(&self) -> BlockGraph<'static, Location, BitVec, Var> {
+        BlockGraph::from_successors(self.entry.index(), self.successors())
+    }
+
+    fn successors(&self) -> Vec<Vec<usize>> {
+        self.blocks
+            .iter()
+            .map(|b| {
+                b.successors()
+                    .iter()
+                    .map(|r| r.target_id().index())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The index of the architecture of the function's entry block; see
+    /// [`crate::Database::architecture`].
+    pub fn architecture(&self) -> Option<usize> {
+        self.entry().map(BasicBlock::architecture)
+    }
+
     pub fn segment_id(&self) -> Id<Segment> {
         self.segment.clone()
     }
@@ -44,6 +73,92 @@ impl<'db> Function<'db> {
         &self.references
     }
 
+    /// Adds `reference`, unless the function already holds it; returns
+    /// `true` if it was added.
+    pub(crate) fn add_reference(&mut self, reference: InterRef<'db>) -> bool {
+        if self.references.contains(&reference) {
+            false
+        } else {
+            self.references.push(reference);
+            true
+        }
+    }
+
+    /// Computes a stable hash of the function's lifted IL, visiting its
+    /// blocks in address order; see [`HashOptions`] for the default
+    /// normalisation.
+    pub fn semantic_hash(&self) -> Result<u64, Error> {
+        self.semantic_hash_with(HashOptions::default())
+    }
+
+    pub fn semantic_hash_with(&self, options: HashOptions) -> Result<u64, Error> {
+        let mut blocks = self.blocks.iter().collect::<Vec<_>>();
+        blocks.sort_by_key(|b| b.address());
+
+        let mut hasher = SemanticHasher::new(options);
+        for block in blocks {
+            block.hash_into(&mut hasher)?;
+        }
+
+        Ok(hasher.finish())
+    }
+
+    /// Infers the function's register inputs, stack arguments and
+    /// clobbered registers from its lifted IL, using the translator's
+    /// `default` calling convention to locate the stack pointer and return
+    /// address. Calls to functions for whose addresses `callees` returns a
+    /// prototype are summarised by it; other calls are treated as opaque.
+    /// See [`Database::inferred_prototype`](crate::Database::inferred_prototype),
+    /// which infers the prototypes of callees first.
+    pub fn inferred_prototype_with<'c, F>(&self, callees: F) -> Result<InferredPrototype, Error>
+    where
+        F: Fn(u64) -> Option<&'c InferredPrototype>,
+    {
+        let translator = if let Some(block) = self.entry() {
+            block.translator()
+        } else {
+            return Ok(InferredPrototype::default())
+        };
+
+        let conventions = translator.compiler_conventions();
+        let convention = conventions
+            .get("default")
+            .or_else(|| conventions.iter().min_by_key(|(name, _)| *name).map(|(_, c)| c))
+            .ok_or(Error::NoCallingConvention(self.address))?;
+
+        let lifted = self.lift_ecode()?;
+        let graph = self.dataflow_graph(&lifted);
+
+        Ok(InferredPrototype::infer(&graph, convention, |location: &Location| {
+            callees(location.address().offset())
+        }))
+    }
+
+    /// The lifted instructions of each of the function's blocks; see
+    /// [`BasicBlock::ecode`].
+    pub(crate) fn lift_ecode(&self) -> Result<Vec<&[ECode]>, Error> {
+        self.blocks.iter().map(|b| b.ecode()).collect()
+    }
+
+    /// Builds the function's control-flow graph over `lifted`, the lifted
+    /// instructions of each of its blocks.
+    pub(crate) fn dataflow_graph<'e>(&self, lifted: &[&'e [ECode]]) -> BlockGraph<'e, Location, BitVec, Var> {
+        let mut graph = BlockGraph::new();
+        for insns in lifted.iter().copied() {
+            graph.add_ecode_block(insns);
+        }
+
+        let count = graph.len();
+        for (source, succs) in self.successors().into_iter().enumerate() {
+            for target in succs.into_iter().filter(|t| *t < count) {
+                graph.add_edge(source, target);
+            }
+        }
+        graph.set_entry(self.entry.index());
+
+        graph
+    }
+
     pub(crate) fn from_reader(reader: schema::Function, segments: &'db IntervalMap<u64, Segment>, translators: &'db [Translator]) -> Result<Self, Error> {
         let address = reader.address();
         Ok(Self {
@@ -0,0 +1,3 @@
+// control-flow graphs share their representation with the dataflow
+// analyses over lifted code
+pub use fugue_ir::il::dataflow::{BlockGraph, Dominators, Loops, NaturalLoop};
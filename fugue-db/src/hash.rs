@@ -0,0 +1,350 @@
+use fugue_ir::digest::Digest;
+use fugue_ir::il::pcode::{Operand, PCodeOp};
+use fugue_ir::PCode;
+
+// operand tags; these are part of the hash's format, so must not change
+const ADDRESS: u64 = 0;
+const CONSTANT: u64 = 1;
+const REGISTER: u64 = 2;
+const VARIABLE: u64 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Controls which parts of the lifted IL contribute to a semantic hash.
+/// By default, addresses are ignored and constants are retained.
+pub struct HashOptions {
+    ignore_addresses: bool,
+    ignore_constants: bool,
+}
+
+impl Default for HashOptions {
+    fn default() -> Self {
+        Self {
+            ignore_addresses: true,
+            ignore_constants: false,
+        }
+    }
+}
+
+impl HashOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ignore_addresses(self, ignore: bool) -> Self {
+        Self {
+            ignore_addresses: ignore,
+            ..self
+        }
+    }
+
+    pub fn ignore_constants(self, ignore: bool) -> Self {
+        Self {
+            ignore_constants: ignore,
+            ..self
+        }
+    }
+}
+
+/// Hashes a sequence of lifted instructions after normalisation. The
+/// temporaries of each instruction are renumbered (see
+/// [`PCode::renumber_temporaries`]), so that their allocation offsets do not
+/// affect the result. Each operation is tagged by its SLEIGH opcode name and
+/// each operand by an explicit kind, and names are length-prefixed, so the
+/// hash does not depend on the layout of the IL's types.
+pub(crate) struct SemanticHasher {
+    options: HashOptions,
+    state: Digest,
+}
+
+impl SemanticHasher {
+    pub fn new(options: HashOptions) -> Self {
+        Self {
+            options,
+            state: Digest::new(),
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.state.finish()
+    }
+
+    pub fn hash_pcode(&mut self, pcode: &PCode) {
+        let mut pcode = pcode.clone();
+        pcode.renumber_temporaries();
+
+        for op in pcode.operations() {
+            self.hash_operation(op);
+        }
+    }
+
+    fn hash_operation(&mut self, op: &PCodeOp) {
+        // skipped operations have no effect
+        let opcode = if let Some(opcode) = op.opcode() {
+            opcode
+        } else {
+            return
+        };
+
+        self.state.write_prefixed(opcode.name().as_bytes());
+
+        match op {
+            PCodeOp::Load { space, .. } | PCodeOp::Store { space, .. } => {
+                self.state.write_u64(space.index() as u64);
+            }
+            PCodeOp::Intrinsic { name, result, .. } => {
+                self.state.write_prefixed(name.as_bytes());
+                // distinguishes a result from a leading operand
+                self.state.write_u64(result.is_some() as u64);
+            }
+            _ => (),
+        }
+
+        for_each_operand(op, |operand| self.hash_operand(operand));
+    }
+
+    fn hash_operand(&mut self, operand: &Operand) {
+        match operand {
+            Operand::Address { value, size } => {
+                self.state.write_u64(ADDRESS);
+                if !self.options.ignore_addresses {
+                    self.state.write_u64(value.offset());
+                }
+                self.state.write_u64(*size as u64);
+            }
+            Operand::Constant { value, size } => {
+                self.state.write_u64(CONSTANT);
+                if !self.options.ignore_constants {
+                    self.state.write_u64(*value);
+                }
+                self.state.write_u64(*size as u64);
+            }
+            Operand::Register { name, size, .. } => {
+                self.state.write_u64(REGISTER);
+                self.state.write_prefixed(name.as_bytes());
+                self.state.write_u64(*size as u64);
+            }
+            Operand::Variable {
+                offset,
+                size,
+                space,
+            } => {
+                self.state.write_u64(VARIABLE);
+                self.state.write_u64(space.index() as u64);
+                self.state.write_u64(*offset);
+                self.state.write_u64(*size as u64);
+            }
+        }
+    }
+}
+
+/// Calls `f` on each operand of `op`, outputs first.
+pub(crate) fn for_each_operand<F>(op: &PCodeOp, mut f: F)
+where
+    F: FnMut(&Operand),
+{
+    match op {
+        PCodeOp::Copy {
+            source,
+            destination,
+        } => {
+            f(destination);
+            f(source);
+        }
+        PCodeOp::Load {
+            source,
+            destination,
+            ..
+        }
+        | PCodeOp::Store {
+            source,
+            destination,
+            ..
+        } => {
+            f(destination);
+            f(source);
+        }
+        PCodeOp::Branch { destination }
+        | PCodeOp::IBranch { destination }
+        | PCodeOp::Call { destination }
+        | PCodeOp::ICall { destination }
+        | PCodeOp::Return { destination } => {
+            f(destination);
+        }
+        PCodeOp::CBranch {
+            destination,
+            condition,
+        } => {
+            f(destination);
+            f(condition);
+        }
+        PCodeOp::Intrinsic {
+            operands, result, ..
+        } => {
+            if let Some(result) = result {
+                f(result);
+            }
+            for operand in operands.iter() {
+                f(operand);
+            }
+        }
+        PCodeOp::IntEq { result, operands }
+        | PCodeOp::IntNotEq { result, operands }
+        | PCodeOp::IntLess { result, operands }
+        | PCodeOp::IntLessEq { result, operands }
+        | PCodeOp::IntSLess { result, operands }
+        | PCodeOp::IntSLessEq { result, operands }
+        | PCodeOp::IntAdd { result, operands }
+        | PCodeOp::IntSub { result, operands }
+        | PCodeOp::IntCarry { result, operands }
+        | PCodeOp::IntSCarry { result, operands }
+        | PCodeOp::IntSBorrow { result, operands }
+        | PCodeOp::IntXor { result, operands }
+        | PCodeOp::IntAnd { result, operands }
+        | PCodeOp::IntOr { result, operands }
+        | PCodeOp::IntLeftShift { result, operands }
+        | PCodeOp::IntRightShift { result, operands }
+        | PCodeOp::IntSRightShift { result, operands }
+        | PCodeOp::IntMul { result, operands }
+        | PCodeOp::IntDiv { result, operands }
+        | PCodeOp::IntSDiv { result, operands }
+        | PCodeOp::IntRem { result, operands }
+        | PCodeOp::IntSRem { result, operands }
+        | PCodeOp::BoolXor { result, operands }
+        | PCodeOp::BoolAnd { result, operands }
+        | PCodeOp::BoolOr { result, operands }
+        | PCodeOp::FloatEq { result, operands }
+        | PCodeOp::FloatNotEq { result, operands }
+        | PCodeOp::FloatLess { result, operands }
+        | PCodeOp::FloatLessEq { result, operands }
+        | PCodeOp::FloatAdd { result, operands }
+        | PCodeOp::FloatDiv { result, operands }
+        | PCodeOp::FloatMul { result, operands }
+        | PCodeOp::FloatSub { result, operands } => {
+            f(result);
+            f(&operands[0]);
+            f(&operands[1]);
+        }
+        PCodeOp::IntZExt { result, operand }
+        | PCodeOp::IntSExt { result, operand }
+        | PCodeOp::IntNeg { result, operand }
+        | PCodeOp::IntNot { result, operand }
+        | PCodeOp::BoolNot { result, operand }
+        | PCodeOp::FloatIsNaN { result, operand }
+        | PCodeOp::FloatNeg { result, operand }
+        | PCodeOp::FloatAbs { result, operand }
+        | PCodeOp::FloatSqrt { result, operand }
+        | PCodeOp::FloatOfInt { result, operand }
+        | PCodeOp::FloatOfFloat { result, operand }
+        | PCodeOp::FloatTruncate { result, operand }
+        | PCodeOp::FloatCeiling { result, operand }
+        | PCodeOp::FloatFloor { result, operand }
+        | PCodeOp::FloatRound { result, operand }
+        | PCodeOp::PopCount { result, operand } => {
+            f(result);
+            f(operand);
+        }
+        PCodeOp::Subpiece {
+            result,
+            operand,
+            amount,
+        } => {
+            f(result);
+            f(operand);
+            f(amount);
+        }
+        PCodeOp::Skip => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_ir::address::AddressValue;
+    use fugue_ir::space::{AddressSpace, AddressSpaceId, Space, SpaceKind};
+
+    use super::*;
+
+    fn register(name: &str) -> Operand {
+        Operand::Register {
+            name: name.into(),
+            offset: 0,
+            size: 4,
+        }
+    }
+
+    fn temporary(offset: u64) -> Operand {
+        Operand::Variable {
+            offset,
+            size: 4,
+            space: AddressSpaceId::unique_id(2),
+        }
+    }
+
+    // tmp = r0 + constant; r1 = tmp ^ r1, with the temporary's offset
+    // salted as if lifted at a different address
+    fn pcode(salt: u64, constant: u64, destination: &str) -> PCode {
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 4, 1, 1, None, 0));
+        let mut pcode = PCode::nop(AddressValue::new(&ram, 0x1000 + salt), 4);
+
+        pcode.operations.clear();
+        pcode.operations.push(PCodeOp::IntAdd {
+            result: temporary(0x1000 + salt),
+            operands: [register("r0"), Operand::Constant { value: constant, size: 4 }],
+        });
+        pcode.operations.push(PCodeOp::IntXor {
+            result: register(destination),
+            operands: [temporary(0x1000 + salt), register("r1")],
+        });
+
+        pcode
+    }
+
+    fn hash(pcode: &PCode, options: HashOptions) -> u64 {
+        let mut hasher = SemanticHasher::new(options);
+        hasher.hash_pcode(pcode);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_stable_under_renumbering() {
+        let options = HashOptions::default();
+        assert_eq!(hash(&pcode(0, 1, "r1"), options), hash(&pcode(0x7780, 1, "r1"), options));
+    }
+
+    #[test]
+    fn test_changes_for_semantic_edits() {
+        let options = HashOptions::default();
+        let base = hash(&pcode(0, 1, "r1"), options);
+
+        assert_ne!(base, hash(&pcode(0, 2, "r1"), options));
+        assert_ne!(base, hash(&pcode(0, 1, "r2"), options));
+
+        let mut edited = pcode(0, 1, "r1");
+        edited.operations[1] = PCodeOp::IntOr {
+            result: register("r1"),
+            operands: [temporary(0x1000), register("r1")],
+        };
+        assert_ne!(base, hash(&edited, options));
+
+        // unless constants are ignored
+        let options = options.ignore_constants(true);
+        assert_eq!(hash(&pcode(0, 1, "r1"), options), hash(&pcode(0, 2, "r1"), options));
+    }
+
+    #[test]
+    fn test_names_are_length_prefixed() {
+        let options = HashOptions::default();
+        let mut joined = pcode(0, 1, "r1");
+        joined.operations[0] = PCodeOp::Copy {
+            source: register("ab"),
+            destination: register("c"),
+        };
+
+        let mut split = joined.clone();
+        split.operations[0] = PCodeOp::Copy {
+            source: register("a"),
+            destination: register("bc"),
+        };
+
+        assert_ne!(hash(&joined, options), hash(&split, options));
+    }
+}
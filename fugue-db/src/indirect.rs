@@ -0,0 +1,93 @@
+use fugue_bv::BitVec;
+use fugue_bytes::Endian;
+use fugue_ir::il::dataflow::{Confidence, StaticMemory};
+use iset::IntervalMap;
+
+use crate::Id;
+use crate::Function;
+use crate::Segment;
+
+/// Static memory backed by the contents of a database's segments; external
+/// segments are treated as having no contents.
+pub struct SegmentMemory<'db> {
+    segments: &'db IntervalMap<u64, Segment>,
+}
+
+impl<'db> SegmentMemory<'db> {
+    pub fn new(segments: &'db IntervalMap<u64, Segment>) -> Self {
+        Self { segments }
+    }
+
+    fn segment(&self, address: u64) -> Option<&'db Segment> {
+        self.segments
+            .iter(address..address + 1)
+            .map(|(_, s)| s)
+            .find(|s| !s.is_external())
+    }
+}
+
+impl<'db> StaticMemory for SegmentMemory<'db> {
+    fn read(&self, address: u64, bits: usize) -> Option<BitVec> {
+        if bits == 0 || bits & 7 != 0 {
+            return None;
+        }
+
+        let segment = self.segment(address)?;
+        let offset = (address - segment.address()) as usize;
+        let contents = segment.try_bytes().ok()?;
+        let bytes = contents.get(offset..offset.checked_add(bits / 8)?)?;
+
+        Some(match segment.endian() {
+            Endian::Big => BitVec::from_be_bytes(bytes),
+            Endian::Little => BitVec::from_le_bytes(bytes),
+        })
+    }
+
+    fn is_writable(&self, address: u64) -> bool {
+        self.segment(address).map(Segment::is_writable).unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A proposed target for an indirect call.
+pub struct IndirectCall<'db> {
+    address: u64,
+    target: u64,
+    target_id: Option<Id<Function<'db>>>,
+    confidence: Confidence,
+}
+
+impl<'db> IndirectCall<'db> {
+    pub(crate) fn new(
+        address: u64,
+        target: u64,
+        target_id: Option<Id<Function<'db>>>,
+        confidence: Confidence,
+    ) -> Self {
+        Self {
+            address,
+            target,
+            target_id,
+            confidence,
+        }
+    }
+
+    /// The address of the calling instruction.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    pub fn target(&self) -> u64 {
+        self.target
+    }
+
+    /// The function starting at the target, if it is known to the
+    /// database.
+    pub fn target_id(&self) -> Option<Id<Function<'db>>> {
+        self.target_id.clone()
+    }
+
+    pub fn confidence(&self) -> Confidence {
+        self.confidence
+    }
+}
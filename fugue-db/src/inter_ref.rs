@@ -13,6 +13,20 @@ pub struct InterRef<'db> {
 }
 
 impl<'db> InterRef<'db> {
+    pub(crate) fn new(
+        address: u64,
+        source_id: Id<Function<'db>>,
+        target_id: Id<Function<'db>>,
+        call: bool,
+    ) -> Self {
+        Self {
+            address,
+            source_id,
+            target_id,
+            call,
+        }
+    }
+
     pub fn address(&self) -> u64 {
         self.address
     }
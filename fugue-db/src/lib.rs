@@ -4,29 +4,48 @@
 //pub use fugue_schema::fugue::schema as schema;
 
 
+pub mod analysis;
+pub mod annotation;
 pub mod architecture;
 pub mod backend;
 pub mod basic_block;
+pub mod cache;
+pub mod crypto;
 pub mod database;
 pub mod error;
+pub mod export;
 pub mod format;
 pub mod function;
+pub mod graph;
+pub mod hash;
 pub mod id;
+pub mod indirect;
 pub mod inter_ref;
 pub mod intra_ref;
 pub mod metadata;
 pub mod schema;
 pub mod segment;
+pub mod symbol;
+mod trailer;
 
 pub use error::*;
 pub use id::Id;
 
+pub use analysis::Analyses;
+pub use annotation::{Annotation, Annotations, Colour};
 pub use architecture::{ArchitectureDef, Endian};
 pub use basic_block::BasicBlock;
-pub use database::{Database, DatabaseImporter};
+pub use cache::SegmentCache;
+pub use crypto::{CryptoMatch, CryptoMatchKind};
+pub use database::{Database, DatabaseImporter, ImportProgress};
+pub use export::ExportFormat;
 pub use format::Format;
 pub use function::Function;
+pub use graph::{BlockGraph, Dominators, Loops, NaturalLoop};
+pub use hash::HashOptions;
+pub use indirect::{IndirectCall, SegmentMemory};
 pub use inter_ref::InterRef;
 pub use intra_ref::IntraRef;
 pub use metadata::Metadata;
-pub use segment::Segment;
+pub use segment::{Segment, SegmentBytes};
+pub use symbol::{Symbol, SymbolSource, SymbolTable};
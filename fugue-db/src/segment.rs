@@ -1,9 +1,159 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, Range};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::cache::SegmentCache;
 use crate::error::Error;
 use crate::schema;
 use crate::Id;
 
 use fugue_bytes::Endian;
 
+/// The contents of a segment, or a range within them; contents loaded
+/// lazily remain available while referenced, even if evicted from the
+/// database's [`SegmentCache`].
+#[derive(Clone)]
+pub struct SegmentBytes {
+    bytes: Arc<[u8]>,
+    range: Range<usize>,
+}
+
+impl SegmentBytes {
+    fn new(bytes: Arc<[u8]>) -> Self {
+        let range = 0..bytes.len();
+        Self { bytes, range }
+    }
+
+    /// A range within the bytes; panics if `range` is out of bounds, as
+    /// when slicing.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        assert!(range.start <= range.end && range.end <= self.len(), "range within bytes");
+        Self {
+            bytes: self.bytes.clone(),
+            range: self.range.start + range.start..self.range.start + range.end,
+        }
+    }
+}
+
+impl Deref for SegmentBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes[self.range.clone()]
+    }
+}
+
+impl Default for SegmentBytes {
+    fn default() -> Self {
+        Self::new(Arc::from([]))
+    }
+}
+
+impl AsRef<[u8]> for SegmentBytes {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl fmt::Debug for SegmentBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+#[derive(Clone)]
+enum Contents {
+    Resident(Arc<[u8]>),
+    Lazy {
+        cache: Arc<SegmentCache>,
+        offset: u64,
+        length: usize,
+    },
+}
+
+impl Contents {
+    fn load(&self) -> Result<SegmentBytes, Error> {
+        match self {
+            Self::Resident(bytes) => Ok(SegmentBytes::new(bytes.clone())),
+            Self::Lazy { cache, offset, length } => {
+                cache.load(*offset, *length).map(SegmentBytes::new)
+            }
+        }
+    }
+
+    // lazily loaded contents are identified by their location, so they
+    // can be compared and hashed without reading them
+    fn key(&self) -> ContentsKey<'_> {
+        match self {
+            Self::Resident(bytes) => ContentsKey::Resident(bytes),
+            Self::Lazy { cache, offset, length } => {
+                ContentsKey::Lazy(cache.path(), *offset, *length)
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum ContentsKey<'a> {
+    Resident(&'a [u8]),
+    Lazy(&'a Path, u64, usize),
+}
+
+impl fmt::Debug for Contents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Resident(bytes) => bytes.fmt(f),
+            Self::Lazy { offset, length, .. } => f
+                .debug_struct("Lazy")
+                .field("offset", offset)
+                .field("length", length)
+                .finish(),
+        }
+    }
+}
+
+impl PartialEq for Contents {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Contents {}
+
+impl PartialOrd for Contents {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Contents {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for Contents {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+impl serde::Serialize for Contents {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = self.load().map_err(serde::ser::Error::custom)?;
+        serializer.collect_seq(bytes.iter())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Contents {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<u8>::deserialize(deserializer).map(|bytes| Self::Resident(bytes.into()))
+    }
+}
+
 #[derive(
     Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize, serde::Serialize,
 )]
@@ -22,7 +172,7 @@ pub struct Segment {
     executable: bool,
     readable: bool,
     writable: bool,
-    bytes: Vec<u8>,
+    bytes: Contents,
 }
 
 impl Segment {
@@ -82,11 +232,48 @@ impl Segment {
         self.writable
     }
 
+    /// The segment's contents, which must be held in memory; contents
+    /// loaded lazily (see [`Segment::is_lazy`]) must be read using
+    /// [`Segment::try_bytes`].
+    ///
+    /// # Panics
+    ///
+    /// If the segment's contents are loaded lazily.
     pub fn bytes(&self) -> &[u8] {
-        &self.bytes
+        match self.bytes {
+            Contents::Resident(ref bytes) => bytes,
+            Contents::Lazy { .. } => panic!(
+                "contents of segment `{}` are loaded lazily; use Segment::try_bytes",
+                self.name
+            ),
+        }
     }
 
-    pub(crate) fn from_reader(id: Id<Self>, reader: &schema::Segment) -> Result<Self, Error> {
+    /// The segment's contents, reading them from the database file if they
+    /// are loaded lazily.
+    pub fn try_bytes(&self) -> Result<SegmentBytes, Error> {
+        self.bytes.load()
+    }
+
+    /// True if the segment's contents are loaded on demand; see
+    /// [`SegmentCache`].
+    pub fn is_lazy(&self) -> bool {
+        matches!(self.bytes, Contents::Lazy { .. })
+    }
+
+    /// Reads a segment from `reader`; if `lazy` is given, with the buffer
+    /// `reader` reads from, the segment's contents are not copied, and are
+    /// loaded via the cache from their offset within the buffer.
+    pub(crate) fn from_reader(
+        id: Id<Self>,
+        reader: &schema::Segment,
+        lazy: Option<(&[u8], &Arc<SegmentCache>)>,
+    ) -> Result<Self, Error> {
+        let bytes = reader
+            .bytes()
+            .ok_or(Error::DeserialiseField("bytes"))?
+            .bytes();
+
         Ok(Self {
             id,
             name: reader
@@ -109,11 +296,15 @@ impl Segment {
             executable: reader.executable(),
             readable: reader.readable(),
             writable: reader.writable(),
-            bytes: reader
-                .bytes()
-                .ok_or(Error::DeserialiseField("bytes"))?
-                .bytes()
-                .to_vec(),
+            bytes: if let Some((buffer, cache)) = lazy {
+                Contents::Lazy {
+                    cache: cache.clone(),
+                    offset: (bytes.as_ptr() as usize - buffer.as_ptr() as usize) as u64,
+                    length: bytes.len(),
+                }
+            } else {
+                Contents::Resident(bytes.into())
+            },
         })
     }
 
@@ -122,7 +313,7 @@ impl Segment {
         builder: &'b mut flatbuffers::FlatBufferBuilder<'a>,
     ) -> Result<flatbuffers::WIPOffset<schema::Segment<'a>>, Error> {
         let name = builder.create_string(self.name());
-        let bytes = builder.create_vector(&self.bytes);
+        let bytes = builder.create_vector(&self.try_bytes()?[..]);
 
         let mut sbuilder = schema::SegmentBuilder::new(builder);
 
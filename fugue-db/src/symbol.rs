@@ -0,0 +1,287 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::Function;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+/// Where a symbol came from; when two sources name the same address, the
+/// greater source wins (user annotations override everything else).
+pub enum SymbolSource {
+    Importer,
+    DebugInfo,
+    User,
+}
+
+impl SymbolSource {
+    pub fn is_importer(&self) -> bool {
+        *self == Self::Importer
+    }
+
+    pub fn is_debug_info(&self) -> bool {
+        *self == Self::DebugInfo
+    }
+
+    pub fn is_user(&self) -> bool {
+        *self == Self::User
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Symbol {
+    name: String,
+    address: u64,
+    size: Option<u64>,
+    source: SymbolSource,
+}
+
+impl Symbol {
+    pub fn new<S: Into<String>>(name: S, address: u64, source: SymbolSource) -> Self {
+        Self {
+            name: name.into(),
+            address,
+            size: None,
+            source,
+        }
+    }
+
+    pub fn new_with_size<S: Into<String>>(
+        name: S,
+        address: u64,
+        size: u64,
+        source: SymbolSource,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            address,
+            size: Some(size),
+            source,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    pub fn source(&self) -> SymbolSource {
+        self.source
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        match self.size {
+            Some(size) => address >= self.address && address - self.address < size,
+            None => address == self.address,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(from = "Vec<Symbol>", into = "Vec<Symbol>")]
+/// Symbols aggregated from importers, debug information and user
+/// annotations, indexed by both address and name.
+///
+/// Each address holds at most one symbol, but names need not be unique
+/// (e.g., static functions of the same name in different compilation
+/// units); see [`SymbolTable::lookup`] and [`SymbolTable::lookup_all`].
+pub struct SymbolTable {
+    symbols: BTreeMap<u64, Symbol>,
+    names: HashMap<String, BTreeSet<u64>>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_functions<'a, 'db: 'a, I>(functions: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Function<'db>>,
+    {
+        let mut table = Self::new();
+        for f in functions {
+            table.insert(Symbol::new(f.name(), f.address(), SymbolSource::Importer));
+        }
+        table
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Inserts `symbol`, replacing any existing symbol at the same address
+    /// unless that symbol comes from a higher priority source. Returns
+    /// `true` if the symbol was inserted.
+    pub fn insert(&mut self, symbol: Symbol) -> bool {
+        if let Some(current) = self.symbols.get(&symbol.address) {
+            if current.source > symbol.source {
+                return false;
+            }
+            let name = current.name.clone();
+            self.remove_name(&name, symbol.address);
+        }
+
+        self.names
+            .entry(symbol.name.clone())
+            .or_default()
+            .insert(symbol.address);
+        self.symbols.insert(symbol.address, symbol);
+
+        true
+    }
+
+    /// Renames the symbol at `address`, or creates one if none exists. The
+    /// new name is recorded as a user symbol and so takes precedence over
+    /// imported names.
+    pub fn rename<S: Into<String>>(&mut self, address: u64, name: S) {
+        let size = self.symbols.get(&address).and_then(Symbol::size);
+        let mut symbol = Symbol::new(name, address, SymbolSource::User);
+        symbol.size = size;
+        self.insert(symbol);
+    }
+
+    pub fn remove(&mut self, address: u64) -> Option<Symbol> {
+        let symbol = self.symbols.remove(&address)?;
+        self.remove_name(&symbol.name, address);
+        Some(symbol)
+    }
+
+    fn remove_name(&mut self, name: &str, address: u64) {
+        if let Some(addresses) = self.names.get_mut(name) {
+            addresses.remove(&address);
+            if addresses.is_empty() {
+                self.names.remove(name);
+            }
+        }
+    }
+
+    pub fn get(&self, address: u64) -> Option<&Symbol> {
+        self.symbols.get(&address)
+    }
+
+    /// Returns the symbol named `name`. If several addresses share the
+    /// name, that from the highest priority source is returned, and of
+    /// those, that at the lowest address.
+    pub fn lookup<S: AsRef<str>>(&self, name: S) -> Option<&Symbol> {
+        self.lookup_all(name)
+            .max_by_key(|s| (s.source, Reverse(s.address)))
+    }
+
+    /// Returns all symbols named `name`, in address order.
+    pub fn lookup_all<S: AsRef<str>>(&self, name: S) -> impl Iterator<Item = &Symbol> {
+        self.names
+            .get(name.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|address| self.symbols.get(address))
+    }
+
+    /// Returns the closest symbol at or below `address`.
+    pub fn nearest(&self, address: u64) -> Option<&Symbol> {
+        self.symbols.range(..=address).next_back().map(|(_, s)| s)
+    }
+
+    /// Returns the symbol covering `address` and the offset of `address`
+    /// from its start. Symbols without a known size only cover their own
+    /// address.
+    pub fn resolve(&self, address: u64) -> Option<(&Symbol, u64)> {
+        self.nearest(address)
+            .filter(|s| s.contains(address))
+            .map(|s| (s, address - s.address))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Symbol> {
+        self.symbols.values()
+    }
+
+    pub fn user_symbols(&self) -> impl Iterator<Item = &Symbol> {
+        self.iter().filter(|s| s.source.is_user())
+    }
+}
+
+impl Extend<Symbol> for SymbolTable {
+    fn extend<T: IntoIterator<Item = Symbol>>(&mut self, iter: T) {
+        for symbol in iter {
+            self.insert(symbol);
+        }
+    }
+}
+
+impl FromIterator<Symbol> for SymbolTable {
+    fn from_iter<T: IntoIterator<Item = Symbol>>(iter: T) -> Self {
+        let mut table = Self::new();
+        table.extend(iter);
+        table
+    }
+}
+
+// only the symbols are serialised; the index by name is rebuilt
+impl From<Vec<Symbol>> for SymbolTable {
+    fn from(symbols: Vec<Symbol>) -> Self {
+        symbols.into_iter().collect()
+    }
+}
+
+impl From<SymbolTable> for Vec<Symbol> {
+    fn from(table: SymbolTable) -> Self {
+        table.symbols.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_names() {
+        let mut table = SymbolTable::new();
+        table.insert(Symbol::new("helper", 0x2000, SymbolSource::Importer));
+        table.insert(Symbol::new("helper", 0x1000, SymbolSource::Importer));
+        table.insert(Symbol::new("main", 0x3000, SymbolSource::Importer));
+
+        let all = table.lookup_all("helper").map(Symbol::address).collect::<Vec<_>>();
+        assert_eq!(all, [0x1000, 0x2000]);
+        assert_eq!(table.lookup("helper").map(Symbol::address), Some(0x1000));
+
+        // a higher priority source is preferred over a lower address
+        table.insert(Symbol::new("helper", 0x2000, SymbolSource::DebugInfo));
+        assert_eq!(table.lookup("helper").map(Symbol::address), Some(0x2000));
+
+        // renaming one leaves the other
+        table.rename(0x2000, "helper.cold");
+        assert_eq!(table.lookup("helper").map(Symbol::address), Some(0x1000));
+        assert_eq!(table.lookup("helper.cold").map(Symbol::address), Some(0x2000));
+
+        table.remove(0x1000);
+        assert!(table.lookup("helper").is_none());
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn test_serialise() {
+        let mut table = SymbolTable::new();
+        table.insert(Symbol::new_with_size("f", 0x1000, 0x20, SymbolSource::Importer));
+        table.insert(Symbol::new("f", 0x2000, SymbolSource::Importer));
+        table.rename(0x1000, "g");
+
+        let json = serde_json::to_string(&table).unwrap();
+        let restored = serde_json::from_str::<SymbolTable>(&json).unwrap();
+
+        assert_eq!(restored, table);
+        assert_eq!(restored.lookup("g").and_then(Symbol::size), Some(0x20));
+        assert_eq!(restored.lookup("f").map(Symbol::address), Some(0x2000));
+    }
+}
@@ -0,0 +1,81 @@
+//! Data held alongside the imported program that is not described by the
+//! flatbuffers schema, i.e., its symbols and annotations. Within a database file,
+//! it follows the serialised program, and is itself followed by its length
+//! and a magic number; files without a trailer remain readable, as do the
+//! programs of files with one by tools that expect only the program.
+
+use std::convert::TryInto;
+use std::io::Write;
+
+use crate::error::Error;
+use crate::{Annotations, SymbolTable};
+
+const MAGIC: &[u8; 8] = b"FDBTRLR1";
+const FOOTER_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+pub(crate) struct Trailer {
+    #[serde(default)]
+    pub(crate) symbols: SymbolTable,
+    #[serde(default)]
+    pub(crate) annotations: Annotations,
+}
+
+impl Trailer {
+    /// Splits `bytes` into the serialised program and its trailer, if any.
+    pub(crate) fn split(bytes: &[u8]) -> Result<(&[u8], Option<Self>), Error> {
+        let footer = match bytes.len().checked_sub(FOOTER_SIZE) {
+            Some(footer) if &bytes[footer + 8..] == MAGIC => footer,
+            _ => return Ok((bytes, None)),
+        };
+
+        let length = u64::from_le_bytes(bytes[footer..footer + 8].try_into().unwrap());
+        let start = (footer as u64)
+            .checked_sub(length)
+            .ok_or(Error::DeserialiseField("trailer"))? as usize;
+
+        let trailer = serde_json::from_slice(&bytes[start..footer])
+            .map_err(Error::DeserialiseTrailer)?;
+
+        Ok((&bytes[..start], Some(trailer)))
+    }
+
+    pub(crate) fn write<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let bytes = serde_json::to_vec(self).map_err(|e| Error::CannotWriteFile(e.into()))?;
+
+        writer
+            .write_all(&bytes)
+            .and_then(|_| writer.write_all(&(bytes.len() as u64).to_le_bytes()))
+            .and_then(|_| writer.write_all(MAGIC))
+            .map_err(Error::CannotWriteFile)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Symbol, SymbolSource};
+
+    #[test]
+    fn test_trailer() -> Result<(), Error> {
+        let program = b"program".to_vec();
+        assert!(Trailer::split(&program)?.1.is_none());
+
+        let mut trailer = Trailer::default();
+        trailer.symbols.insert(Symbol::new("main", 0x1000, SymbolSource::User));
+
+        let mut bytes = program.clone();
+        trailer.write(&mut bytes)?;
+
+        let (rest, restored) = Trailer::split(&bytes)?;
+        assert_eq!(rest, &program[..]);
+        assert_eq!(restored.map(|t| t.symbols), Some(trailer.symbols));
+
+        // a length beyond the start of the file
+        let footer = bytes.len() - FOOTER_SIZE;
+        bytes[footer..footer + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(Trailer::split(&bytes).is_err());
+
+        Ok(())
+    }
+}
@@ -15,6 +15,8 @@ use std::mem::take;
 
 use rug::Assign;
 use rug::Integer as BigInt;
+use rug::float::Round;
+use rug::float::Special;
 
 use thiserror::Error;
 
@@ -22,6 +24,8 @@ use thiserror::Error;
 pub enum Error {
     #[error("no corresponding float format representation for `{}` bits", .0 * 8)]
     UnsupportedFloatFormat(usize),
+    #[error("invalid decimal floating-point literal `{0}`")]
+    InvalidDecimal(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -801,6 +805,198 @@ impl Float {
     }
 }
 
+impl Float {
+    /// Parses a decimal literal, e.g., `-1.5e-3`, `inf`, or `nan`, rounding
+    /// it to the nearest value representable in the format.
+    pub fn from_decimal_str(frac_bits: u32, exp_bits: u32, s: &str) -> Result<Self, Error> {
+        let value = rug::Float::parse(s.trim())
+            .map_err(|_| Error::InvalidDecimal(s.to_owned()))?;
+        let (value, ordering) = rug::Float::with_val_round(
+            frac_bits + 1,
+            value,
+            Round::Nearest,
+        );
+        Ok(Self::from_rug(frac_bits, exp_bits, value, ordering))
+    }
+
+    /// Formats the value using the fewest decimal digits that parse back
+    /// (using [`Float::from_decimal_str`]) to the same value.
+    pub fn to_decimal_string(&self) -> String {
+        if self.is_nan() {
+            return "NaN".to_owned()
+        }
+
+        let sign = if self.sign < 0 { "-" } else { "" };
+
+        if self.is_infinite() {
+            return format!("{}inf", sign)
+        }
+
+        if self.is_zero() {
+            return format!("{}0", sign)
+        }
+
+        let value = self.to_rug();
+        let parses_back = |s: &str, exp: i32| {
+            let candidate = format!("{}0.{}e{}", sign, s, exp);
+            Self::from_decimal_str(self.frac_bits, self.exp_bits, &candidate)
+                .map(|f| f == *self)
+                .unwrap_or(false)
+        };
+
+        // the nearest candidate may lie outside of the rounding interval
+        // when the interval is asymmetric, so also try its neighbours
+        let mut digits = 1;
+        let (digits, exp) = 'search: loop {
+            for round in [Round::Nearest, Round::Up, Round::Down] {
+                let (_, s, exp) = value.to_sign_string_exp_round(10, Some(digits), round);
+                let s = s.trim_end_matches('0');
+                let exp = exp.expect("finite non-zero float");
+
+                if parses_back(s, exp) {
+                    break 'search (s.to_owned(), exp)
+                }
+            }
+            digits += 1;
+        };
+
+        // value = 0.{digits} * 10^exp
+        let n = digits.len() as i32;
+        if !(-5..=21).contains(&exp) {
+            let (lead, rest) = digits.split_at(1);
+            if rest.is_empty() {
+                format!("{}{}e{}", sign, lead, exp - 1)
+            } else {
+                format!("{}{}.{}e{}", sign, lead, rest, exp - 1)
+            }
+        } else if exp <= 0 {
+            format!("{}0.{}{}", sign, "0".repeat(exp.unsigned_abs() as usize), digits)
+        } else if exp >= n {
+            format!("{}{}{}", sign, digits, "0".repeat((exp - n) as usize))
+        } else {
+            let (int, frac) = digits.split_at(exp as usize);
+            format!("{}{}.{}", sign, int, frac)
+        }
+    }
+
+    /// Converts `value` to the format, rounding to the nearest
+    /// representable value.
+    pub fn from_f32(frac_bits: u32, exp_bits: u32, value: f32) -> Self {
+        let (value, ordering) = rug::Float::with_val_round(frac_bits + 1, value, Round::Nearest);
+        Self::from_rug(frac_bits, exp_bits, value, ordering)
+    }
+
+    /// Converts `value` to the format, rounding to the nearest
+    /// representable value.
+    pub fn from_f64(frac_bits: u32, exp_bits: u32, value: f64) -> Self {
+        let (value, ordering) = rug::Float::with_val_round(frac_bits + 1, value, Round::Nearest);
+        Self::from_rug(frac_bits, exp_bits, value, ordering)
+    }
+
+    /// Converts the value to the nearest `f32`.
+    pub fn to_f32_lossy(&self) -> f32 {
+        self.to_rug().to_f32()
+    }
+
+    /// Converts the value to an `f32`, or `None` if it is not exactly
+    /// representable as one. NaN payloads are not preserved.
+    pub fn to_f32_exact(&self) -> Option<f32> {
+        let value = self.to_rug();
+        let f = value.to_f32();
+        if value.is_nan() || value == f {
+            Some(f)
+        } else {
+            None
+        }
+    }
+
+    /// Converts the value to the nearest `f64`.
+    pub fn to_f64_lossy(&self) -> f64 {
+        self.to_rug().to_f64()
+    }
+
+    /// Converts the value to an `f64`, or `None` if it is not exactly
+    /// representable as one. NaN payloads are not preserved.
+    pub fn to_f64_exact(&self) -> Option<f64> {
+        let value = self.to_rug();
+        let f = value.to_f64();
+        if value.is_nan() || value == f {
+            Some(f)
+        } else {
+            None
+        }
+    }
+
+    fn to_rug(&self) -> rug::Float {
+        let negative = self.sign < 0;
+
+        if self.is_nan() {
+            let nan = rug::Float::with_val(1, Special::Nan);
+            return if negative { -nan } else { nan }
+        }
+
+        if self.is_infinite() {
+            let special = if negative { Special::NegInfinity } else { Special::Infinity };
+            return rug::Float::with_val(1, special)
+        }
+
+        if self.is_zero() {
+            let special = if negative { Special::NegZero } else { Special::Zero };
+            return rug::Float::with_val(1, special)
+        }
+
+        let prec = self.unscaled.significant_bits();
+        let value = rug::Float::with_val(prec, &self.unscaled)
+            << self.scale.wrapping_sub(self.frac_bits as i32);
+
+        if negative { -value } else { value }
+    }
+
+    // `value` must already be rounded to `frac_bits + 1` bits of precision;
+    // `ordering` is the direction of that rounding.
+    fn from_rug(frac_bits: u32, exp_bits: u32, value: rug::Float, ordering: Ordering) -> Self {
+        let sign = if value.is_sign_negative() { Sign::Negative } else { Sign::Positive };
+
+        if value.is_nan() {
+            return Self::quiet_nan(frac_bits, exp_bits, sign)
+        }
+
+        if value.is_infinite() {
+            return Self::infinity(frac_bits, exp_bits, sign)
+        }
+
+        let mut f = Self::zero_with(frac_bits, exp_bits, sign.clone());
+        if value.is_zero() {
+            return f
+        }
+
+        let mut value = value;
+        value.subnormalize_round(f.min_scale + 1, ordering, Round::Nearest);
+
+        if value.is_zero() {
+            return f
+        }
+
+        // value = unscaled * 2^exp
+        let (unscaled, exp) = value.to_integer_exp().expect("finite float");
+
+        f.unscaled = unscaled.abs();
+        f.scale = exp.wrapping_add(frac_bits as i32);
+
+        // normalise, leaving subnormal values at the minimum scale
+        let shift = (frac_bits as i32 + 1)
+            .wrapping_sub(f.unscaled.significant_bits() as i32)
+            .min(f.scale.wrapping_sub(f.min_scale));
+        f.upscale(shift as u32);
+
+        if f.scale > f.max_scale {
+            return Self::infinity(frac_bits, exp_bits, sign)
+        }
+
+        f
+    }
+}
+
 impl From<Float> for BigInt {
     fn from(f: Float) -> Self {
         f.into_bigint()
@@ -1225,3 +1421,252 @@ impl FloatFormatOps for FloatFormat {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const F32: (u32, u32) = (23, 8);
+    const F64: (u32, u32) = (52, 11);
+    const F80: (u32, u32) = (64, 15);
+
+    fn decimal((frac_bits, exp_bits): (u32, u32), s: &str) -> Float {
+        Float::from_decimal_str(frac_bits, exp_bits, s).unwrap()
+    }
+
+    fn round_trips(format: (u32, u32), f: &Float) -> bool {
+        let back = decimal(format, &f.to_decimal_string());
+        if f.is_nan() {
+            back.is_nan()
+        } else {
+            back == *f && back.sign == f.sign
+        }
+    }
+
+    #[test]
+    fn test_decimal_f32() {
+        let values = [
+            0.0,
+            -0.0,
+            1.0,
+            -1.5,
+            0.1,
+            1.0 / 3.0,
+            16777216.0,
+            f32::from_bits(1),
+            f32::from_bits(0x007f_ffff),
+            f32::MIN_POSITIVE,
+            f32::MAX,
+            f32::MIN,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NAN,
+        ];
+
+        for value in values {
+            let f = Float::from_f32(F32.0, F32.1, value);
+            let s = f.to_decimal_string();
+
+            assert!(round_trips(F32, &f), "{} does not round trip", s);
+
+            let parsed = s.parse::<f32>().unwrap();
+            assert!(
+                parsed.to_bits() == value.to_bits() || (parsed.is_nan() && value.is_nan()),
+                "{} formatted as {}",
+                value,
+                s
+            );
+        }
+
+        let formatted = |value: f32| Float::from_f32(F32.0, F32.1, value).to_decimal_string();
+
+        assert_eq!(formatted(0.1), "0.1");
+        assert_eq!(formatted(-0.0), "-0");
+        assert_eq!(formatted(f32::from_bits(1)), "1e-45");
+        assert_eq!(formatted(f32::MAX), "3.4028235e38");
+        assert_eq!(formatted(f32::NEG_INFINITY), "-inf");
+        assert_eq!(formatted(f32::NAN), "NaN");
+    }
+
+    #[test]
+    fn test_decimal_f64() {
+        let values = [
+            0.0,
+            -0.0,
+            0.1,
+            -2.5e-3,
+            1e23,
+            9007199254740993.0,
+            f64::from_bits(1),
+            f64::from_bits(0x000f_ffff_ffff_ffff),
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NAN,
+        ];
+
+        for value in values {
+            let f = Float::from_f64(F64.0, F64.1, value);
+            let s = f.to_decimal_string();
+
+            assert!(round_trips(F64, &f), "{} does not round trip", s);
+
+            let parsed = s.parse::<f64>().unwrap();
+            assert!(
+                parsed.to_bits() == value.to_bits() || (parsed.is_nan() && value.is_nan()),
+                "{} formatted as {}",
+                value,
+                s
+            );
+        }
+
+        let formatted = |value: f64| Float::from_f64(F64.0, F64.1, value).to_decimal_string();
+
+        assert_eq!(formatted(1e20), "100000000000000000000");
+        assert_eq!(formatted(1e21), "1e21");
+        assert_eq!(formatted(1e-6), "0.000001");
+        assert_eq!(formatted(1e-7), "1e-7");
+        assert_eq!(formatted(f64::from_bits(1)), "5e-324");
+    }
+
+    #[test]
+    fn test_decimal_f80() {
+        for s in ["0", "-0", "0.1", "-2.5", "1e-4940", "1e4930", "3.3621031431120935063e-4932"] {
+            let f = decimal(F80, s);
+            assert!(round_trips(F80, &f), "{} does not round trip", s);
+        }
+
+        assert_eq!(decimal(F80, "0.1").to_decimal_string(), "0.1");
+        assert_eq!(decimal(F80, "-1e4930").to_decimal_string(), "-1e4930");
+
+        // more precise than an f64
+        let third = decimal(F80, "0.33333333333333333333");
+        assert_ne!(third, Float::from_f64(F80.0, F80.1, 1.0 / 3.0));
+
+        assert!(decimal(F80, "1e5000").is_infinite());
+        assert!(decimal(F80, "1e-5000").is_zero());
+    }
+
+    #[test]
+    fn test_decimal_boundaries() {
+        // halfway between f32::MAX and 2^128 rounds to infinity
+        assert_eq!(decimal(F32, "3.4028235e38").to_f32_exact(), Some(f32::MAX));
+        assert!(decimal(F32, "3.4028236e38").is_infinite());
+        assert!(decimal(F32, "-1e39").is_infinite());
+
+        // below half of the smallest subnormal rounds to zero
+        assert!(decimal(F32, "7e-46").is_zero());
+        assert_eq!(decimal(F32, "8e-46").to_f32_exact(), Some(f32::from_bits(1)));
+        assert!(!decimal(F32, "1e-40").is_normal());
+
+        assert!(decimal(F64, "2e-324").is_zero());
+        assert_eq!(decimal(F64, "3e-324").to_f64_exact(), Some(f64::from_bits(1)));
+
+        assert!(decimal(F64, "inf").is_infinite());
+        assert!(decimal(F64, "-inf").is_infinite());
+        assert!(decimal(F64, "nan").is_nan());
+        assert!(decimal(F64, " 1.5 ").to_f64_exact() == Some(1.5));
+
+        assert!(matches!(
+            Float::from_decimal_str(F64.0, F64.1, "1.5x"),
+            Err(Error::InvalidDecimal(_))
+        ));
+        assert!(Float::from_decimal_str(F64.0, F64.1, "").is_err());
+    }
+
+    #[test]
+    fn test_f32_conversions() {
+        let values = [
+            0.0,
+            -0.0,
+            1.0,
+            -0.1,
+            f32::from_bits(1),
+            f32::from_bits(0x007f_ffff),
+            f32::MIN_POSITIVE,
+            f32::MAX,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+        ];
+
+        for value in values {
+            let f = Float::from_f32(F32.0, F32.1, value);
+            assert_eq!(f.to_f32_exact().map(f32::to_bits), Some(value.to_bits()));
+            assert_eq!(f.to_f32_lossy().to_bits(), value.to_bits());
+
+            // every f32 is exactly representable as an f64
+            assert_eq!(f.to_f64_exact(), Some(value as f64));
+        }
+
+        let nan = Float::from_f32(F32.0, F32.1, f32::NAN);
+        assert!(nan.is_nan());
+        assert!(nan.to_f32_exact().map(f32::is_nan).unwrap_or(false));
+
+        assert!(!Float::from_f32(F32.0, F32.1, f32::from_bits(1)).is_normal());
+        assert!(Float::from_f32(F32.0, F32.1, -0.0).is_zero());
+    }
+
+    #[test]
+    fn test_f64_conversions() {
+        let values = [
+            0.0,
+            -0.0,
+            0.1,
+            -1e300,
+            f64::from_bits(1),
+            f64::from_bits(0x000f_ffff_ffff_ffff),
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            f64::NEG_INFINITY,
+        ];
+
+        for value in values {
+            let f = Float::from_f64(F64.0, F64.1, value);
+            assert_eq!(f.to_f64_exact().map(f64::to_bits), Some(value.to_bits()));
+            assert_eq!(f.to_f64_lossy().to_bits(), value.to_bits());
+
+            // and within the extended format
+            let f = Float::from_f64(F80.0, F80.1, value);
+            assert_eq!(f.to_f64_exact().map(f64::to_bits), Some(value.to_bits()));
+        }
+
+        let nan = Float::from_f64(F64.0, F64.1, -f64::NAN);
+        assert!(nan.is_nan());
+        assert!(nan.to_f64_exact().map(f64::is_nan).unwrap_or(false));
+    }
+
+    #[test]
+    fn test_inexact_conversions() {
+        // not representable in fewer bits
+        let tenth = Float::from_f64(F64.0, F64.1, 0.1);
+        assert_eq!(tenth.to_f32_exact(), None);
+        assert_eq!(tenth.to_f32_lossy(), 0.1f32);
+        assert_eq!(Float::from_f64(F64.0, F64.1, 0.5).to_f32_exact(), Some(0.5));
+
+        // beyond the range of an f32, or below its smallest subnormal
+        assert_eq!(Float::from_f64(F64.0, F64.1, 1e300).to_f32_exact(), None);
+        assert_eq!(Float::from_f64(F64.0, F64.1, 1e-300).to_f32_exact(), None);
+        assert_eq!(Float::from_f64(F64.0, F64.1, f64::from_bits(1)).to_f32_exact(), None);
+
+        // more precise than an f64
+        let third = Float::from_decimal_str(F80.0, F80.1, "0.33333333333333333333").unwrap();
+        assert_eq!(third.to_f64_exact(), None);
+        assert_eq!(third.to_f64_lossy(), 1.0 / 3.0);
+
+        // rounding into a narrower format
+        let rounded = Float::from_f64(F32.0, F32.1, 0.1);
+        assert_eq!(rounded.to_f32_exact(), Some(0.1f32));
+        assert_eq!(rounded.to_f64_exact(), Some(0.1f32 as f64));
+
+        let rounded = Float::from_f64(F32.0, F32.1, 1.0 + f64::EPSILON);
+        assert_eq!(rounded.to_f32_exact(), Some(1.0));
+
+        assert!(Float::from_f64(F32.0, F32.1, 1e300).is_infinite());
+        assert!(Float::from_f64(F32.0, F32.1, -1e300).is_infinite());
+        assert!(Float::from_f64(F32.0, F32.1, 1e-300).is_zero());
+        assert_eq!(
+            Float::from_f64(F32.0, F32.1, f32::MAX as f64 * 1.0000000001).to_f32_exact(),
+            Some(f32::MAX)
+        );
+    }
+}
@@ -4,6 +4,7 @@ use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 use fugue_bv::BitVec;
 
+use crate::disassembly::Error as DisassemblyError;
 use crate::disassembly::IRBuilderArena;
 use crate::space::{AddressSpace, AddressSpaceId};
 use crate::space_manager::{FromSpace, SpaceManager};
@@ -121,12 +122,16 @@ impl From<&'_ AddressValue> for Address {
 }
 
 impl<'z> FromSpace<'z, Address> for AddressValue {
-    fn from_space(t: Address, manager: &SpaceManager) -> Self {
-        AddressValue::new(manager.default_space_ref(), t.offset())
+    fn from_space(t: Address, manager: &SpaceManager) -> Result<Self, DisassemblyError> {
+        Ok(AddressValue::new(manager.default_space_ref(), t.offset()))
     }
 
-    fn from_space_with(t: Address, _arena: &'z IRBuilderArena, manager: &SpaceManager) -> Self {
-        AddressValue::new(manager.default_space_ref(), t.offset())
+    fn from_space_with(
+        t: Address,
+        _arena: &'z IRBuilderArena,
+        manager: &SpaceManager,
+    ) -> Result<Self, DisassemblyError> {
+        Ok(AddressValue::new(manager.default_space_ref(), t.offset()))
     }
 }
 
@@ -715,6 +720,17 @@ impl AddressValue {
         }
     }
 
+    /// Constructs an address from the components of an existing address,
+    /// e.g., when deserialising one without access to its space.
+    pub fn from_parts(space: AddressSpaceId, word_size: u32, highest: u64, offset: u64) -> Self {
+        Self {
+            space,
+            word_size,
+            highest,
+            offset,
+        }
+    }
+
     pub fn space(&self) -> AddressSpaceId {
         self.space
     }
@@ -723,6 +739,10 @@ impl AddressValue {
         self.offset
     }
 
+    pub fn word_size(&self) -> u32 {
+        self.word_size
+    }
+
     pub fn difference(&self, other: &AddressValue) -> AddressValue {
         // reinterpret other as if it were in `self's` space
         Self {
@@ -0,0 +1,262 @@
+//! An interval map keyed by address, e.g., for segment lookup or memory
+//! region metadata.
+//!
+//! Unlike `PartMap`, which partitions a single key space and yields a
+//! default value outside of explicitly set ranges, an `AddressMap` only
+//! covers the intervals inserted into it, and keeps the intervals of each
+//! address space separate.
+
+use std::collections::BTreeMap;
+use std::ops::Bound::{Included, Unbounded};
+
+use crate::address::AddressValue;
+use crate::space::AddressSpaceId;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Entry<V> {
+    start: AddressValue,
+    last: u64,
+    value: V,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+/// A map from non-overlapping address intervals to values.
+///
+/// Inserting an interval that overlaps existing intervals replaces the
+/// overlapped portions of them; the portions that are not overlapped are
+/// kept, hence values must be `Clone`.
+pub struct AddressMap<V> {
+    mapping: BTreeMap<(AddressSpaceId, u64), Entry<V>>,
+}
+
+impl<V> Default for AddressMap<V> {
+    fn default() -> Self {
+        Self {
+            mapping: BTreeMap::new(),
+        }
+    }
+}
+
+impl<V> AddressMap<V>
+where
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of intervals.
+    pub fn len(&self) -> usize {
+        self.mapping.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mapping.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.mapping.clear();
+    }
+
+    /// Maps the `length` bytes from `start` to `value`; intervals are
+    /// truncated at the highest offset of `start`'s space.
+    pub fn insert(&mut self, start: AddressValue, length: u64, value: V) {
+        if let Some(last) = Self::last_offset(&start, length) {
+            self.remove_offsets(start.space(), start.offset(), last);
+            self.mapping.insert(
+                (start.space(), start.offset()),
+                Entry { start, last, value },
+            );
+        }
+    }
+
+    /// Unmaps the `length` bytes from `start`, splitting any intervals
+    /// that partially overlap them.
+    pub fn remove(&mut self, start: &AddressValue, length: u64) {
+        if let Some(last) = Self::last_offset(start, length) {
+            self.remove_offsets(start.space(), start.offset(), last);
+        }
+    }
+
+    pub fn contains(&self, address: &AddressValue) -> bool {
+        self.get(address).is_some()
+    }
+
+    pub fn get(&self, address: &AddressValue) -> Option<&V> {
+        self.find(address).map(|entry| &entry.value)
+    }
+
+    pub fn get_mut(&mut self, address: &AddressValue) -> Option<&mut V> {
+        let key = self.find(address).map(|entry| Self::key(&entry.start))?;
+        self.mapping.get_mut(&key).map(|entry| &mut entry.value)
+    }
+
+    /// The interval containing `address`, as its start and length, and its
+    /// value.
+    pub fn get_interval(&self, address: &AddressValue) -> Option<(AddressValue, u64, &V)> {
+        self.find(address).map(Self::interval)
+    }
+
+    /// The intervals overlapping the `length` bytes from `start`, in order
+    /// of their starting offsets.
+    pub fn overlapping<'a>(
+        &'a self,
+        start: &AddressValue,
+        length: u64,
+    ) -> impl Iterator<Item = (AddressValue, u64, &'a V)> + 'a {
+        let space = start.space();
+        let bounds = Self::last_offset(start, length).map(|last| {
+            let first = self
+                .find(start)
+                .map(|entry| entry.start.offset())
+                .unwrap_or_else(|| start.offset());
+            (first, last)
+        });
+
+        bounds
+            .into_iter()
+            .flat_map(move |(first, last)| {
+                self.mapping
+                    .range((Included((space, first)), Included((space, last))))
+                    .map(|(_, entry)| entry)
+            })
+            .map(Self::interval)
+    }
+
+    /// All intervals, ordered by address space and starting offset.
+    pub fn iter(&self) -> impl Iterator<Item = (AddressValue, u64, &V)> {
+        self.mapping.values().map(Self::interval)
+    }
+
+    fn find(&self, address: &AddressValue) -> Option<&Entry<V>> {
+        self.mapping
+            .range((Unbounded, Included(Self::key(address))))
+            .next_back()
+            .map(|(_, entry)| entry)
+            .filter(|entry| {
+                entry.start.space() == address.space() && entry.last >= address.offset()
+            })
+    }
+
+    fn remove_offsets(&mut self, space: AddressSpaceId, first: u64, last: u64) {
+        let first_key = self
+            .mapping
+            .range((Unbounded, Included((space, first))))
+            .next_back()
+            .filter(|((s, _), entry)| *s == space && entry.last >= first)
+            .map(|(key, _)| *key)
+            .unwrap_or((space, first));
+
+        let keys = self
+            .mapping
+            .range((Included(first_key), Included((space, last))))
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+
+        for key in keys {
+            let entry = self.mapping.remove(&key).unwrap();
+            let offset = entry.start.offset();
+
+            if offset < first {
+                self.mapping.insert(
+                    key,
+                    Entry {
+                        start: entry.start,
+                        last: first - 1,
+                        value: entry.value.clone(),
+                    },
+                );
+            }
+
+            if entry.last > last {
+                let start = entry.start.wrap(last + 1);
+                self.mapping.insert(
+                    Self::key(&start),
+                    Entry {
+                        start,
+                        last: entry.last,
+                        value: entry.value,
+                    },
+                );
+            }
+        }
+    }
+
+    fn interval(entry: &Entry<V>) -> (AddressValue, u64, &V) {
+        let length = entry.last - entry.start.offset() + 1;
+        (entry.start, length, &entry.value)
+    }
+
+    fn key(address: &AddressValue) -> (AddressSpaceId, u64) {
+        (address.space(), address.offset())
+    }
+
+    fn last_offset(start: &AddressValue, length: u64) -> Option<u64> {
+        if length == 0 {
+            return None;
+        }
+        Some(
+            start
+                .offset()
+                .saturating_add(length - 1)
+                .min(start.highest_offset()),
+        )
+    }
+}
+
+impl<V> FromIterator<(AddressValue, u64, V)> for AddressMap<V>
+where
+    V: Clone,
+{
+    fn from_iter<I: IntoIterator<Item = (AddressValue, u64, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (start, length, value) in iter {
+            map.insert(start, length, value);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::space::{AddressSpace, Space, SpaceKind};
+
+    #[test]
+    fn test_address_map() {
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 8, 1, 1, None, 0));
+        let ram = |offset| AddressValue::new(&ram, offset);
+
+        let mut map = AddressMap::new();
+        map.insert(ram(0x1000), 0x1000, "text");
+        map.insert(ram(0x3000), 0x100, "data");
+
+        assert_eq!(map.get(&ram(0x1fff)), Some(&"text"));
+        assert_eq!(map.get(&ram(0x2000)), None);
+        assert_eq!(
+            map.get_interval(&ram(0x3080)),
+            Some((ram(0x3000), 0x100, &"data"))
+        );
+
+        // split text into two intervals
+        map.insert(ram(0x1800), 0x10, "plt");
+        assert_eq!(map.len(), 4);
+        assert_eq!(
+            map.overlapping(&ram(0x1400), 0x2000)
+                .map(|(start, length, value)| (start.offset(), length, *value))
+                .collect::<Vec<_>>(),
+            vec![
+                (0x1000, 0x800, "text"),
+                (0x1800, 0x10, "plt"),
+                (0x1810, 0x7f0, "text"),
+                (0x3000, 0x100, "data"),
+            ]
+        );
+
+        map.remove(&ram(0x1000), 0x1000);
+        assert_eq!(map.len(), 1);
+        assert!(!map.contains(&ram(0x1800)));
+    }
+}
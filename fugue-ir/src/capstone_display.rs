@@ -0,0 +1,158 @@
+//! Assembly text rendered by capstone, for languages whose SLEIGH display
+//! output is awkward to read.
+//!
+//! Instructions are still decoded by the translator, which remains the
+//! source of their lengths and semantics; capstone is only consulted for
+//! their mnemonics and operands. Where capstone cannot decode an
+//! instruction, or decodes it with a different length, the translator's
+//! text is kept.
+
+use ::capstone::{Arch, Capstone, Endian as CsEndian, Mode, NO_EXTRA_MODE};
+
+use fugue_arch::ArchitectureDef;
+use fugue_bytes::Endian;
+
+use crate::address::AddressValue;
+use crate::disassembly::{ContextDatabase, IRBuilderArena};
+use crate::error::Error as LiftError;
+use crate::il::instruction::Instruction;
+use crate::translator::Translator;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("capstone does not support architecture `{0}`")]
+    Unsupported(ArchitectureDef),
+    #[error("capstone initialisation error: {0}")]
+    Capstone(::capstone::Error),
+}
+
+/// Renders instructions using capstone.
+///
+/// For ARM, separate decoders are used for ARM and Thumb instructions,
+/// selected by the `TMode` context variable at each instruction's address.
+pub struct CapstoneDisplay {
+    primary: Capstone,
+    thumb: Option<Capstone>,
+}
+
+impl CapstoneDisplay {
+    pub fn new(architecture: &ArchitectureDef) -> Result<Self, Error> {
+        let endian = if architecture.endian() == Endian::Big {
+            CsEndian::Big
+        } else {
+            CsEndian::Little
+        };
+
+        let unsupported = || Error::Unsupported(architecture.clone());
+
+        let (arch, mode) = match (architecture.processor(), architecture.bits()) {
+            ("x86", 16) => (Arch::X86, Mode::Mode16),
+            ("x86", 32) => (Arch::X86, Mode::Mode32),
+            ("x86", 64) => (Arch::X86, Mode::Mode64),
+            ("ARM", 32) => (Arch::ARM, Mode::Arm),
+            ("AARCH64", 64) => (Arch::ARM64, Mode::Arm),
+            ("MIPS", 32) => (Arch::MIPS, Mode::Mips32),
+            ("MIPS", 64) => (Arch::MIPS, Mode::Mips64),
+            ("PowerPC", 32) => (Arch::PPC, Mode::Mode32),
+            ("PowerPC", 64) => (Arch::PPC, Mode::Mode64),
+            ("sparc", 32) => (Arch::SPARC, Mode::Default),
+            ("sparc", 64) => (Arch::SPARC, Mode::V9),
+            ("68000", 32) => (Arch::M68K, Mode::M68k040),
+            _ => return Err(unsupported()),
+        };
+
+        let open = |arch, mode| {
+            Capstone::new_raw(arch, mode, NO_EXTRA_MODE, Some(endian)).map_err(Error::Capstone)
+        };
+
+        let primary = open(arch, mode)?;
+        let thumb = if arch == Arch::ARM {
+            Some(open(Arch::ARM, Mode::Thumb)?)
+        } else {
+            None
+        };
+
+        Ok(Self { primary, thumb })
+    }
+
+    /// The mnemonic and operands of the first instruction in `bytes`, and
+    /// its length, if capstone can decode it.
+    pub fn render(
+        &self,
+        address: u64,
+        bytes: &[u8],
+        thumb: bool,
+    ) -> Option<(String, String, usize)> {
+        let cs = if thumb {
+            self.thumb.as_ref()?
+        } else {
+            &self.primary
+        };
+
+        let insns = cs.disasm_count(bytes, address, 1).ok()?;
+        let insn = insns.first()?;
+
+        Some((
+            insn.mnemonic()?.to_owned(),
+            insn.op_str().unwrap_or_default().to_owned(),
+            insn.bytes().len(),
+        ))
+    }
+
+    /// Disassembles the instruction at `address` using `translator`,
+    /// replacing its text with capstone's where capstone decodes an
+    /// instruction of the same length.
+    pub fn disassemble<'z>(
+        &self,
+        translator: &Translator,
+        db: &mut ContextDatabase,
+        builder: &'z IRBuilderArena,
+        address: AddressValue,
+        bytes: &[u8],
+    ) -> Result<Instruction<'z>, LiftError> {
+        let thumb =
+            self.thumb.is_some() && db.get_variable("TMode", address.clone()).unwrap_or(0) != 0;
+
+        let mut insn = translator.disassemble(db, builder, address, bytes)?;
+
+        let length = insn.length();
+        if let Some((mnemonic, operands, cs_length)) = self.render(
+            insn.address().offset(),
+            &bytes[..length.min(bytes.len())],
+            thumb,
+        ) {
+            if cs_length == length {
+                insn.mnemonic = bumpalo::format!(in builder.inner(), "{}", mnemonic);
+                insn.operands = bumpalo::format!(in builder.inner(), "{}", operands);
+            }
+        }
+
+        Ok(insn)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_x86_64() -> Result<(), Box<dyn std::error::Error>> {
+        let display =
+            CapstoneDisplay::new(&ArchitectureDef::new("x86", Endian::Little, 64, "default"))?;
+
+        let (mnemonic, operands, length) = display
+            .render(0x1000, &[0x48, 0x89, 0xe5], false)
+            .expect("decodable instruction");
+
+        assert_eq!(mnemonic, "mov");
+        assert_eq!(operands, "rbp, rsp");
+        assert_eq!(length, 3);
+
+        assert!(
+            CapstoneDisplay::new(&ArchitectureDef::new("6502", Endian::Little, 16, "default"))
+                .is_err()
+        );
+
+        Ok(())
+    }
+}
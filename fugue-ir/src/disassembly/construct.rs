@@ -8,7 +8,6 @@ use crate::disassembly::{Error, ParserWalker};
 use crate::space::{AddressSpace, AddressSpaceId};
 use crate::space_manager::SpaceManager;
 
-use unsafe_unwrap::UnsafeUnwrap;
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub enum HandleKind {
@@ -34,6 +33,20 @@ pub enum ConstTpl {
     FlowDestSize,
 }
 
+// templates are evaluated once the instruction's operands are resolved, in
+// the space of the instruction being lifted
+fn current_space<'m>(walker: &ParserWalker, manager: &'m SpaceManager) -> &'m AddressSpace {
+    manager
+        .space_by_id(walker.address().space())
+        .expect("instruction address is in a space managed by the translator")
+}
+
+fn operand_handle<'w, 'b>(walker: &'w ParserWalker<'b, '_, '_>, index: usize) -> &'w FixedHandle<'b> {
+    walker
+        .handle_ref(index)
+        .expect("operand handles are resolved before templates are evaluated")
+}
+
 impl ConstTpl {
     pub fn is_handle(&self) -> bool {
         matches!(self, Self::Handle(_, _))
@@ -68,10 +81,11 @@ impl ConstTpl {
     ) -> u64 {
         match self {
             Self::Start => walker.address().offset(),
-            Self::Next => walker.unchecked_next_address().offset(), // .ok_or_else(|| Error::InvalidNextAddress)?.offset(),
-            Self::CurrentSpaceSize => manager
-                .unchecked_space_by_id(walker.address().space())
-                .address_size() as u64,
+            Self::Next => walker
+                .next_address()
+                .expect("next address is set before templates are evaluated")
+                .offset(),
+            Self::CurrentSpaceSize => current_space(walker, manager).address_size() as u64,
             Self::CurrentSpace => walker.address().space().index() as u64,
             Self::Relative(value) | Self::Real(value) => *value,
             Self::SpaceId(space) => space.index() as u64, /* { manager.space_by_name(name)
@@ -79,14 +93,16 @@ impl ConstTpl {
             .index() as u64,
             },*/
             Self::Handle(index, kind) => {
-                let handle = walker.unchecked_handle_ref(*index); //?.ok_or_else(|| Error::InvalidHandle)?;
+                let handle = operand_handle(walker, *index);
                 match kind {
                     HandleKind::Space => {
                         if handle.offset_space.is_none() {
                             handle.space.index() as u64
                         } else {
-                            unsafe { handle.temporary_space.unsafe_unwrap() }.index() as u64
-                            // .ok_or_else(|| Error::InvalidSpace)?.index() as u64
+                            handle
+                                .temporary_space
+                                .expect("dynamic handle has a temporary space")
+                                .index() as u64
                         }
                     }
                     HandleKind::Offset => {
@@ -128,8 +144,7 @@ impl ConstTpl {
     ) {
         match self {
             Self::Handle(index, _) => {
-                let h = walker.unchecked_handle_ref(*index); //?
-                                                             //.ok_or_else(|| Error::InvalidHandle)?;
+                let h = operand_handle(walker, *index);
                 handle.offset_space = h.offset_space;
                 handle.offset_offset = h.offset_offset;
                 handle.offset_size = h.offset_size;
@@ -149,56 +164,36 @@ impl ConstTpl {
         manager: &'b SpaceManager,
     ) -> &'b AddressSpace {
         match self {
-            Self::CurrentSpace => manager.unchecked_space_by_id(walker.address().space()),
+            Self::CurrentSpace => current_space(walker, manager),
             Self::Handle(index, kind) => {
                 if *kind == HandleKind::Space {
-                    walker
-                        .unchecked_handle_ref(*index) /*?.ok_or_else(|| Error::InvalidHandle)?*/
-                        .space
+                    operand_handle(walker, *index).space
                 } else {
                     unreachable!()
                     //return Err(Error::InconsistentState)
                 }
             }
-            Self::SpaceId(space) => manager.unchecked_space_by_id(*space),
+            Self::SpaceId(space) => manager
+                .space_by_id(*space)
+                .expect("space of template is managed by the translator"),
             _ => unreachable!(), //return Err(Error::InconsistentState)
         }
     }
 
-    pub(crate) fn unchecked_fix_space<'a, 'b, 'c, 'z>(
-        &'b self,
-        walker: &mut ParserWalker<'b, 'c, 'z>,
-        manager: &'b SpaceManager,
-    ) -> Option<&'b AddressSpace> {
-        match self {
-            Self::CurrentSpace => Some(manager.unchecked_space_by_id(walker.address().space())),
-            Self::Handle(index, kind) => {
-                if *kind == HandleKind::Space {
-                    let h = walker.unchecked_handle_ref(*index); //?.ok_or_else(|| Error::InvalidHandle)?;
-                    if h.offset_space.is_none() {
-                        Some(h.space)
-                    } else {
-                        h.temporary_space
-                    }
-                } else {
-                    unreachable!()
-                }
-            }
-            Self::SpaceId(space) => Some(manager.unchecked_space_by_id(*space)),
-            _ => unreachable!(),
-        }
-    }
-
     pub fn fix_space<'a, 'b, 'c, 'z>(
         &'b self,
         walker: &mut ParserWalker<'b, 'c, 'z>,
         manager: &'b SpaceManager,
     ) -> Result<Option<&'b AddressSpace>, Error> {
         Ok(match self {
-            Self::CurrentSpace => Some(manager.unchecked_space_by_id(walker.address().space())),
+            Self::CurrentSpace => Some(
+                manager
+                    .space_by_id(walker.address().space())
+                    .ok_or(Error::InvalidSpace)?,
+            ),
             Self::Handle(index, kind) => {
                 if *kind == HandleKind::Space {
-                    let h = walker.unchecked_handle_ref(*index); //?.ok_or_else(|| Error::InvalidHandle)?;
+                    let h = walker.handle_ref(*index).ok_or(Error::InvalidHandle)?;
                     if h.offset_space.is_none() {
                         Some(h.space)
                     } else {
@@ -208,7 +203,7 @@ impl ConstTpl {
                     return Err(Error::InconsistentState);
                 }
             }
-            Self::SpaceId(space) => Some(manager.unchecked_space_by_id(*space)),
+            Self::SpaceId(space) => Some(manager.space_by_id(*space).ok_or(Error::InvalidSpace)?),
             _ => return Err(Error::InconsistentState),
         })
     }
@@ -277,22 +272,33 @@ impl HandleTpl {
             self.ptr_offset.offset(&mut handle, walker, manager);
             handle
         } else {
-            let mut handle = FixedHandle::new(unsafe {
-                self.space
-                    .unchecked_fix_space(walker, manager)
-                    .unsafe_unwrap()
-            });
+            let space = self
+                .space
+                .fix_space(walker, manager)
+                .expect("space of handle template resolves")
+                .expect("handle template has a space");
+            let mut handle = FixedHandle::new(space);
             handle.size = self.size.fix(walker, manager) as usize;
             handle.offset_offset = self.ptr_offset.fix(walker, manager);
-            handle.offset_space = self.ptr_space.unchecked_fix_space(walker, manager);
-
-            if unsafe { handle.offset_space.unsafe_unwrap() }.is_constant() {
+            handle.offset_space = self
+                .ptr_space
+                .fix_space(walker, manager)
+                .expect("pointer space of handle template resolves");
+
+            if handle
+                .offset_space
+                .expect("dynamic handle template has a pointer space")
+                .is_constant()
+            {
                 handle.offset_space = None;
                 handle.offset_offset = handle.offset_offset * handle.space.word_size() as u64;
                 handle.offset_offset = handle.space.wrap_offset(handle.offset_offset);
             } else {
                 handle.offset_size = self.ptr_size.fix(walker, manager) as usize;
-                handle.temporary_space = self.tmp_space.unchecked_fix_space(walker, manager);
+                handle.temporary_space = self
+                    .tmp_space
+                    .fix_space(walker, manager)
+                    .expect("temporary space of handle template resolves");
                 handle.temporary_offset = self.tmp_offset.fix(walker, manager);
             }
 
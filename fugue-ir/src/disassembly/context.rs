@@ -9,7 +9,7 @@ use crate::address::AddressValue;
 use crate::disassembly::partmap::{BoundKind, PartMap};
 use crate::disassembly::VarnodeData;
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct ContextBitRange {
     word: usize,
     start_bit: usize,
@@ -57,7 +57,7 @@ impl ContextBitRange {
     }
 
     #[inline]
-    fn get(&self, values: &[u32]) -> u32 {
+    pub(crate) fn get(&self, values: &[u32]) -> u32 {
         values[self.word()].checked_shr(self.shift()).unwrap_or(0) & self.mask()
     }
 
@@ -82,13 +82,13 @@ impl ContextBitRange {
     }
 }
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct TrackedContext {
     location: VarnodeData,
     value: u32,
 }
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct TrackedSet(Vec<TrackedContext>);
 
 impl Default for TrackedSet {
@@ -111,7 +111,7 @@ impl DerefMut for TrackedSet {
     }
 }
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 struct FreeArray {
     values: Vec<u32>,
     masks: Vec<u32>,
@@ -302,12 +302,23 @@ impl ContextCache {
 }
 */
 
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, Eq, serde::Deserialize, serde::Serialize)]
 pub struct ContextDatabase {
     size: usize,
     variables: Map<String, ContextBitRange>,
     database: PartMap<AddressValue, FreeArray>,
     trackbase: PartMap<AddressValue, TrackedSet>,
+    #[serde(skip)]
+    generation: u64,
+}
+
+impl PartialEq for ContextDatabase {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && self.variables == other.variables
+            && self.database == other.database
+            && self.trackbase == other.trackbase
+    }
 }
 
 impl ContextDatabase {
@@ -317,6 +328,7 @@ impl ContextDatabase {
             variables: Map::new(),
             database: PartMap::new(Default::default()),
             trackbase: PartMap::new(Default::default()),
+            generation: 0,
         }
     }
 
@@ -324,7 +336,16 @@ impl ContextDatabase {
         self.size
     }
 
+    /// A counter that advances whenever the database is (or may have been)
+    /// modified, e.g., by an instruction committing context when lifted;
+    /// comparing it before and after an operation is cheaper than
+    /// comparing the database's contents.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
     pub fn new_tracked_set(&mut self, addr1: AddressValue, addr2: AddressValue) -> &mut TrackedSet {
+        self.generation += 1;
         let range = self.trackbase.clear_range(&addr1, &addr2);
         range.clear();
         range
@@ -339,14 +360,22 @@ impl ContextDatabase {
     }
 
     pub fn tracked_default_mut(&mut self) -> &mut TrackedSet {
+        self.generation += 1;
         self.trackbase.default_value_mut()
     }
 
+    pub fn variables(&self) -> impl Iterator<Item = (&str, &ContextBitRange)> {
+        self.variables
+            .iter()
+            .map(|(name, range)| (name.as_str(), range))
+    }
+
     pub fn variable<S: Borrow<str>>(&self, name: S) -> Option<&ContextBitRange> {
         self.variables.get(name.borrow())
     }
 
     pub fn variable_mut<S: Borrow<str>>(&mut self, name: S) -> Option<&mut ContextBitRange> {
+        self.generation += 1;
         self.variables.get_mut(name.borrow())
     }
 
@@ -361,6 +390,7 @@ impl ContextDatabase {
         address: AddressValue,
         value: u32,
     ) -> Option<()> {
+        self.generation += 1;
         let context = self.variables.get(name.borrow())?;
         let num = context.word();
         let mask = context.mask().checked_shl(context.shift()).unwrap_or(0);
@@ -373,6 +403,7 @@ impl ContextDatabase {
     }
 
     pub fn set_variable_default<S: Borrow<str>>(&mut self, name: S, value: u32) -> Option<()> {
+        self.generation += 1;
         let context = self.variables.get(name.borrow())?;
         let default = self.database.default_value_mut();
 
@@ -387,6 +418,7 @@ impl ContextDatabase {
         start_bit: usize,
         end_bit: usize,
     ) -> Option<()> {
+        self.generation += 1;
         if !self.database.is_empty() {
             return None;
         }
@@ -453,6 +485,7 @@ impl ContextDatabase {
         mask: u32,
         value: u32,
     ) {
+        self.generation += 1;
         self.database.split(&current_address);
 
         get_region_to_change_point(&mut self.database, commit_address, num, mask, |change| {
@@ -470,6 +503,7 @@ impl ContextDatabase {
         mask: u32,
         value: u32,
     ) {
+        self.generation += 1;
         get_region_for_set(&mut self.database, addr1, addr2, num, mask, |change| {
             change[num] = (change[num] & !mask) | value;
         })
@@ -482,6 +516,7 @@ impl ContextDatabase {
         addr2: Option<AddressValue>,
         value: u32,
     ) -> Option<()> {
+        self.generation += 1;
         let context = self.variables.get(name.borrow())?;
         get_region_for_set(
             &mut self.database,
@@ -493,6 +528,64 @@ impl ContextDatabase {
         );
         Some(())
     }
+
+    /// Runs `f` on the database, then restores the context state in
+    /// effect before it was run, e.g., to speculatively lift instructions
+    /// without recording their context changes.
+    pub fn scoped<T, F>(&mut self, f: F) -> T
+    where
+        F: FnOnce(&mut ContextDatabase) -> T,
+    {
+        f(&mut self.guard())
+    }
+
+    /// Snapshots the context state, which is restored when the returned
+    /// guard is dropped unless it is committed.
+    pub fn guard(&mut self) -> ContextGuard<'_> {
+        let saved = Some((self.database.clone(), self.trackbase.clone()));
+        ContextGuard { db: self, saved }
+    }
+}
+
+/// A snapshot of the state of a [`ContextDatabase`]; see
+/// [`ContextDatabase::guard`].
+pub struct ContextGuard<'a> {
+    db: &'a mut ContextDatabase,
+    saved: Option<(
+        PartMap<AddressValue, FreeArray>,
+        PartMap<AddressValue, TrackedSet>,
+    )>,
+}
+
+impl<'a> ContextGuard<'a> {
+    /// Keeps the changes made since the snapshot was taken.
+    pub fn commit(mut self) {
+        self.saved = None;
+    }
+}
+
+impl<'a> Deref for ContextGuard<'a> {
+    type Target = ContextDatabase;
+
+    fn deref(&self) -> &Self::Target {
+        self.db
+    }
+}
+
+impl<'a> DerefMut for ContextGuard<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.db
+    }
+}
+
+impl<'a> Drop for ContextGuard<'a> {
+    fn drop(&mut self) {
+        if let Some((database, trackbase)) = self.saved.take() {
+            self.db.database = database;
+            self.db.trackbase = trackbase;
+            self.db.generation += 1;
+        }
+    }
 }
 
 fn get_region_to_change_point<'a, F>(
@@ -553,3 +646,33 @@ fn get_region_for_set<'a, F>(
         f(change)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::space::{AddressSpace, Space, SpaceKind};
+
+    #[test]
+    fn test_scoped() {
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 4, 1, 1, None, 0));
+        let address = AddressValue::new(&ram, 0x1000);
+
+        let mut db = ContextDatabase::new();
+        db.register_variable("TMode", 0, 0);
+        db.set_variable_default("TMode", 0);
+
+        let tmode = db.scoped(|db| {
+            db.set_variable("TMode", address, 1);
+            db.get_variable("TMode", address)
+        });
+
+        assert_eq!(tmode, Some(1));
+        assert_eq!(db.get_variable("TMode", address), Some(0));
+
+        let mut guard = db.guard();
+        guard.set_variable("TMode", address, 1);
+        guard.commit();
+
+        assert_eq!(db.get_variable("TMode", address), Some(1));
+    }
+}
@@ -29,6 +29,21 @@ pub enum Error {
     InvalidHandle,
     #[error("inconsistent disassembly state")]
     InconsistentState,
+    #[error("address `{:#x}` is not mapped", address)]
+    Unmapped { address: u64 },
+    #[error("address `{:#x}` is not executable", address)]
+    NonExecutable { address: u64 },
+    #[error(
+        "instruction at `{:#x}` of length {} extends past boundary at `{:#x}`",
+        address,
+        length,
+        boundary
+    )]
+    CrossesBoundary {
+        address: u64,
+        length: usize,
+        boundary: u64,
+    },
     #[error("{0}")]
     Invariant(String),
 }
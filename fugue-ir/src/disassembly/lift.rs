@@ -25,7 +25,6 @@ use std::sync::Arc;
 use ustr::Ustr;
 
 use smallvec::SmallVec;
-use unsafe_unwrap::UnsafeUnwrap;
 
 use crate::il::ecode::{self, ECode};
 use crate::il::pcode::{self, PCode};
@@ -539,23 +538,22 @@ impl<'b, 'c, 'cz, 'z> IRBuilder<'b, 'c, 'cz, 'z> {
         let nops = ctor.operand_count();
 
         for i in 0..nops {
-            let operand = symbols.unchecked_symbol(self.walker.unchecked_constructor().operand(i));
+            let operand = symbols
+                .symbol(self.current_constructor()?.operand(i))
+                .ok_or(Error::InvalidSymbol)?;
             let symbol = operand.defining_symbol(symbols);
             if symbol.is_none() || !symbol.as_ref().unwrap().is_subtable() {
                 continue;
             }
 
-            self.walker.unchecked_push_operand(i); //?;
-            if let Some(ctpl) = self
-                .walker
-                .unchecked_constructor()
-                .named_template(unsafe { section_num.unsafe_unwrap() })
-            {
+            self.walker.push_operand(i)?;
+            let constructor = self.current_constructor()?;
+            if let Some(ctpl) = section_num.and_then(|n| constructor.named_template(n)) {
                 self.build(ctpl, section_num, symbols)?;
             } else {
-                self.build_empty(self.walker.unchecked_constructor(), section_num, symbols)?;
+                self.build_empty(constructor, section_num, symbols)?;
             }
-            self.walker.unchecked_pop_operand(); //?;
+            self.walker.pop_operand()?;
         }
         Ok(())
     }
@@ -568,14 +566,16 @@ impl<'b, 'c, 'cz, 'z> IRBuilder<'b, 'c, 'cz, 'z> {
         symbols: &'b SymbolTable,
     ) -> Result<(), Error> {
         let index = op.input(0).offset().real() as usize;
-        let operand = symbols.unchecked_symbol(self.walker.unchecked_constructor().operand(index));
+        let operand = symbols
+            .symbol(self.current_constructor()?.operand(index))
+            .ok_or(Error::InvalidSymbol)?;
         let symbol = operand.defining_symbol(symbols);
         if symbol.is_none() || !symbol.as_ref().unwrap().is_subtable() {
             return Ok(());
         }
 
-        self.walker.unchecked_push_operand(index);
-        let constructor = self.walker.unchecked_constructor();
+        self.walker.push_operand(index)?;
+        let constructor = self.current_constructor()?;
         if let Some(section_num) = section_num {
             if let Some(ctpl) = constructor.named_template(section_num) {
                 self.build(ctpl, Some(section_num), symbols)?;
@@ -587,10 +587,14 @@ impl<'b, 'c, 'cz, 'z> IRBuilder<'b, 'c, 'cz, 'z> {
                 self.build(ctpl, None, symbols)?;
             }
         }
-        self.walker.unchecked_pop_operand();
+        self.walker.pop_operand()?;
         Ok(())
     }
 
+    fn current_constructor(&self) -> Result<&'b Constructor, Error> {
+        self.walker.constructor()?.ok_or(Error::InvalidConstructor)
+    }
+
     pub fn delay_slot(&mut self, symbols: &'b SymbolTable) -> Result<(), Error> {
         let old_unique_offset = self.unique_offset;
         let base_address = self.walker.address();
@@ -602,7 +606,10 @@ impl<'b, 'c, 'cz, 'z> IRBuilder<'b, 'c, 'cz, 'z> {
             let address = base_address.clone() + fall_offset;
             self.set_unique_offset(address.offset());
 
-            let context = unsafe { self.delay_contexts.remove(&address).unsafe_unwrap() };
+            let context = self
+                .delay_contexts
+                .remove(&address)
+                .ok_or(Error::InconsistentState)?;
             let mut nwalker = ParserWalker::new(context);
             let length = nwalker.length();
 
@@ -611,7 +618,7 @@ impl<'b, 'c, 'cz, 'z> IRBuilder<'b, 'c, 'cz, 'z> {
 
             self.walker.base_state();
 
-            if let Some(ctpl) = self.walker.unchecked_constructor().template() {
+            if let Some(ctpl) = self.current_constructor()?.template() {
                 self.build(ctpl, None, symbols)?;
             }
 
@@ -857,7 +864,7 @@ impl<'b, 'c, 'cz, 'z> IRBuilder<'b, 'c, 'cz, 'z> {
         }
     }
 
-    pub fn emit_ecode(self, length: usize) -> ECode {
+    pub fn emit_ecode(self, length: usize) -> Result<ECode, Error> {
         let mut slf = self;
         slf.walker.base_state();
 
@@ -880,14 +887,14 @@ impl<'b, 'c, 'cz, 'z> IRBuilder<'b, 'c, 'cz, 'z> {
                 op.opcode,
                 op.inputs.into_iter(),
                 op.output,
-            ));
+            )?);
         }
 
-        ECode {
+        Ok(ECode {
             operations,
             address,
             delay_slots,
             length,
-        }
+        })
     }
 }
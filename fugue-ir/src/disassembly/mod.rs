@@ -21,9 +21,18 @@ pub use lift::{
     IRBuilder, IRBuilderBase, IRBuilderArena
 };
 
+pub mod permissioned;
+pub use permissioned::{PermissionedBytes, Permissions, Span};
+
 pub mod symbol;
 pub use symbol::{Symbol, SymbolTable};
 
+pub mod speculative;
+pub use speculative::{Decoding, MultiDecode};
+
+pub mod sweep;
+pub use sweep::{BadInstruction, LinearSweep, RecoveryPolicy, SweepItem};
+
 pub mod varnodedata;
 pub use varnodedata::VarnodeData;
 
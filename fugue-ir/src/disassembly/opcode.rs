@@ -1,6 +1,6 @@
 use crate::deserialise::Error;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[derive(serde::Deserialize, serde::Serialize)]
 #[repr(u16)]
 pub enum Opcode {
@@ -154,4 +154,82 @@ impl Opcode {
             _ => return Err(Error::Invariant("invalid opcode name")),
         })
     }
+
+    /// The opcode's name, as used by SLEIGH; the inverse of
+    /// [`Opcode::from_str`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Copy => "COPY",
+            Self::Load => "LOAD",
+            Self::Store => "STORE",
+            Self::Branch => "BRANCH",
+            Self::CBranch => "CBRANCH",
+            Self::IBranch => "BRANCHIND",
+            Self::Call => "CALL",
+            Self::ICall => "CALLIND",
+            Self::CallOther => "CALLOTHER",
+            Self::Return => "RETURN",
+            Self::IntEq => "INT_EQUAL",
+            Self::IntNotEq => "INT_NOTEQUAL",
+            Self::IntSLess => "INT_SLESS",
+            Self::IntSLessEq => "INT_SLESSEQUAL",
+            Self::IntLess => "INT_LESS",
+            Self::IntLessEq => "INT_LESSEQUAL",
+            Self::IntZExt => "INT_ZEXT",
+            Self::IntSExt => "INT_SEXT",
+            Self::IntAdd => "INT_ADD",
+            Self::IntSub => "INT_SUB",
+            Self::IntCarry => "INT_CARRY",
+            Self::IntSCarry => "INT_SCARRY",
+            Self::IntSBorrow => "INT_SBORROW",
+            Self::IntNeg => "INT_2COMP",
+            Self::IntNot => "INT_NEGATE",
+            Self::IntXor => "INT_XOR",
+            Self::IntAnd => "INT_AND",
+            Self::IntOr => "INT_OR",
+            Self::IntLShift => "INT_LEFT",
+            Self::IntRShift => "INT_RIGHT",
+            Self::IntSRShift => "INT_SRIGHT",
+            Self::IntMul => "INT_MULT",
+            Self::IntDiv => "INT_DIV",
+            Self::IntSDiv => "INT_SDIV",
+            Self::IntRem => "INT_REM",
+            Self::IntSRem => "INT_SREM",
+            Self::BoolNot => "BOOL_NEGATE",
+            Self::BoolXor => "BOOL_XOR",
+            Self::BoolAnd => "BOOL_AND",
+            Self::BoolOr => "BOOL_OR",
+            Self::FloatEq => "FLOAT_EQUAL",
+            Self::FloatNotEq => "FLOAT_NOTEQUAL",
+            Self::FloatLess => "FLOAT_LESS",
+            Self::FloatLessEq => "FLOAT_LESSEQUAL",
+            Self::FloatIsNaN => "FLOAT_NAN",
+            Self::FloatAdd => "FLOAT_ADD",
+            Self::FloatDiv => "FLOAT_DIV",
+            Self::FloatMul => "FLOAT_MULT",
+            Self::FloatSub => "FLOAT_SUB",
+            Self::FloatNeg => "FLOAT_NEG",
+            Self::FloatAbs => "FLOAT_ABS",
+            Self::FloatSqrt => "FLOAT_SQRT",
+            Self::FloatOfInt => "INT2FLOAT",
+            Self::FloatOfFloat => "FLOAT2FLOAT",
+            Self::FloatTruncate => "TRUNC",
+            Self::FloatCeiling => "CEIL",
+            Self::FloatFloor => "FLOOR",
+            Self::FloatRound => "ROUND",
+            Self::Build => "BUILD",
+            Self::DelaySlot => "DELAY_SLOT",
+            Self::Piece => "PIECE",
+            Self::Subpiece => "SUBPIECE",
+            Self::Cast => "CAST",
+            Self::Label => "LABEL",
+            Self::CrossBuild => "CROSSBUILD",
+            Self::SegmentOp => "SEGMENTOP",
+            Self::CPoolRef => "CPOOLREF",
+            Self::New => "NEW",
+            Self::Insert => "INSERT",
+            Self::Extract => "EXTRACT",
+            Self::PopCount => "POPCOUNT",
+        }
+    }
 }
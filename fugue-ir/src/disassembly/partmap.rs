@@ -35,7 +35,7 @@ impl<'a, K, V> BoundKind<'a, K, V> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct PartMap<K: Ord, V> {
     mapping: Map<K, V>,
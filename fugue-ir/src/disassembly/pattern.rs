@@ -119,7 +119,7 @@ impl PatternExpression {
                     bits::zero_extend(res, bit_end - bit_start)
                 };
 
-                let offset = (*byte_start + walker.offset(None)) as u32 * 8;
+                let offset = (*byte_start + walker.offset(None)?) as u32 * 8;
                 let end_offset = size as u32 * 8;
 
                 (value, Some(offset + end_offset - (*bit_end as u32 + 1)..offset + end_offset - *bit_start as u32))
@@ -136,6 +136,12 @@ impl PatternExpression {
                 let mut size = (*byte_end as isize) - (*byte_start as isize) + 1;
                 let mut start = *byte_start as isize;
 
+                let width = bit_end - bit_start + 1;
+                walker.mark_context_bits(
+                    (8 * (*byte_end + 1)).saturating_sub(*shift as usize + width),
+                    width,
+                );
+
                 while size >= size_of::<u32>() as isize {
                     let tmp = walker.context_bytes(start as usize, size_of::<u32>());
                     res = res.checked_shl(8 * size_of::<u32>() as u32).unwrap_or(0);
@@ -157,7 +163,7 @@ impl PatternExpression {
                     bits::zero_extend(res, bit_end - bit_start)
                 };
 
-                let offset = (*byte_start + walker.offset(None)) as u32 * 8;
+                let offset = (*byte_start + walker.offset(None)?) as u32 * 8;
                 let end_offset = size as u32 * 8;
 
                 (value, Some(offset + end_offset - (*bit_end as u32 + 1)..offset + end_offset - *bit_start as u32))
@@ -168,9 +174,9 @@ impl PatternExpression {
                 constructor_id,
                 index,
             } => {
-                let table = symbols.unchecked_symbol(*table_id); //.ok_or_else(|| Error::InvalidSymbol)?;
+                let table = symbols.symbol(*table_id).ok_or(Error::InvalidSymbol)?;
                 let ctor = if let Symbol::Subtable { constructors, .. } = table {
-                    unsafe { constructors.get_unchecked(*constructor_id) }
+                    constructors.get(*constructor_id).ok_or(Error::InvalidConstructor)?
                 } else {
                     unreachable!()
                     //return Err(Error::InconsistentState)
@@ -180,11 +186,11 @@ impl PatternExpression {
                     def_expr,
                     subsym_id,
                     ..
-                } = symbols.unchecked_symbol(ctor.operand(*index)) /* .ok_or_else(|| Error::InvalidSymbol)? */ {
+                } = symbols.symbol(ctor.operand(*index)).ok_or(Error::InvalidSymbol)? {
                     if let Some(def_expr) = def_expr {
                         def_expr
                     } else if let Some(subsym_id) = subsym_id {
-                        let sym = symbols.unchecked_symbol(*subsym_id); /* .ok_or_else(|| Error::InvalidSymbol)?; */
+                        let sym = symbols.symbol(*subsym_id).ok_or(Error::InvalidSymbol)?;
                         sym.pattern_value()
                     } else {
                         return Ok((0, None))
@@ -346,6 +352,12 @@ impl PatternExpression {
                 let mut size = (*byte_end as isize) - (*byte_start as isize) + 1;
                 let mut start = *byte_start as isize;
 
+                let width = bit_end - bit_start + 1;
+                walker.mark_context_bits(
+                    (8 * (*byte_end + 1)).saturating_sub(*shift as usize + width),
+                    width,
+                );
+
                 while size >= size_of::<u32>() as isize {
                     let tmp = walker.context_bytes(start as usize, size_of::<u32>());
                     res = res.checked_shl(8 * size_of::<u32>() as u32).unwrap_or(0);
@@ -373,9 +385,9 @@ impl PatternExpression {
                 constructor_id,
                 index,
             } => {
-                let table = symbols.unchecked_symbol(*table_id); //.ok_or_else(|| Error::InvalidSymbol)?;
+                let table = symbols.symbol(*table_id).ok_or(Error::InvalidSymbol)?;
                 let ctor = if let Symbol::Subtable { constructors, .. } = table {
-                    unsafe { constructors.get_unchecked(*constructor_id) }
+                    constructors.get(*constructor_id).ok_or(Error::InvalidConstructor)?
                 } else {
                     unreachable!()
                     //return Err(Error::InconsistentState)
@@ -385,11 +397,11 @@ impl PatternExpression {
                     def_expr,
                     subsym_id,
                     ..
-                } = symbols.unchecked_symbol(ctor.operand(*index)) /* .ok_or_else(|| Error::InvalidSymbol)? */ {
+                } = symbols.symbol(ctor.operand(*index)).ok_or(Error::InvalidSymbol)? {
                     if let Some(def_expr) = def_expr {
                         def_expr
                     } else if let Some(subsym_id) = subsym_id {
-                        let sym = symbols.unchecked_symbol(*subsym_id); /* .ok_or_else(|| Error::InvalidSymbol)?; */
+                        let sym = symbols.symbol(*subsym_id).ok_or(Error::InvalidSymbol)?;
                         sym.pattern_value()
                     } else {
                         return Ok(0)
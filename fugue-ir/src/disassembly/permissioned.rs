@@ -0,0 +1,253 @@
+use std::borrow::Cow;
+
+use crate::disassembly::Error;
+
+/// The maximum number of bytes made available to the decoder past the end
+/// of a span, when decoding across span boundaries is allowed.
+const BOUNDARY_WINDOW: usize = 32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Permissions {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl Permissions {
+    pub const R: Self = Self::new(true, false, false);
+    pub const RW: Self = Self::new(true, true, false);
+    pub const RX: Self = Self::new(true, false, true);
+    pub const RWX: Self = Self::new(true, true, true);
+
+    pub const fn new(read: bool, write: bool, execute: bool) -> Self {
+        Self {
+            read,
+            write,
+            execute,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+/// A contiguous range of bytes mapped at `address`, e.g., a segment.
+pub struct Span<'a> {
+    address: u64,
+    bytes: &'a [u8],
+    permissions: Permissions,
+}
+
+impl<'a> Span<'a> {
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn permissions(&self) -> Permissions {
+        self.permissions
+    }
+
+    /// The address immediately following the span.
+    pub fn end(&self) -> u64 {
+        self.address.saturating_add(self.bytes.len() as u64)
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        self.address <= address && address < self.end()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// A byte source for disassembly and lifting composed of spans with
+/// permissions.
+///
+/// By default, instructions must be decoded from, and lie entirely within,
+/// a single executable span; `PermissionedBytes::allow_crossing` and
+/// `PermissionedBytes::allow_non_executable` relax these restrictions.
+/// Violations are reported as `Error::Unmapped`, `Error::NonExecutable`,
+/// or `Error::CrossesBoundary`.
+pub struct PermissionedBytes<'a> {
+    spans: Vec<Span<'a>>,
+    allow_crossing: bool,
+    allow_non_executable: bool,
+}
+
+impl<'a> PermissionedBytes<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a span; spans are expected not to overlap.
+    pub fn span(mut self, address: u64, bytes: &'a [u8], permissions: Permissions) -> Self {
+        let index = self.spans.partition_point(|span| span.address < address);
+        self.spans.insert(
+            index,
+            Span {
+                address,
+                bytes,
+                permissions,
+            },
+        );
+        self
+    }
+
+    /// Permits instructions to extend into adjacent spans.
+    pub fn allow_crossing(self, allow: bool) -> Self {
+        Self {
+            allow_crossing: allow,
+            ..self
+        }
+    }
+
+    /// Permits decoding from spans that are not executable.
+    pub fn allow_non_executable(self, allow: bool) -> Self {
+        Self {
+            allow_non_executable: allow,
+            ..self
+        }
+    }
+
+    pub fn spans(&self) -> &[Span<'a>] {
+        &self.spans
+    }
+
+    /// The span containing `address`, if any.
+    pub fn span_at(&self, address: u64) -> Option<&Span<'a>> {
+        self.index_at(address).map(|index| &self.spans[index])
+    }
+
+    /// The bytes available to the decoder at `address`; when crossing is
+    /// allowed, these include bytes from the spans that follow it.
+    pub fn view(&self, address: u64) -> Result<Cow<'a, [u8]>, Error> {
+        let index = self.permitted_span(address)?;
+        let span = &self.spans[index];
+        let bytes = &span.bytes[(address - span.address) as usize..];
+
+        if !self.allow_crossing || bytes.len() >= BOUNDARY_WINDOW {
+            return Ok(Cow::Borrowed(bytes));
+        }
+
+        let mut window = bytes.to_vec();
+        let mut end = span.end();
+
+        for next in self.spans[index + 1..].iter() {
+            if next.address != end || !self.is_permitted(next) || window.len() >= BOUNDARY_WINDOW {
+                break;
+            }
+            let take = (BOUNDARY_WINDOW - window.len()).min(next.bytes.len());
+            window.extend_from_slice(&next.bytes[..take]);
+            end = next.end();
+        }
+
+        Ok(Cow::Owned(window))
+    }
+
+    /// Checks that an instruction of `length` bytes at `address` lies
+    /// within the permitted spans.
+    pub fn check(&self, address: u64, length: usize) -> Result<(), Error> {
+        let index = self.permitted_span(address)?;
+        let last = address.saturating_add(length.max(1) as u64 - 1);
+
+        let mut span = &self.spans[index];
+        for next in self.spans[index + 1..].iter() {
+            if last < span.end() || !self.allow_crossing {
+                break;
+            }
+            if next.address != span.end() || !self.is_permitted(next) {
+                break;
+            }
+            span = next;
+        }
+
+        if last < span.end() {
+            Ok(())
+        } else {
+            Err(Error::CrossesBoundary {
+                address,
+                length,
+                boundary: span.end(),
+            })
+        }
+    }
+
+    fn permitted_span(&self, address: u64) -> Result<usize, Error> {
+        let index = self.index_at(address).ok_or(Error::Unmapped { address })?;
+
+        let permissions = self.spans[index].permissions;
+        if !permissions.read {
+            Err(Error::Unmapped { address })
+        } else if !(permissions.execute || self.allow_non_executable) {
+            Err(Error::NonExecutable { address })
+        } else {
+            Ok(index)
+        }
+    }
+
+    fn index_at(&self, address: u64) -> Option<usize> {
+        self.spans
+            .partition_point(|span| span.address <= address)
+            .checked_sub(1)
+            .filter(|&index| self.spans[index].contains(address))
+    }
+
+    fn is_permitted(&self, span: &Span) -> bool {
+        span.permissions.read && (span.permissions.execute || self.allow_non_executable)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_permissioned_bytes() {
+        let text = [0x90; 8];
+        let tail = [0xcc; 4];
+        let data = [0x00; 4];
+
+        let bytes = PermissionedBytes::new()
+            .span(0x1008, &tail, Permissions::RX)
+            .span(0x1000, &text, Permissions::RX)
+            .span(0x100c, &data, Permissions::RW);
+
+        assert!(matches!(
+            bytes.view(0x0fff),
+            Err(Error::Unmapped { address: 0x0fff })
+        ));
+        assert!(matches!(
+            bytes.view(0x100c),
+            Err(Error::NonExecutable { address: 0x100c })
+        ));
+        assert_eq!(bytes.view(0x1006).unwrap().len(), 2);
+
+        assert!(bytes.check(0x1004, 4).is_ok());
+        assert!(matches!(
+            bytes.check(0x1006, 4),
+            Err(Error::CrossesBoundary {
+                boundary: 0x1008,
+                ..
+            })
+        ));
+
+        let bytes = bytes.allow_crossing(true);
+        assert_eq!(
+            bytes.view(0x1006).unwrap().as_ref(),
+            &[0x90, 0x90, 0xcc, 0xcc, 0xcc, 0xcc]
+        );
+        assert!(bytes.check(0x1006, 4).is_ok());
+        assert!(matches!(
+            bytes.check(0x100a, 4),
+            Err(Error::CrossesBoundary {
+                boundary: 0x100c,
+                ..
+            })
+        ));
+
+        let bytes = bytes.allow_non_executable(true);
+        assert!(bytes.check(0x100a, 4).is_ok());
+        assert_eq!(bytes.view(0x1006).unwrap().len(), 10);
+    }
+}
@@ -0,0 +1,295 @@
+//! Decoding of the same bytes under alternative context assignments, e.g.,
+//! to decide whether code following a literal pool is ARM or Thumb.
+//!
+//! Each assignment is applied at the start of the window within a scope of
+//! the context database (see [`ContextDatabase::scoped`]), hence the
+//! database is left unchanged by speculation.
+
+use crate::address::AddressValue;
+use crate::disassembly::{ContextDatabase, LinearSweep, RecoveryPolicy, SweepItem};
+use crate::error::Error;
+use crate::il::ecode::ECode;
+use crate::il::pcode::PCode;
+use crate::translator::Translator;
+
+#[derive(Debug, Clone)]
+/// The instructions decoded under a single context assignment.
+pub struct Decoding<T> {
+    assignment: Vec<(String, u32)>,
+    instructions: Vec<T>,
+    length: usize,
+    score: f64,
+}
+
+impl<T> Decoding<T> {
+    /// The context variables set before decoding.
+    pub fn assignment(&self) -> &[(String, u32)] {
+        &self.assignment
+    }
+
+    pub fn instructions(&self) -> &[T] {
+        &self.instructions
+    }
+
+    pub fn into_instructions(self) -> Vec<T> {
+        self.instructions
+    }
+
+    /// The number of bytes decoded before the first failure.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// The proportion of the window decoded before the first failure, or
+    /// one if decoding did not fail.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+/// Decodes a window of bytes under multiple candidate context assignments.
+pub struct MultiDecode<'a> {
+    translator: &'a Translator,
+    limit: Option<usize>,
+}
+
+impl<'a> MultiDecode<'a> {
+    pub fn new(translator: &'a Translator) -> Self {
+        Self {
+            translator,
+            limit: None,
+        }
+    }
+
+    /// The maximum number of instructions to decode under each assignment.
+    pub fn limit(self, limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    pub fn pcode(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+        assignments: &[&[(&str, u32)]],
+    ) -> Vec<Decoding<PCode>> {
+        self.decode_with(
+            db,
+            address,
+            bytes,
+            assignments,
+            |translator, db, address, bytes| {
+                let pcode = translator.lift_pcode(db, address, bytes)?;
+                let length = pcode.length();
+                Ok((pcode, length))
+            },
+        )
+    }
+
+    pub fn ecode(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+        assignments: &[&[(&str, u32)]],
+    ) -> Vec<Decoding<ECode>> {
+        self.decode_with(
+            db,
+            address,
+            bytes,
+            assignments,
+            |translator, db, address, bytes| {
+                let ecode = translator.lift_ecode(db, address, bytes)?;
+                let length = ecode.length();
+                Ok((ecode, length))
+            },
+        )
+    }
+
+    /// Decodes `bytes` under each of `assignments` using `decode` (see
+    /// [`LinearSweep::sweep_with`]), returning the decodings that contain
+    /// at least one instruction, from highest to lowest score. Assignments
+    /// that refer to unknown context variables are skipped.
+    pub fn decode_with<T, F>(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+        assignments: &[&[(&str, u32)]],
+        mut decode: F,
+    ) -> Vec<Decoding<T>>
+    where
+        F: FnMut(
+            &Translator,
+            &mut ContextDatabase,
+            AddressValue,
+            &[u8],
+        ) -> Result<(T, usize), Error>,
+    {
+        let sweep = LinearSweep::new(self.translator).policy(RecoveryPolicy::Abort);
+        let sweep = if let Some(limit) = self.limit {
+            sweep.limit(limit)
+        } else {
+            sweep
+        };
+
+        let mut decodings = Vec::new();
+
+        for assignment in assignments {
+            let items = db.scoped(|db| {
+                for (name, value) in assignment.iter() {
+                    if db.set_variable(*name, address, *value).is_none() {
+                        log::debug!("skipping assignment to unknown context variable {}", name);
+                        return None;
+                    }
+                }
+
+                Some(sweep.sweep_with(
+                    db,
+                    address,
+                    bytes,
+                    |translator, db, address, bytes| {
+                        let (insn, length) = decode(translator, db, address, bytes)?;
+                        Ok(((insn, length), length))
+                    },
+                ))
+            });
+
+            let items = if let Some(items) = items {
+                items
+            } else {
+                continue;
+            };
+
+            let failed = items.iter().any(SweepItem::is_bad);
+            let mut instructions = Vec::with_capacity(items.len());
+            let mut length = 0;
+
+            for item in items {
+                if let SweepItem::Instruction((insn, size)) = item {
+                    instructions.push(insn);
+                    length += size;
+                }
+            }
+
+            if instructions.is_empty() {
+                continue;
+            }
+
+            let score = if failed {
+                length as f64 / bytes.len() as f64
+            } else {
+                1.0
+            };
+
+            decodings.push(Decoding {
+                assignment: assignment
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), *value))
+                    .collect(),
+                instructions,
+                length,
+                score,
+            });
+        }
+
+        // stable, so ties are ordered as their assignments
+        decodings.sort_by(|a, b| b.score.total_cmp(&a.score));
+        decodings
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_arch::ArchitectureDef;
+    use fugue_bytes::Endian;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_with() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("tiny", Endian::Little, 32, "default"),
+            &Default::default(),
+            "./data/test/tiny.sla",
+        )?;
+
+        // nop; alt (only decodable when mode = 1)
+        let bytes = [0x00, 0x00, 0x01, 0x00];
+
+        let mut db = translator.context_database();
+        let addr = translator.address(0x1000u64);
+
+        let decodings = MultiDecode::new(&translator).pcode(
+            &mut db,
+            addr,
+            &bytes,
+            &[&[("mode", 0)], &[("mode", 1)], &[("NotAVariable", 1)]],
+        );
+
+        assert_eq!(decodings.len(), 2);
+        assert_eq!(decodings[0].assignment(), &[("mode".to_string(), 1)]);
+        assert_eq!(decodings[0].length(), 4);
+        assert_eq!(decodings[0].score(), 1.0);
+        assert_eq!(decodings[1].assignment(), &[("mode".to_string(), 0)]);
+        assert_eq!(decodings[1].instructions().len(), 1);
+        assert_eq!(decodings[1].score(), 0.5);
+
+        // speculation leaves the database unchanged
+        assert_eq!(db.get_variable("mode", addr), Some(0));
+
+        let decodings = MultiDecode::new(&translator).limit(1).decode_with(
+            &mut db,
+            addr,
+            &bytes,
+            &[&[("mode", 1)]],
+            |_, _, address, _| Ok((address.offset(), 2)),
+        );
+
+        assert_eq!(decodings.len(), 1);
+        assert_eq!(decodings[0].instructions(), &[0x1000]);
+
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "requires processor specifications"]
+    fn test_multi_decode() -> Result<(), Box<dyn std::error::Error>> {
+        let mut translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("ARM", Endian::Little, 32, "V8T"),
+            &Default::default(),
+            "./data/processors/ARM/ARM8_le.sla",
+        )?;
+
+        translator.set_variable_default("TMode", 0);
+        translator.set_variable_default("LRset", 0);
+        translator.set_variable_default("spsr", 0);
+
+        // mov r0, r0; mov r0, r0
+        let bytes = [0x00, 0x00, 0xa0, 0xe1, 0x00, 0x00, 0xa0, 0xe1];
+
+        let mut db = translator.context_database();
+        let addr = translator.address(0x1000u64);
+
+        let decodings = MultiDecode::new(&translator).pcode(
+            &mut db,
+            addr,
+            &bytes,
+            &[&[("TMode", 0)], &[("TMode", 1)], &[("NotAVariable", 1)]],
+        );
+
+        assert_eq!(decodings.len(), 2);
+        assert_eq!(decodings[0].assignment(), &[("TMode".to_string(), 0)]);
+        assert_eq!(decodings[0].instructions().len(), 2);
+        assert_eq!(decodings[0].length(), 8);
+
+        assert_eq!(db.get_variable("TMode", addr), Some(0));
+
+        Ok(())
+    }
+}
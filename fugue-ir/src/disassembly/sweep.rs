@@ -0,0 +1,299 @@
+use crate::address::AddressValue;
+use crate::disassembly::{ContextDatabase, Error as DisassemblyError};
+use crate::error::Error;
+use crate::il::ecode::ECode;
+use crate::il::pcode::PCode;
+use crate::translator::Translator;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+/// What a sweep does on encountering bytes that cannot be decoded.
+pub enum RecoveryPolicy {
+    /// Stop the sweep.
+    #[default]
+    Abort,
+    /// Treat the bytes up to the next address aligned to the translator's
+    /// instruction alignment as data, and resume decoding from there.
+    NextAlignment,
+    /// Skip `n` bytes and retry.
+    Skip(usize),
+}
+
+#[derive(Debug)]
+/// A range of bytes that could not be decoded.
+pub struct BadInstruction {
+    address: AddressValue,
+    length: usize,
+    error: Error,
+}
+
+impl BadInstruction {
+    pub fn address(&self) -> AddressValue {
+        self.address
+    }
+
+    /// The number of bytes skipped.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// The error raised when decoding the first instruction of the range.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+}
+
+#[derive(Debug)]
+pub enum SweepItem<T> {
+    Instruction(T),
+    BadInstruction(BadInstruction),
+}
+
+impl<T> SweepItem<T> {
+    pub fn instruction(&self) -> Option<&T> {
+        if let Self::Instruction(insn) = self {
+            Some(insn)
+        } else {
+            None
+        }
+    }
+
+    pub fn bad_instruction(&self) -> Option<&BadInstruction> {
+        if let Self::BadInstruction(bad) = self {
+            Some(bad)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_bad(&self) -> bool {
+        matches!(self, Self::BadInstruction(_))
+    }
+}
+
+/// Decodes a sequence of instructions from a byte buffer, recovering from
+/// decoding failures according to a `RecoveryPolicy`. Runs of adjacent
+/// bytes that cannot be decoded are recorded as a single `BadInstruction`.
+pub struct LinearSweep<'a> {
+    translator: &'a Translator,
+    policy: RecoveryPolicy,
+    limit: Option<usize>,
+}
+
+impl<'a> LinearSweep<'a> {
+    pub fn new(translator: &'a Translator) -> Self {
+        Self {
+            translator,
+            policy: RecoveryPolicy::default(),
+            limit: None,
+        }
+    }
+
+    pub fn policy(self, policy: RecoveryPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    /// The maximum number of instructions to decode; bad instructions are
+    /// not counted.
+    pub fn limit(self, limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    pub fn pcode(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+    ) -> Vec<SweepItem<PCode>> {
+        self.sweep_with(db, address, bytes, |translator, db, address, bytes| {
+            let pcode = translator.lift_pcode(db, address, bytes)?;
+            let length = pcode.length();
+            Ok((pcode, length))
+        })
+    }
+
+    pub fn ecode(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+    ) -> Vec<SweepItem<ECode>> {
+        self.sweep_with(db, address, bytes, |translator, db, address, bytes| {
+            let ecode = translator.lift_ecode(db, address, bytes)?;
+            let length = ecode.length();
+            Ok((ecode, length))
+        })
+    }
+
+    /// Sweeps `bytes` using `decode`, which returns a decoded instruction
+    /// and its length in bytes.
+    pub fn sweep_with<T, F>(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+        mut decode: F,
+    ) -> Vec<SweepItem<T>>
+    where
+        F: FnMut(
+            &Translator,
+            &mut ContextDatabase,
+            AddressValue,
+            &[u8],
+        ) -> Result<(T, usize), Error>,
+    {
+        let mut items = Vec::new();
+        let mut decoded = 0;
+        let mut offset = 0;
+
+        while offset < bytes.len() && self.limit.map(|limit| decoded < limit).unwrap_or(true) {
+            let current = address + offset;
+
+            let error = match decode(self.translator, db, current, &bytes[offset..]) {
+                Ok((insn, length)) if length > 0 => {
+                    items.push(SweepItem::Instruction(insn));
+                    decoded += 1;
+                    offset += length;
+                    continue;
+                }
+                Ok(_) => Error::Disassembly(DisassemblyError::InstructionResolution),
+                Err(error) => error,
+            };
+
+            let skip = match self.policy {
+                RecoveryPolicy::Abort => bytes.len() - offset,
+                RecoveryPolicy::NextAlignment => {
+                    let alignment = self.translator.alignment().max(1) as u64;
+                    (alignment - current.offset() % alignment) as usize
+                }
+                RecoveryPolicy::Skip(n) => n.max(1),
+            }
+            .min(bytes.len() - offset);
+
+            match items.last_mut() {
+                Some(SweepItem::BadInstruction(bad))
+                    if bad.address.offset() + bad.length as u64 == current.offset() =>
+                {
+                    bad.length += skip;
+                }
+                _ => items.push(SweepItem::BadInstruction(BadInstruction {
+                    address: current,
+                    length: skip,
+                    error,
+                })),
+            }
+
+            if matches!(self.policy, RecoveryPolicy::Abort) {
+                break;
+            }
+
+            offset += skip;
+        }
+
+        items
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_arch::ArchitectureDef;
+    use fugue_bytes::Endian;
+
+    use super::*;
+
+    #[test]
+    fn test_sweep_with() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("tiny", Endian::Little, 32, "default"),
+            &Default::default(),
+            "./data/test/tiny.sla",
+        )?;
+
+        // two-byte instructions, where those starting with 0xff are invalid
+        let decode = |_: &Translator, _: &mut ContextDatabase, address: AddressValue, bytes: &[u8]| {
+            if bytes.len() < 2 || bytes[0] == 0xff {
+                Err(Error::Disassembly(DisassemblyError::InstructionResolution))
+            } else {
+                Ok((address.offset(), 2))
+            }
+        };
+
+        let bytes = [0x00, 0x00, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00];
+
+        let mut db = translator.context_database();
+        let addr = translator.address(0x1000u64);
+
+        let summary = |items: Vec<SweepItem<u64>>| {
+            items
+                .into_iter()
+                .map(|item| match item {
+                    SweepItem::Instruction(offset) => (offset, 0),
+                    SweepItem::BadInstruction(bad) => (bad.address().offset(), bad.length()),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let items = LinearSweep::new(&translator).sweep_with(&mut db, addr, &bytes, decode);
+        assert_eq!(summary(items), [(0x1000, 0), (0x1002, 6)]);
+
+        // bad bytes run to the next two-byte boundary
+        let items = LinearSweep::new(&translator)
+            .policy(RecoveryPolicy::NextAlignment)
+            .sweep_with(&mut db, addr, &bytes, decode);
+        assert_eq!(summary(items), [(0x1000, 0), (0x1002, 4), (0x1006, 0)]);
+
+        let items = LinearSweep::new(&translator)
+            .policy(RecoveryPolicy::Skip(1))
+            .sweep_with(&mut db, addr, &bytes, decode);
+        assert_eq!(summary(items), [(0x1000, 0), (0x1002, 3), (0x1005, 0), (0x1007, 1)]);
+
+        let items = LinearSweep::new(&translator)
+            .policy(RecoveryPolicy::NextAlignment)
+            .limit(1)
+            .sweep_with(&mut db, addr, &bytes, decode);
+        assert_eq!(summary(items), [(0x1000, 0)]);
+
+        // the same, lifted using the language
+        let items = LinearSweep::new(&translator)
+            .policy(RecoveryPolicy::NextAlignment)
+            .pcode(&mut db, addr, &bytes);
+        assert_eq!(items.len(), 3);
+        assert!(items[1].is_bad() && items[2].instruction().map(PCode::length) == Some(2));
+
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "requires processor specifications"]
+    fn test_sweep_recovery() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("AARCH64", Endian::Little, 64, "v8A"),
+            &Default::default(),
+            "./data/processors/AARCH64/AARCH64.sla",
+        )?;
+
+        // nop; <undefined>; nop
+        let bytes = [
+            0x1f, 0x20, 0x03, 0xd5, 0xff, 0xff, 0xff, 0xff, 0x1f, 0x20, 0x03, 0xd5,
+        ];
+
+        let mut db = translator.context_database();
+        let addr = translator.address(0x1000u64);
+
+        let items = LinearSweep::new(&translator).pcode(&mut db, addr, &bytes);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].bad_instruction().map(|b| b.length()), Some(8));
+
+        let items = LinearSweep::new(&translator)
+            .policy(RecoveryPolicy::NextAlignment)
+            .pcode(&mut db, addr, &bytes);
+        assert_eq!(items.len(), 3);
+        assert!(items[1].is_bad() && !items[2].is_bad());
+
+        Ok(())
+    }
+}
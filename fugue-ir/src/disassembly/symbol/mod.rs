@@ -3,9 +3,10 @@ pub mod symbol;
 pub mod symbol_scope;
 pub mod symbol_table;
 
+use std::fmt;
 use std::ops::Range;
 
-pub use sub_table::{Constructor, DecisionNode};
+pub use sub_table::{Constructor, DecisionNode, DisjointPattern};
 pub use symbol::{FixedHandle, Symbol, SymbolBuilder, SymbolKind};
 pub use symbol_scope::SymbolScope;
 pub use symbol_table::SymbolTable;
@@ -173,6 +174,20 @@ impl<'a, 'z> Tokens<'a, 'z> {
     pub fn append(&mut self, mut tokens: Self) {
         self.0.append(&mut tokens.0)
     }
+
+    /// Copies the tokens into `arena`, along with the names they borrow
+    /// from the symbol table, e.g., to retain them beyond the lifetime of
+    /// the translator that produced them.
+    pub fn to_owned_in<'az>(&self, arena: &'az IRBuilderArena) -> Tokens<'az, 'az> {
+        let mut tokens = Tokens::new(arena);
+        tokens.0.extend(self.0.iter().map(|token| match token {
+            Token::Address(address) => Token::Address(*address),
+            Token::Symbol(name) => Token::Symbol(arena.inner().alloc_str(name)),
+            Token::Register(name) => Token::Register(arena.inner().alloc_str(name)),
+            Token::Value(value) => Token::Value(*value),
+        }));
+        tokens
+    }
 }
 
 impl<'a> Token<'a> {
@@ -195,6 +210,17 @@ impl<'a> Token<'a> {
     }
 }
 
+impl<'a> fmt::Display for Token<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "{}", address),
+            Self::Symbol(name) | Self::Register(name) => write!(f, "{}", name),
+            Self::Value(value) if *value < 0 => write!(f, "-{:#x}", -(*value as i128)),
+            Self::Value(value) => write!(f, "{:#x}", value),
+        }
+    }
+}
+
 impl<'a> From<i64> for Token<'a> {
     fn from(v: i64) -> Self {
         Self::Value(v)
@@ -12,6 +12,8 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::mem::size_of;
 
+use unsafe_unwrap::UnsafeUnwrap;
+
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub enum Context {
     Operator {
@@ -51,7 +53,7 @@ impl Context {
                 mask,
                 flow,
             } => {
-                let sym = symbols.unchecked_symbol(*symbol_id); //.ok_or_else(|| Error::InvalidSymbol)?;
+                let sym = symbols.symbol(*symbol_id).ok_or(Error::InvalidSymbol)?;
                 walker.add_commit(sym, *num, *mask, *flow);
             }
         })
@@ -122,6 +124,16 @@ impl Constructor {
         self.min_length
     }
 
+    /// The index of the SLEIGH source file the constructor is defined in;
+    /// see [`crate::Translator::source_files`].
+    pub fn source_file_index(&self) -> usize {
+        self.source_file_index
+    }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
     pub(crate) fn operands<'b, 'c, 'z, 'az>(
         &'b self,
         arena: &'az IRBuilderArena,
@@ -142,15 +154,19 @@ impl Constructor {
     ) {
         if let Some(index) = self.flow_through_index {
             match symbols
-                .unchecked_symbol(self.operands[index])
+                .symbol(self.operands[index])
+                .expect("symbol")
                 .defining_symbol(symbols)
             {
                 Some(Symbol::Subtable { .. }) => {
-                    walker.unchecked_push_operand(index);
+                    walker.push_operand(index).expect("operand");
                     walker
-                        .unchecked_constructor()
+                        .constructor()
+                        .ok()
+                        .flatten()
+                        .expect("constructor")
                         .operands_into(arena, operands, walker, symbols);
-                    walker.unchecked_pop_operand();
+                    walker.pop_operand().expect("operand");
                     return;
                 }
                 _ => (),
@@ -161,7 +177,8 @@ impl Constructor {
                 if self.print_pieces[i].as_bytes()[0] == b'\n' {
                     let index = (self.print_pieces[i].as_bytes()[1] - b'A') as usize;
                     symbols
-                        .unchecked_symbol(self.operands[index])
+                        .symbol(self.operands[index])
+                        .expect("symbol")
                         .collect_operands(arena, operands, walker, symbols);
                 }
             }
@@ -194,15 +211,19 @@ impl Constructor {
     ) {
         if let Some(index) = self.flow_through_index {
             match symbols
-                .unchecked_symbol(self.operands[index])
+                .symbol(self.operands[index])
+                .expect("symbol")
                 .defining_symbol(symbols)
             {
                 Some(Symbol::Subtable { .. }) => {
-                    walker.unchecked_push_operand(index);
+                    walker.push_operand(index).expect("operand");
                     walker
-                        .unchecked_constructor()
+                        .constructor()
+                        .ok()
+                        .flatten()
+                        .expect("constructor")
                         .mnemonic_tokens(tokens, walker, symbols);
-                    walker.unchecked_pop_operand();
+                    walker.pop_operand().expect("operand");
                     return;
                 }
                 _ => (),
@@ -213,7 +234,8 @@ impl Constructor {
             if self.print_pieces[i].as_bytes()[0] == b'\n' {
                 let index = (self.print_pieces[i].as_bytes()[1] - b'A') as usize;
                 symbols
-                    .unchecked_symbol(self.operands[index])
+                        .symbol(self.operands[index])
+                        .expect("symbol")
                     .tokens(tokens, walker, symbols);
             } else {
                 tokens.push(Token::symbol(&self.print_pieces[i]));
@@ -229,15 +251,19 @@ impl Constructor {
     ) {
         if let Some(index) = self.flow_through_index {
             match symbols
-                .unchecked_symbol(self.operands[index])
+                .symbol(self.operands[index])
+                .expect("symbol")
                 .defining_symbol(symbols)
             {
                 Some(Symbol::Subtable { .. }) => {
-                    walker.unchecked_push_operand(index);
+                    walker.push_operand(index).expect("operand");
                     walker
-                        .unchecked_constructor()
+                        .constructor()
+                        .ok()
+                        .flatten()
+                        .expect("constructor")
                         .body_tokens(tokens, walker, symbols);
-                    walker.unchecked_pop_operand();
+                    walker.pop_operand().expect("operand");
                     return;
                 }
                 _ => (),
@@ -248,7 +274,8 @@ impl Constructor {
                 if self.print_pieces[i].as_bytes()[0] == b'\n' {
                     let index = (self.print_pieces[i].as_bytes()[1] - b'A') as usize;
                     symbols
-                        .unchecked_symbol(self.operands[index])
+                        .symbol(self.operands[index])
+                        .expect("symbol")
                         .tokens(tokens, walker, symbols);
                 } else {
                     tokens.push(Token::symbol(&self.print_pieces[i]));
@@ -304,15 +331,19 @@ impl Constructor {
     ) -> Result<(), fmt::Error> {
         if let Some(index) = self.flow_through_index {
             match symbols
-                .unchecked_symbol(self.operands[index])
+                .symbol(self.operands[index])
+                .expect("symbol")
                 .defining_symbol(symbols)
             {
                 Some(Symbol::Subtable { .. }) => {
-                    walker.unchecked_push_operand(index);
+                    walker.push_operand(index).expect("operand");
                     walker
-                        .unchecked_constructor()
+                        .constructor()
+                        .ok()
+                        .flatten()
+                        .expect("constructor")
                         .format_mnemonic(fmt, walker, symbols)?;
-                    walker.unchecked_pop_operand();
+                    walker.pop_operand().expect("operand");
                     return Ok(());
                 }
                 _ => (),
@@ -323,7 +354,8 @@ impl Constructor {
             if self.print_pieces[i].as_bytes()[0] == b'\n' {
                 let index = (self.print_pieces[i].as_bytes()[1] - b'A') as usize;
                 symbols
-                    .unchecked_symbol(self.operands[index])
+                        .symbol(self.operands[index])
+                        .expect("symbol")
                     .format(fmt, walker, symbols)?;
             } else {
                 write!(fmt, "{}", self.print_pieces[i])?;
@@ -340,15 +372,19 @@ impl Constructor {
     ) -> Result<(), fmt::Error> {
         if let Some(index) = self.flow_through_index {
             match symbols
-                .unchecked_symbol(self.operands[index])
+                .symbol(self.operands[index])
+                .expect("symbol")
                 .defining_symbol(symbols)
             {
                 Some(Symbol::Subtable { .. }) => {
-                    walker.unchecked_push_operand(index);
+                    walker.push_operand(index).expect("operand");
                     walker
-                        .unchecked_constructor()
+                        .constructor()
+                        .ok()
+                        .flatten()
+                        .expect("constructor")
                         .format_body(fmt, walker, symbols)?;
-                    walker.unchecked_pop_operand();
+                    walker.pop_operand().expect("operand");
                     return Ok(());
                 }
                 _ => (),
@@ -359,7 +395,8 @@ impl Constructor {
                 if self.print_pieces[i].as_bytes()[0] == b'\n' {
                     let index = (self.print_pieces[i].as_bytes()[1] - b'A') as usize;
                     symbols
-                        .unchecked_symbol(self.operands[index])
+                        .symbol(self.operands[index])
+                        .expect("symbol")
                         .format(fmt, walker, symbols)?;
                 } else {
                     write!(fmt, "{}", self.print_pieces[i])?;
@@ -381,24 +418,22 @@ impl Constructor {
         self.template.as_ref()
     }
 
-    pub fn unchecked_template(&self) -> &ConstructTpl {
-        if let Some(ref templ) = self.template {
-            templ
-        } else {
-            unreachable!()
-        }
+    /// # Safety
+    ///
+    /// The constructor must have a template.
+    pub unsafe fn unchecked_template(&self) -> &ConstructTpl {
+        self.template.as_ref().unsafe_unwrap()
     }
 
     pub fn named_template(&self, index: usize) -> Option<&ConstructTpl> {
         self.named_template.get(index).and_then(|v| v.as_ref())
     }
 
-    pub fn unchecked_named_template(&self, index: usize) -> &ConstructTpl {
-        if let Some(ref named) = unsafe { self.named_template.get_unchecked(index) } {
-            named
-        } else {
-            unreachable!()
-        }
+    /// # Safety
+    ///
+    /// The constructor must have a named template at `index`.
+    pub unsafe fn unchecked_named_template(&self, index: usize) -> &ConstructTpl {
+        self.named_template.get_unchecked(index).as_ref().unsafe_unwrap()
     }
 
     pub fn from_xml(
@@ -544,12 +579,29 @@ impl DecisionNode {
             let val = if self.context_decision {
                 walker.context_bits(self.start_bit, self.size)
             } else {
-                walker.unchecked_instruction_bits(self.start_bit, self.size)
+                // SAFETY: decision trees are only consulted while resolving the
+                // constructor of the state the walker is positioned at
+                unsafe { walker.unchecked_instruction_bits(self.start_bit, self.size) }
             };
 
             self.children[val as usize].resolve(walker, ctors)
         }
     }
+
+    /// The patterns of each leaf of the tree, paired with the index of the
+    /// constructor they select.
+    pub fn patterns(&self) -> Vec<(usize, &DisjointPattern)> {
+        let mut patterns = Vec::new();
+        self.patterns_into(&mut patterns);
+        patterns
+    }
+
+    fn patterns_into<'a>(&'a self, patterns: &mut Vec<(usize, &'a DisjointPattern)>) {
+        patterns.extend(self.patterns.iter().map(|pair| (pair.id, &pair.pattern)));
+        for child in self.children.iter() {
+            child.patterns_into(patterns);
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
@@ -609,6 +661,23 @@ impl DisjointPattern {
     }
     */
 
+    /// Whether the pattern constrains the context.
+    pub fn has_context(&self) -> bool {
+        !matches!(self, Self::Instruction(_))
+    }
+
+    /// The shortest instruction bytes matching the instruction part of the
+    /// pattern, with all unconstrained bits clear; `None` if no bytes can
+    /// match.
+    pub fn instruction_encoding(&self) -> Option<Vec<u8>> {
+        match self {
+            Self::Instruction(pat) | Self::Combine { instruction: pat, .. } => {
+                pat.mask_value.encoding()
+            }
+            Self::Context(_) => Some(Vec::new()),
+        }
+    }
+
     pub fn from_xml(input: xml::Node) -> Result<Self, DeserialiseError> {
         Ok(match input.tag_name().name() {
             "instruct_pat" => Self::Instruction(InstructionPattern::from_xml(input)?),
@@ -718,6 +787,22 @@ impl PatternBlock {
         self.non_zero_size == Self::ALWAYS_FALSE
     }
 
+    /// The bytes matched by the block, with all unconstrained bits clear;
+    /// `None` if the block never matches.
+    pub fn encoding(&self) -> Option<Vec<u8>> {
+        if self.always_false() {
+            return None;
+        }
+
+        let mut bytes = vec![0u8; self.offset + size_of::<u32>() * self.values.len()];
+        for (i, (mask, value)) in self.masks.iter().zip(self.values.iter()).enumerate() {
+            let start = self.offset + size_of::<u32>() * i;
+            let word = (mask & value).to_be_bytes();
+            bytes[start..start + size_of::<u32>()].copy_from_slice(&word);
+        }
+        Some(bytes)
+    }
+
     pub fn is_context_match<'b, 'c, 'z>(&'b self, walker: &ParserWalker<'b, 'c, 'z>) -> bool {
         match self.non_zero_size {
             Self::ALWAYS_FALSE => false,
@@ -726,6 +811,7 @@ impl PatternBlock {
                 let mut offset = self.offset;
                 for i in 0..self.values.len() {
                     let data = walker.context_bytes(offset, size_of::<u32>());
+                    walker.mark_context(8 * offset, self.masks[i]);
                     if self.masks[i] & data != self.values[i] {
                         return false;
                     }
@@ -763,7 +849,9 @@ impl PatternBlock {
             _ => {
                 let mut offset = self.offset;
                 for i in 0..self.values.len() {
-                    let data = walker.unchecked_instruction_bytes(offset, size_of::<u32>());
+                    // SAFETY: patterns are only matched while resolving the constructor
+                    // of the state the walker is positioned at
+                    let data = unsafe { walker.unchecked_instruction_bytes(offset, size_of::<u32>()) };
                     if self.masks[i] & data != self.values[i] {
                         return false;
                     }
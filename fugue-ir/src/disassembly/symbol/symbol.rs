@@ -11,7 +11,6 @@ use crate::space_manager::SpaceManager;
 
 use std::fmt;
 
-use unsafe_unwrap::UnsafeUnwrap;
 use ustr::Ustr;
 
 #[derive(Debug, Clone)]
@@ -169,6 +168,14 @@ pub enum Symbol {
     },
 }
 
+// the symbol of an entry of a varnode list; well-formed specifications
+// only select defined entries
+fn varnode_symbol<'b>(symbols: &'b SymbolTable, table: &[Option<usize>], index: usize) -> &'b Symbol {
+    table[index]
+        .and_then(|id| symbols.symbol(id))
+        .expect("varnode list entry")
+}
+
 impl Symbol {
     pub fn id(&self) -> usize {
         match self {
@@ -245,9 +252,7 @@ impl Symbol {
     pub fn defining_symbol<'b>(&self, symbols: &'b SymbolTable) -> Option<&'b Symbol> {
         if let Self::Operand { subsym_id, .. } = self {
             if let Some(id) = subsym_id {
-                Some(
-                    symbols.unchecked_symbol(*id), // .ok_or_else(|| Error::InvalidSymbol)?,
-                )
+                Some(symbols.symbol(*id).expect("symbol"))
             } else {
                 None
             }
@@ -367,7 +372,7 @@ impl Symbol {
                 size,
                 ..
             } => FixedHandle {
-                space: manager.unchecked_space_by_id(*space),
+                space: manager.space_by_id(*space).ok_or(Error::InvalidSpace)?,
                 size: *size,
                 offset_space: None,
                 offset_offset: *offset,
@@ -375,9 +380,14 @@ impl Symbol {
                 temporary_space: None,
                 temporary_offset: 0,
             },
-            Self::Operand { handle_index, .. } => walker.unchecked_handle(*handle_index),
+            Self::Operand { handle_index, .. } => walker
+                .handle_ref(*handle_index)
+                .cloned()
+                .ok_or(Error::InvalidHandle)?,
             Self::Start { .. } => {
-                let space = manager.unchecked_space_by_id(walker.address().space());
+                let space = manager
+                    .space_by_id(walker.address().space())
+                    .ok_or(Error::InvalidSpace)?;
                 let size = space.address_size();
                 FixedHandle {
                     space,
@@ -390,14 +400,17 @@ impl Symbol {
                 }
             }
             Self::End { .. } => {
-                let space = manager.unchecked_space_by_id(walker.address().space());
+                let space = manager
+                    .space_by_id(walker.address().space())
+                    .ok_or(Error::InvalidSpace)?;
                 let size = space.address_size();
                 FixedHandle {
                     space,
                     size,
                     offset_space: None,
                     offset_offset: walker
-                        .unchecked_next_address() /*.ok_or_else(|| Error::InvalidNextAddress)?*/
+                        .next_address()
+                        .ok_or(Error::InvalidNextAddress)?
                         .offset(),
                     offset_size: 0,
                     temporary_space: None,
@@ -429,9 +442,10 @@ impl Symbol {
                 ..
             } => {
                 let index = pattern_value.value(walker, symbols)?;
-                let varnode = symbols.unchecked_symbol(
-                    unsafe { varnode_table.get_unchecked(index as usize).unsafe_unwrap() }, //.ok_or_else(|| Error::InvalidSymbol)?
-                ); //.ok_or_else(|| Error::InvalidSymbol)?;
+                let varnode = varnode_table
+                    .get(index as usize)
+                    .and_then(|id| symbols.symbol((*id)?))
+                    .ok_or_else(|| Error::InvalidSymbol)?;
                 varnode.fixed_handle(walker, manager, symbols)?
             }
             Self::ValueMap {
@@ -481,13 +495,16 @@ impl Symbol {
                 def_expr,
                 ..
             } => {
-                walker.unchecked_push_operand(*handle_index);
+                walker.push_operand(*handle_index).expect("operand");
                 if let Some(id) = subsym_id {
-                    let sym = symbols.unchecked_symbol(*id);
+                    let sym = symbols.symbol(*id).expect("symbol");
                     if sym.is_subtable() {
                         let mut inner = Operands::new(arena);
                         walker
-                            .unchecked_constructor()
+                            .constructor()
+                            .ok()
+                            .flatten()
+                            .expect("constructor")
                             .collect_operands(arena, &mut inner, walker, symbols);
                         operands.append(inner);
                     } else {
@@ -501,7 +518,7 @@ impl Symbol {
                     };
                     operands.push_with(value, bits);
                 }
-                walker.unchecked_pop_operand();
+                walker.pop_operand().expect("operand");
             }
             Self::Varnode {
                 name,
@@ -518,9 +535,7 @@ impl Symbol {
             } => {
                 let (index, bits) = pattern_value.value_with(walker, symbols).unwrap();
                 if index >= 0 && (index as usize) < varnode_table.len() {
-                    let named = symbols.unchecked_symbol(unsafe {
-                        varnode_table.get_unchecked(index as usize).unsafe_unwrap()
-                    });
+                    let named = varnode_symbol(symbols, varnode_table, index as usize);
                     operands.push_with(named.name(), bits);
                 }
             }
@@ -545,17 +560,17 @@ impl Symbol {
                 ..
             } => {
                 let (index, bits) = pattern_value.value_with(walker, symbols).unwrap();
-                let value = *unsafe { value_table.get_unchecked(index as usize) };
+                let value = value_table[index as usize];
                 operands.push_with(value, bits);
             }
             Self::Start { .. } => {
                 operands.push(walker.address());
             }
             Self::End { .. } => {
-                operands.push(walker.unchecked_next_address());
+                operands.push(walker.next_address().expect("next address"));
             }
             Self::Next2 { .. } => {
-                operands.push(walker.unchecked_next2_address());
+                operands.push(walker.next2_address().expect("inst_next2 is not supported"));
             }
             _ => unreachable!(),
         }
@@ -574,12 +589,15 @@ impl Symbol {
                 def_expr,
                 ..
             } => {
-                walker.unchecked_push_operand(*handle_index);
+                walker.push_operand(*handle_index).expect("operand");
                 if let Some(id) = subsym_id {
-                    let sym = symbols.unchecked_symbol(*id);
+                    let sym = symbols.symbol(*id).expect("symbol");
                     if sym.is_subtable() {
                         walker
-                            .unchecked_constructor()
+                            .constructor()
+                            .ok()
+                            .flatten()
+                            .expect("constructor")
                             .format(fmt, walker, symbols)?;
                     } else {
                         sym.format(fmt, walker, symbols)?;
@@ -596,7 +614,7 @@ impl Symbol {
                         write!(fmt, "{:#x}", value)?;
                     }
                 }
-                walker.unchecked_pop_operand();
+                walker.pop_operand().expect("operand");
                 Ok(())
             }
             Self::Varnode { name, .. } => {
@@ -612,11 +630,7 @@ impl Symbol {
                     write!(
                         fmt,
                         "{}",
-                        symbols
-                            .unchecked_symbol(unsafe {
-                                varnode_table.get_unchecked(index as usize).unsafe_unwrap()
-                            })
-                            .name()
+                        varnode_symbol(symbols, varnode_table, index as usize).name()
                     )?;
                 }
                 Ok(())
@@ -646,7 +660,7 @@ impl Symbol {
                 ..
             } => {
                 let index = pattern_value.value(walker, symbols).unwrap() as usize;
-                let value = *unsafe { value_table.get_unchecked(index) };
+                let value = value_table[index];
                 if value < 0 {
                     write!(fmt, "-{:#x}", -(value as i128))
                 } else {
@@ -657,10 +671,10 @@ impl Symbol {
                 write!(fmt, "{:#x}", walker.address().offset())
             }
             Self::End { .. } => {
-                write!(fmt, "{:#x}", walker.unchecked_next_address().offset())
+                write!(fmt, "{:#x}", walker.next_address().expect("next address").offset())
             }
             Self::Next2 { .. } => {
-                write!(fmt, "{:#x}", walker.unchecked_next2_address().offset())
+                write!(fmt, "{:#x}", walker.next2_address().expect("inst_next2 is not supported").offset())
             }
             _ => unreachable!(),
         }
@@ -679,12 +693,15 @@ impl Symbol {
                 def_expr,
                 ..
             } => {
-                walker.unchecked_push_operand(*handle_index);
+                walker.push_operand(*handle_index).expect("operand");
                 if let Some(id) = subsym_id {
-                    let sym = symbols.unchecked_symbol(*id);
+                    let sym = symbols.symbol(*id).expect("symbol");
                     if sym.is_subtable() {
                         walker
-                            .unchecked_constructor()
+                            .constructor()
+                            .ok()
+                            .flatten()
+                            .expect("constructor")
                             .tokens_aux(tokens, walker, symbols);
                     } else {
                         sym.tokens(tokens, walker, symbols);
@@ -697,7 +714,7 @@ impl Symbol {
                     };
                     tokens.push(value);
                 }
-                walker.unchecked_pop_operand();
+                walker.pop_operand().expect("operand");
             }
             Self::Varnode {
                 name,
@@ -714,11 +731,7 @@ impl Symbol {
             } => {
                 let index = pattern_value.value(walker, symbols).unwrap();
                 if index >= 0 && (index as usize) < varnode_table.len() {
-                    let register = symbols
-                        .unchecked_symbol(unsafe {
-                            varnode_table.get_unchecked(index as usize).unsafe_unwrap()
-                        })
-                        .name();
+                    let register = varnode_symbol(symbols, varnode_table, index as usize).name();
                     tokens.push(Token::register(register));
                 }
             }
@@ -743,17 +756,17 @@ impl Symbol {
                 ..
             } => {
                 let index = pattern_value.value(walker, symbols).unwrap() as usize;
-                let value = *unsafe { value_table.get_unchecked(index) };
+                let value = value_table[index];
                 tokens.push(value);
             }
             Self::Start { .. } => {
                 tokens.push(walker.address());
             }
             Self::End { .. } => {
-                tokens.push(walker.unchecked_next_address());
+                tokens.push(walker.next_address().expect("next address"));
             }
             Self::Next2 { .. } => {
-                tokens.push(walker.unchecked_next2_address());
+                tokens.push(walker.next2_address().expect("inst_next2 is not supported"));
             }
             _ => unreachable!(),
         }
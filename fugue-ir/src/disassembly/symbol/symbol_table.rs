@@ -27,12 +27,18 @@ impl SymbolTable {
         self.symbols.get(id)
     }
 
-    pub fn unchecked_symbol(&self, id: usize) -> &Symbol {
+    /// Get the symbol by id without bounds checking
+    ///
+    /// # Safety
+    ///
+    /// `id` must be a valid symbol id for this table.
+    pub unsafe fn unchecked_symbol(&self, id: usize) -> &Symbol {
+        // SAFETY: the caller guarantees that `id` indexes `self.symbols`
         unsafe { self.symbols.get_unchecked(id) }
     }
 
     pub (crate) fn resolve<'b, 'c, 'z>(&'b self, id: usize, walker: &mut ParserWalker<'b, 'c, 'z>) -> Result<&'b Constructor, Error> {
-        if let Symbol::Subtable { decision_tree, constructors, .. } = unsafe { self.symbols.get_unchecked(id) } {
+        if let Some(Symbol::Subtable { decision_tree, constructors, .. }) = self.symbols.get(id) {
             decision_tree.resolve(walker, constructors)
         } else {
             Err(Error::InvalidSymbol)
@@ -38,7 +38,18 @@ impl<'a> VarnodeDataFormatter<'a> {
 
 impl<'a> fmt::Display for VarnodeDataFormatter<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        let space = self.translator.manager().unchecked_space_by_id(self.varnode.space);
+        let space = if let Some(space) = self.translator.manager().space_by_id(self.varnode.space) {
+            space
+        } else {
+            return write!(
+                f,
+                "Varnode(space={}, offset={:#x}, size={})",
+                self.varnode.space.index(),
+                self.varnode.offset,
+                self.varnode.size
+            )
+        };
+
         if space.is_register() {
             let name = self.translator.registers()
                 .get(self.varnode.offset, self.varnode.size)
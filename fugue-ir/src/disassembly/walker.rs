@@ -5,7 +5,7 @@ use crate::disassembly::symbol::{Constructor, FixedHandle, Operands, Symbol, Sym
 use crate::disassembly::{Error, IRBuilderArena};
 use crate::space_manager::SpaceManager;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::fmt;
 use std::mem::size_of;
 use std::ops::Range;
@@ -167,6 +167,8 @@ pub enum ParserState {
 pub struct ParserContext<'b, 'z> {
     parse_state: ParserState,
     context: BVec<'z, u32>,
+    context_read: BVec<'z, Cell<u32>>,
+    track_context: bool,
     context_commit: BVec<'z, ContextSet<'b>>,
 
     backing: [u8; 16],
@@ -186,6 +188,8 @@ impl<'b, 'z> ParserContext<'b, 'z> {
         Self {
             parse_state: ParserState::Uninitialised,
             context: BVec::with_capacity_in(2, arena.inner()),
+            context_read: BVec::with_capacity_in(2, arena.inner()),
+            track_context: false,
             context_commit: BVec::with_capacity_in(2, arena.inner()),
             backing: [0; 16],
             address: AddressValue::new(space_manager.default_space(), 0),
@@ -213,6 +217,8 @@ impl<'b, 'z> ParserContext<'b, 'z> {
         Self {
             parse_state: ParserState::Uninitialised,
             context: BVec::from_iter_in(context.iter().map(|v| *v), arena.inner()),
+            context_read: BVec::new_in(arena.inner()),
+            track_context: false,
             backing,
             context_commit: BVec::with_capacity_in(2, arena.inner()),
             address,
@@ -241,6 +247,10 @@ impl<'b, 'z> ParserContext<'b, 'z> {
         self.context.clear();
         self.context
             .extend_from_slice(&context_db.get_context(&address));
+        self.context_read.clear();
+        if self.track_context {
+            self.context_read.resize(self.context.len(), Cell::new(0));
+        }
         self.context_commit.clear();
         self.address = address;
         self.next_address = None;
@@ -259,12 +269,13 @@ impl<'b, 'z> ParserContext<'b, 'z> {
                 .resize((1 + self.state.len()) * 2, Default::default());
         }
 
+        // SAFETY: the states were resized above to hold at least `id + 1`
         let op = unsafe { self.state.get_unchecked_mut(id) };
 
         op.parent = parent;
         op.constructor = None;
         op.handle = None;
-        *unsafe { op.resolve.get_unchecked_mut(0) } = None;
+        op.resolve[0] = None;
         op.offset = 0;
         op.length = 0;
 
@@ -274,30 +285,35 @@ impl<'b, 'z> ParserContext<'b, 'z> {
     }
 
     pub(crate) fn set_constructor(&mut self, point: usize, constructor: &'b Constructor) {
-        unsafe { self.state.get_unchecked_mut(point) }.constructor = Some(constructor);
+        self.state[point].constructor = Some(constructor);
     }
 
     pub(crate) fn set_offset(&mut self, point: usize, offset: usize) {
-        unsafe { self.state.get_unchecked_mut(point) }.offset = offset;
+        self.state[point].offset = offset;
     }
 
     pub(crate) fn point(&self, point: usize) -> &ConstructState<'b> {
-        unsafe { self.state.get_unchecked(point) }
+        &self.state[point]
     }
 
     pub(crate) fn point_mut(&mut self, point: usize) -> &mut ConstructState<'b> {
-        unsafe { self.state.get_unchecked_mut(point) }
+        &mut self.state[point]
     }
 
     pub(crate) fn set_handle(&mut self, point: usize, handle: FixedHandle<'b>) {
-        unsafe { self.state.get_unchecked_mut(point) }.handle = Some(handle);
+        self.state[point].handle = Some(handle);
     }
 
     pub fn handle(&self, point: usize) -> Option<&FixedHandle<'b>> {
-        self.state[point].handle.as_ref()
+        self.state.get(point).and_then(|state| state.handle.as_ref())
     }
 
-    pub fn unchecked_handle(&self, point: usize) -> &FixedHandle<'b> {
+    /// # Safety
+    ///
+    /// `point` must be a valid construct state with a handle set.
+    pub unsafe fn unchecked_handle(&self, point: usize) -> &FixedHandle<'b> {
+        // SAFETY: the caller guarantees that `point` indexes the states, and
+        // that its handle is set
         unsafe {
             if let Some(ref handle) = self.state.get_unchecked(point).handle {
                 handle
@@ -402,6 +418,8 @@ impl<'b, 'z> ParserContext<'b, 'z> {
     }
 
     pub fn context_bits(&self, start: usize, size: usize) -> u32 {
+        self.mark_context_bits(start, size);
+
         let start_off = start / (8 * size_of::<u32>());
         let bits_off = start % (8 * size_of::<u32>());
 
@@ -427,6 +445,77 @@ impl<'b, 'z> ParserContext<'b, 'z> {
         self.context[num] = (self.context[num] & !mask) | (mask & value);
     }
 
+    /// Enables recording which context bits are read while decoding, as
+    /// reported by [`ParserContext::decode_context`]; recording is
+    /// disabled by default, and takes effect from the next instruction
+    /// decoded.
+    pub fn set_track_context(&mut self, enabled: bool) {
+        self.track_context = enabled;
+    }
+
+    pub fn track_context(&self) -> bool {
+        self.track_context
+    }
+
+    /// Records that the context bits selected by `mask`, whose most
+    /// significant bit corresponds to context bit `start`, were read.
+    #[inline]
+    pub(crate) fn mark_context(&self, start: usize, mask: u32) {
+        if !self.track_context {
+            return;
+        }
+
+        let bits = 8 * size_of::<u32>();
+        let word = start / bits;
+        let shift = (start % bits) as u32;
+
+        if let Some(read) = self.context_read.get(word) {
+            read.set(read.get() | mask >> shift);
+        }
+
+        if shift > 0 {
+            if let Some(read) = self.context_read.get(word + 1) {
+                read.set(read.get() | mask << (bits as u32 - shift));
+            }
+        }
+    }
+
+    /// Records that `size` context bits, starting from context bit
+    /// `start`, were read.
+    #[inline]
+    pub(crate) fn mark_context_bits(&self, mut start: usize, mut size: usize) {
+        if !self.track_context {
+            return;
+        }
+
+        let bits = 8 * size_of::<u32>();
+        while size > 0 {
+            let chunk = size.min(bits);
+            self.mark_context(start, !0u32 << (bits - chunk));
+            start += chunk;
+            size -= chunk;
+        }
+    }
+
+    /// The context variables read while decoding the current instruction,
+    /// and their values at the end of decoding, e.g., to determine the
+    /// mode (such as ARM or THUMB) an instruction was decoded in. This is
+    /// empty unless recording is enabled with
+    /// [`ParserContext::set_track_context`].
+    pub fn decode_context<'d>(&self, context_db: &'d ContextDatabase) -> Vec<(&'d str, u32)> {
+        context_db
+            .variables()
+            .filter(|(_, range)| {
+                let mask = range.mask().checked_shl(range.shift()).unwrap_or(0);
+                self.context_read
+                    .get(range.word())
+                    .map(|read| read.get() & mask != 0)
+                    .unwrap_or(false)
+            })
+            .map(|(name, range)| (name, range.get(&self.context)))
+            .collect()
+    }
+
     pub fn add_commit(
         &mut self,
         symbol: &'b Symbol,
@@ -463,8 +552,9 @@ impl<'b, 'z> ParserContext<'b, 'z> {
         for commit in commits {
             let symbol = commit.triple;
             let mut address = if let Symbol::Operand { handle_index, .. } = symbol {
-                let handle = nwalker.unchecked_handle_ref_via(commit.point, *handle_index); //?
-                                                                                            //.ok_or_else(|| Error::InvalidHandle)?;
+                let handle = nwalker
+                    .handle_ref_via(commit.point, *handle_index)
+                    .ok_or(Error::InvalidHandle)?;
                 AddressValue::new(handle.space, handle.offset_offset)
             } else {
                 let handle = symbol.fixed_handle(&mut nwalker, manager, symbols)?;
@@ -472,7 +562,9 @@ impl<'b, 'z> ParserContext<'b, 'z> {
             };
 
             if address.is_constant() {
-                let space = manager.unchecked_space_by_id(address.space());
+                let space = manager
+                    .space_by_id(address.space())
+                    .ok_or(Error::InvalidSpace)?;
                 let noffset = address.offset() * space.word_size() as u64;
                 address = AddressValue::new(space, noffset);
             }
@@ -511,10 +603,15 @@ impl<'b, 'z> ParserContext<'b, 'z> {
     }
 
     pub fn constructor(&self, point: usize) -> Option<&'b Constructor> {
-        self.state[point].constructor
+        self.state.get(point).and_then(|state| state.constructor)
     }
 
-    pub fn unchecked_constructor(&self, point: usize) -> &'b Constructor {
+    /// # Safety
+    ///
+    /// `point` must be a valid construct state with a resolved constructor.
+    pub unsafe fn unchecked_constructor(&self, point: usize) -> &'b Constructor {
+        // SAFETY: the caller guarantees that `point` indexes the states, and
+        // that its constructor is resolved
         unsafe { self.state.get_unchecked(point).constructor.unsafe_unwrap() }
     }
 
@@ -563,19 +660,22 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         self.ctx.address.clone()
     }
 
-    pub fn unchecked_next_address(&self) -> &AddressValue {
-        if let Some(ref address) = self.ctx.next_address {
-            address
-        } else {
-            unreachable!()
-        }
+    /// # Safety
+    ///
+    /// The address of the next instruction must have been set.
+    pub unsafe fn unchecked_next_address(&self) -> &AddressValue {
+        // SAFETY: the caller guarantees that the next address is set
+        self.ctx.next_address.as_ref().unsafe_unwrap()
     }
 
     pub fn next_address(&self) -> Option<AddressValue> {
         self.ctx.next_address.clone()
     }
 
-    pub fn unchecked_next2_address(&self) -> &AddressValue {
+    /// # Safety
+    ///
+    /// Not supported; this function always panics.
+    pub unsafe fn unchecked_next2_address(&self) -> &AddressValue {
         unimplemented!("inst_next2")
     }
 
@@ -587,13 +687,19 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         self.ctx.point(0).length
     }
 
+    // the current construct state; the walker is only positioned outside
+    // of a state once it has walked past the root
+    fn current(&self) -> usize {
+        self.point
+            .expect("walker is positioned at a construct state")
+    }
+
     pub fn set_parent_handle(&mut self, handle: FixedHandle<'b>) {
-        self.ctx
-            .set_handle(unsafe { self.point.unsafe_unwrap() }, handle);
+        self.ctx.set_handle(self.current(), handle);
     }
 
     pub fn parent_handle_mut(&mut self) -> Option<&mut FixedHandle<'b>> {
-        self.ctx.handle_mut(unsafe { self.point.unsafe_unwrap() })
+        self.ctx.handle_mut(self.current())
     }
 
     pub fn handle(&self, index: usize) -> Result<Option<FixedHandle<'b>>, Error> {
@@ -621,29 +727,48 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
             .and_then(|hidx| self.ctx.handle(hidx))
     }
 
-    pub fn unchecked_handle(&self, index: usize) -> FixedHandle<'b> {
-        self.unchecked_handle_ref(index).clone()
+    /// # Safety
+    ///
+    /// The walker must be positioned at a construct state whose operand
+    /// `index` has been resolved and has a handle set.
+    pub unsafe fn unchecked_handle(&self, index: usize) -> FixedHandle<'b> {
+        // SAFETY: the caller upholds the same preconditions
+        unsafe { self.unchecked_handle_ref(index) }.clone()
     }
 
-    pub fn unchecked_handle_ref(&self, index: usize) -> &FixedHandle<'b> {
+    /// # Safety
+    ///
+    /// The walker must be positioned at a construct state whose operand
+    /// `index` has been resolved and has a handle set.
+    pub unsafe fn unchecked_handle_ref(&self, index: usize) -> &FixedHandle<'b> {
+        // SAFETY: the caller guarantees that the walker is positioned at a
+        // state whose operand `index` is resolved and has a handle set
         let ph = unsafe {
             self.unchecked_point()
                 .resolve
                 .get_unchecked(index)
                 .unsafe_unwrap()
         };
-        self.ctx.unchecked_handle(ph)
+        // SAFETY: as above
+        unsafe { self.ctx.unchecked_handle(ph) }
     }
 
-    pub fn unchecked_handle_ref_via(&self, point: usize, index: usize) -> &FixedHandle<'b> {
-        let ph = unsafe {
-            self.ctx
+    /// # Safety
+    ///
+    /// Operand `index` of the construct state `point` must have been
+    /// resolved and have a handle set.
+    pub unsafe fn unchecked_handle_ref_via(&self, point: usize, index: usize) -> &FixedHandle<'b> {
+        // SAFETY: the caller guarantees that operand `index` of `point` is
+        // resolved and has a handle set
+        unsafe {
+            let ph = self
+                .ctx
                 .point(point)
                 .resolve
                 .get_unchecked(index)
-                .unsafe_unwrap()
-        };
-        self.ctx.unchecked_handle(ph)
+                .unsafe_unwrap();
+            self.ctx.unchecked_handle(ph)
+        }
     }
 
     pub fn set_next_address(&mut self, address: AddressValue) {
@@ -655,9 +780,8 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
     }
 
     pub fn set_current_length(&mut self, length: usize) {
-        self.ctx
-            .point_mut(unsafe { self.point.unsafe_unwrap() })
-            .length = length;
+        let point = self.current();
+        self.ctx.point_mut(point).length = length;
     }
 
     pub fn set_delay_slot(&mut self, delay: usize) {
@@ -688,19 +812,15 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
     */
 
     pub fn calculate_length(&mut self, length: usize, nops: usize) {
-        let index = unsafe { self.point.unsafe_unwrap() };
+        let index = self.current();
         let poff = self.ctx.point(index).offset;
 
         let length = length + poff;
         let length = (0..nops).fold(length, |length, id| {
-            let subpt = self.ctx.point(unsafe {
-                self.ctx
-                    .state
-                    .get_unchecked(index)
-                    .resolve
-                    .get_unchecked(id)
-                    .unsafe_unwrap()
-            });
+            let subpt = self.ctx.point(
+                self.ctx.point(index).resolve[id]
+                    .expect("operands are resolved before their length is calculated"),
+            );
             let sub_length = subpt.length + subpt.offset;
             length.max(sub_length)
         });
@@ -709,7 +829,7 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
     }
 
     pub fn operand(&self) -> usize {
-        *unsafe { self.breadcrumb.get_unchecked(self.depth as usize) }
+        self.breadcrumb[self.depth as usize]
     }
 
     pub fn allocate_operand(&mut self, id: usize) -> Result<(), Error> {
@@ -728,23 +848,6 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         Ok(())
     }
 
-    pub(crate) fn unchecked_allocate_operand(&mut self, id: usize) {
-        let op = self.ctx.allocate_operand(self.point);
-
-        *unsafe {
-            self.ctx
-                .point_mut(self.point.unsafe_unwrap())
-                .resolve
-                .get_unchecked_mut(id)
-        } = Some(op);
-
-        *unsafe { self.breadcrumb.get_unchecked_mut(self.depth as usize) } += 1;
-        self.depth += 1;
-
-        self.point = Some(op);
-        *unsafe { self.breadcrumb.get_unchecked_mut(self.depth as usize) } = 0;
-    }
-
     pub fn push_operand(&mut self, id: usize) -> Result<(), Error> {
         self.breadcrumb[self.depth as usize] = id + 1;
         self.depth += 1;
@@ -756,9 +859,17 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         Ok(())
     }
 
-    pub(crate) fn unchecked_push_operand(&mut self, id: usize) {
+    /// # Safety
+    ///
+    /// The walker must be positioned at a construct state whose operand
+    /// `id` has been allocated, and below the maximum parse depth.
+    pub(crate) unsafe fn unchecked_push_operand(&mut self, id: usize) {
+        // SAFETY: the caller guarantees that the walker is below the maximum
+        // parse depth, and positioned at a state whose operand `id` is
+        // allocated
         *unsafe { self.breadcrumb.get_unchecked_mut(self.depth as usize) } = id + 1;
         self.depth += 1;
+        // SAFETY: as above; operand `id` of the state is within its operands
         self.point = unsafe {
             *self
                 .ctx
@@ -766,6 +877,7 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
                 .resolve
                 .get_unchecked(id)
         };
+        // SAFETY: as above; the new depth is at most the maximum parse depth
         *unsafe { self.breadcrumb.get_unchecked_mut(self.depth as usize) } = 0;
     }
 
@@ -778,24 +890,31 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         Ok(())
     }
 
-    pub(crate) fn unchecked_pop_operand(&mut self) {
+    /// # Safety
+    ///
+    /// The walker must be positioned at a construct state.
+    pub(crate) unsafe fn unchecked_pop_operand(&mut self) {
+        // SAFETY: the caller guarantees that the walker is positioned at a
+        // construct state
         self.point = unsafe { self.ctx.point(self.point.unsafe_unwrap()) }.parent;
         self.depth -= 1;
     }
 
-    pub fn offset(&self, offset: Option<usize>) -> usize {
+    /// The offset of the current construct state, or, given the index of
+    /// one of its operands, the offset immediately following the operand.
+    pub fn offset(&self, offset: Option<usize>) -> Result<usize, Error> {
+        let point = self.point().ok_or(Error::InconsistentState)?;
         match offset {
-            None => self.unchecked_point().offset, //.ok_or_else(|| Error::InconsistentState)?.offset,
+            None => Ok(point.offset),
             Some(index) => {
-                let op_index = unsafe {
-                    self.unchecked_point()
-                        .resolve
-                        .get_unchecked(index)
-                        .unsafe_unwrap()
-                };
-                //.ok_or_else(|| Error::InconsistentState)?;
-                let op = self.ctx.point(op_index);
-                op.offset + op.length
+                let op = point
+                    .resolve
+                    .get(index)
+                    .copied()
+                    .flatten()
+                    .and_then(|op_index| self.ctx.state.get(op_index))
+                    .ok_or(Error::InconsistentState)?;
+                Ok(op.offset + op.length)
             }
         }
     }
@@ -817,7 +936,7 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         ) -> Result<T, Error>,
     {
         let mut cur_depth = self.depth;
-        let mut point = self.unchecked_point();
+        let mut point = self.point().ok_or(Error::InconsistentState)?;
 
         while point.constructor.map(|ct| ct != ctor).unwrap_or(false) {
             if cur_depth == 0 {
@@ -835,17 +954,19 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
                 return Ok(value);
             }
             cur_depth -= 1;
-            point = self.ctx.point(unsafe { point.parent.unsafe_unwrap() });
+            point = self.ctx.point(point.parent.ok_or(Error::InconsistentState)?);
         }
 
-        let sym = symbols.unchecked_symbol(ctor.operand(index)); //.ok_or_else(|| Error::InvalidSymbol)?;
+        let sym = symbols
+            .symbol(ctor.operand(index))
+            .ok_or(Error::InvalidSymbol)?;
         let offset = if sym.offset_base().is_none() {
             // relative
             point.offset + sym.relative_offset()
         } else {
             self.ctx
-                .point(unsafe { point.resolve.get_unchecked(index).unsafe_unwrap() })
-                .offset //[index].ok_or_else(|| Error::InconsistentState)?).offset
+                .point(point.resolve[index].ok_or(Error::InconsistentState)?)
+                .offset
         };
 
         let mut state = ConstructState::default();
@@ -886,7 +1007,7 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
     }
 
     pub fn add_commit(&mut self, symbol: &'b Symbol, num: usize, mask: u32, flow: bool) {
-        let point = unsafe { self.point.unsafe_unwrap() };
+        let point = self.current();
         self.ctx.add_commit(symbol, num, mask, point, flow)
     }
 
@@ -904,8 +1025,8 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
     }
 
     pub fn set_constructor(&mut self, constructor: &'b Constructor) {
-        self.ctx
-            .set_constructor(unsafe { self.point.unsafe_unwrap() }, constructor)
+        let point = self.current();
+        self.ctx.set_constructor(point, constructor)
     }
 
     pub fn constructor(&self) -> Result<Option<&'b Constructor>, Error> {
@@ -918,16 +1039,29 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         }
     }
 
-    pub fn unchecked_constructor(&self) -> &'b Constructor {
-        self.ctx
-            .unchecked_constructor(unsafe { self.point.unsafe_unwrap() })
+    /// # Safety
+    ///
+    /// The walker must be positioned at a construct state with a resolved
+    /// constructor.
+    pub unsafe fn unchecked_constructor(&self) -> &'b Constructor {
+        // SAFETY: the caller guarantees that the walker is positioned at a
+        // construct state whose constructor is resolved
+        unsafe {
+            self.ctx
+                .unchecked_constructor(self.point.unsafe_unwrap())
+        }
     }
 
     pub fn point(&self) -> Option<&ConstructState<'b>> {
         self.point.map(|index| self.ctx.point(index))
     }
 
-    pub fn unchecked_point(&self) -> &ConstructState<'b> {
+    /// # Safety
+    ///
+    /// The walker must be positioned at a construct state.
+    pub unsafe fn unchecked_point(&self) -> &ConstructState<'b> {
+        // SAFETY: the caller guarantees that the walker is positioned at a
+        // construct state
         unsafe { self.ctx.point(self.point.unsafe_unwrap()) }
     }
 
@@ -945,6 +1079,14 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         self.ctx.context_bits(offset, size)
     }
 
+    pub(crate) fn mark_context(&self, offset: usize, mask: u32) {
+        self.ctx.mark_context(offset, mask)
+    }
+
+    pub(crate) fn mark_context_bits(&self, offset: usize, size: usize) {
+        self.ctx.mark_context_bits(offset, size)
+    }
+
     pub fn instruction_bytes(&self, offset: usize, size: usize) -> Result<u32, Error> {
         let point = self
             .ctx
@@ -952,7 +1094,12 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         Ok(self.ctx.instruction_bytes(offset, size, point.offset)?)
     }
 
-    pub fn unchecked_instruction_bytes(&self, offset: usize, size: usize) -> u32 {
+    /// # Safety
+    ///
+    /// The walker must be positioned at a construct state.
+    pub unsafe fn unchecked_instruction_bytes(&self, offset: usize, size: usize) -> u32 {
+        // SAFETY: the caller guarantees that the walker is positioned at a
+        // construct state
         let point = self.ctx.point(unsafe { self.point.unsafe_unwrap() });
         self.ctx
             .instruction_bytes(offset, size, point.offset)
@@ -966,7 +1113,12 @@ impl<'b, 'c, 'z> ParserWalker<'b, 'c, 'z> {
         Ok(self.ctx.instruction_bits(offset, size, point.offset)?)
     }
 
-    pub fn unchecked_instruction_bits(&self, offset: usize, size: usize) -> u32 {
+    /// # Safety
+    ///
+    /// The walker must be positioned at a construct state.
+    pub unsafe fn unchecked_instruction_bits(&self, offset: usize, size: usize) -> u32 {
+        // SAFETY: the caller guarantees that the walker is positioned at a
+        // construct state
         let point = self.ctx.point(unsafe { self.point.unsafe_unwrap() });
         self.ctx
             .instruction_bits(offset, size, point.offset)
@@ -1,19 +1,169 @@
+use std::fmt;
 use std::io;
 use std::path::PathBuf;
+
+use fugue_arch::ArchitectureDef;
 use thiserror::Error;
 
+use crate::address::AddressValue;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("cannot deserialise file `{}`: {}", path.display(), error)]
     DeserialiseFile {
         path: PathBuf,
+        #[source]
         error: crate::deserialise::Error,
     },
     #[error("cannot parse from file `{}`: {}", path.display(), error)]
     ParseFile {
         path: PathBuf,
+        #[source]
         error: io::Error,
     },
     #[error(transparent)]
     Disassembly(#[from] crate::disassembly::Error),
+    #[error("cannot {context}")]
+    Context {
+        context: Box<ErrorContext>,
+        #[source]
+        error: Box<Error>,
+    },
+}
+
+impl Error {
+    pub(crate) fn in_context(self, context: ErrorContext) -> Self {
+        Self::Context {
+            context: Box::new(context),
+            error: Box::new(self),
+        }
+    }
+
+    /// The outermost context of the error, if any.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        if let Self::Context { context, .. } = self {
+            Some(context)
+        } else {
+            None
+        }
+    }
+
+    /// The error with all of its contexts removed.
+    pub fn root_cause(&self) -> &Self {
+        let mut error = self;
+        while let Self::Context { error: inner, .. } = error {
+            error = inner;
+        }
+        error
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Phase {
+    Disassembly,
+    Lifting,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disassembly => write!(f, "disassemble"),
+            Self::Lifting => write!(f, "lift"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The instruction being processed when an error occurred.
+pub struct ErrorContext {
+    architecture: ArchitectureDef,
+    address: AddressValue,
+    bytes: Vec<u8>,
+    phase: Phase,
+}
+
+impl ErrorContext {
+    /// The maximum number of instruction bytes recorded.
+    pub const MAX_BYTES: usize = 16;
+
+    pub fn new(
+        architecture: ArchitectureDef,
+        address: AddressValue,
+        bytes: &[u8],
+        phase: Phase,
+    ) -> Self {
+        Self {
+            architecture,
+            address,
+            bytes: bytes[..bytes.len().min(Self::MAX_BYTES)].to_vec(),
+            phase,
+        }
+    }
+
+    pub fn architecture(&self) -> &ArchitectureDef {
+        &self.architecture
+    }
+
+    pub fn address(&self) -> &AddressValue {
+        &self.address
+    }
+
+    /// The bytes at `address`, up to [`ErrorContext::MAX_BYTES`].
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} instruction at {} (", self.phase, self.address)?;
+        for (i, b) in self.bytes.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:02x}", b)?;
+        }
+        write!(f, ") for {}", self.architecture)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error as StdError;
+
+    use fugue_bytes::Endian;
+
+    use super::*;
+    use crate::disassembly::Error as DisassemblyError;
+    use crate::space::{AddressSpace, Space, SpaceKind};
+
+    #[test]
+    fn test_context() {
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 4, 1, 1, None, 0));
+        let context = ErrorContext::new(
+            ArchitectureDef::new("ARM", Endian::Little, 32, "v8T"),
+            AddressValue::new(&ram, 0x1000),
+            &[0xf5, 0xf7],
+            Phase::Lifting,
+        );
+
+        let error = Error::from(DisassemblyError::InstructionResolution).in_context(context);
+
+        assert_eq!(
+            error.context().map(ErrorContext::phase),
+            Some(Phase::Lifting)
+        );
+        assert!(matches!(
+            error.root_cause(),
+            Error::Disassembly(DisassemblyError::InstructionResolution)
+        ));
+        assert!(error
+            .to_string()
+            .starts_with("cannot lift instruction at 0x1000 (f5 f7)"));
+        assert!(error.source().is_some());
+    }
 }
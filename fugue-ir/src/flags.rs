@@ -0,0 +1,232 @@
+//! Synthesis of architectural flag registers from the operands and results
+//! of operations, for evaluators that compute operations natively (e.g.,
+//! host-accelerated fast paths or hooks) but must keep flags consistent
+//! with the lifted semantics.
+//!
+//! Operands may be of any width, but must be of equal width; results wrap
+//! to the width of the operands.
+
+use fugue_bv::BitVec;
+
+/// Computes `lhs + rhs + carry` and its unsigned carry out.
+fn add_with_carry(lhs: &BitVec, rhs: &BitVec, carry: bool) -> (BitVec, bool) {
+    let bits = lhs.bits();
+
+    let mut wide = lhs.unsigned_cast(bits + 1) + rhs.unsigned_cast(bits + 1);
+    if carry {
+        wide += &BitVec::from_u64(1, bits + 1);
+    }
+
+    (wide.unsigned_cast(bits), wide.bit(bits as u32))
+}
+
+/// Signed overflow of an addition of operands with sign bits `lhs` and
+/// `rhs` yielding a result with sign bit `result`.
+fn add_overflow(lhs: bool, rhs: bool, result: bool) -> bool {
+    lhs == rhs && result != lhs
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[derive(serde::Deserialize, serde::Serialize)]
+/// The condition flags of ARM and AArch64.
+pub struct Nzcv {
+    pub n: bool,
+    pub z: bool,
+    pub c: bool,
+    pub v: bool,
+}
+
+impl Nzcv {
+    /// The flags set by `lhs + rhs + carry` (e.g., ADDS, ADCS).
+    pub fn add(lhs: &BitVec, rhs: &BitVec, carry: bool) -> (BitVec, Self) {
+        let (result, c) = add_with_carry(lhs, rhs, carry);
+        let flags = Self {
+            n: result.msb(),
+            z: result.is_zero(),
+            c,
+            v: add_overflow(lhs.msb(), rhs.msb(), result.msb()),
+        };
+        (result, flags)
+    }
+
+    /// The flags set by `lhs - rhs` (e.g., SUBS, CMP); the carry flag is
+    /// set when no borrow occurs.
+    pub fn sub(lhs: &BitVec, rhs: &BitVec) -> (BitVec, Self) {
+        Self::add(lhs, &!rhs, true)
+    }
+
+    /// The flags set by `lhs - rhs - !carry` (e.g., SBCS).
+    pub fn sub_with_carry(lhs: &BitVec, rhs: &BitVec, carry: bool) -> (BitVec, Self) {
+        Self::add(lhs, &!rhs, carry)
+    }
+
+    /// The flags set by a logical operation yielding `result` (e.g., ANDS,
+    /// TST); the carry flag is taken from the shifter, if it produced one,
+    /// and the overflow flag is unchanged.
+    pub fn logical(self, result: &BitVec, shifter_carry: Option<bool>) -> Self {
+        Self {
+            n: result.msb(),
+            z: result.is_zero(),
+            c: shifter_carry.unwrap_or(self.c),
+            v: self.v,
+        }
+    }
+
+    /// The flags as they appear in bits 31 to 28 of the (A)PSR or NZCV
+    /// register.
+    pub fn pack(self) -> u32 {
+        (self.n as u32) << 31
+            | (self.z as u32) << 30
+            | (self.c as u32) << 29
+            | (self.v as u32) << 28
+    }
+
+    /// Replaces bits 31 to 28 of `psr` with the flags.
+    pub fn pack_into(self, psr: u32) -> u32 {
+        (psr & 0x0fff_ffff) | self.pack()
+    }
+
+    pub fn unpack(psr: u32) -> Self {
+        Self {
+            n: psr & (1 << 31) != 0,
+            z: psr & (1 << 30) != 0,
+            c: psr & (1 << 29) != 0,
+            v: psr & (1 << 28) != 0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[derive(serde::Deserialize, serde::Serialize)]
+/// The status flags of x86's EFLAGS register.
+pub struct Eflags {
+    pub cf: bool,
+    pub pf: bool,
+    pub af: bool,
+    pub zf: bool,
+    pub sf: bool,
+    pub of: bool,
+}
+
+impl Eflags {
+    pub const CF: u64 = 1 << 0;
+    pub const PF: u64 = 1 << 2;
+    pub const AF: u64 = 1 << 4;
+    pub const ZF: u64 = 1 << 6;
+    pub const SF: u64 = 1 << 7;
+    pub const OF: u64 = 1 << 11;
+
+    /// The mask of the bits of EFLAGS holding status flags.
+    pub const STATUS_MASK: u64 = Self::CF | Self::PF | Self::AF | Self::ZF | Self::SF | Self::OF;
+
+    /// The flags set by `lhs + rhs + carry` (e.g., ADD, ADC).
+    pub fn add(lhs: &BitVec, rhs: &BitVec, carry: bool) -> (BitVec, Self) {
+        let (result, cf) = add_with_carry(lhs, rhs, carry);
+        let flags = Self {
+            cf,
+            of: add_overflow(lhs.msb(), rhs.msb(), result.msb()),
+            ..Self::arithmetic(lhs, rhs, &result)
+        };
+        (result, flags)
+    }
+
+    /// The flags set by `lhs - rhs - borrow` (e.g., SUB, CMP, SBB); unlike
+    /// ARM, the carry flag is set when a borrow occurs.
+    pub fn sub(lhs: &BitVec, rhs: &BitVec, borrow: bool) -> (BitVec, Self) {
+        let (result, carry) = add_with_carry(lhs, &!rhs, !borrow);
+        let flags = Self {
+            cf: !carry,
+            of: add_overflow(lhs.msb(), !rhs.msb(), result.msb()),
+            ..Self::arithmetic(lhs, rhs, &result)
+        };
+        (result, flags)
+    }
+
+    /// The flags set by a logical operation yielding `result` (e.g., AND,
+    /// TEST); the carry, overflow, and (undefined) adjust flags are
+    /// cleared.
+    pub fn logical(result: &BitVec) -> Self {
+        Self {
+            cf: false,
+            pf: parity(result),
+            af: false,
+            zf: result.is_zero(),
+            sf: result.msb(),
+            of: false,
+        }
+    }
+
+    /// The flags as they appear in EFLAGS; all other bits are clear.
+    pub fn pack(self) -> u64 {
+        ((self.cf as u64) * Self::CF)
+            | ((self.pf as u64) * Self::PF)
+            | ((self.af as u64) * Self::AF)
+            | ((self.zf as u64) * Self::ZF)
+            | ((self.sf as u64) * Self::SF)
+            | ((self.of as u64) * Self::OF)
+    }
+
+    /// Replaces the status flags of `eflags` with the flags.
+    pub fn pack_into(self, eflags: u64) -> u64 {
+        (eflags & !Self::STATUS_MASK) | self.pack()
+    }
+
+    pub fn unpack(eflags: u64) -> Self {
+        Self {
+            cf: eflags & Self::CF != 0,
+            pf: eflags & Self::PF != 0,
+            af: eflags & Self::AF != 0,
+            zf: eflags & Self::ZF != 0,
+            sf: eflags & Self::SF != 0,
+            of: eflags & Self::OF != 0,
+        }
+    }
+
+    fn arithmetic(lhs: &BitVec, rhs: &BitVec, result: &BitVec) -> Self {
+        Self {
+            pf: parity(result),
+            af: (&(lhs ^ rhs) ^ result).bit(4),
+            zf: result.is_zero(),
+            sf: result.msb(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Set when the least significant byte of `result` has an even number of
+/// bits set.
+fn parity(result: &BitVec) -> bool {
+    result.unsigned_cast(8).count_ones().is_multiple_of(2)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_flags() {
+        let bv = |v| BitVec::from_u64(v, 32);
+
+        // cmp r0, r0
+        let (result, flags) = Nzcv::sub(&bv(5), &bv(5));
+        assert!(result.is_zero());
+        assert_eq!(flags.pack(), 0x6000_0000);
+
+        // 0x7fffffff + 1 overflows
+        let (_, flags) = Nzcv::add(&bv(0x7fff_ffff), &bv(1), false);
+        assert!(flags.n && flags.v && !flags.c && !flags.z);
+        assert_eq!(Nzcv::unpack(flags.pack_into(0x1d3)), flags);
+
+        // sub eax, ebx with eax < ebx borrows
+        let (result, flags) = Eflags::sub(&bv(1), &bv(2), false);
+        assert_eq!(result, bv(0xffff_ffff));
+        assert!(flags.cf && flags.sf && flags.pf && flags.af && !flags.zf && !flags.of);
+
+        // add al, 0xff with al = 1 carries into the adjust flag
+        let (result, flags) =
+            Eflags::add(&BitVec::from_u64(1, 8), &BitVec::from_u64(0xff, 8), false);
+        assert!(result.is_zero());
+        assert!(flags.cf && flags.zf && flags.af && flags.pf && !flags.of);
+        assert_eq!(flags.pack_into(0x202), 0x202 | 0x55);
+    }
+}
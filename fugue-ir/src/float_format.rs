@@ -415,6 +415,8 @@ fn frexp(x: f64) -> (f64, i32) {
         fn frexp(x: f64, exp: *mut i32) -> f64;
     }
     let mut exp = 0;
+    // SAFETY: `frexp` only writes the exponent through the pointer, which
+    // refers to a live local
     let r = unsafe {
         frexp(x, &mut exp as *mut i32)
     };
@@ -425,6 +427,7 @@ fn ldexp(x: f64, exp: i32) -> f64 {
     extern "C" {
         fn ldexp(x: f64, exp: i32) -> f64;
     }
+    // SAFETY: `ldexp` is a pure function of its arguments
     unsafe {
         ldexp(x, exp)
     }
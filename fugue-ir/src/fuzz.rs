@@ -0,0 +1,183 @@
+//! Harnesses for the fuzz targets in the repository's `fuzz` directory.
+//!
+//! The harnesses are deterministic and swallow all errors: any panic,
+//! abort, or hang observed while running them is a bug.
+
+use std::path::Path;
+
+use ahash::AHashMap as Map;
+use fugue_arch::ArchitectureDef;
+
+use crate::compiler;
+use crate::disassembly::IRBuilderArena;
+use crate::endian::Endian;
+use crate::error::Error;
+use crate::language::LanguageDB;
+use crate::processor;
+use crate::translator::Translator;
+
+/// The address each input is decoded at.
+pub const BASE_ADDRESS: u64 = 0x1000;
+
+/// The maximum number of instructions decoded for each input.
+pub const MAX_INSTRUCTIONS: usize = 64;
+
+/// The size of the translator selector prefixed to each lifting input.
+pub const SELECTOR_SIZE: usize = 2;
+
+/// Decodes and lifts instructions with one of a fixed set of translators.
+///
+/// Each input consists of a little-endian `u16` selecting a translator
+/// (modulo the number of translators), followed by the bytes to decode.
+/// Instructions are decoded sequentially from the start of the bytes until
+/// decoding fails, the bytes are exhausted, or `MAX_INSTRUCTIONS` have been
+/// decoded.
+pub struct LiftHarness {
+    translators: Vec<Translator>,
+}
+
+impl LiftHarness {
+    pub fn new(translators: Vec<Translator>) -> Self {
+        Self { translators }
+    }
+
+    /// Builds a translator for each language in `db`; languages that fail
+    /// to build are skipped. Translators are ordered by architecture, so
+    /// that selectors are stable for a given set of languages.
+    pub fn from_language_db(db: &LanguageDB) -> Self {
+        let mut languages = db.iter().collect::<Vec<_>>();
+        languages.sort_by(|a, b| {
+            a.language()
+                .architecture()
+                .cmp(b.language().architecture())
+        });
+
+        Self::new(
+            languages
+                .into_iter()
+                .filter_map(|builder| builder.build().ok())
+                .collect(),
+        )
+    }
+
+    pub fn from_directory<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        LanguageDB::from_directory_with(path, true).map(|db| Self::from_language_db(&db))
+    }
+
+    pub fn translators(&self) -> &[Translator] {
+        &self.translators
+    }
+
+    pub fn len(&self) -> usize {
+        self.translators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.translators.is_empty()
+    }
+
+    pub fn run(&self, data: &[u8]) {
+        if self.translators.is_empty() || data.len() < SELECTOR_SIZE {
+            return;
+        }
+
+        let selector = u16::from_le_bytes([data[0], data[1]]) as usize;
+        let translator = &self.translators[selector % self.translators.len()];
+        let bytes = &data[SELECTOR_SIZE..];
+
+        let mut db = translator.context_database();
+        let irb = IRBuilderArena::with_capacity(4096);
+
+        let mut offset = 0;
+        for _ in 0..MAX_INSTRUCTIONS {
+            if offset >= bytes.len() {
+                break;
+            }
+
+            let address = translator.address(BASE_ADDRESS + offset as u64);
+            let code = &bytes[offset..];
+
+            let length = match translator.disassemble(&mut db, &irb, address, code) {
+                Ok(insn) => insn.length(),
+                Err(_) => break,
+            };
+
+            if let Ok(insn) = translator.disassemble_tokens(&mut db, &irb, address, code) {
+                let _ = insn.display_tokens();
+            }
+
+            let _ = translator.lift_pcode(&mut db, address, code);
+            let _ = translator.lift_ecode(&mut db, address, code);
+
+            if length == 0 {
+                break;
+            }
+            offset += length;
+        }
+    }
+
+    /// The input that decodes `code` using the translator for
+    /// `architecture`, if the harness has one.
+    pub fn seed(&self, architecture: &ArchitectureDef, code: &[u8]) -> Option<Vec<u8>> {
+        let index = self
+            .translators
+            .iter()
+            .position(|t| t.architecture() == architecture)?;
+
+        let mut input = Vec::with_capacity(SELECTOR_SIZE + code.len());
+        input.extend_from_slice(&(index as u16).to_le_bytes());
+        input.extend_from_slice(code);
+        Some(input)
+    }
+
+    /// Seed inputs for the binary `image`: the image is split into windows
+    /// of `window` bytes, each decoded using the translator for the most
+    /// likely architecture of the image (see [`fugue_arch::detect`]) that
+    /// the harness has. Windows of only zero bytes are skipped.
+    pub fn seeds(&self, image: &[u8], window: usize) -> Vec<Vec<u8>> {
+        let architecture = fugue_arch::detect(image).into_iter().find_map(|def| {
+            self.translators
+                .iter()
+                .map(Translator::architecture)
+                .find(|arch| **arch == def)
+                .or_else(|| {
+                    self.translators.iter().map(Translator::architecture).find(|arch| {
+                        arch.processor().eq_ignore_ascii_case(def.processor())
+                            && arch.endian() == def.endian()
+                            && arch.bits() == def.bits()
+                    })
+                })
+        });
+
+        let architecture = if let Some(architecture) = architecture {
+            architecture
+        } else {
+            return Vec::new();
+        };
+
+        image
+            .chunks(window.max(1))
+            .filter(|chunk| chunk.iter().any(|b| *b != 0))
+            .filter_map(|chunk| self.seed(architecture, chunk))
+            .collect()
+    }
+}
+
+/// Attempts to parse `data` as each of the specification formats read by
+/// the crate: compiled SLEIGH (.sla), processor specifications (.pspec),
+/// and compiler specifications (.cspec).
+pub fn deserialise(data: &[u8]) {
+    let input = if let Ok(input) = std::str::from_utf8(data) {
+        input
+    } else {
+        return;
+    };
+
+    let architecture = ArchitectureDef::new("fuzz", Endian::Little, 32, "default");
+    let _ = Translator::from_str("pc", &architecture, &Map::default(), input);
+    let _ = processor::Specification::from_str(input);
+
+    if let Ok(document) = xml::Document::parse(input) {
+        let _ = compiler::Specification::named_from_xml("fuzz", document.root_element());
+    }
+}
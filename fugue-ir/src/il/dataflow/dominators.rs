@@ -0,0 +1,338 @@
+use std::collections::BTreeSet;
+
+use super::BlockGraph;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The dominator tree of a [`BlockGraph`], computed using the algorithm of
+/// Cooper, Harvey and Kennedy.
+pub struct Dominators {
+    entry: usize,
+    idoms: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+}
+
+impl Dominators {
+    pub fn new<Loc, Val, Var>(graph: &BlockGraph<Loc, Val, Var>) -> Self {
+        let order = graph.reverse_post_order();
+
+        let mut rpo_index = vec![usize::MAX; graph.len()];
+        for (i, block) in order.iter().enumerate() {
+            rpo_index[*block] = i;
+        }
+
+        let mut idoms = vec![None; graph.len()];
+        if let Some(entry) = order.first() {
+            idoms[*entry] = Some(*entry);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in order.iter().skip(1) {
+                let mut new_idom = None;
+                for pred in graph.predecessors(*block) {
+                    if idoms[*pred].is_none() {
+                        continue;
+                    }
+                    new_idom = Some(match new_idom {
+                        None => *pred,
+                        Some(current) => Self::intersect(&idoms, &rpo_index, *pred, current),
+                    });
+                }
+
+                if new_idom.is_some() && idoms[*block] != new_idom {
+                    idoms[*block] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut children = vec![Vec::new(); graph.len()];
+        for (block, idom) in idoms.iter().enumerate() {
+            if let Some(idom) = idom {
+                if *idom != block {
+                    children[*idom].push(block);
+                }
+            }
+        }
+
+        Self {
+            entry: graph.entry(),
+            idoms,
+            children,
+        }
+    }
+
+    fn intersect(idoms: &[Option<usize>], rpo_index: &[usize], a: usize, b: usize) -> usize {
+        let mut a = a;
+        let mut b = b;
+        while a != b {
+            while rpo_index[a] > rpo_index[b] {
+                a = idoms[a].expect("processed block has a dominator");
+            }
+            while rpo_index[b] > rpo_index[a] {
+                b = idoms[b].expect("processed block has a dominator");
+            }
+        }
+        a
+    }
+
+    pub fn entry(&self) -> usize {
+        self.entry
+    }
+
+    pub fn is_reachable(&self, block: usize) -> bool {
+        self.idoms.get(block).map(Option::is_some).unwrap_or(false)
+    }
+
+    /// The immediate dominator of `block`; `None` for the entry block and
+    /// for blocks unreachable from it.
+    pub fn immediate_dominator(&self, block: usize) -> Option<usize> {
+        self.idoms
+            .get(block)
+            .copied()
+            .flatten()
+            .filter(|idom| *idom != block)
+    }
+
+    /// Blocks immediately dominated by `block`.
+    pub fn children(&self, block: usize) -> &[usize] {
+        &self.children[block]
+    }
+
+    /// Returns `block` followed by each of its dominators, ending at the
+    /// entry block.
+    pub fn dominators(&self, block: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = if self.is_reachable(block) {
+            Some(block)
+        } else {
+            None
+        };
+        std::iter::successors(start, move |b| self.immediate_dominator(*b))
+    }
+
+    pub fn dominates(&self, dominator: usize, block: usize) -> bool {
+        self.dominators(block).any(|b| b == dominator)
+    }
+
+    pub fn strictly_dominates(&self, dominator: usize, block: usize) -> bool {
+        dominator != block && self.dominates(dominator, block)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NaturalLoop {
+    header: usize,
+    latches: Vec<usize>,
+    blocks: BTreeSet<usize>,
+    parent: Option<usize>,
+    depth: usize,
+}
+
+impl NaturalLoop {
+    pub fn header(&self) -> usize {
+        self.header
+    }
+
+    /// Sources of the back edges to the loop's header.
+    pub fn latches(&self) -> &[usize] {
+        &self.latches
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().copied()
+    }
+
+    pub fn contains(&self, block: usize) -> bool {
+        self.blocks.contains(&block)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Index of the innermost loop enclosing this loop.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Nesting depth; outermost loops have depth 1.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The natural loops of a [`BlockGraph`]. Back edges sharing a header are
+/// merged into a single loop; irreducible cycles, whose headers do not
+/// dominate their latches, are not reported.
+pub struct Loops {
+    loops: Vec<NaturalLoop>,
+    innermost: Vec<Option<usize>>,
+}
+
+impl Loops {
+    pub fn new<Loc, Val, Var>(graph: &BlockGraph<Loc, Val, Var>, dominators: &Dominators) -> Self {
+        let mut loops = Vec::<NaturalLoop>::new();
+
+        for block in graph.reverse_post_order() {
+            for succ in graph.successors(block) {
+                if !dominators.dominates(*succ, block) {
+                    continue;
+                }
+
+                let index = if let Some(index) = loops.iter().position(|l| l.header == *succ) {
+                    index
+                } else {
+                    loops.push(NaturalLoop {
+                        header: *succ,
+                        latches: Vec::new(),
+                        blocks: BTreeSet::from([*succ]),
+                        parent: None,
+                        depth: 0,
+                    });
+                    loops.len() - 1
+                };
+
+                let natural_loop = &mut loops[index];
+                natural_loop.latches.push(block);
+
+                let mut stack = vec![block];
+                while let Some(node) = stack.pop() {
+                    if natural_loop.blocks.insert(node) {
+                        stack.extend(
+                            graph
+                                .predecessors(node)
+                                .iter()
+                                .filter(|p| dominators.is_reachable(**p)),
+                        );
+                    }
+                }
+            }
+        }
+
+        // order outer loops before inner loops so that parents are always
+        // assigned their depth before their children
+        loops.sort_by(|a, b| b.len().cmp(&a.len()).then(a.header.cmp(&b.header)));
+
+        for i in 0..loops.len() {
+            let parent = (0..i)
+                .rev()
+                .find(|j| loops[*j].contains(loops[i].header) && loops[*j].len() > loops[i].len());
+            loops[i].parent = parent;
+            loops[i].depth = parent.map(|p| loops[p].depth + 1).unwrap_or(1);
+        }
+
+        let mut innermost = vec![None; graph.len()];
+        for (index, natural_loop) in loops.iter().enumerate() {
+            for block in natural_loop.blocks() {
+                innermost[block] = Some(index);
+            }
+        }
+
+        Self { loops, innermost }
+    }
+
+    pub fn len(&self) -> usize {
+        self.loops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.loops.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&NaturalLoop> {
+        self.loops.get(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &NaturalLoop> {
+        self.loops.iter()
+    }
+
+    /// The innermost loop containing `block`.
+    pub fn innermost(&self, block: usize) -> Option<&NaturalLoop> {
+        self.innermost
+            .get(block)
+            .copied()
+            .flatten()
+            .map(|index| &self.loops[index])
+    }
+
+    /// The number of loops containing `block`.
+    pub fn depth(&self, block: usize) -> usize {
+        self.innermost(block).map(NaturalLoop::depth).unwrap_or(0)
+    }
+
+    pub fn is_header(&self, block: usize) -> bool {
+        self.loops.iter().any(|l| l.header == block)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Graph = BlockGraph<'static, (), u64, &'static str>;
+
+    #[test]
+    fn test_dominators() {
+        // 0 -> 1 -> 2 -> 4
+        //      1 -> 3 -> 4
+        // 5 is unreachable
+        let graph = Graph::from_successors(0, vec![vec![1], vec![2, 3], vec![4], vec![4], vec![], vec![4]]);
+        let doms = graph.dominators();
+
+        assert_eq!(doms.immediate_dominator(0), None);
+        assert_eq!(doms.immediate_dominator(1), Some(0));
+        assert_eq!(doms.immediate_dominator(2), Some(1));
+        assert_eq!(doms.immediate_dominator(3), Some(1));
+        assert_eq!(doms.immediate_dominator(4), Some(1));
+        assert_eq!(doms.immediate_dominator(5), None);
+
+        assert!(doms.dominates(0, 4));
+        assert!(!doms.dominates(2, 4));
+        assert!(!doms.is_reachable(5));
+        assert_eq!(doms.dominators(4).collect::<Vec<_>>(), vec![4, 1, 0]);
+        assert_eq!(doms.children(1), &[2, 3, 4]);
+    }
+
+    #[test]
+    fn test_nested_loops() {
+        // outer: 1 -> 2 -> 3 -> 1
+        // inner: 2 -> 2
+        // exit:  1 -> 4
+        let graph = Graph::from_successors(0, vec![vec![1], vec![2, 4], vec![2, 3], vec![1], vec![]]);
+        let loops = graph.loops();
+
+        assert_eq!(loops.len(), 2);
+
+        let outer = loops.innermost(1).unwrap();
+        assert_eq!(outer.header(), 1);
+        assert_eq!(outer.latches(), &[3]);
+        assert_eq!(outer.blocks().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(outer.depth(), 1);
+        assert_eq!(outer.parent(), None);
+
+        let inner = loops.innermost(2).unwrap();
+        assert_eq!(inner.header(), 2);
+        assert_eq!(inner.blocks().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(inner.depth(), 2);
+        assert_eq!(inner.parent(), Some(0));
+
+        assert_eq!(loops.depth(0), 0);
+        assert_eq!(loops.depth(3), 1);
+        assert_eq!(loops.depth(2), 2);
+        assert!(loops.is_header(1) && !loops.is_header(3));
+    }
+
+    #[test]
+    fn test_irreducible_cycle() {
+        // 1 <-> 2 with both entered from 0
+        let graph = Graph::from_successors(0, vec![vec![1, 2], vec![2], vec![1]]);
+        assert!(graph.loops().is_empty());
+    }
+}
@@ -0,0 +1,370 @@
+use std::collections::BTreeMap;
+
+use crate::il::ecode::{BinOp, BranchTargetT, Cast, Expr, ExprT, Location, Stmt, StmtT, UnOp, Var};
+use crate::space::AddressSpaceId;
+
+use fugue_bv::BitVec;
+
+use super::{BlockGraph, ProgramPoint};
+
+/// The number of blocks preceding an indirect call emulated by default.
+pub const DEFAULT_SNIPPET_DEPTH: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// How much a resolved target depends on state outside of the emulated
+/// snippet.
+pub enum Confidence {
+    /// The target was read from writable memory, e.g., a GOT entry or a
+    /// function pointer in a data section; its value may differ at run-time.
+    Low,
+    /// The target was read from read-only memory, e.g., a vtable.
+    Medium,
+    /// The target was computed from constants within the snippet.
+    High,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ResolvedTarget {
+    point: ProgramPoint,
+    target: u64,
+    confidence: Confidence,
+}
+
+impl ResolvedTarget {
+    /// The position of the call statement within its graph.
+    pub fn point(&self) -> ProgramPoint {
+        self.point
+    }
+
+    pub fn target(&self) -> u64 {
+        self.target
+    }
+
+    pub fn confidence(&self) -> Confidence {
+        self.confidence
+    }
+}
+
+/// Memory consulted for loads within a snippet that are not satisfied by
+/// an earlier store in the same snippet.
+pub trait StaticMemory {
+    /// Reads `bits` bits from `address`; `None` if the address is not
+    /// backed by known contents.
+    fn read(&self, address: u64, bits: usize) -> Option<BitVec>;
+
+    /// True if the contents at `address` may be modified at run-time.
+    fn is_writable(&self, address: u64) -> bool;
+}
+
+impl<M: StaticMemory + ?Sized> StaticMemory for &M {
+    fn read(&self, address: u64, bits: usize) -> Option<BitVec> {
+        (**self).read(address, bits)
+    }
+
+    fn is_writable(&self, address: u64) -> bool {
+        (**self).is_writable(address)
+    }
+}
+
+/// Memory with no known contents; only targets computed from constants
+/// will be resolved.
+pub struct NoMemory;
+
+impl StaticMemory for NoMemory {
+    fn read(&self, _address: u64, _bits: usize) -> Option<BitVec> {
+        None
+    }
+
+    fn is_writable(&self, _address: u64) -> bool {
+        true
+    }
+}
+
+/// Proposes concrete targets for indirect calls by concretely emulating a
+/// short snippet of the code leading up to each call: the call's block and
+/// up to `depth` of its ancestors, following predecessors while they are
+/// unique. Values not determined by the snippet (e.g., function arguments)
+/// are unknown, and calls whose target depends upon them are not resolved.
+pub struct IndirectCallResolver<M> {
+    memory: M,
+    depth: usize,
+}
+
+impl<M> IndirectCallResolver<M>
+where
+    M: StaticMemory,
+{
+    pub fn new(memory: M) -> Self {
+        Self {
+            memory,
+            depth: DEFAULT_SNIPPET_DEPTH,
+        }
+    }
+
+    pub fn depth(self, depth: usize) -> Self {
+        Self { depth, ..self }
+    }
+
+    pub fn resolve(&self, graph: &BlockGraph<Location, BitVec, Var>) -> Vec<ResolvedTarget> {
+        let mut targets = Vec::new();
+
+        for block in 0..graph.len() {
+            let is_indirect_call =
+                |stmt: &&Stmt| matches!(stmt, StmtT::Call(BranchTargetT::Computed(_), _));
+
+            if !graph.statements(block).iter().any(is_indirect_call) {
+                continue;
+            }
+
+            let mut snapshot = Snapshot::default();
+            for ancestor in self.ancestors(graph, block) {
+                for stmt in graph.statements(ancestor) {
+                    snapshot.step(stmt, &self.memory);
+                }
+            }
+
+            for (index, stmt) in graph.statements(block).iter().enumerate() {
+                if let StmtT::Call(BranchTargetT::Computed(expr), _) = stmt {
+                    if let Some((value, confidence)) = snapshot.evaluate(expr, &self.memory) {
+                        if let Some(target) = value.to_u64() {
+                            targets.push(ResolvedTarget {
+                                point: ProgramPoint::new(block, index),
+                                target,
+                                confidence,
+                            });
+                        }
+                    }
+                }
+                snapshot.step(stmt, &self.memory);
+            }
+        }
+
+        targets
+    }
+
+    /// The unique-predecessor chain ending at (and excluding) `block`,
+    /// ordered from the furthest ancestor.
+    fn ancestors(&self, graph: &BlockGraph<Location, BitVec, Var>, block: usize) -> Vec<usize> {
+        let mut chain = Vec::new();
+        let mut current = block;
+
+        while chain.len() < self.depth {
+            match graph.predecessors(current) {
+                [pred] if *pred != block && !chain.contains(pred) => {
+                    chain.push(*pred);
+                    current = *pred;
+                }
+                _ => break,
+            }
+        }
+
+        chain.reverse();
+        chain
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct Snapshot {
+    variables: BTreeMap<Var, (BitVec, Confidence)>,
+    stores: BTreeMap<(AddressSpaceId, u64, usize), (BitVec, Confidence)>,
+}
+
+impl Snapshot {
+    fn step<M: StaticMemory>(&mut self, stmt: &Stmt, memory: &M) {
+        match stmt {
+            StmtT::Assign(var, expr) => {
+                if let Some(value) = self.evaluate(expr, memory) {
+                    self.variables.insert(*var, value);
+                } else {
+                    self.variables.remove(var);
+                }
+            }
+            StmtT::Store(address, value, bits, space) => {
+                let address = self
+                    .evaluate(address, memory)
+                    .and_then(|(address, _)| address.to_u64());
+
+                if let Some(address) = address {
+                    self.stores
+                        .retain(|(s, a, _), _| *s != *space || *a != address);
+                    if let Some(value) = self.evaluate(value, memory) {
+                        self.stores.insert((*space, address, *bits), value);
+                    }
+                } else {
+                    // the store may alias any previous store
+                    self.stores.clear();
+                }
+            }
+            StmtT::Call(_, _) => {
+                // the callee may modify any register or memory
+                self.variables.clear();
+                self.stores.clear();
+            }
+            _ => (),
+        }
+    }
+
+    fn evaluate<M: StaticMemory>(&self, expr: &Expr, memory: &M) -> Option<(BitVec, Confidence)> {
+        match expr {
+            ExprT::Val(bv) => Some((bv.clone(), Confidence::High)),
+            ExprT::Var(var) => self.variables.get(var).cloned(),
+            ExprT::UnOp(op, expr) => {
+                let (value, confidence) = self.evaluate(expr, memory)?;
+                let value = match op {
+                    UnOp::NOT => !value,
+                    UnOp::NEG => -value,
+                    _ => return None,
+                };
+                Some((value, confidence))
+            }
+            ExprT::BinOp(op, lhs, rhs) => {
+                let (lhs, lconf) = self.evaluate(lhs, memory)?;
+                let (rhs, rconf) = self.evaluate(rhs, memory)?;
+                let value = match op {
+                    BinOp::SHL => lhs << shift_amount(&rhs)?,
+                    BinOp::SHR => lhs >> shift_amount(&rhs)?,
+                    _ => {
+                        let rhs = rhs.unsigned_cast(lhs.bits());
+                        match op {
+                            BinOp::AND => lhs & rhs,
+                            BinOp::OR => lhs | rhs,
+                            BinOp::XOR => lhs ^ rhs,
+                            BinOp::ADD => lhs + rhs,
+                            BinOp::SUB => lhs - rhs,
+                            BinOp::MUL => lhs * rhs,
+                            BinOp::DIV if !rhs.is_zero() => lhs / rhs,
+                            BinOp::REM if !rhs.is_zero() => lhs % rhs,
+                            _ => return None,
+                        }
+                    }
+                };
+                Some((value, lconf.min(rconf)))
+            }
+            ExprT::Cast(expr, cast) => {
+                let (value, confidence) = self.evaluate(expr, memory)?;
+                let value = match cast {
+                    Cast::Unsigned(bits) | Cast::Pointer(_, bits) => value.unsigned_cast(*bits),
+                    Cast::Signed(bits) => value.signed_cast(*bits).unsigned(),
+                    _ => return None,
+                };
+                Some((value, confidence))
+            }
+            ExprT::Load(address, bits, space) => {
+                let (address, confidence) = self.evaluate(address, memory)?;
+                let address = address.to_u64()?;
+
+                if let Some((value, stored)) = self.stores.get(&(*space, address, *bits)) {
+                    return Some((value.clone(), confidence.min(*stored)));
+                }
+
+                if !space.is_default() {
+                    return None;
+                }
+
+                let value = memory.read(address, *bits)?;
+                let source = if memory.is_writable(address) {
+                    Confidence::Low
+                } else {
+                    Confidence::Medium
+                };
+                Some((value, confidence.min(source)))
+            }
+            ExprT::IfElse(cond, texpr, fexpr) => {
+                let (cond, confidence) = self.evaluate(cond, memory)?;
+                let (value, vconf) = if cond.is_zero() {
+                    self.evaluate(fexpr, memory)?
+                } else {
+                    self.evaluate(texpr, memory)?
+                };
+                Some((value, confidence.min(vconf)))
+            }
+            ExprT::Extract(expr, lsb, msb) => {
+                let (value, confidence) = self.evaluate(expr, memory)?;
+                Some(((value >> *lsb as u32).unsigned_cast(msb - lsb), confidence))
+            }
+            ExprT::ExtractLow(expr, bits) => {
+                let (value, confidence) = self.evaluate(expr, memory)?;
+                Some((value.unsigned_cast(*bits), confidence))
+            }
+            ExprT::ExtractHigh(expr, bits) => {
+                let (value, confidence) = self.evaluate(expr, memory)?;
+                let shift = value.bits().checked_sub(*bits)? as u32;
+                Some(((value >> shift).unsigned_cast(*bits), confidence))
+            }
+            ExprT::Concat(hi, lo) => {
+                let (hi, hconf) = self.evaluate(hi, memory)?;
+                let (lo, lconf) = self.evaluate(lo, memory)?;
+                let bits = hi.bits() + lo.bits();
+                let value = (hi.unsigned_cast(bits) << lo.bits() as u32) | lo.unsigned_cast(bits);
+                Some((value, hconf.min(lconf)))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn shift_amount(amount: &BitVec) -> Option<u32> {
+    amount.to_u64().and_then(|v| u32::try_from(v).ok())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Table;
+
+    impl StaticMemory for Table {
+        fn read(&self, address: u64, bits: usize) -> Option<BitVec> {
+            if address == 0x2000 && bits == 64 {
+                Some(BitVec::from_u64(0x4000, 64))
+            } else {
+                None
+            }
+        }
+
+        fn is_writable(&self, _address: u64) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_resolve_through_predecessor() {
+        let rax = Var::new(AddressSpaceId::register_id(1), 0x0, 64, 0);
+        let rdi = Var::new(AddressSpaceId::register_id(1), 0x38, 64, 0);
+        let ram = AddressSpaceId::default_id(2);
+
+        let call = |expr| Stmt::Call(BranchTargetT::Computed(expr), Default::default());
+
+        // mov rax, 0x1000; call rax; mov rax, [0x2000]; call rax; call rdi
+        let first = [Stmt::Assign(rax, ExprT::Val(BitVec::from_u64(0x1000, 64)))];
+        let second = [
+            call(ExprT::Var(rax)),
+            Stmt::Assign(
+                rax,
+                ExprT::Load(Box::new(ExprT::Val(BitVec::from_u64(0x2000, 64))), 64, ram),
+            ),
+            call(ExprT::Var(rax)),
+            call(ExprT::Var(rdi)),
+        ];
+
+        let mut graph = BlockGraph::new();
+        let b0 = graph.add_block(&first);
+        let b1 = graph.add_block(&second);
+        graph.add_edge(b0, b1);
+
+        let targets = IndirectCallResolver::new(Table).resolve(&graph);
+
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].point(), ProgramPoint::new(b1, 0));
+        assert_eq!(targets[0].target(), 0x1000);
+        assert_eq!(targets[0].confidence(), Confidence::High);
+        assert_eq!(targets[1].point(), ProgramPoint::new(b1, 2));
+        assert_eq!(targets[1].target(), 0x4000);
+        assert_eq!(targets[1].confidence(), Confidence::Medium);
+
+        assert!(IndirectCallResolver::new(NoMemory)
+            .depth(0)
+            .resolve(&graph)
+            .is_empty());
+    }
+}
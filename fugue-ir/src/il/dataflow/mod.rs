@@ -0,0 +1,576 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::il::ecode::{ECode, ExprT, ExprVisitor, Location, StmtT, Var};
+
+use fugue_bv::BitVec;
+
+pub mod dominators;
+pub use dominators::{Dominators, Loops, NaturalLoop};
+
+pub mod indirect;
+pub use indirect::{Confidence, IndirectCallResolver, ResolvedTarget, StaticMemory};
+
+pub mod prototype;
+pub use prototype::InferredPrototype;
+
+pub mod stack;
+pub use stack::{StackDelta, StackFrame, StackSlot};
+
+/// A join semi-lattice of dataflow facts.
+pub trait Lattice: Clone + PartialEq {
+    fn bottom() -> Self;
+
+    /// Joins `other` into `self`, returning `true` if `self` changed.
+    fn join(&mut self, other: &Self) -> bool;
+}
+
+impl<T> Lattice for BTreeSet<T>
+where
+    T: Clone + Ord,
+{
+    fn bottom() -> Self {
+        BTreeSet::new()
+    }
+
+    fn join(&mut self, other: &Self) -> bool {
+        let len = self.len();
+        self.extend(other.iter().cloned());
+        self.len() != len
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// The position of a statement within a [`BlockGraph`].
+pub struct ProgramPoint {
+    pub block: usize,
+    pub index: usize,
+}
+
+impl ProgramPoint {
+    pub fn new(block: usize, index: usize) -> Self {
+        Self { block, index }
+    }
+}
+
+pub trait Analysis<Loc, Val, Var> {
+    type Fact: Lattice;
+
+    fn direction(&self) -> Direction;
+
+    /// The fact holding on entry to the graph for forward analyses, or at
+    /// each exit block for backward analyses.
+    fn boundary(&self) -> Self::Fact;
+
+    fn transfer(&self, point: ProgramPoint, stmt: &StmtT<Loc, Val, Var>, fact: &mut Self::Fact);
+}
+
+#[derive(Debug, Clone)]
+/// Basic blocks of statements and the control-flow edges between them.
+pub struct BlockGraph<'a, Loc, Val, Var> {
+    entry: usize,
+    blocks: Vec<Vec<&'a StmtT<Loc, Val, Var>>>,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl<'a, Loc, Val, Var> Default for BlockGraph<'a, Loc, Val, Var> {
+    fn default() -> Self {
+        Self {
+            entry: 0,
+            blocks: Vec::new(),
+            successors: Vec::new(),
+            predecessors: Vec::new(),
+        }
+    }
+}
+
+impl<'a, Loc, Val, Var> BlockGraph<'a, Loc, Val, Var> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a graph of empty blocks from the successors of each block;
+    /// edges to blocks outside of `successors` are ignored.
+    pub fn from_successors(entry: usize, successors: Vec<Vec<usize>>) -> Self {
+        let count = successors.len();
+
+        let mut graph = Self::new();
+        for _ in 0..count {
+            graph.add_block(std::iter::empty());
+        }
+
+        for (source, succs) in successors.into_iter().enumerate() {
+            for target in succs.into_iter().filter(|t| *t < count) {
+                graph.add_edge(source, target);
+            }
+        }
+
+        graph.set_entry(entry);
+        graph
+    }
+
+    pub fn add_block<I>(&mut self, statements: I) -> usize
+    where
+        I: IntoIterator<Item = &'a StmtT<Loc, Val, Var>>,
+    {
+        self.blocks.push(statements.into_iter().collect());
+        self.successors.push(Vec::new());
+        self.predecessors.push(Vec::new());
+        self.blocks.len() - 1
+    }
+
+    pub fn add_edge(&mut self, source: usize, target: usize) {
+        if !self.successors[source].contains(&target) {
+            self.successors[source].push(target);
+            self.predecessors[target].push(source);
+        }
+    }
+
+    pub fn set_entry(&mut self, entry: usize) {
+        self.entry = entry;
+    }
+
+    pub fn entry(&self) -> usize {
+        self.entry
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn statements(&self, block: usize) -> &[&'a StmtT<Loc, Val, Var>] {
+        &self.blocks[block]
+    }
+
+    pub fn successors(&self, block: usize) -> &[usize] {
+        &self.successors[block]
+    }
+
+    pub fn predecessors(&self, block: usize) -> &[usize] {
+        &self.predecessors[block]
+    }
+
+    /// Blocks reachable from the entry in reverse post-order.
+    pub fn reverse_post_order(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.len());
+        if self.entry >= self.len() {
+            return order;
+        }
+
+        let mut visited = vec![false; self.len()];
+        let mut stack = vec![(self.entry, 0)];
+        visited[self.entry] = true;
+
+        while let Some((block, next)) = stack.last_mut() {
+            if let Some(succ) = self.successors[*block].get(*next).copied() {
+                *next += 1;
+                if !visited[succ] {
+                    visited[succ] = true;
+                    stack.push((succ, 0));
+                }
+            } else {
+                order.push(*block);
+                stack.pop();
+            }
+        }
+
+        order.reverse();
+        order
+    }
+
+    pub fn dominators(&self) -> Dominators {
+        Dominators::new(self)
+    }
+
+    pub fn loops(&self) -> Loops {
+        Loops::new(self, &self.dominators())
+    }
+
+    pub fn solve<A>(&self, analysis: &A) -> Solution<A::Fact>
+    where
+        A: Analysis<Loc, Val, Var>,
+    {
+        Solution::new(self, analysis)
+    }
+}
+
+impl<'a> BlockGraph<'a, Location, BitVec, Var> {
+    /// Adds a block formed from the statements of a sequence of lifted
+    /// instructions.
+    pub fn add_ecode_block<I>(&mut self, instructions: I) -> usize
+    where
+        I: IntoIterator<Item = &'a ECode>,
+    {
+        self.add_block(instructions.into_iter().flat_map(|insn| insn.operations()))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// The fixed point of an [`Analysis`] over a [`BlockGraph`]; facts are
+/// recorded at the start and end of each block in program order,
+/// irrespective of the direction of the analysis.
+pub struct Solution<F> {
+    direction: Direction,
+    entries: Vec<F>,
+    exits: Vec<F>,
+}
+
+impl<F> Solution<F>
+where
+    F: Lattice,
+{
+    fn new<A, Loc, Val, Var>(graph: &BlockGraph<Loc, Val, Var>, analysis: &A) -> Self
+    where
+        A: Analysis<Loc, Val, Var, Fact = F>,
+    {
+        let direction = analysis.direction();
+
+        let mut entries = vec![F::bottom(); graph.len()];
+        let mut exits = vec![F::bottom(); graph.len()];
+
+        let mut worklist = (0..graph.len()).collect::<VecDeque<_>>();
+        let mut queued = vec![true; graph.len()];
+
+        while let Some(block) = worklist.pop_front() {
+            queued[block] = false;
+
+            let (input, output, sources, targets) = match direction {
+                Direction::Forward => (
+                    &mut entries,
+                    &mut exits,
+                    graph.predecessors(block),
+                    graph.successors(block),
+                ),
+                Direction::Backward => (
+                    &mut exits,
+                    &mut entries,
+                    graph.successors(block),
+                    graph.predecessors(block),
+                ),
+            };
+
+            let is_boundary = match direction {
+                Direction::Forward => block == graph.entry(),
+                Direction::Backward => sources.is_empty(),
+            };
+
+            let mut fact = if is_boundary {
+                analysis.boundary()
+            } else {
+                F::bottom()
+            };
+
+            for source in sources {
+                fact.join(&output[*source]);
+            }
+            input[block] = fact.clone();
+
+            Self::transfer_block(analysis, direction, graph, block, &mut fact, None);
+
+            if output[block] != fact {
+                output[block] = fact;
+                for target in targets {
+                    if !queued[*target] {
+                        queued[*target] = true;
+                        worklist.push_back(*target);
+                    }
+                }
+            }
+        }
+
+        Self {
+            direction,
+            entries,
+            exits,
+        }
+    }
+
+    fn transfer_block<A, Loc, Val, Var>(
+        analysis: &A,
+        direction: Direction,
+        graph: &BlockGraph<Loc, Val, Var>,
+        block: usize,
+        fact: &mut F,
+        until: Option<usize>,
+    ) where
+        A: Analysis<Loc, Val, Var, Fact = F>,
+    {
+        let statements = graph.statements(block);
+        match direction {
+            Direction::Forward => {
+                let end = until.unwrap_or(statements.len());
+                for (index, stmt) in statements[..end].iter().enumerate() {
+                    analysis.transfer(ProgramPoint::new(block, index), stmt, fact);
+                }
+            }
+            Direction::Backward => {
+                let start = until.unwrap_or(0);
+                for (index, stmt) in statements.iter().enumerate().skip(start).rev() {
+                    analysis.transfer(ProgramPoint::new(block, index), stmt, fact);
+                }
+            }
+        }
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// The fact holding at the start of `block`.
+    pub fn entry(&self, block: usize) -> &F {
+        &self.entries[block]
+    }
+
+    /// The fact holding at the end of `block`.
+    pub fn exit(&self, block: usize) -> &F {
+        &self.exits[block]
+    }
+
+    /// The fact holding immediately before the statement at `point`.
+    pub fn before<A, Loc, Val, Var>(
+        &self,
+        graph: &BlockGraph<Loc, Val, Var>,
+        analysis: &A,
+        point: ProgramPoint,
+    ) -> F
+    where
+        A: Analysis<Loc, Val, Var, Fact = F>,
+    {
+        let mut fact = match self.direction {
+            Direction::Forward => self.entries[point.block].clone(),
+            Direction::Backward => self.exits[point.block].clone(),
+        };
+        Self::transfer_block(
+            analysis,
+            self.direction,
+            graph,
+            point.block,
+            &mut fact,
+            Some(point.index),
+        );
+        fact
+    }
+
+    /// The fact holding immediately after the statement at `point`.
+    pub fn after<A, Loc, Val, Var>(
+        &self,
+        graph: &BlockGraph<Loc, Val, Var>,
+        analysis: &A,
+        point: ProgramPoint,
+    ) -> F
+    where
+        A: Analysis<Loc, Val, Var, Fact = F>,
+    {
+        let next = ProgramPoint::new(point.block, point.index + 1);
+        self.before(graph, analysis, next)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Definition<Var> {
+    pub variable: Var,
+    pub point: ProgramPoint,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+/// Forward analysis computing the assignments that may reach each point.
+/// Variables are compared for equality, so partially overlapping
+/// assignments (e.g., to a register and one of its sub-registers) do not
+/// kill one another.
+pub struct ReachingDefinitions;
+
+impl<Loc, Val, Var> Analysis<Loc, Val, Var> for ReachingDefinitions
+where
+    Var: Clone + Ord,
+{
+    type Fact = BTreeSet<Definition<Var>>;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        BTreeSet::new()
+    }
+
+    fn transfer(&self, point: ProgramPoint, stmt: &StmtT<Loc, Val, Var>, fact: &mut Self::Fact) {
+        if let StmtT::Assign(variable, _) = stmt {
+            fact.retain(|d| d.variable != *variable);
+            fact.insert(Definition {
+                variable: variable.clone(),
+                point,
+            });
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+/// Backward analysis computing the variables that may be read before
+/// being reassigned.
+pub struct LiveVariables;
+
+impl<Loc, Val, Var> Analysis<Loc, Val, Var> for LiveVariables
+where
+    Var: Clone + Ord,
+{
+    type Fact = BTreeSet<Var>;
+
+    fn direction(&self) -> Direction {
+        Direction::Backward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        BTreeSet::new()
+    }
+
+    fn transfer(&self, _point: ProgramPoint, stmt: &StmtT<Loc, Val, Var>, fact: &mut Self::Fact) {
+        if let StmtT::Assign(variable, _) = stmt {
+            fact.remove(variable);
+        }
+        stmt_uses(stmt, &mut |var| {
+            fact.insert(var.clone());
+        });
+    }
+}
+
+struct Uses<'f, F>(&'f mut F);
+
+impl<'a, 'f, Loc: 'a, Val: 'a, Var: 'a, F> ExprVisitor<'a, Loc, Val, Var> for Uses<'f, F>
+where
+    F: FnMut(&Var),
+{
+    fn visit_var(&mut self, var: &'a Var) {
+        (self.0)(var)
+    }
+}
+
+/// Calls `f` on each variable read by `stmt`.
+pub fn stmt_uses<Loc, Val, Var, F>(stmt: &StmtT<Loc, Val, Var>, f: &mut F)
+where
+    F: FnMut(&Var),
+{
+    Uses(f).visit_stmt(stmt)
+}
+
+/// Calls `f` on each variable read by `expr`.
+pub fn expr_uses<Loc, Val, Var, F>(expr: &ExprT<Loc, Val, Var>, f: &mut F)
+where
+    F: FnMut(&Var),
+{
+    Uses(f).visit_expr(expr)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::il::ecode::BranchTargetT;
+
+    type Stmt = StmtT<(), u64, &'static str>;
+    type Expr = ExprT<(), u64, &'static str>;
+
+    fn var(name: &'static str) -> Expr {
+        ExprT::Var(name)
+    }
+
+    #[test]
+    fn test_reaching_definitions() {
+        // 0: x = 1; y = 2
+        // 1: x = y      (loop: 1 -> 1, 1 -> 2)
+        // 2: z = x
+        let b0 = [
+            Stmt::Assign("x", ExprT::Val(1)),
+            Stmt::Assign("y", ExprT::Val(2)),
+        ];
+        let b1 = [Stmt::Assign("x", var("y"))];
+        let b2 = [Stmt::Assign("z", var("x"))];
+
+        let mut graph = BlockGraph::new();
+        let n0 = graph.add_block(&b0);
+        let n1 = graph.add_block(&b1);
+        let n2 = graph.add_block(&b2);
+        graph.add_edge(n0, n1);
+        graph.add_edge(n1, n1);
+        graph.add_edge(n1, n2);
+
+        let solution = graph.solve(&ReachingDefinitions);
+
+        let reaching = solution
+            .entry(n1)
+            .iter()
+            .map(|d| (d.variable, d.point))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reaching,
+            vec![
+                ("x", ProgramPoint::new(0, 0)),
+                ("x", ProgramPoint::new(1, 0)),
+                ("y", ProgramPoint::new(0, 1)),
+            ]
+        );
+
+        let reaching = solution
+            .entry(n2)
+            .iter()
+            .map(|d| d.point)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reaching,
+            vec![ProgramPoint::new(1, 0), ProgramPoint::new(0, 1)]
+        );
+
+        let before = solution.before(&graph, &ReachingDefinitions, ProgramPoint::new(n0, 1));
+        assert_eq!(before.len(), 1);
+    }
+
+    #[test]
+    fn test_live_variables() {
+        // 0: a = 1; b = a
+        // 1: c = b + a
+        // 2: return c
+        let b0 = [
+            Stmt::Assign("a", ExprT::Val(1)),
+            Stmt::Assign("b", var("a")),
+        ];
+        let b1 = [Stmt::Assign(
+            "c",
+            ExprT::BinOp(
+                crate::il::ecode::BinOp::ADD,
+                Box::new(var("b")),
+                Box::new(var("a")),
+            ),
+        )];
+        let b2 = [Stmt::Return(BranchTargetT::Computed(var("c")))];
+
+        let mut graph = BlockGraph::new();
+        let n0 = graph.add_block(&b0);
+        let n1 = graph.add_block(&b1);
+        let n2 = graph.add_block(&b2);
+        graph.add_edge(n0, n1);
+        graph.add_edge(n1, n2);
+
+        let solution = graph.solve(&LiveVariables);
+
+        assert!(solution.entry(n0).is_empty());
+        assert_eq!(
+            solution.exit(n0).iter().copied().collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            solution.entry(n2).iter().copied().collect::<Vec<_>>(),
+            vec!["c"]
+        );
+
+        let before = solution.before(&graph, &LiveVariables, ProgramPoint::new(n0, 1));
+        assert_eq!(before.into_iter().collect::<Vec<_>>(), vec!["a"]);
+    }
+}
@@ -0,0 +1,393 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::convention::{Convention, ReturnAddress};
+use crate::il::ecode::{BranchTargetT, ExprT, Location, Stmt, StmtT, Var};
+use crate::il::traits::*;
+
+use fugue_bv::BitVec;
+
+use super::{
+    stmt_uses, Analysis, BlockGraph, Direction, Lattice, ProgramPoint, StackFrame, StackSlot,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+/// The inputs and side-effects of a function inferred from its body rather
+/// than taken from debug information or an exporter.
+pub struct InferredPrototype {
+    inputs: BTreeSet<Var>,
+    stack_inputs: Vec<StackSlot>,
+    clobbered: BTreeSet<Var>,
+}
+
+impl InferredPrototype {
+    /// Infers the prototype of the function represented by `graph`. The
+    /// stack pointer and return address are taken from `convention`; the
+    /// prototypes of called functions, where known, are provided by
+    /// `callees` and are used in place of treating calls as opaque.
+    pub fn infer<'c, F>(
+        graph: &BlockGraph<Location, BitVec, Var>,
+        convention: &Convention,
+        callees: F,
+    ) -> Self
+    where
+        F: Fn(&Location) -> Option<&'c InferredPrototype>,
+    {
+        let stack_pointer = Var::from(*convention.stack_pointer().varnode());
+        let return_address = convention.return_address();
+
+        let liveness = SummaryLiveness { callees: &callees };
+        let solution = graph.solve(&liveness);
+
+        let ignored = |var: &Var| {
+            *var == stack_pointer
+                || matches!(return_address, ReturnAddress::Register { varnode, .. } if Var::from(*varnode) == *var)
+        };
+
+        let inputs = if graph.is_empty() {
+            BTreeSet::new()
+        } else {
+            solution
+                .entry(graph.entry())
+                .iter()
+                .filter(|var| var.space().is_register() && !ignored(var))
+                .copied()
+                .collect()
+        };
+
+        let frame = StackFrame::new(graph, stack_pointer);
+
+        let mut clobbered = BTreeSet::new();
+        for block in 0..graph.len() {
+            for stmt in graph.statements(block) {
+                match stmt {
+                    StmtT::Assign(var, _) if var.space().is_register() && !ignored(var) => {
+                        clobbered.insert(*var);
+                    }
+                    StmtT::Call(BranchTargetT::Location(location), _) => {
+                        if let Some(callee) = callees(location) {
+                            clobbered.extend(callee.clobbered.iter().copied());
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let preserved = preserved(graph, &frame, &callees, &clobbered);
+        clobbered.retain(|var| !preserved.contains(var));
+
+        let stack_inputs = frame
+            .arguments()
+            .filter(|slot| {
+                if let ReturnAddress::StackRelative { offset, size } = return_address {
+                    let start = *offset as i64;
+                    let end = start + *size as i64;
+                    slot.offset() >= end || slot.offset() + (slot.size() as i64) <= start
+                } else {
+                    true
+                }
+            })
+            .copied()
+            .collect();
+
+        Self {
+            inputs,
+            stack_inputs,
+            clobbered,
+        }
+    }
+
+    /// Registers read before being written.
+    pub fn inputs(&self) -> impl Iterator<Item = &Var> {
+        self.inputs.iter()
+    }
+
+    /// Stack slots at or above the entry stack pointer accessed by the
+    /// function, excluding the return address.
+    pub fn stack_inputs(&self) -> &[StackSlot] {
+        &self.stack_inputs
+    }
+
+    /// Registers written by the function or any of the known functions it
+    /// calls, excluding those whose values on entry it restores before
+    /// returning (e.g., callee-saved registers spilled to the stack).
+    pub fn clobbered(&self) -> impl Iterator<Item = &Var> {
+        self.clobbered.iter()
+    }
+
+    pub fn parameter_count(&self) -> usize {
+        self.inputs.len() + self.stack_inputs.len()
+    }
+
+    pub fn is_input(&self, var: &Var) -> bool {
+        self.inputs.contains(var)
+    }
+
+    pub fn is_clobbered(&self, var: &Var) -> bool {
+        self.clobbered.contains(var)
+    }
+}
+
+/// Live variable analysis that applies the summaries of called functions.
+struct SummaryLiveness<'f, F> {
+    callees: &'f F,
+}
+
+impl<'c, 'f, F> Analysis<Location, BitVec, Var> for SummaryLiveness<'f, F>
+where
+    F: Fn(&Location) -> Option<&'c InferredPrototype>,
+{
+    type Fact = BTreeSet<Var>;
+
+    fn direction(&self) -> Direction {
+        Direction::Backward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        BTreeSet::new()
+    }
+
+    fn transfer(&self, _point: ProgramPoint, stmt: &Stmt, fact: &mut Self::Fact) {
+        match stmt {
+            StmtT::Assign(var, _) => {
+                fact.remove(var);
+            }
+            StmtT::Call(BranchTargetT::Location(location), _) => {
+                if let Some(callee) = (self.callees)(location) {
+                    for var in callee.clobbered() {
+                        fact.remove(var);
+                    }
+                    fact.extend(callee.inputs().copied());
+                }
+            }
+            _ => (),
+        }
+        stmt_uses(stmt, &mut |var| {
+            fact.insert(*var);
+        });
+    }
+}
+
+/// The registers of `candidates` that hold their values on entry at each
+/// return from the function represented by `graph`, e.g., as they are
+/// saved to and restored from the stack. If the function does not return,
+/// or a candidate partially overlaps a register that is not restored, none
+/// are considered preserved.
+fn preserved<'c, F>(
+    graph: &BlockGraph<Location, BitVec, Var>,
+    frame: &StackFrame,
+    callees: &F,
+    candidates: &BTreeSet<Var>,
+) -> BTreeSet<Var>
+where
+    F: Fn(&Location) -> Option<&'c InferredPrototype>,
+{
+    // stack slots are accessed by loads and stores; localising them makes
+    // saves and restores copies between registers and slot variables
+    let localised = (0..graph.len())
+        .map(|block| frame.localise(graph, block))
+        .collect::<Vec<_>>();
+
+    let analysis = EntryValues {
+        localised: &localised,
+        callees,
+    };
+    let solution = graph.solve(&analysis);
+
+    let mut preserved: Option<BTreeSet<Var>> = None;
+    for (block, stmts) in localised.iter().enumerate() {
+        let mut fact = solution.entry(block).clone();
+        for (index, stmt) in stmts.iter().enumerate() {
+            if let (StmtT::Return(_), HeldValues::Reached(values)) = (stmt, &fact) {
+                let restored = candidates
+                    .iter()
+                    .filter(|var| held(values, var) == Some(**var))
+                    .copied()
+                    .collect::<BTreeSet<_>>();
+
+                preserved = Some(match preserved {
+                    Some(preserved) => &preserved & &restored,
+                    None => restored,
+                });
+            }
+            analysis.transfer(ProgramPoint::new(block, index), stmt, &mut fact);
+        }
+    }
+
+    let mut preserved = preserved.unwrap_or_default();
+
+    let overlaps = |a: &Var, b: &Var| {
+        a.space() == b.space()
+            && a.offset() < b.offset() + (b.bits() / 8) as u64
+            && b.offset() < a.offset() + (a.bits() / 8) as u64
+    };
+    let lost = candidates.difference(&preserved).copied().collect::<Vec<_>>();
+    preserved.retain(|var| !lost.iter().any(|other| overlaps(var, other)));
+
+    preserved
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The values on entry to the function held by registers and stack slots.
+enum HeldValues {
+    Unreached,
+    /// Registers absent from the map hold their own values on entry; other
+    /// variables absent from the map hold no value on entry.
+    Reached(BTreeMap<Var, Option<Var>>),
+}
+
+fn held(values: &BTreeMap<Var, Option<Var>>, var: &Var) -> Option<Var> {
+    match values.get(var) {
+        Some(value) => *value,
+        None if var.space().is_register() => Some(*var),
+        None => None,
+    }
+}
+
+impl Lattice for HeldValues {
+    fn bottom() -> Self {
+        Self::Unreached
+    }
+
+    fn join(&mut self, other: &Self) -> bool {
+        match (&mut *self, other) {
+            (_, Self::Unreached) => false,
+            (Self::Unreached, _) => {
+                *self = other.clone();
+                true
+            }
+            (Self::Reached(values), Self::Reached(others)) => {
+                let vars = values
+                    .keys()
+                    .chain(others.keys())
+                    .copied()
+                    .collect::<BTreeSet<_>>();
+
+                let mut changed = false;
+                for var in vars {
+                    let value = held(values, &var);
+                    if value.is_some() && value != held(others, &var) {
+                        values.insert(var, None);
+                        changed = true;
+                    }
+                }
+                changed
+            }
+        }
+    }
+}
+
+/// Forward analysis tracking copies of the values of registers on entry,
+/// over the function's localised statements.
+struct EntryValues<'a, 'f, F> {
+    localised: &'a [Vec<Stmt>],
+    callees: &'f F,
+}
+
+impl<'a, 'c, 'f, F> Analysis<Location, BitVec, Var> for EntryValues<'a, 'f, F>
+where
+    F: Fn(&Location) -> Option<&'c InferredPrototype>,
+{
+    type Fact = HeldValues;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        HeldValues::Reached(BTreeMap::new())
+    }
+
+    fn transfer(&self, point: ProgramPoint, _stmt: &Stmt, fact: &mut Self::Fact) {
+        let values = if let HeldValues::Reached(values) = fact {
+            values
+        } else {
+            return;
+        };
+
+        match &self.localised[point.block][point.index] {
+            StmtT::Assign(var, ExprT::Var(source)) => {
+                let value = held(values, source);
+                values.insert(*var, value);
+            }
+            StmtT::Assign(var, _) => {
+                values.insert(*var, None);
+            }
+            StmtT::Call(BranchTargetT::Location(location), _) => {
+                if let Some(callee) = (self.callees)(location) {
+                    for var in callee.clobbered() {
+                        values.insert(*var, None);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::il::ecode::{BinOp, Expr};
+    use crate::space::AddressSpaceId;
+
+    #[test]
+    fn test_saved_registers_are_preserved() {
+        let sp = Var::new(AddressSpaceId::register_id(1), 0x20, 64, 0);
+        let rax = Var::new(AddressSpaceId::register_id(1), 0x0, 64, 0);
+        let rbx = Var::new(AddressSpaceId::register_id(1), 0x18, 64, 0);
+        let ebx = Var::new(AddressSpaceId::register_id(1), 0x18, 32, 0);
+        let ram = AddressSpaceId::default_id(2);
+
+        let adjust = |op, n: u64| {
+            Stmt::Assign(
+                sp,
+                ExprT::BinOp(
+                    op,
+                    Box::new(ExprT::Var(sp)),
+                    Box::new(ExprT::Val(BitVec::from_u64(n, 64))),
+                ),
+            )
+        };
+        let top = || Expr::Load(Box::new(ExprT::Var(sp)), 64, ram);
+
+        // push rbx; mov rbx, 5; mov rax, rbx; pop rbx; ret
+        let body = |restore: bool| {
+            let mut stmts = vec![
+                adjust(BinOp::SUB, 8),
+                Stmt::Store(ExprT::Var(sp), ExprT::Var(rbx), 64, ram),
+                Stmt::Assign(rbx, ExprT::Val(BitVec::from_u64(5, 64))),
+                Stmt::Assign(rax, ExprT::Var(rbx)),
+            ];
+            if restore {
+                stmts.push(Stmt::Assign(rbx, top()));
+            }
+            stmts.push(adjust(BinOp::ADD, 8));
+            stmts.push(Stmt::Return(BranchTargetT::Computed(top())));
+            stmts
+        };
+
+        let preserved_by = |stmts: &[Stmt], candidates: &[Var]| {
+            let mut graph = BlockGraph::new();
+            graph.add_block(stmts);
+
+            let frame = StackFrame::new(&graph, sp);
+            let candidates = candidates.iter().copied().collect();
+            preserved(&graph, &frame, &|_: &Location| None, &candidates)
+        };
+
+        let restored = body(true);
+        assert_eq!(
+            preserved_by(&restored, &[rax, rbx]),
+            BTreeSet::from([rbx])
+        );
+
+        let clobbered = body(false);
+        assert!(preserved_by(&clobbered, &[rax, rbx]).is_empty());
+
+        // writing part of a restored register clobbers it
+        let mut partial = body(true);
+        partial.insert(5, Stmt::Assign(ebx, ExprT::Val(BitVec::from_u64(1, 32))));
+        assert!(preserved_by(&partial, &[rax, rbx, ebx]).is_empty());
+    }
+}
@@ -0,0 +1,432 @@
+use std::collections::BTreeMap;
+
+use crate::il::ecode::visit::{rewrite_expr_children, walk_expr, walk_stmt};
+use crate::il::ecode::{
+    BinOp, Cast, Expr, ExprRewriter, ExprT, ExprVisitor, Location, Stmt, StmtT, Var,
+};
+use crate::space::AddressSpaceId;
+
+use fugue_bv::BitVec;
+
+use super::{Analysis, BlockGraph, Direction, Lattice, ProgramPoint, Solution};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Variables known to hold the value of the stack pointer on entry to the
+/// function plus a fixed displacement.
+pub enum StackOffsets {
+    Unreached,
+    Reached(BTreeMap<Var, i64>),
+}
+
+impl StackOffsets {
+    pub fn offset(&self, var: &Var) -> Option<i64> {
+        if let Self::Reached(offsets) = self {
+            offsets.get(var).copied()
+        } else {
+            None
+        }
+    }
+}
+
+impl Lattice for StackOffsets {
+    fn bottom() -> Self {
+        Self::Unreached
+    }
+
+    fn join(&mut self, other: &Self) -> bool {
+        match (&mut *self, other) {
+            (_, Self::Unreached) => false,
+            (Self::Unreached, _) => {
+                *self = other.clone();
+                true
+            }
+            (Self::Reached(offsets), Self::Reached(others)) => {
+                let len = offsets.len();
+                offsets.retain(|var, offset| others.get(var) == Some(offset));
+                offsets.len() != len
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Value {
+    Stack(i64),
+    Constant(i64),
+}
+
+fn evaluate(expr: &Expr, offsets: &StackOffsets) -> Option<Value> {
+    match expr {
+        ExprT::Var(var) => offsets.offset(var).map(Value::Stack),
+        ExprT::Val(bv) => {
+            let bits = bv.bits();
+            let value = bv.to_u64()?;
+            let value = if bits == 0 || bits >= 64 {
+                value as i64
+            } else {
+                ((value << (64 - bits)) as i64) >> (64 - bits)
+            };
+            Some(Value::Constant(value))
+        }
+        ExprT::Cast(expr, Cast::Signed(_) | Cast::Unsigned(_) | Cast::Pointer(_, _)) => {
+            evaluate(expr, offsets)
+        }
+        ExprT::BinOp(BinOp::ADD, lhs, rhs) => {
+            match (evaluate(lhs, offsets)?, evaluate(rhs, offsets)?) {
+                (Value::Stack(s), Value::Constant(c)) | (Value::Constant(c), Value::Stack(s)) => {
+                    Some(Value::Stack(s.wrapping_add(c)))
+                }
+                (Value::Constant(c1), Value::Constant(c2)) => {
+                    Some(Value::Constant(c1.wrapping_add(c2)))
+                }
+                _ => None,
+            }
+        }
+        ExprT::BinOp(BinOp::SUB, lhs, rhs) => {
+            match (evaluate(lhs, offsets)?, evaluate(rhs, offsets)?) {
+                (Value::Stack(s), Value::Constant(c)) => Some(Value::Stack(s.wrapping_sub(c))),
+                (Value::Stack(s1), Value::Stack(s2)) => Some(Value::Constant(s1.wrapping_sub(s2))),
+                (Value::Constant(c1), Value::Constant(c2)) => {
+                    Some(Value::Constant(c1.wrapping_sub(c2)))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn stack_offset(expr: &Expr, offsets: &StackOffsets) -> Option<i64> {
+    if let Some(Value::Stack(offset)) = evaluate(expr, offsets) {
+        Some(offset)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Forward analysis tracking the displacement of the stack pointer (and
+/// values derived from it) relative to its value on function entry.
+pub struct StackDelta {
+    stack_pointer: Var,
+}
+
+impl StackDelta {
+    pub fn new(stack_pointer: Var) -> Self {
+        Self { stack_pointer }
+    }
+
+    pub fn stack_pointer(&self) -> &Var {
+        &self.stack_pointer
+    }
+}
+
+impl Analysis<Location, BitVec, Var> for StackDelta {
+    type Fact = StackOffsets;
+
+    fn direction(&self) -> Direction {
+        Direction::Forward
+    }
+
+    fn boundary(&self) -> Self::Fact {
+        StackOffsets::Reached(BTreeMap::from([(self.stack_pointer, 0)]))
+    }
+
+    fn transfer(&self, _point: ProgramPoint, stmt: &Stmt, fact: &mut Self::Fact) {
+        if let StmtT::Assign(var, expr) = stmt {
+            let offset = stack_offset(expr, fact);
+            if let StackOffsets::Reached(offsets) = fact {
+                if let Some(offset) = offset {
+                    offsets.insert(*var, offset);
+                } else {
+                    offsets.remove(var);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A stack location accessed via a load or store; offsets are relative to
+/// the stack pointer on function entry.
+pub struct StackSlot {
+    offset: i64,
+    size: usize,
+    space: AddressSpaceId,
+}
+
+impl StackSlot {
+    pub fn offset(&self) -> i64 {
+        self.offset
+    }
+
+    /// The size of the largest access to the slot in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.offset < 0
+    }
+
+    pub fn is_argument(&self) -> bool {
+        self.offset >= 0
+    }
+
+    /// Returns `local_N` for slots below the entry stack pointer and
+    /// `arg_N` otherwise, where `N` is the hexadecimal distance from it.
+    pub fn name(&self) -> String {
+        if self.is_local() {
+            format!("local_{:x}", self.offset.unsigned_abs())
+        } else {
+            format!("arg_{:x}", self.offset)
+        }
+    }
+
+    /// A frame-relative variable standing for the slot; its space is the
+    /// accessed space marked with the stack hint.
+    pub fn var(&self) -> Var {
+        Var::new(self.space.stack(), self.offset as u64, self.size * 8, 0)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Stack pointer displacements and stack slots recovered for a function.
+pub struct StackFrame {
+    analysis: StackDelta,
+    solution: Solution<StackOffsets>,
+    frame_size: u64,
+    slots: BTreeMap<i64, StackSlot>,
+}
+
+impl StackFrame {
+    pub fn new(graph: &BlockGraph<Location, BitVec, Var>, stack_pointer: Var) -> Self {
+        let analysis = StackDelta::new(stack_pointer);
+        let solution = graph.solve(&analysis);
+
+        let mut lowest = 0i64;
+        let mut slots = BTreeMap::<i64, StackSlot>::new();
+
+        for block in 0..graph.len() {
+            let mut fact = solution.entry(block).clone();
+            for (index, stmt) in graph.statements(block).iter().enumerate() {
+                if let Some(offset) = fact.offset(&stack_pointer) {
+                    lowest = lowest.min(offset);
+                }
+
+                stmt_accesses(stmt, &mut |address, bits, space| {
+                    if let Some(offset) = stack_offset(address, &fact) {
+                        let size = bits / 8;
+                        let slot = slots.entry(offset).or_insert(StackSlot {
+                            offset,
+                            size,
+                            space,
+                        });
+                        slot.size = slot.size.max(size);
+                    }
+                });
+
+                analysis.transfer(ProgramPoint::new(block, index), stmt, &mut fact);
+            }
+
+            if let Some(offset) = fact.offset(&stack_pointer) {
+                lowest = lowest.min(offset);
+            }
+        }
+
+        Self {
+            analysis,
+            solution,
+            frame_size: lowest.unsigned_abs(),
+            slots,
+        }
+    }
+
+    pub fn stack_pointer(&self) -> &Var {
+        self.analysis.stack_pointer()
+    }
+
+    /// The greatest displacement of the stack pointer below its value on
+    /// entry.
+    pub fn frame_size(&self) -> u64 {
+        self.frame_size
+    }
+
+    /// The displacement of the stack pointer immediately before the
+    /// statement at `point`, if it is known.
+    pub fn offset_before(
+        &self,
+        graph: &BlockGraph<Location, BitVec, Var>,
+        point: ProgramPoint,
+    ) -> Option<i64> {
+        self.solution
+            .before(graph, &self.analysis, point)
+            .offset(self.stack_pointer())
+    }
+
+    pub fn slot(&self, offset: i64) -> Option<&StackSlot> {
+        self.slots.get(&offset)
+    }
+
+    pub fn slots(&self) -> impl Iterator<Item = &StackSlot> {
+        self.slots.values()
+    }
+
+    pub fn locals(&self) -> impl Iterator<Item = &StackSlot> {
+        self.slots().filter(|s| s.is_local())
+    }
+
+    pub fn arguments(&self) -> impl Iterator<Item = &StackSlot> {
+        self.slots().filter(|s| s.is_argument())
+    }
+
+    /// Returns the statements of `block` with loads and stores of recovered
+    /// stack slots replaced by reads and assignments of their frame-relative
+    /// variables.
+    pub fn localise(&self, graph: &BlockGraph<Location, BitVec, Var>, block: usize) -> Vec<Stmt> {
+        let mut fact = self.solution.entry(block).clone();
+        let mut localised = Vec::with_capacity(graph.statements(block).len());
+
+        for (index, stmt) in graph.statements(block).iter().enumerate() {
+            let mut nstmt = (*stmt).clone();
+
+            if let StmtT::Store(address, value, size, _) = &nstmt {
+                if let Some(slot) = self.localised_slot(address, *size, &fact) {
+                    nstmt = StmtT::Assign(slot.var(), value.clone());
+                }
+            }
+
+            let mut localiser = Localiser {
+                frame: self,
+                fact: &fact,
+            };
+            localised.push(localiser.rewrite_stmt(nstmt));
+
+            self.analysis
+                .transfer(ProgramPoint::new(block, index), stmt, &mut fact);
+        }
+
+        localised
+    }
+
+    fn localised_slot(
+        &self,
+        address: &Expr,
+        bits: usize,
+        fact: &StackOffsets,
+    ) -> Option<StackSlot> {
+        let slot = self.slots.get(&stack_offset(address, fact)?)?;
+        if slot.size * 8 == bits {
+            Some(*slot)
+        } else {
+            None
+        }
+    }
+}
+
+/// Replaces loads of recovered stack slots by their frame-relative
+/// variables.
+struct Localiser<'a> {
+    frame: &'a StackFrame,
+    fact: &'a StackOffsets,
+}
+
+impl<'a> ExprRewriter<Location, BitVec, Var> for Localiser<'a> {
+    fn rewrite_expr(&mut self, expr: Expr) -> Expr {
+        if let ExprT::Load(address, size, _) = &expr {
+            if let Some(slot) = self.frame.localised_slot(address, *size, self.fact) {
+                return ExprT::Var(slot.var());
+            }
+        }
+        rewrite_expr_children(self, expr)
+    }
+}
+
+struct Accesses<'f, F>(&'f mut F);
+
+impl<'a, 'f, F> ExprVisitor<'a, Location, BitVec, Var> for Accesses<'f, F>
+where
+    F: FnMut(&Expr, usize, AddressSpaceId),
+{
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let StmtT::Store(address, _, size, space) = stmt {
+            (self.0)(address, *size, *space);
+        }
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let ExprT::Load(address, size, space) = expr {
+            (self.0)(address, *size, *space);
+        }
+        walk_expr(self, expr)
+    }
+}
+
+fn stmt_accesses<F>(stmt: &Stmt, f: &mut F)
+where
+    F: FnMut(&Expr, usize, AddressSpaceId),
+{
+    Accesses(f).visit_stmt(stmt)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frame_and_locals() {
+        let sp = Var::new(AddressSpaceId::register_id(1), 0x20, 64, 0);
+        let rax = Var::new(AddressSpaceId::register_id(1), 0x0, 64, 0);
+        let ram = AddressSpaceId::default_id(2);
+
+        let sub = |n: u64| {
+            ExprT::BinOp(
+                BinOp::SUB,
+                Box::new(ExprT::Var(sp)),
+                Box::new(ExprT::Val(BitVec::from_u64(n, 64))),
+            )
+        };
+        let add = |n: u64| {
+            ExprT::BinOp(
+                BinOp::ADD,
+                Box::new(ExprT::Var(sp)),
+                Box::new(ExprT::Val(BitVec::from_u64(n, 64))),
+            )
+        };
+
+        // push rax; sub rsp, 0x10; mov [rsp+8], rax; mov rax, [rsp+0x18]
+        let stmts = [
+            Stmt::Assign(sp, sub(8)),
+            Stmt::Store(ExprT::Var(sp), ExprT::Var(rax), 64, ram),
+            Stmt::Assign(sp, sub(0x10)),
+            Stmt::Store(add(8), ExprT::Var(rax), 64, ram),
+            Stmt::Assign(rax, ExprT::Load(Box::new(add(0x18)), 64, ram)),
+        ];
+
+        let mut graph = BlockGraph::new();
+        let block = graph.add_block(&stmts);
+
+        let frame = StackFrame::new(&graph, sp);
+
+        assert_eq!(frame.frame_size(), 0x18);
+        assert_eq!(
+            frame.offset_before(&graph, ProgramPoint::new(block, 3)),
+            Some(-0x18)
+        );
+        assert_eq!(
+            frame.slots().map(StackSlot::name).collect::<Vec<_>>(),
+            vec!["local_10", "local_8", "arg_0"]
+        );
+
+        let localised = frame.localise(&graph, block);
+        assert_eq!(
+            localised[3],
+            Stmt::Assign(frame.slot(-0x10).unwrap().var(), ExprT::Var(rax))
+        );
+        assert_eq!(
+            localised[4],
+            Stmt::Assign(rax, ExprT::Var(frame.slot(0).unwrap().var()))
+        );
+    }
+}
@@ -0,0 +1,292 @@
+//! Construction of ECode from hand-written semantics, e.g., to replace
+//! those of a hooked function or to give semantics to a custom
+//! instruction.
+//!
+//! Statements are built using the smart constructors of [`ExprT`] and
+//! [`StmtT`], which insert the casts required to keep operand sizes
+//! consistent; the builder additionally allocates temporaries and resolves
+//! branches to labels within the instruction:
+//!
+//! ```ignore
+//! let mut builder = ECodeBuilder::new(address, manager.unique_space_id());
+//!
+//! let done = builder.label();
+//! let t = builder.temporary(32);
+//!
+//! builder
+//!     .assign(t, Expr::int_add(eax, ecx))
+//!     .cbranch(Expr::int_eq(t, BitVec::zero(32)), done)
+//!     .assign(eax, t)
+//!     .bind(done)
+//!     .ret(Expr::load(esp, 32, &ram), &ram);
+//!
+//! let ecode = builder.build(0)?;
+//! ```
+
+use smallvec::SmallVec;
+use ustr::Ustr;
+
+use crate::address::AddressValue;
+use crate::il::traits::*;
+use crate::space::{AddressSpace, AddressSpaceId};
+
+use super::{BranchTarget, BranchTargetT, ECode, Expr, Location, Stmt, StmtT, Var};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BuildError {
+    #[error("label {0} is branched to but never bound")]
+    UnboundLabel(usize),
+    #[error("label {0} is bound more than once")]
+    Rebound(usize),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A position within the instruction being built, which may be branched
+/// to before it is bound.
+pub struct Label(usize);
+
+pub struct ECodeBuilder {
+    address: AddressValue,
+    operations: SmallVec<[Stmt; 8]>,
+    labels: Vec<Option<usize>>,
+    branches: Vec<(usize, Label)>,
+    unique_space: AddressSpaceId,
+    unique_offset: u64,
+    delay_slots: usize,
+    error: Option<BuildError>,
+}
+
+impl ECodeBuilder {
+    /// Creates a builder for the semantics of the instruction at
+    /// `address`, allocating temporaries within `unique_space`.
+    pub fn new<S: Into<AddressSpaceId>>(address: AddressValue, unique_space: S) -> Self {
+        Self {
+            address,
+            operations: SmallVec::new(),
+            labels: Vec::new(),
+            branches: Vec::new(),
+            unique_space: unique_space.into(),
+            unique_offset: 0,
+            delay_slots: 0,
+            error: None,
+        }
+    }
+
+    /// The offset from which temporaries are allocated; by default, zero.
+    pub fn unique_offset(self, unique_offset: u64) -> Self {
+        Self {
+            unique_offset,
+            ..self
+        }
+    }
+
+    pub fn delay_slots(self, delay_slots: usize) -> Self {
+        Self {
+            delay_slots,
+            ..self
+        }
+    }
+
+    /// Allocates a fresh temporary of `bits` bits.
+    pub fn temporary(&mut self, bits: usize) -> Var {
+        let var = Var::new(self.unique_space, self.unique_offset, bits, 0);
+        self.unique_offset += bits.div_ceil(8) as u64;
+        var
+    }
+
+    /// Creates a label that is not yet bound to a position.
+    pub fn label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    /// Binds `label` to the position of the next statement.
+    pub fn bind(&mut self, label: Label) -> &mut Self {
+        let position = &mut self.labels[label.0];
+        if position.is_some() {
+            self.error.get_or_insert(BuildError::Rebound(label.0));
+        } else {
+            *position = Some(self.operations.len());
+        }
+        self
+    }
+
+    pub fn push(&mut self, stmt: Stmt) -> &mut Self {
+        self.operations.push(stmt);
+        self
+    }
+
+    pub fn assign<D, S>(&mut self, destination: D, source: S) -> &mut Self
+    where
+        D: Into<Var>,
+        S: Into<Expr>,
+    {
+        self.push(StmtT::assign(destination, source))
+    }
+
+    /// Stores `source` to `destination` within `space`; the size of the
+    /// store is that of `source`.
+    pub fn store<D, S>(&mut self, destination: D, source: S, space: &AddressSpace) -> &mut Self
+    where
+        D: Into<Expr>,
+        S: Into<Expr>,
+    {
+        let source = source.into();
+        let size = source.bits();
+        self.push(StmtT::store(destination, source, size, space))
+    }
+
+    /// Branches to `label` within the instruction.
+    pub fn branch(&mut self, label: Label) -> &mut Self {
+        let target = self.label_target(label);
+        self.push(StmtT::branch(target))
+    }
+
+    /// Branches to `label` within the instruction if `condition` holds.
+    pub fn cbranch<C>(&mut self, condition: C, label: Label) -> &mut Self
+    where
+        C: Into<Expr>,
+    {
+        let target = self.label_target(label);
+        self.push(StmtT::branch_conditional(condition, target))
+    }
+
+    /// Branches to the instruction at `address`.
+    pub fn jump(&mut self, address: AddressValue) -> &mut Self {
+        self.push(StmtT::branch(Location::new(address, 0)))
+    }
+
+    /// Branches to the address computed by `target` within `space`.
+    pub fn jump_indirect<T>(&mut self, target: T, space: &AddressSpace) -> &mut Self
+    where
+        T: Into<Expr>,
+    {
+        self.push(StmtT::branch_indirect(target, space))
+    }
+
+    pub fn call<T, I, E>(&mut self, target: T, arguments: I) -> &mut Self
+    where
+        T: Into<BranchTarget>,
+        I: IntoIterator<Item = E>,
+        E: Into<Expr>,
+    {
+        let arguments = arguments.into_iter().map(E::into).collect();
+        self.push(StmtT::Call(target.into(), arguments))
+    }
+
+    pub fn ret<T>(&mut self, target: T, space: &AddressSpace) -> &mut Self
+    where
+        T: Into<Expr>,
+    {
+        self.push(StmtT::return_(target, space))
+    }
+
+    pub fn intrinsic<N, I, E>(&mut self, name: N, arguments: I) -> &mut Self
+    where
+        N: Into<Ustr>,
+        I: IntoIterator<Item = E>,
+        E: Into<Expr>,
+    {
+        let arguments = arguments.into_iter().map(E::into).collect();
+        self.push(StmtT::Intrinsic(name.into(), arguments))
+    }
+
+    /// Builds the ECode of an instruction of `length` bytes, resolving
+    /// branches to labels.
+    pub fn build(mut self, length: usize) -> Result<ECode, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        for (index, label) in self.branches.iter() {
+            let position = self.labels[label.0].ok_or(BuildError::UnboundLabel(label.0))?;
+            if let StmtT::Branch(BranchTargetT::Location(location))
+            | StmtT::CBranch(_, BranchTargetT::Location(location)) = &mut self.operations[*index]
+            {
+                *location = Location::new(self.address, position);
+            }
+        }
+
+        // labels bound at the end of the instruction need a statement to
+        // refer to
+        if self.operations.is_empty()
+            || self
+                .labels
+                .iter()
+                .any(|position| *position == Some(self.operations.len()))
+        {
+            self.operations.push(StmtT::skip());
+        }
+
+        Ok(ECode {
+            address: self.address,
+            operations: self.operations,
+            delay_slots: self.delay_slots,
+            length,
+        })
+    }
+
+    fn label_target(&mut self, label: Label) -> Location {
+        self.branches.push((self.operations.len(), label));
+        Location::new(self.address, usize::MAX)
+    }
+}
+
+impl ECode {
+    /// Creates a builder for the semantics of the instruction at `address`;
+    /// see [`crate::il::ecode::builder`].
+    pub fn builder<S: Into<AddressSpaceId>>(
+        address: AddressValue,
+        unique_space: S,
+    ) -> ECodeBuilder {
+        ECodeBuilder::new(address, unique_space)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::space::{Space, SpaceKind};
+    use fugue_bv::BitVec;
+
+    #[test]
+    fn test_builder() -> Result<(), BuildError> {
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 4, 1, 1, None, 0));
+        let address = AddressValue::new(&ram, 0x1000);
+
+        let eax = Var::new(AddressSpaceId::register_id(2), 0, 32, 0);
+        let al = Var::new(AddressSpaceId::register_id(2), 0, 8, 0);
+
+        let mut builder = ECode::builder(address, AddressSpaceId::unique_id(3));
+
+        let done = builder.label();
+        let t = builder.temporary(32);
+
+        builder
+            .assign(t, Expr::int_add(eax, al))
+            .cbranch(Expr::int_eq(t, BitVec::zero(32)), done)
+            .store(eax, al, &ram)
+            .bind(done);
+
+        let ecode = builder.build(2)?;
+
+        assert_eq!(ecode.operations().len(), 4);
+        assert_eq!(
+            ecode.operations()[1],
+            StmtT::branch_conditional(
+                Expr::int_eq(t, BitVec::zero(32)),
+                Location::new(address, 3)
+            )
+        );
+        assert_eq!(ecode.operations()[3], StmtT::skip());
+        assert_eq!(ecode.validate(), Ok(()));
+
+        let mut builder = ECode::builder(address, AddressSpaceId::unique_id(3));
+        let missing = builder.label();
+        builder.branch(missing);
+
+        assert_eq!(builder.build(2).err(), Some(BuildError::UnboundLabel(0)));
+
+        Ok(())
+    }
+}
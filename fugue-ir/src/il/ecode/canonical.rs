@@ -0,0 +1,358 @@
+//! Canonicalisation of ECode into a normal form, so that structurally
+//! comparing IL produced by different builds or tools is reliable.
+//!
+//! Expressions are rewritten bottom-up. In normal form:
+//!
+//! - the operands of commutative integer operations and relations (`AND`,
+//!   `OR`, `XOR`, `ADD`, `MUL`, `EQ`, `NEQ`, `CARRY`, `SCARRY`) are
+//!   ordered, with constants on the right and all other operands in
+//!   ascending order (per `Ord`);
+//! - chains of integer casts that are equivalent to a single cast are
+//!   replaced by that cast, and unsigned casts of uncast expressions to
+//!   their own size are removed;
+//! - integer identities are applied: `a - a` and `a ^ a` become zero, `a &
+//!   a` and `a | a` become `a`, operations with an identity element on
+//!   their right-hand side (e.g., `a + 0`, `a * 1`, `a & -1`) become `a`,
+//!   relations between identical operands become constants, and double
+//!   negation (`--a`, `!!a`) becomes `a`.
+//!
+//! Operations on floating-point operands are never reordered or
+//! simplified, and identities that would drop an expression are only
+//! applied when the expression has no loads, calls, or intrinsics.
+
+use fugue_bv::BitVec;
+
+use crate::il::traits::BitSize;
+
+use super::visit::{rewrite_expr_children, try_walk_expr};
+use super::{
+    BinOp, BinRel, BranchTarget, Cast, ECode, Expr, ExprRewriter, ExprT, Location, Stmt,
+    TryExprVisitor, UnOp, Var,
+};
+
+/// Rewrites ECode into normal form; each class of rewrite can be disabled
+/// independently.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Canonicaliser {
+    order_operands: bool,
+    fold_casts: bool,
+    simplify: bool,
+}
+
+impl Default for Canonicaliser {
+    fn default() -> Self {
+        Self {
+            order_operands: true,
+            fold_casts: true,
+            simplify: true,
+        }
+    }
+}
+
+impl Canonicaliser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Order the operands of commutative operations.
+    pub fn order_operands(self, enabled: bool) -> Self {
+        Self {
+            order_operands: enabled,
+            ..self
+        }
+    }
+
+    /// Replace chains of casts by equivalent single casts.
+    pub fn fold_casts(self, enabled: bool) -> Self {
+        Self {
+            fold_casts: enabled,
+            ..self
+        }
+    }
+
+    /// Apply integer identities.
+    pub fn simplify(self, enabled: bool) -> Self {
+        Self {
+            simplify: enabled,
+            ..self
+        }
+    }
+
+    pub fn ecode(&self, ecode: &mut ECode) {
+        let mut canonicaliser = *self;
+        ecode.rewrite(&mut canonicaliser)
+    }
+
+    pub fn stmt(&self, stmt: Stmt) -> Stmt {
+        let mut canonicaliser = *self;
+        canonicaliser.rewrite_stmt(stmt)
+    }
+
+    pub fn branch_target(&self, target: BranchTarget) -> BranchTarget {
+        let mut canonicaliser = *self;
+        canonicaliser.rewrite_branch_target(target)
+    }
+
+    pub fn expr(&self, expr: Expr) -> Expr {
+        let mut canonicaliser = *self;
+        canonicaliser.rewrite_expr(expr)
+    }
+
+    fn cast(&self, expr: Expr, cast: Cast) -> Expr {
+        if !self.fold_casts {
+            return ExprT::Cast(Box::new(expr), cast);
+        }
+
+        match expr {
+            ExprT::Cast(inner, inner_cast) if folds_into(&inner, &inner_cast, &cast) => {
+                self.cast(*inner, cast)
+            }
+            expr if matches!(cast, Cast::Unsigned(bits) if bits == expr.bits())
+                && !matches!(expr, ExprT::Cast(_, _)) =>
+            {
+                expr
+            }
+            expr => ExprT::Cast(Box::new(expr), cast),
+        }
+    }
+
+    fn unary_op(&self, op: UnOp, expr: Box<Expr>) -> Expr {
+        if self.simplify && matches!(op, UnOp::NOT | UnOp::NEG) {
+            if let ExprT::UnOp(inner, e) = &*expr {
+                if *inner == op {
+                    return (**e).clone();
+                }
+            }
+
+            // integer negation casts its operand to signed
+            if let ExprT::Cast(e, Cast::Signed(bits)) = &*expr {
+                if let ExprT::UnOp(UnOp::NEG, n) = &**e {
+                    if op == UnOp::NEG && n.is_signed_bits(*bits) {
+                        return (**n).clone();
+                    }
+                }
+            }
+        }
+        ExprT::UnOp(op, expr)
+    }
+
+    fn binary_op(&self, op: BinOp, lhs: Box<Expr>, rhs: Box<Expr>) -> Expr {
+        let integer = !(lhs.is_float() || rhs.is_float());
+
+        let (lhs, rhs) = if self.order_operands && integer && is_commutative_op(op) {
+            ordered(lhs, rhs)
+        } else {
+            (lhs, rhs)
+        };
+
+        if self.simplify && integer {
+            if let Some(expr) = simplify_op(op, &lhs, &rhs) {
+                return expr;
+            }
+        }
+
+        ExprT::BinOp(op, lhs, rhs)
+    }
+
+    fn binary_rel(&self, rel: BinRel, lhs: Box<Expr>, rhs: Box<Expr>) -> Expr {
+        let integer = !(lhs.is_float() || rhs.is_float());
+
+        let (lhs, rhs) = if self.order_operands && integer && is_commutative_rel(rel) {
+            ordered(lhs, rhs)
+        } else {
+            (lhs, rhs)
+        };
+
+        if self.simplify && integer && lhs == rhs && is_pure(&lhs) {
+            let holds = match rel {
+                BinRel::EQ | BinRel::LE | BinRel::SLE => Some(true),
+                BinRel::NEQ | BinRel::LT | BinRel::SLT => Some(false),
+                _ => None,
+            };
+
+            if let Some(holds) = holds {
+                return ExprT::Val(BitVec::from_u64(holds as u64, 1));
+            }
+        }
+
+        ExprT::BinRel(rel, lhs, rhs)
+    }
+}
+
+impl ExprRewriter<Location, BitVec, Var> for Canonicaliser {
+    fn rewrite_expr(&mut self, expr: Expr) -> Expr {
+        match rewrite_expr_children(self, expr) {
+            ExprT::BinRel(rel, l, r) => self.binary_rel(rel, l, r),
+            ExprT::UnOp(op, e) => self.unary_op(op, e),
+            ExprT::BinOp(op, l, r) => self.binary_op(op, l, r),
+            ExprT::Cast(e, cast) => self.cast(*e, cast),
+            expr => expr,
+        }
+    }
+}
+
+impl ECode {
+    /// Rewrites the instruction's operations into normal form; see
+    /// [`crate::il::ecode::canonical`].
+    pub fn canonicalise(&mut self) {
+        Canonicaliser::default().ecode(self)
+    }
+}
+
+fn is_commutative_op(op: BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::AND | BinOp::OR | BinOp::XOR | BinOp::ADD | BinOp::MUL
+    )
+}
+
+fn is_commutative_rel(rel: BinRel) -> bool {
+    matches!(
+        rel,
+        BinRel::EQ | BinRel::NEQ | BinRel::CARRY | BinRel::SCARRY
+    )
+}
+
+/// Constants last, otherwise ascending.
+fn ordered(lhs: Box<Expr>, rhs: Box<Expr>) -> (Box<Expr>, Box<Expr>) {
+    let swap = match (lhs.value(), rhs.value()) {
+        (Some(_), None) => true,
+        (None, Some(_)) => false,
+        _ => lhs > rhs,
+    };
+
+    if swap {
+        (rhs, lhs)
+    } else {
+        (lhs, rhs)
+    }
+}
+
+/// If casting `expr` by `inner` then by `outer` is equivalent to casting
+/// `expr` by `outer`.
+fn folds_into(expr: &Expr, inner: &Cast, outer: &Cast) -> bool {
+    if expr.is_float() {
+        // float to integer conversions are not truncations
+        return false;
+    }
+
+    match (inner, outer) {
+        (Cast::Bool, Cast::Bool) => true,
+        (Cast::Unsigned(m), Cast::Unsigned(n)) | (Cast::Signed(m), Cast::Signed(n)) => {
+            m >= n || expr.bits() <= *m
+        }
+        (Cast::Unsigned(m) | Cast::Signed(m), Cast::Unsigned(n) | Cast::Signed(n)) => {
+            m >= n && expr.bits() >= *n
+        }
+        _ => false,
+    }
+}
+
+fn simplify_op(op: BinOp, lhs: &Expr, rhs: &Expr) -> Option<Expr> {
+    let bits = lhs.bits();
+
+    if lhs == rhs && is_pure(lhs) {
+        match op {
+            BinOp::SUB | BinOp::XOR => return Some(ExprT::Val(BitVec::zero(bits))),
+            BinOp::AND | BinOp::OR => return Some(lhs.clone()),
+            _ => (),
+        }
+    }
+
+    let value = rhs.value().filter(|value| value.bits() == bits)?;
+
+    let identity = if value.is_zero() {
+        matches!(
+            op,
+            BinOp::ADD | BinOp::SUB | BinOp::OR | BinOp::XOR | BinOp::SHL | BinOp::SHR | BinOp::SAR
+        )
+    } else if value.is_one() {
+        matches!(op, BinOp::MUL | BinOp::DIV | BinOp::SDIV)
+    } else {
+        op == BinOp::AND && value.count_ones() as usize == bits
+    };
+
+    if identity {
+        Some(lhs.clone())
+    } else if value.is_zero() && matches!(op, BinOp::AND | BinOp::MUL) && is_pure(lhs) {
+        Some(ExprT::Val(BitVec::zero(bits)))
+    } else {
+        None
+    }
+}
+
+struct Impure;
+
+impl<'a> TryExprVisitor<'a, Location, BitVec, Var> for Impure {
+    type Error = ();
+
+    fn visit_expr(&mut self, expr: &'a Expr) -> Result<(), Self::Error> {
+        match expr {
+            ExprT::Load(_, _, _) | ExprT::Call(_, _, _) | ExprT::Intrinsic(_, _, _) => Err(()),
+            expr => try_walk_expr(self, expr),
+        }
+    }
+}
+
+/// If `expr` has no loads, calls, or intrinsics, and so may be duplicated
+/// or dropped.
+pub(super) fn is_pure(expr: &Expr) -> bool {
+    Impure.visit_expr(expr).is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::space::AddressSpaceId;
+
+    #[test]
+    fn test_canonicalise() {
+        let reg = |offset| Expr::from(Var::new(AddressSpaceId::register_id(1), offset, 32, 0));
+        let val = |v| Expr::from(BitVec::from_u64(v, 32));
+
+        let c = Canonicaliser::default();
+
+        // constants are ordered last
+        assert_eq!(
+            c.expr(Expr::int_add(val(4), reg(8))),
+            Expr::int_add(reg(8), val(4))
+        );
+
+        // operand order no longer matters
+        assert_eq!(
+            c.expr(Expr::int_and(reg(8), reg(0))),
+            c.expr(Expr::int_and(reg(0), reg(8)))
+        );
+
+        assert_eq!(c.expr(Expr::int_sub(reg(0), reg(0))), val(0));
+        assert_eq!(c.expr(Expr::int_xor(reg(0), val(0))), reg(0));
+        assert_eq!(c.expr(Expr::int_not(Expr::int_not(reg(0)))), reg(0));
+        assert_eq!(
+            c.expr(Expr::int_neg(Expr::int_neg(reg(0)))),
+            Expr::cast_signed(reg(0), 32)
+        );
+
+        // zero-extend to 64, truncate to 16
+        assert_eq!(
+            c.expr(Expr::Cast(
+                Box::new(Expr::cast_unsigned(reg(0), 64)),
+                Cast::Unsigned(16)
+            )),
+            Expr::cast_unsigned(reg(0), 16)
+        );
+
+        // truncate to 16, zero-extend to 64 is kept
+        let truncated = Expr::Cast(
+            Box::new(Expr::cast_unsigned(reg(0), 16)),
+            Cast::Unsigned(64),
+        );
+        assert_eq!(c.expr(truncated.clone()), truncated);
+
+        // identities dropping loads are not applied
+        let load = Expr::Load(Box::new(reg(0)), 32, AddressSpaceId::default_id(2));
+        assert_eq!(
+            c.expr(Expr::int_sub(load.clone(), load.clone())),
+            Expr::int_sub(load.clone(), load)
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Dead code elimination within lifted instructions.
+//!
+//! Assignments to temporaries (i.e., variables in the unique space) that
+//! are overwritten or reach the end of the instruction without being read
+//! are removed; temporaries cannot be observed outside of the instruction
+//! that assigns them, so this is always sound.
+//!
+//! Given registers known to be dead after the instruction (e.g., flags
+//! that are reassigned before being read, as computed by
+//! [`LiveVariables`](crate::il::dataflow::LiveVariables) over the
+//! surrounding block), assignments to them may also be removed. Such
+//! registers are assumed to be dead at every exit of the instruction, but
+//! are considered read by calls and intrinsics.
+//!
+//! Assignments of expressions that load, call, or invoke intrinsics are
+//! never removed.
+
+use std::collections::BTreeSet;
+
+use crate::il::dataflow::stmt_uses;
+use crate::il::traits::*;
+
+use super::canonical::is_pure;
+use super::{BranchTargetT, ECode, Location, Stmt, StmtT, Var};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DeadCodeEliminator {
+    dead_registers: Vec<Var>,
+}
+
+impl DeadCodeEliminator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers that are not live after the instruction; assignments
+    /// contained within them may be removed.
+    pub fn dead_registers<I>(self, registers: I) -> Self
+    where
+        I: IntoIterator<Item = Var>,
+    {
+        Self {
+            dead_registers: registers.into_iter().collect(),
+        }
+    }
+
+    /// Removes dead assignments from `ecode`, returning the number of
+    /// statements removed.
+    pub fn eliminate(&self, ecode: &mut ECode) -> usize {
+        let dead = self.dead_statements(ecode);
+        let removed = dead.iter().filter(|dead| **dead).count();
+
+        if removed == 0 {
+            return 0;
+        }
+
+        // the positions of intra-instruction branch targets after removal
+        let mut positions = Vec::with_capacity(dead.len() + 1);
+        let mut position = 0;
+        for dead in dead.iter() {
+            positions.push(position);
+            position += !*dead as usize;
+        }
+        positions.push(position);
+
+        let address = ecode.address;
+        let operations = std::mem::take(&mut ecode.operations);
+
+        ecode.operations = operations
+            .into_iter()
+            .zip(dead)
+            .filter_map(|(mut stmt, dead)| {
+                if dead {
+                    return None;
+                }
+
+                if let StmtT::Branch(BranchTargetT::Location(loc))
+                | StmtT::CBranch(_, BranchTargetT::Location(loc)) = &mut stmt
+                {
+                    if *loc.address() == address {
+                        let position = positions[loc.position().min(positions.len() - 1)];
+                        *loc = Location::new(address, position);
+                    }
+                }
+
+                Some(stmt)
+            })
+            .collect();
+
+        if ecode.operations.is_empty() {
+            ecode.operations.push(StmtT::skip());
+        }
+
+        removed
+    }
+
+    fn dead_statements(&self, ecode: &ECode) -> Vec<bool> {
+        let operations = ecode.operations();
+        let count = operations.len();
+
+        let local = |loc: &Location| {
+            if *loc.address() == ecode.address {
+                Some(loc.position().min(count))
+            } else {
+                None
+            }
+        };
+
+        // an empty set of successors is an exit from the instruction
+        let successors = operations
+            .iter()
+            .enumerate()
+            .map(|(index, stmt)| match stmt {
+                StmtT::Branch(BranchTargetT::Location(loc)) => local(loc).into_iter().collect(),
+                StmtT::Branch(_) | StmtT::Return(_) => Vec::new(),
+                StmtT::CBranch(_, BranchTargetT::Location(loc)) => {
+                    std::iter::once(index + 1).chain(local(loc)).collect()
+                }
+                _ => vec![index + 1],
+            })
+            .collect::<Vec<_>>();
+
+        // faint variable analysis: the reads of dead assignments are
+        // ignored, so chains of dead assignments are removed together
+        let mut live = vec![BTreeSet::new(); count + 1];
+        let mut dead = vec![false; count];
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for index in (0..count).rev() {
+                let mut fact = BTreeSet::new();
+                for successor in successors[index].iter() {
+                    fact.extend(live[*successor].iter().copied());
+                }
+
+                dead[index] = self.transfer(&operations[index], &mut fact);
+
+                if live[index] != fact {
+                    live[index] = fact;
+                    changed = true;
+                }
+            }
+        }
+
+        dead
+    }
+
+    /// Updates the set of live tracked variables before `stmt` from those
+    /// live after it, returning true if `stmt` is dead.
+    fn transfer(&self, stmt: &Stmt, live: &mut BTreeSet<Var>) -> bool {
+        if let StmtT::Assign(var, expr) = stmt {
+            if self.is_removable(var) && !live.iter().any(|v| overlaps(v, var)) && is_pure(expr) {
+                return true;
+            }
+            live.retain(|v| !covers(var, v));
+        }
+
+        if let StmtT::Call(_, _) | StmtT::Intrinsic(_, _) = stmt {
+            live.extend(self.dead_registers.iter().copied());
+        }
+
+        stmt_uses(stmt, &mut |var: &Var| {
+            if self.is_tracked(var) {
+                live.insert(*var);
+            }
+        });
+
+        false
+    }
+
+    fn is_removable(&self, var: &Var) -> bool {
+        var.space().is_unique() || self.dead_registers.iter().any(|reg| covers(reg, var))
+    }
+
+    fn is_tracked(&self, var: &Var) -> bool {
+        var.space().is_unique() || self.dead_registers.iter().any(|reg| overlaps(reg, var))
+    }
+}
+
+impl ECode {
+    /// Removes assignments to temporaries that are never read; see
+    /// [`crate::il::ecode::dce`].
+    pub fn eliminate_dead_code(&mut self) -> usize {
+        DeadCodeEliminator::default().eliminate(self)
+    }
+}
+
+fn end(var: &Var) -> u64 {
+    var.offset() + var.bits().div_ceil(8) as u64
+}
+
+fn overlaps(a: &Var, b: &Var) -> bool {
+    a.space() == b.space() && a.offset() < end(b) && b.offset() < end(a)
+}
+
+fn covers(a: &Var, b: &Var) -> bool {
+    a.space() == b.space() && a.offset() <= b.offset() && end(b) <= end(a)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::address::AddressValue;
+    use crate::il::ecode::Expr;
+    use crate::space::{AddressSpace, AddressSpaceId, Space, SpaceKind};
+    use fugue_bv::BitVec;
+    use smallvec::smallvec;
+
+    #[test]
+    fn test_eliminate() {
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 8, 1, 1, None, 0));
+        let address = AddressValue::new(&ram, 0x1000);
+
+        let reg = |offset, bits| Var::new(AddressSpaceId::register_id(1), offset, bits, 0);
+        let tmp = |offset| Var::new(AddressSpaceId::unique_id(2), offset, 32, 0);
+
+        let (eax, ebx, zf, cf) = (reg(0, 32), reg(4, 32), reg(0x200, 8), reg(0x201, 8));
+
+        // $u0 = eax + ebx; $u1 = $u0 (overwritten); $u1 = ebx; zf = $u0 == 0;
+        // cf = $u1 == 0; eax = $u0
+        let ecode = || ECode {
+            address,
+            operations: smallvec![
+                StmtT::assign(tmp(0), Expr::int_add(eax, ebx)),
+                StmtT::assign(tmp(4), tmp(0)),
+                StmtT::assign(tmp(4), ebx),
+                StmtT::assign(zf, Expr::int_eq(tmp(0), BitVec::zero(32))),
+                StmtT::assign(cf, Expr::int_eq(tmp(4), BitVec::zero(32))),
+                StmtT::assign(eax, tmp(0)),
+            ],
+            delay_slots: 0,
+            length: 2,
+        };
+
+        let mut all_live = ecode();
+        assert_eq!(all_live.eliminate_dead_code(), 1);
+        assert_eq!(all_live.operations().len(), 5);
+
+        // with cf dead, $u1 is dead
+        let mut flags_dead = ecode();
+        let dce = DeadCodeEliminator::new().dead_registers([cf]);
+        assert_eq!(dce.eliminate(&mut flags_dead), 3);
+        assert_eq!(
+            flags_dead.operations()[1],
+            StmtT::assign(zf, Expr::int_eq(tmp(0), BitVec::zero(32)))
+        );
+    }
+}
@@ -1,13 +1,15 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::fmt;
 use std::hash::Hash;
 use std::sync::Arc;
 
 use crate::address::AddressValue;
 use crate::disassembly::lift::{FloatFormats, UserOpStr};
-use crate::disassembly::{IRBuilderArena, Opcode, VarnodeData};
+use crate::disassembly::{Error, IRBuilderArena, Opcode, VarnodeData};
 use crate::float_format::FloatFormat;
 use crate::il::pcode::{Operand, PCode, PCodeOp};
+use crate::il::temporaries::Renumbering;
 use crate::il::traits::*;
 use crate::space::{AddressSpace, AddressSpaceId};
 use crate::space_manager::{FromSpace, IntoSpace, SpaceManager};
@@ -17,6 +19,24 @@ use fugue_bv::BitVec;
 use smallvec::{smallvec, SmallVec};
 use ustr::Ustr;
 
+pub mod builder;
+pub use builder::{BuildError, ECodeBuilder, Label};
+
+pub mod canonical;
+pub use canonical::Canonicaliser;
+
+pub mod dce;
+pub use dce::DeadCodeEliminator;
+
+pub mod text;
+pub use text::{ParseError, Text, ToText};
+
+pub mod validate;
+pub use validate::{SizeError, Violation};
+
+pub mod visit;
+pub use visit::{ExprRewriter, ExprVisitor, TryExprRewriter, TryExprVisitor};
+
 #[derive(
     Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize, serde::Serialize,
 )]
@@ -90,25 +110,29 @@ impl fmt::Display for Var {
 
 impl<'var, 'trans> fmt::Display for VarFormatter<'var, 'trans> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(trans) = self.fmt.translator {
-            let space = trans.manager().unchecked_space_by_id(self.var.space());
+        let space = self
+            .fmt
+            .translator
+            .and_then(|trans| Some((trans, trans.manager().space_by_id(self.var.space())?)));
+
+        if let Some((trans, space)) = space {
             if space.is_register() {
                 let name = trans
                     .registers()
-                    .get(self.var.offset(), self.var.bits() / 8)
+                    .display_name(self.var.offset(), self.var.bits() / 8)
                     .unwrap();
                 write!(
                     f,
                     "{}{}{}.{}{}{}:{}{}{}",
-                    self.fmt.variable_start,
+                    self.fmt.start(DisplayTokenKind::Variable),
                     name,
-                    self.fmt.variable_end,
-                    self.fmt.value_start,
+                    self.fmt.end(DisplayTokenKind::Variable),
+                    self.fmt.start(DisplayTokenKind::Value),
                     self.var.generation(),
-                    self.fmt.value_end,
-                    self.fmt.value_start,
+                    self.fmt.end(DisplayTokenKind::Value),
+                    self.fmt.start(DisplayTokenKind::Value),
                     self.var.bits(),
-                    self.fmt.value_end,
+                    self.fmt.end(DisplayTokenKind::Value),
                 )
             } else {
                 let off = self.var.offset();
@@ -116,10 +140,10 @@ impl<'var, 'trans> fmt::Display for VarFormatter<'var, 'trans> {
                 write!(
                     f,
                     "{}{}{}[{}{}{}{}{:#x}{}].{}{}{}:{}{}{}",
-                    self.fmt.variable_start,
+                    self.fmt.start(DisplayTokenKind::Variable),
                     space.name(),
-                    self.fmt.variable_end,
-                    self.fmt.keyword_start,
+                    self.fmt.end(DisplayTokenKind::Variable),
+                    self.fmt.start(DisplayTokenKind::Keyword),
                     if sig == 0 {
                         ""
                     } else if sig > 0 {
@@ -127,16 +151,16 @@ impl<'var, 'trans> fmt::Display for VarFormatter<'var, 'trans> {
                     } else {
                         "-"
                     },
-                    self.fmt.keyword_end,
-                    self.fmt.value_start,
+                    self.fmt.end(DisplayTokenKind::Keyword),
+                    self.fmt.start(DisplayTokenKind::Value),
                     self.var.offset() as i64 as i128 * sig,
-                    self.fmt.value_end,
-                    self.fmt.value_start,
+                    self.fmt.end(DisplayTokenKind::Value),
+                    self.fmt.start(DisplayTokenKind::Value),
                     self.var.generation(),
-                    self.fmt.value_end,
-                    self.fmt.value_start,
+                    self.fmt.end(DisplayTokenKind::Value),
+                    self.fmt.start(DisplayTokenKind::Value),
                     self.var.bits(),
-                    self.fmt.value_end,
+                    self.fmt.end(DisplayTokenKind::Value),
                 )
             }
         } else {
@@ -189,15 +213,43 @@ impl fmt::Display for Location {
     }
 }
 
-impl<'loc, 'trans> TranslatorDisplay<'loc, 'trans> for Location {
-    type Target = &'loc Self;
+pub struct LocationFormatter<'loc, 'trans> {
+    location: &'loc Location,
+    fmt: Cow<'trans, TranslatorFormatter<'trans>>,
+}
 
-    fn display_with(&'loc self, _translator: Option<&'trans Translator>) -> Self::Target {
-        self
+impl<'loc, 'trans> fmt::Display for LocationFormatter<'loc, 'trans> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = self
+            .fmt
+            .symbols
+            .and_then(|symbols| symbols.resolve(&self.location.address));
+
+        let (name, offset) = if let Some(symbol) = symbol {
+            symbol
+        } else {
+            return write!(f, "{}", self.location);
+        };
+
+        write!(f, "{}", name)?;
+        if offset != 0 {
+            write!(f, "+{:#x}", offset)?;
+        }
+        if self.location.position != 0 {
+            write!(f, ".{}", self.location.position)?;
+        }
+        Ok(())
     }
+}
+
+impl<'loc, 'trans> TranslatorDisplay<'loc, 'trans> for Location {
+    type Target = LocationFormatter<'loc, 'trans>;
 
-    fn display_full(&'loc self, _fmt: Cow<'trans, TranslatorFormatter<'trans>>) -> Self::Target {
-        self
+    fn display_full(&'loc self, fmt: Cow<'trans, TranslatorFormatter<'trans>>) -> Self::Target {
+        LocationFormatter {
+            location: self,
+            fmt,
+        }
     }
 }
 
@@ -257,15 +309,20 @@ impl Location {
 }
 
 impl<'z> FromSpace<'z, VarnodeData> for Location {
-    fn from_space_with(t: VarnodeData, _arena: &'z IRBuilderArena, manager: &SpaceManager) -> Self {
+    fn from_space_with(
+        t: VarnodeData,
+        _arena: &'z IRBuilderArena,
+        manager: &SpaceManager,
+    ) -> Result<Self, Error> {
         Location::from_space(t, manager)
     }
 
-    fn from_space(vnd: VarnodeData, manager: &SpaceManager) -> Self {
-        Self {
-            address: AddressValue::new(manager.unchecked_space_by_id(vnd.space()), vnd.offset()),
+    fn from_space(vnd: VarnodeData, manager: &SpaceManager) -> Result<Self, Error> {
+        let space = manager.space_by_id(vnd.space()).ok_or(Error::InvalidSpace)?;
+        Ok(Self {
+            address: AddressValue::new(space, vnd.offset()),
             position: 0,
-        }
+        })
     }
 }
 
@@ -318,9 +375,9 @@ where
                 write!(
                     f,
                     "{}{}{}",
-                    self.fmt.branch_start,
+                    self.fmt.start(DisplayTokenKind::Branch),
                     loc.display_full(Cow::Borrowed(&*self.fmt)),
-                    self.fmt.branch_end
+                    self.fmt.end(DisplayTokenKind::Branch)
                 )
             }
             BranchTargetT::Computed(expr) => {
@@ -790,7 +847,7 @@ where
             ExprT::Var(v) => write!(f, "{}", v.display_full(Cow::Borrowed(&*d.fmt))),
 
             ExprT::Intrinsic(name, args, _) => {
-                write!(f, "{}{}{}(", d.fmt.keyword_start, name, d.fmt.keyword_end)?;
+                write!(f, "{}{}{}(", d.fmt.start(DisplayTokenKind::Keyword), name, d.fmt.end(DisplayTokenKind::Keyword))?;
                 if !args.is_empty() {
                     write!(f, "{}", args[0].display_full(Cow::Borrowed(&*d.fmt)))?;
                     for arg in &args[1..] {
@@ -803,26 +860,26 @@ where
             ExprT::ExtractHigh(expr, bits) => write!(
                 f,
                 "{}extract-high{}({}, {}bits{}={}{}{})",
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
                 expr.display_full(Cow::Borrowed(&*d.fmt)),
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
-                d.fmt.value_start,
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
+                d.fmt.start(DisplayTokenKind::Value),
                 bits,
-                d.fmt.value_end,
+                d.fmt.end(DisplayTokenKind::Value),
             ),
             ExprT::ExtractLow(expr, bits) => write!(
                 f,
                 "{}extract-low{}({}, {}bits{}={}{}{})",
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
                 expr.display_full(Cow::Borrowed(&*d.fmt)),
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
-                d.fmt.value_start,
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
+                d.fmt.start(DisplayTokenKind::Value),
                 bits,
-                d.fmt.value_end,
+                d.fmt.end(DisplayTokenKind::Value),
             ),
 
             ExprT::Cast(expr, t) => {
@@ -830,37 +887,42 @@ where
                 write!(
                     f,
                     " {}as{} {}{}{}",
-                    d.fmt.keyword_start, d.fmt.keyword_end, d.fmt.type_start, t, d.fmt.type_end
+                    d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword), d.fmt.start(DisplayTokenKind::Type), t, d.fmt.end(DisplayTokenKind::Type)
                 )
             }
 
             ExprT::Load(expr, bits, space) => {
-                if let Some(trans) = d.fmt.translator {
-                    let space = trans.manager().unchecked_space_by_id(*space);
+                let space_id = *space;
+                let space = d
+                    .fmt
+                    .translator
+                    .and_then(|trans| trans.manager().space_by_id(space_id));
+
+                if let Some(space) = space {
                     write!(
                         f,
                         "{}{}{}[{}]:{}{}{}",
-                        d.fmt.variable_start,
+                        d.fmt.start(DisplayTokenKind::Variable),
                         space.name(),
-                        d.fmt.variable_end,
+                        d.fmt.end(DisplayTokenKind::Variable),
                         expr.display_full(Cow::Borrowed(&*d.fmt)),
-                        d.fmt.value_start,
+                        d.fmt.start(DisplayTokenKind::Value),
                         bits,
-                        d.fmt.value_end,
+                        d.fmt.end(DisplayTokenKind::Value),
                     )
                 } else {
                     write!(
                         f,
                         "{}space{}[{}{}{}][{}]:{}{}{}",
-                        d.fmt.variable_start,
-                        d.fmt.variable_end,
-                        d.fmt.value_start,
-                        space.index(),
-                        d.fmt.value_end,
+                        d.fmt.start(DisplayTokenKind::Variable),
+                        d.fmt.end(DisplayTokenKind::Variable),
+                        d.fmt.start(DisplayTokenKind::Value),
+                        space_id.index(),
+                        d.fmt.end(DisplayTokenKind::Value),
                         expr.display_full(Cow::Borrowed(&*d.fmt)),
-                        d.fmt.value_start,
+                        d.fmt.start(DisplayTokenKind::Value),
                         bits,
-                        d.fmt.value_end,
+                        d.fmt.end(DisplayTokenKind::Value),
                     )
                 }
             }
@@ -868,27 +930,27 @@ where
             ExprT::Extract(expr, lsb, msb) => write!(
                 f,
                 "{}extract{}({}, {}from{}={}{}{}, {}to{}={}{}{})",
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
                 expr.display_full(Cow::Borrowed(&*d.fmt)),
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
-                d.fmt.value_start,
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
+                d.fmt.start(DisplayTokenKind::Value),
                 lsb,
-                d.fmt.value_end,
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
-                d.fmt.value_start,
+                d.fmt.end(DisplayTokenKind::Value),
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
+                d.fmt.start(DisplayTokenKind::Value),
                 msb,
-                d.fmt.value_end,
+                d.fmt.end(DisplayTokenKind::Value),
             ),
 
             ExprT::UnOp(UnOp::ABS, expr) => {
                 write!(
                     f,
                     "{}abs{}({})",
-                    d.fmt.keyword_start,
-                    d.fmt.keyword_end,
+                    d.fmt.start(DisplayTokenKind::Keyword),
+                    d.fmt.end(DisplayTokenKind::Keyword),
                     expr.display_full(Cow::Borrowed(&*d.fmt))
                 )
             }
@@ -896,8 +958,8 @@ where
                 write!(
                     f,
                     "{}sqrt{}({})",
-                    d.fmt.keyword_start,
-                    d.fmt.keyword_end,
+                    d.fmt.start(DisplayTokenKind::Keyword),
+                    d.fmt.end(DisplayTokenKind::Keyword),
                     expr.display_full(Cow::Borrowed(&*d.fmt))
                 )
             }
@@ -905,8 +967,8 @@ where
                 write!(
                     f,
                     "{}round{}({})",
-                    d.fmt.keyword_start,
-                    d.fmt.keyword_end,
+                    d.fmt.start(DisplayTokenKind::Keyword),
+                    d.fmt.end(DisplayTokenKind::Keyword),
                     expr.display_full(Cow::Borrowed(&*d.fmt))
                 )
             }
@@ -914,8 +976,8 @@ where
                 write!(
                     f,
                     "{}ceiling{}({})",
-                    d.fmt.keyword_start,
-                    d.fmt.keyword_end,
+                    d.fmt.start(DisplayTokenKind::Keyword),
+                    d.fmt.end(DisplayTokenKind::Keyword),
                     expr.display_full(Cow::Borrowed(&*d.fmt))
                 )
             }
@@ -923,8 +985,8 @@ where
                 write!(
                     f,
                     "{}floor{}({})",
-                    d.fmt.keyword_start,
-                    d.fmt.keyword_end,
+                    d.fmt.start(DisplayTokenKind::Keyword),
+                    d.fmt.end(DisplayTokenKind::Keyword),
                     expr.display_full(Cow::Borrowed(&*d.fmt))
                 )
             }
@@ -932,8 +994,8 @@ where
                 write!(
                     f,
                     "{}popcount{}({})",
-                    d.fmt.keyword_start,
-                    d.fmt.keyword_end,
+                    d.fmt.start(DisplayTokenKind::Keyword),
+                    d.fmt.end(DisplayTokenKind::Keyword),
                     expr.display_full(Cow::Borrowed(&*d.fmt))
                 )
             }
@@ -942,8 +1004,8 @@ where
                 write!(
                     f,
                     "{}is-nan{}({})",
-                    d.fmt.keyword_start,
-                    d.fmt.keyword_end,
+                    d.fmt.start(DisplayTokenKind::Keyword),
+                    d.fmt.end(DisplayTokenKind::Keyword),
                     expr.display_full(Cow::Borrowed(&*d.fmt))
                 )
             }
@@ -951,24 +1013,24 @@ where
             ExprT::BinRel(BinRel::CARRY, e1, e2) => write!(
                 f,
                 "{}carry{}({}, {})",
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
                 e1.display_full(Cow::Borrowed(&*d.fmt)),
                 e2.display_full(Cow::Borrowed(&*d.fmt))
             ),
             ExprT::BinRel(BinRel::SCARRY, e1, e2) => write!(
                 f,
                 "{}scarry{}({}, {})",
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
                 e1.display_full(Cow::Borrowed(&*d.fmt)),
                 e2.display_full(Cow::Borrowed(&*d.fmt))
             ),
             ExprT::BinRel(BinRel::SBORROW, e1, e2) => write!(
                 f,
                 "{}sborrow{}({}, {})",
-                d.fmt.keyword_start,
-                d.fmt.keyword_end,
+                d.fmt.start(DisplayTokenKind::Keyword),
+                d.fmt.end(DisplayTokenKind::Keyword),
                 e1.display_full(Cow::Borrowed(&*d.fmt)),
                 e2.display_full(Cow::Borrowed(&*d.fmt))
             ),
@@ -984,11 +1046,11 @@ where
     ) -> fmt::Result {
         match self {
             ExprT::UnOp(UnOp::NEG, expr) => {
-                write!(f, "{}-{}", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, "{}-{}", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 expr.fmt_l1_with(f, d)
             }
             ExprT::UnOp(UnOp::NOT, expr) => {
-                write!(f, "{}!{}", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, "{}!{}", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 expr.fmt_l1_with(f, d)
             }
             expr => expr.fmt_l1_with(f, d),
@@ -1003,27 +1065,27 @@ where
         match self {
             ExprT::BinOp(BinOp::MUL, e1, e2) => {
                 e1.fmt_l3_with(f, d)?;
-                write!(f, " {}*{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}*{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l2_with(f, d)
             }
             ExprT::BinOp(BinOp::DIV, e1, e2) => {
                 e1.fmt_l3_with(f, d)?;
-                write!(f, " {}/{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}/{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l2_with(f, d)
             }
             ExprT::BinOp(BinOp::SDIV, e1, e2) => {
                 e1.fmt_l3_with(f, d)?;
-                write!(f, " {}s/{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}s/{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l2_with(f, d)
             }
             ExprT::BinOp(BinOp::REM, e1, e2) => {
                 e1.fmt_l3_with(f, d)?;
-                write!(f, " {}%{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}%{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l2_with(f, d)
             }
             ExprT::BinOp(BinOp::SREM, e1, e2) => {
                 e1.fmt_l3_with(f, d)?;
-                write!(f, " {}s%{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}s%{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l2_with(f, d)
             }
             expr => expr.fmt_l2_with(f, d),
@@ -1038,12 +1100,12 @@ where
         match self {
             ExprT::BinOp(BinOp::ADD, e1, e2) => {
                 e1.fmt_l4_with(f, d)?;
-                write!(f, " {}+{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}+{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l3_with(f, d)
             }
             ExprT::BinOp(BinOp::SUB, e1, e2) => {
                 e1.fmt_l4_with(f, d)?;
-                write!(f, " {}-{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}-{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l3_with(f, d)
             }
             expr => expr.fmt_l3_with(f, d),
@@ -1058,17 +1120,17 @@ where
         match self {
             ExprT::BinOp(BinOp::SHL, e1, e2) => {
                 e1.fmt_l5_with(f, d)?;
-                write!(f, " {}<<{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}<<{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l4_with(f, d)
             }
             ExprT::BinOp(BinOp::SHR, e1, e2) => {
                 e1.fmt_l5_with(f, d)?;
-                write!(f, " {}>>{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}>>{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l4_with(f, d)
             }
             ExprT::BinOp(BinOp::SAR, e1, e2) => {
                 e1.fmt_l5_with(f, d)?;
-                write!(f, " {}s>>{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}s>>{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l4_with(f, d)
             }
             expr => expr.fmt_l4_with(f, d),
@@ -1083,22 +1145,22 @@ where
         match self {
             ExprT::BinRel(BinRel::LT, e1, e2) => {
                 e1.fmt_l6_with(f, d)?;
-                write!(f, " {}<{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}<{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l5_with(f, d)
             }
             ExprT::BinRel(BinRel::LE, e1, e2) => {
                 e1.fmt_l6_with(f, d)?;
-                write!(f, " {}<={} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}<={} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l5_with(f, d)
             }
             ExprT::BinRel(BinRel::SLT, e1, e2) => {
                 e1.fmt_l6_with(f, d)?;
-                write!(f, " {}s<{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}s<{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l5_with(f, d)
             }
             ExprT::BinRel(BinRel::SLE, e1, e2) => {
                 e1.fmt_l6_with(f, d)?;
-                write!(f, " {}s<={} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}s<={} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l5_with(f, d)
             }
             expr => expr.fmt_l5_with(f, d),
@@ -1113,12 +1175,12 @@ where
         match self {
             ExprT::BinRel(BinRel::EQ, e1, e2) => {
                 e1.fmt_l7_with(f, d)?;
-                write!(f, " {}=={} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}=={} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l6_with(f, d)
             }
             ExprT::BinRel(BinRel::NEQ, e1, e2) => {
                 e1.fmt_l7_with(f, d)?;
-                write!(f, " {}!={} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+                write!(f, " {}!={} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
                 e2.fmt_l6_with(f, d)
             }
             expr => expr.fmt_l6_with(f, d),
@@ -1132,7 +1194,7 @@ where
     ) -> fmt::Result {
         if let ExprT::BinOp(BinOp::AND, e1, e2) = self {
             e1.fmt_l8_with(f, d)?;
-            write!(f, " {}&{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+            write!(f, " {}&{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
             e2.fmt_l7_with(f, d)
         } else {
             self.fmt_l7_with(f, d)
@@ -1146,7 +1208,7 @@ where
     ) -> fmt::Result {
         if let ExprT::BinOp(BinOp::XOR, e1, e2) = self {
             e1.fmt_l9_with(f, d)?;
-            write!(f, " {}^{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+            write!(f, " {}^{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
             e2.fmt_l8_with(f, d)
         } else {
             self.fmt_l8_with(f, d)
@@ -1160,7 +1222,7 @@ where
     ) -> fmt::Result {
         if let ExprT::BinOp(BinOp::OR, e1, e2) = self {
             e1.fmt_l10_with(f, d)?;
-            write!(f, " {}|{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+            write!(f, " {}|{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
             e2.fmt_l9_with(f, d)
         } else {
             self.fmt_l9_with(f, d)
@@ -1174,7 +1236,7 @@ where
     ) -> fmt::Result {
         if let ExprT::Concat(e1, e2) = self {
             e1.fmt_l11_with(f, d)?;
-            write!(f, " {}++{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+            write!(f, " {}++{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
             e2.fmt_l10_with(f, d)
         } else {
             self.fmt_l10_with(f, d)
@@ -1187,11 +1249,11 @@ where
         d: &ExprTFormatter<'v, 't, Loc, Val, Var>,
     ) -> fmt::Result {
         if let ExprT::IfElse(c, et, ef) = self {
-            write!(f, "{}if{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+            write!(f, "{}if{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
             c.fmt_l12_with(f, d)?;
-            write!(f, " {}then{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+            write!(f, " {}then{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
             et.fmt_l12_with(f, d)?;
-            write!(f, " {}else{} ", d.fmt.keyword_start, d.fmt.keyword_end)?;
+            write!(f, " {}else{} ", d.fmt.start(DisplayTokenKind::Keyword), d.fmt.end(DisplayTokenKind::Keyword))?;
             ef.fmt_l12_with(f, d)
         } else {
             self.fmt_l11_with(f, d)
@@ -1252,18 +1314,22 @@ impl<Loc, Val> From<Var> for ExprT<Loc, Val, Var> {
 }
 
 impl<'z, Loc> FromSpace<'z, VarnodeData> for ExprT<Loc, BitVec, Var> {
-    fn from_space_with(t: VarnodeData, _arena: &'_ IRBuilderArena, manager: &SpaceManager) -> Self {
+    fn from_space_with(
+        t: VarnodeData,
+        _arena: &'_ IRBuilderArena,
+        manager: &SpaceManager,
+    ) -> Result<Self, Error> {
         ExprT::from_space(t, manager)
     }
 
-    fn from_space(vnd: VarnodeData, manager: &SpaceManager) -> ExprT<Loc, BitVec, Var> {
-        let space = manager.unchecked_space_by_id(vnd.space());
-        if space.is_constant() {
+    fn from_space(vnd: VarnodeData, manager: &SpaceManager) -> Result<Self, Error> {
+        let space = manager.space_by_id(vnd.space()).ok_or(Error::InvalidSpace)?;
+        Ok(if space.is_constant() {
             ExprT::from(BitVec::from_u64(vnd.offset(), vnd.size() * 8))
         } else {
             // if space.is_unique() || space.is_register() {
             ExprT::from(Var::from(vnd))
-        } /* else {
+        }) /* else {
               // address-like: the vnd size is what it points to
               let asz = space.address_size() * 8;
               let val = BitVec::from_u64(vnd.offset(), asz);
@@ -2219,42 +2285,46 @@ where
                 f,
                 "{} {}←{} {}",
                 dest.display_full(Cow::Borrowed(&*self.fmt)),
-                self.fmt.keyword_start,
-                self.fmt.keyword_end,
+                self.fmt.start(DisplayTokenKind::Keyword),
+                self.fmt.end(DisplayTokenKind::Keyword),
                 src.display_full(Cow::Borrowed(&*self.fmt))
             ),
             StmtT::Store(dest, src, size, spc) => {
-                if let Some(trans) = self.fmt.translator {
-                    let space = trans.manager().unchecked_space_by_id(*spc);
+                let space = self
+                    .fmt
+                    .translator
+                    .and_then(|trans| trans.manager().space_by_id(*spc));
+
+                if let Some(space) = space {
                     write!(
                         f,
                         "{}{}{}[{}]:{}{}{} {}←{} {}",
-                        self.fmt.variable_start,
+                        self.fmt.start(DisplayTokenKind::Variable),
                         space.name(),
-                        self.fmt.variable_end,
+                        self.fmt.end(DisplayTokenKind::Variable),
                         dest.display_full(Cow::Borrowed(&*self.fmt)),
-                        self.fmt.value_start,
+                        self.fmt.start(DisplayTokenKind::Value),
                         size,
-                        self.fmt.value_end,
-                        self.fmt.keyword_start,
-                        self.fmt.keyword_end,
+                        self.fmt.end(DisplayTokenKind::Value),
+                        self.fmt.start(DisplayTokenKind::Keyword),
+                        self.fmt.end(DisplayTokenKind::Keyword),
                         src.display_full(Cow::Borrowed(&*self.fmt))
                     )
                 } else {
                     write!(
                         f,
                         "{}space{}[{}{}{}][{}]:{}{}{} {}←{} {}",
-                        self.fmt.variable_start,
-                        self.fmt.variable_end,
-                        self.fmt.value_start,
+                        self.fmt.start(DisplayTokenKind::Variable),
+                        self.fmt.end(DisplayTokenKind::Variable),
+                        self.fmt.start(DisplayTokenKind::Value),
                         spc.index(),
-                        self.fmt.value_end,
+                        self.fmt.end(DisplayTokenKind::Value),
                         dest.display_full(Cow::Borrowed(&*self.fmt)),
-                        self.fmt.value_start,
+                        self.fmt.start(DisplayTokenKind::Value),
                         size,
-                        self.fmt.value_end,
-                        self.fmt.keyword_start,
-                        self.fmt.keyword_end,
+                        self.fmt.end(DisplayTokenKind::Value),
+                        self.fmt.start(DisplayTokenKind::Keyword),
+                        self.fmt.end(DisplayTokenKind::Keyword),
                         src.display_full(Cow::Borrowed(&*self.fmt))
                     )
                 }
@@ -2263,19 +2333,19 @@ where
                 write!(
                     f,
                     "{}goto{} {}",
-                    self.fmt.keyword_start,
-                    self.fmt.keyword_end,
+                    self.fmt.start(DisplayTokenKind::Keyword),
+                    self.fmt.end(DisplayTokenKind::Keyword),
                     target.display_full(Cow::Borrowed(&*self.fmt)),
                 )
             }
             StmtT::CBranch(cond, target) => write!(
                 f,
                 "{}goto{} {} {}if{} {}",
-                self.fmt.keyword_start,
-                self.fmt.keyword_end,
+                self.fmt.start(DisplayTokenKind::Keyword),
+                self.fmt.end(DisplayTokenKind::Keyword),
                 target.display_full(Cow::Borrowed(&*self.fmt)),
-                self.fmt.keyword_start,
-                self.fmt.keyword_end,
+                self.fmt.start(DisplayTokenKind::Keyword),
+                self.fmt.end(DisplayTokenKind::Keyword),
                 cond.display_full(Cow::Borrowed(&*self.fmt))
             ),
             StmtT::Call(target, args) => {
@@ -2283,8 +2353,8 @@ where
                     write!(
                         f,
                         "{}call{} {}(",
-                        self.fmt.keyword_start,
-                        self.fmt.keyword_end,
+                        self.fmt.start(DisplayTokenKind::Keyword),
+                        self.fmt.end(DisplayTokenKind::Keyword),
                         target.display_full(Cow::Borrowed(&*self.fmt))
                     )?;
                     write!(f, "{}", args[0].display_full(Cow::Borrowed(&*self.fmt)))?;
@@ -2296,8 +2366,8 @@ where
                     write!(
                         f,
                         "{}call{} {}",
-                        self.fmt.keyword_start,
-                        self.fmt.keyword_end,
+                        self.fmt.start(DisplayTokenKind::Keyword),
+                        self.fmt.end(DisplayTokenKind::Keyword),
                         target.display_full(Cow::Borrowed(&*self.fmt))
                     )
                 }
@@ -2306,12 +2376,12 @@ where
                 write!(
                     f,
                     "{}return{} {}",
-                    self.fmt.keyword_start,
-                    self.fmt.keyword_end,
+                    self.fmt.start(DisplayTokenKind::Keyword),
+                    self.fmt.end(DisplayTokenKind::Keyword),
                     target.display_full(Cow::Borrowed(&*self.fmt))
                 )
             }
-            StmtT::Skip => write!(f, "{}skip{}", self.fmt.keyword_start, self.fmt.keyword_end),
+            StmtT::Skip => write!(f, "{}skip{}", self.fmt.start(DisplayTokenKind::Keyword), self.fmt.end(DisplayTokenKind::Keyword)),
             StmtT::Intrinsic(name, args) => {
                 write!(f, "{}(", name)?;
                 if !args.is_empty() {
@@ -2354,18 +2424,20 @@ impl StmtT<Location, BitVec, Var> {
         opcode: Opcode,
         inputs: I,
         output: Option<VarnodeData>,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         let mut inputs = inputs.into_iter();
         let spaces = manager.spaces();
-        match opcode {
+        Ok(match opcode {
             Opcode::Copy => Self::assign(
                 output.unwrap(),
-                ExprT::from_space(inputs.next().unwrap(), manager),
+                ExprT::from_space(inputs.next().unwrap(), manager)?,
             ),
             Opcode::Load => {
-                let space = &spaces[inputs.next().unwrap().offset() as usize];
+                let space = spaces
+                    .get(inputs.next().unwrap().offset() as usize)
+                    .ok_or(Error::InvalidSpace)?;
                 let destination = output.unwrap();
-                let source = inputs.next().unwrap().into_space(manager);
+                let source = inputs.next().unwrap().into_space(manager)?;
                 let size = destination.size() * 8;
 
                 let src = if space.word_size() > 1 {
@@ -2382,8 +2454,10 @@ impl StmtT<Location, BitVec, Var> {
                 Self::assign(destination, ExprT::load(src, size, space))
             }
             Opcode::Store => {
-                let space = &spaces[inputs.next().unwrap().offset() as usize];
-                let destination = inputs.next().unwrap().into_space(manager);
+                let space = spaces
+                    .get(inputs.next().unwrap().offset() as usize)
+                    .ok_or(Error::InvalidSpace)?;
+                let destination = inputs.next().unwrap().into_space(manager)?;
                 let source = inputs.next().unwrap();
                 let size = source.size() * 8;
 
@@ -2398,37 +2472,37 @@ impl StmtT<Location, BitVec, Var> {
                     destination
                 };
 
-                Self::store(dest, ExprT::from_space(source, manager), size, space)
+                Self::store(dest, ExprT::from_space(source, manager)?, size, space)
             }
             Opcode::Branch => {
-                let mut target = Location::from_space(inputs.next().unwrap(), manager);
+                let mut target = Location::from_space(inputs.next().unwrap(), manager)?;
                 target.absolute_from(address.to_owned(), position);
 
                 Self::branch(target)
             }
             Opcode::CBranch => {
-                let mut target = Location::from_space(inputs.next().unwrap(), manager);
+                let mut target = Location::from_space(inputs.next().unwrap(), manager)?;
                 target.absolute_from(address.to_owned(), position);
 
-                let condition = ExprT::from_space(inputs.next().unwrap(), manager);
+                let condition = ExprT::from_space(inputs.next().unwrap(), manager)?;
 
                 Self::branch_conditional(condition, target)
             }
             Opcode::IBranch => {
-                let target = ExprT::from_space(inputs.next().unwrap(), manager);
-                let space = manager.unchecked_space_by_id(address.space());
+                let target = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let space = manager.space_by_id(address.space()).ok_or(Error::InvalidSpace)?;
 
                 Self::branch_indirect(target, space)
             }
             Opcode::Call => {
-                let mut target = Location::from_space(inputs.next().unwrap(), manager);
+                let mut target = Location::from_space(inputs.next().unwrap(), manager)?;
                 target.absolute_from(address.to_owned(), position);
 
                 Self::call(target)
             }
             Opcode::ICall => {
-                let target = ExprT::from_space(inputs.next().unwrap(), manager);
-                let space = manager.unchecked_space_by_id(address.space());
+                let target = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let space = manager.space_by_id(address.space()).ok_or(Error::InvalidSpace)?;
 
                 Self::call_indirect(target, space)
             }
@@ -2442,25 +2516,31 @@ impl StmtT<Location, BitVec, Var> {
                         output,
                         ExprT::intrinsic(
                             &*name,
-                            inputs.into_iter().map(|v| ExprT::from_space(v, manager)),
+                            inputs
+                                .map(|v| ExprT::from_space(v, manager))
+                                .collect::<Result<Vec<_>, _>>()?
+                                .into_iter(),
                             bits,
                         ),
                     )
                 } else {
                     Self::intrinsic(
                         &*name,
-                        inputs.into_iter().map(|v| ExprT::from_space(v, manager)),
+                        inputs
+                            .map(|v| ExprT::from_space(v, manager))
+                            .collect::<Result<Vec<_>, _>>()?
+                            .into_iter(),
                     )
                 }
             }
             Opcode::Return => {
-                let target = ExprT::from_space(inputs.next().unwrap(), manager);
-                let space = manager.unchecked_space_by_id(address.space());
+                let target = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let space = manager.space_by_id(address.space()).ok_or(Error::InvalidSpace)?;
 
                 Self::return_(target, space)
             }
             Opcode::Subpiece => {
-                let source = ExprT::from_space(inputs.next().unwrap(), manager);
+                let source = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let src_size = source.bits();
 
                 let output = output.unwrap();
@@ -2482,7 +2562,7 @@ impl StmtT<Location, BitVec, Var> {
                 Self::assign(output, trun)
             }
             Opcode::PopCount => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = Var::from(output.unwrap());
 
                 let size = output.bits();
@@ -2491,312 +2571,312 @@ impl StmtT<Location, BitVec, Var> {
                 Self::assign(output, ExprT::cast_unsigned(popcount, size))
             }
             Opcode::BoolNot => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::bool_not(input))
             }
             Opcode::BoolAnd => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::bool_and(input1, input2))
             }
             Opcode::BoolOr => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::bool_or(input1, input2))
             }
             Opcode::BoolXor => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::bool_xor(input1, input2))
             }
             Opcode::IntNeg => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_neg(input))
             }
             Opcode::IntNot => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_not(input))
             }
             Opcode::IntSExt => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
                 let size = output.size() * 8;
 
                 Self::assign(output, ExprT::cast_signed(input, size))
             }
             Opcode::IntZExt => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
                 let size = output.size() * 8;
 
                 Self::assign(output, ExprT::cast_unsigned(input, size))
             }
             Opcode::IntEq => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_eq(input1, input2))
             }
             Opcode::IntNotEq => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_neq(input1, input2))
             }
             Opcode::IntLess => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_lt(input1, input2))
             }
             Opcode::IntLessEq => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_le(input1, input2))
             }
             Opcode::IntSLess => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_slt(input1, input2))
             }
             Opcode::IntSLessEq => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_sle(input1, input2))
             }
             Opcode::IntCarry => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_carry(input1, input2))
             }
             Opcode::IntSCarry => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_scarry(input1, input2))
             }
             Opcode::IntSBorrow => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_sborrow(input1, input2))
             }
             Opcode::IntAdd => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_add(input1, input2))
             }
             Opcode::IntSub => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_sub(input1, input2))
             }
             Opcode::IntDiv => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_div(input1, input2))
             }
             Opcode::IntSDiv => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_sdiv(input1, input2))
             }
             Opcode::IntMul => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_mul(input1, input2))
             }
             Opcode::IntRem => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_rem(input1, input2))
             }
             Opcode::IntSRem => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_srem(input1, input2))
             }
             Opcode::IntLShift => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_shl(input1, input2))
             }
             Opcode::IntRShift => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_shr(input1, input2))
             }
             Opcode::IntSRShift => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_sar(input1, input2))
             }
             Opcode::IntAnd => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_and(input1, input2))
             }
             Opcode::IntOr => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_or(input1, input2))
             }
             Opcode::IntXor => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::int_xor(input1, input2))
             }
             Opcode::FloatIsNaN => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_nan(input, float_formats))
             }
             Opcode::FloatAbs => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_abs(input, float_formats))
             }
             Opcode::FloatNeg => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_neg(input, float_formats))
             }
             Opcode::FloatSqrt => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_sqrt(input, float_formats))
             }
             Opcode::FloatFloor => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_floor(input, float_formats))
             }
             Opcode::FloatCeiling => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_ceiling(input, float_formats))
             }
             Opcode::FloatRound => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_round(input, float_formats))
             }
             Opcode::FloatEq => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_eq(input1, input2, float_formats))
             }
             Opcode::FloatNotEq => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_neq(input1, input2, float_formats))
             }
             Opcode::FloatLess => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_lt(input1, input2, float_formats))
             }
             Opcode::FloatLessEq => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_le(input1, input2, float_formats))
             }
             Opcode::FloatAdd => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_add(input1, input2, float_formats))
             }
             Opcode::FloatSub => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_sub(input1, input2, float_formats))
             }
             Opcode::FloatDiv => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_div(input1, input2, float_formats))
             }
             Opcode::FloatMul => {
-                let input1 = ExprT::from_space(inputs.next().unwrap(), manager);
-                let input2 = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input1 = ExprT::from_space(inputs.next().unwrap(), manager)?;
+                let input2 = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let output = output.unwrap();
 
                 Self::assign(output, ExprT::float_mul(input1, input2, float_formats))
             }
             Opcode::FloatOfFloat => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let input_size = input.bits();
 
                 let output = Var::from(output.unwrap());
@@ -2811,7 +2891,7 @@ impl StmtT<Location, BitVec, Var> {
                 )
             }
             Opcode::FloatOfInt => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let input_size = input.bits();
 
                 let output = Var::from(output.unwrap());
@@ -2824,7 +2904,7 @@ impl StmtT<Location, BitVec, Var> {
                 )
             }
             Opcode::FloatTruncate => {
-                let input = ExprT::from_space(inputs.next().unwrap(), manager);
+                let input = ExprT::from_space(inputs.next().unwrap(), manager)?;
                 let input_size = input.bits();
 
                 let output = Var::from(output.unwrap());
@@ -2849,7 +2929,7 @@ impl StmtT<Location, BitVec, Var> {
             | Opcode::SegmentOp => {
                 panic!("unimplemented due to spec.")
             }
-        }
+        })
     }
 }
 
@@ -3012,12 +3092,16 @@ where
 }
 
 impl<'z> FromSpace<'z, Operand> for Var {
-    fn from_space_with(t: Operand, _arena: &'z IRBuilderArena, manager: &SpaceManager) -> Self {
+    fn from_space_with(
+        t: Operand,
+        _arena: &'z IRBuilderArena,
+        manager: &SpaceManager,
+    ) -> Result<Self, Error> {
         Var::from_space(t, manager)
     }
 
-    fn from_space(operand: Operand, manager: &SpaceManager) -> Self {
-        match operand {
+    fn from_space(operand: Operand, manager: &SpaceManager) -> Result<Self, Error> {
+        Ok(match operand {
             Operand::Address { value, size } => Var {
                 offset: value.offset(),
                 space: manager.default_space_id(),
@@ -3041,7 +3125,7 @@ impl<'z> FromSpace<'z, Operand> for Var {
                 generation: 0,
             },
             _ => panic!("cannot create Var from Operand::Constant"),
-        }
+        })
     }
 }
 
@@ -3050,28 +3134,32 @@ impl<'z, Loc> FromSpace<'z, Operand> for ExprT<Loc, BitVec, Var> {
         operand: Operand,
         _arena: &'z IRBuilderArena,
         manager: &SpaceManager,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         ExprT::from_space(operand, manager)
     }
 
-    fn from_space(operand: Operand, manager: &SpaceManager) -> Self {
-        if let Operand::Constant { value, size, .. } = operand {
+    fn from_space(operand: Operand, manager: &SpaceManager) -> Result<Self, Error> {
+        Ok(if let Operand::Constant { value, size, .. } = operand {
             ExprT::Val(BitVec::from_u64(value, size * 8))
         } else {
-            Var::from_space(operand, manager).into()
-        }
+            Var::from_space(operand, manager)?.into()
+        })
     }
 }
 
 impl<'z> FromSpace<'z, Operand> for Location {
-    fn from_space_with(t: Operand, _arena: &'z IRBuilderArena, manager: &SpaceManager) -> Self {
+    fn from_space_with(
+        t: Operand,
+        _arena: &'z IRBuilderArena,
+        manager: &SpaceManager,
+    ) -> Result<Self, Error> {
         Location::from_space(t, manager)
     }
 
-    fn from_space(operand: Operand, manager: &SpaceManager) -> Self {
-        match operand {
+    fn from_space(operand: Operand, manager: &SpaceManager) -> Result<Self, Error> {
+        Ok(match operand {
             Operand::Address { value, .. } => Location {
-                address: value.into_space(manager),
+                address: value.into_space(manager)?,
                 position: 0,
             },
             Operand::Constant { value, .. } => Location {
@@ -3083,10 +3171,13 @@ impl<'z> FromSpace<'z, Operand> for Location {
                 position: 0,
             },
             Operand::Variable { offset, space, .. } => Location {
-                address: AddressValue::new(manager.unchecked_space_by_id(space), offset),
+                address: AddressValue::new(
+                    manager.space_by_id(space).ok_or(Error::InvalidSpace)?,
+                    offset,
+                ),
                 position: 0,
             },
-        }
+        })
     }
 }
 
@@ -3096,38 +3187,38 @@ impl StmtT<Location, BitVec, Var> {
         pcode: PCodeOp,
         address: &AddressValue,
         position: usize,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         let manager = translator.manager();
         let formats = translator.float_formats();
 
-        match pcode {
+        Ok(match pcode {
             PCodeOp::Copy {
                 destination,
                 source,
             } => Self::assign(
-                Var::from_space(destination, manager),
-                ExprT::from_space(source, manager),
+                Var::from_space(destination, manager)?,
+                ExprT::from_space(source, manager)?,
             ),
             PCodeOp::Load {
                 destination,
                 source,
                 space,
             } => {
-                let space = manager.unchecked_space_by_id(space);
+                let space = manager.space_by_id(space).ok_or(Error::InvalidSpace)?;
                 let size = destination.size() * 8;
                 let src = if space.word_size() > 1 {
-                    let s = ExprT::from_space(source, manager);
+                    let s = ExprT::from_space(source, manager)?;
                     let bits = s.bits();
 
                     let w = ExprT::from(BitVec::from_usize(space.word_size(), bits));
 
                     ExprT::int_mul(s, w)
                 } else {
-                    ExprT::from_space(source, manager)
+                    ExprT::from_space(source, manager)?
                 };
 
                 Self::assign(
-                    Var::from_space(destination, manager),
+                    Var::from_space(destination, manager)?,
                     ExprT::load(src, size, space),
                 )
             }
@@ -3136,24 +3227,24 @@ impl StmtT<Location, BitVec, Var> {
                 source,
                 space,
             } => {
-                let space = manager.unchecked_space_by_id(space);
+                let space = manager.space_by_id(space).ok_or(Error::InvalidSpace)?;
                 let size = source.size() * 8;
 
                 let dest = if space.word_size() > 1 {
-                    let d = ExprT::from_space(destination, manager);
+                    let d = ExprT::from_space(destination, manager)?;
                     let bits = d.bits();
 
                     let w = ExprT::from(BitVec::from_usize(space.word_size(), bits));
 
                     ExprT::int_mul(d, w)
                 } else {
-                    ExprT::from_space(destination, manager)
+                    ExprT::from_space(destination, manager)?
                 };
 
-                Self::store(dest, ExprT::from_space(source, manager), size, space)
+                Self::store(dest, ExprT::from_space(source, manager)?, size, space)
             }
             PCodeOp::Branch { destination } => {
-                let mut target = Location::from_space(destination, manager);
+                let mut target = Location::from_space(destination, manager)?;
                 target.absolute_from(address.to_owned(), position);
 
                 Self::branch(target)
@@ -3162,26 +3253,26 @@ impl StmtT<Location, BitVec, Var> {
                 condition,
                 destination,
             } => {
-                let mut target = Location::from_space(destination, manager);
+                let mut target = Location::from_space(destination, manager)?;
                 target.absolute_from(address.to_owned(), position);
 
-                Self::branch_conditional(ExprT::from_space(condition, manager), target)
+                Self::branch_conditional(ExprT::from_space(condition, manager)?, target)
             }
             PCodeOp::IBranch { destination } => {
-                let space = manager.unchecked_space_by_id(address.space());
+                let space = manager.space_by_id(address.space()).ok_or(Error::InvalidSpace)?;
 
-                Self::branch_indirect(ExprT::from_space(destination, manager), space)
+                Self::branch_indirect(ExprT::from_space(destination, manager)?, space)
             }
             PCodeOp::Call { destination } => {
-                let mut target = Location::from_space(destination, manager);
+                let mut target = Location::from_space(destination, manager)?;
                 target.absolute_from(address.to_owned(), position);
 
                 Self::call(target)
             }
             PCodeOp::ICall { destination } => {
-                let space = manager.unchecked_space_by_id(address.space());
+                let space = manager.space_by_id(address.space()).ok_or(Error::InvalidSpace)?;
 
-                Self::call_indirect(ExprT::from_space(destination, manager), space)
+                Self::call_indirect(ExprT::from_space(destination, manager)?, space)
             }
             PCodeOp::Intrinsic {
                 name,
@@ -3189,34 +3280,38 @@ impl StmtT<Location, BitVec, Var> {
                 result,
             } => {
                 if let Some(result) = result {
-                    let output = Var::from_space(result, manager);
+                    let output = Var::from_space(result, manager)?;
                     let bits = output.bits();
                     Self::assign(
                         output,
                         ExprT::intrinsic(
                             &*name,
-                            operands.into_iter().map(|v| ExprT::from_space(v, manager)),
+                            operands.into_iter().map(|v| ExprT::from_space(v, manager))
+                            .collect::<Result<Vec<_>, _>>()?
+                            .into_iter(),
                             bits,
                         ),
                     )
                 } else {
                     Self::intrinsic(
                         &*name,
-                        operands.into_iter().map(|v| ExprT::from_space(v, manager)),
+                        operands.into_iter().map(|v| ExprT::from_space(v, manager))
+                            .collect::<Result<Vec<_>, _>>()?
+                            .into_iter(),
                     )
                 }
             }
             PCodeOp::Return { destination } => {
-                let space = manager.unchecked_space_by_id(address.space());
+                let space = manager.space_by_id(address.space()).ok_or(Error::InvalidSpace)?;
 
-                Self::return_(ExprT::from_space(destination, manager), space)
+                Self::return_(ExprT::from_space(destination, manager)?, space)
             }
             PCodeOp::Subpiece {
                 operand,
                 amount,
                 result,
             } => {
-                let source = ExprT::from_space(operand, manager);
+                let source = ExprT::from_space(operand, manager)?;
                 let src_size = source.bits();
                 let out_size = result.size() * 8;
 
@@ -3233,328 +3328,328 @@ impl StmtT<Location, BitVec, Var> {
                     ExprT::extract(source, loff, hoff)
                 };
 
-                Self::assign(Var::from_space(result, manager), trun)
+                Self::assign(Var::from_space(result, manager)?, trun)
             }
             PCodeOp::PopCount { result, operand } => {
-                let output = Var::from_space(result, manager);
+                let output = Var::from_space(result, manager)?;
 
                 let size = output.bits();
-                let popcount = ExprT::unary_op(UnOp::POPCOUNT, ExprT::from_space(operand, manager));
+                let popcount = ExprT::unary_op(UnOp::POPCOUNT, ExprT::from_space(operand, manager)?);
 
                 Self::assign(output, ExprT::cast_unsigned(popcount, size))
             }
             PCodeOp::BoolNot { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::bool_not(ExprT::from_space(operand, manager)),
+                Var::from_space(result, manager)?,
+                ExprT::bool_not(ExprT::from_space(operand, manager)?),
             ),
             PCodeOp::BoolAnd {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::bool_and(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::BoolOr {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::bool_or(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::BoolXor {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::bool_xor(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntNeg { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::int_neg(ExprT::from_space(operand, manager)),
+                Var::from_space(result, manager)?,
+                ExprT::int_neg(ExprT::from_space(operand, manager)?),
             ),
             PCodeOp::IntNot { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::int_not(ExprT::from_space(operand, manager)),
+                Var::from_space(result, manager)?,
+                ExprT::int_not(ExprT::from_space(operand, manager)?),
             ),
             PCodeOp::IntSExt { result, operand } => {
                 let size = result.size() * 8;
                 Self::assign(
-                    Var::from_space(result, manager),
-                    ExprT::cast_signed(ExprT::from_space(operand, manager), size),
+                    Var::from_space(result, manager)?,
+                    ExprT::cast_signed(ExprT::from_space(operand, manager)?, size),
                 )
             }
             PCodeOp::IntZExt { result, operand } => {
                 let size = result.size() * 8;
                 Self::assign(
-                    Var::from_space(result, manager),
-                    ExprT::cast_unsigned(ExprT::from_space(operand, manager), size),
+                    Var::from_space(result, manager)?,
+                    ExprT::cast_unsigned(ExprT::from_space(operand, manager)?, size),
                 )
             }
             PCodeOp::IntEq {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_eq(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntNotEq {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_neq(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntLess {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_lt(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntLessEq {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_le(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntSLess {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_slt(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntSLessEq {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_sle(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntCarry {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_carry(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntSCarry {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_scarry(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntSBorrow {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_sborrow(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntAdd {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_add(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntSub {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_sub(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntDiv {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_div(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntSDiv {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_sdiv(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntMul {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_mul(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntRem {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_rem(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntSRem {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_srem(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntLeftShift {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_shl(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntRightShift {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_shr(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntSRightShift {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_sar(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntAnd {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_and(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntOr {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_or(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::IntXor {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::int_xor(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                 ),
             ),
             PCodeOp::FloatIsNaN { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::float_nan(ExprT::from_space(operand, manager), &formats),
+                Var::from_space(result, manager)?,
+                ExprT::float_nan(ExprT::from_space(operand, manager)?, &formats),
             ),
             PCodeOp::FloatAbs { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::float_abs(ExprT::from_space(operand, manager), &formats),
+                Var::from_space(result, manager)?,
+                ExprT::float_abs(ExprT::from_space(operand, manager)?, &formats),
             ),
             PCodeOp::FloatNeg { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::float_neg(ExprT::from_space(operand, manager), &formats),
+                Var::from_space(result, manager)?,
+                ExprT::float_neg(ExprT::from_space(operand, manager)?, &formats),
             ),
             PCodeOp::FloatSqrt { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::float_sqrt(ExprT::from_space(operand, manager), &formats),
+                Var::from_space(result, manager)?,
+                ExprT::float_sqrt(ExprT::from_space(operand, manager)?, &formats),
             ),
             PCodeOp::FloatFloor { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::float_floor(ExprT::from_space(operand, manager), &formats),
+                Var::from_space(result, manager)?,
+                ExprT::float_floor(ExprT::from_space(operand, manager)?, &formats),
             ),
             PCodeOp::FloatCeiling { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::float_ceiling(ExprT::from_space(operand, manager), &formats),
+                Var::from_space(result, manager)?,
+                ExprT::float_ceiling(ExprT::from_space(operand, manager)?, &formats),
             ),
             PCodeOp::FloatRound { result, operand } => Self::assign(
-                Var::from_space(result, manager),
-                ExprT::float_round(ExprT::from_space(operand, manager), &formats),
+                Var::from_space(result, manager)?,
+                ExprT::float_round(ExprT::from_space(operand, manager)?, &formats),
             ),
             PCodeOp::FloatEq {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::float_eq(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                     &formats,
                 ),
             ),
@@ -3562,10 +3657,10 @@ impl StmtT<Location, BitVec, Var> {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::float_neq(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                     &formats,
                 ),
             ),
@@ -3573,10 +3668,10 @@ impl StmtT<Location, BitVec, Var> {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::float_lt(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                     &formats,
                 ),
             ),
@@ -3584,10 +3679,10 @@ impl StmtT<Location, BitVec, Var> {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::float_le(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                     &formats,
                 ),
             ),
@@ -3595,10 +3690,10 @@ impl StmtT<Location, BitVec, Var> {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::float_add(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                     &formats,
                 ),
             ),
@@ -3606,10 +3701,10 @@ impl StmtT<Location, BitVec, Var> {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::float_sub(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                     &formats,
                 ),
             ),
@@ -3617,10 +3712,10 @@ impl StmtT<Location, BitVec, Var> {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::float_div(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                     &formats,
                 ),
             ),
@@ -3628,18 +3723,18 @@ impl StmtT<Location, BitVec, Var> {
                 result,
                 operands: [operand1, operand2],
             } => Self::assign(
-                Var::from_space(result, manager),
+                Var::from_space(result, manager)?,
                 ExprT::float_mul(
-                    ExprT::from_space(operand1, manager),
-                    ExprT::from_space(operand2, manager),
+                    ExprT::from_space(operand1, manager)?,
+                    ExprT::from_space(operand2, manager)?,
                     &formats,
                 ),
             ),
             PCodeOp::FloatOfFloat { result, operand } => {
-                let input = ExprT::from_space(operand, manager);
+                let input = ExprT::from_space(operand, manager)?;
                 let input_size = input.bits();
 
-                let output = Var::from_space(result, manager);
+                let output = Var::from_space(result, manager)?;
                 let output_size = output.bits();
 
                 let input_format = formats[&input_size].clone();
@@ -3651,10 +3746,10 @@ impl StmtT<Location, BitVec, Var> {
                 )
             }
             PCodeOp::FloatOfInt { result, operand } => {
-                let input = ExprT::from_space(operand, manager);
+                let input = ExprT::from_space(operand, manager)?;
                 let input_size = input.bits();
 
-                let output = Var::from_space(result, manager);
+                let output = Var::from_space(result, manager)?;
                 let output_size = output.bits();
 
                 let format = formats[&output_size].clone();
@@ -3664,10 +3759,10 @@ impl StmtT<Location, BitVec, Var> {
                 )
             }
             PCodeOp::FloatTruncate { result, operand } => {
-                let input = ExprT::from_space(operand, manager);
+                let input = ExprT::from_space(operand, manager)?;
                 let input_size = input.bits();
 
-                let output = Var::from_space(result, manager);
+                let output = Var::from_space(result, manager)?;
                 let output_size = output.bits();
 
                 let format = formats[&input_size].clone();
@@ -3677,7 +3772,7 @@ impl StmtT<Location, BitVec, Var> {
                 )
             }
             PCodeOp::Skip => Self::skip(),
-        }
+        })
     }
 }
 
@@ -3685,7 +3780,7 @@ pub type BranchTarget = BranchTargetT<Location, BitVec, Var>;
 pub type Expr = ExprT<Location, BitVec, Var>;
 pub type Stmt = StmtT<Location, BitVec, Var>;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Deserialize, serde::Serialize)]
 pub struct ECode {
     pub address: AddressValue,
     pub operations: SmallVec<[Stmt; 8]>,
@@ -3725,20 +3820,90 @@ impl ECode {
 }
 
 impl ECode {
-    pub fn from_pcode(translator: &Translator, pcode: PCode) -> Self {
+    /// Renumbers the instruction's temporaries so that they no longer
+    /// depend upon its address; see [`crate::il::temporaries`].
+    pub fn renumber_temporaries(&mut self) {
+        let collector = TemporaryCollector::default();
+        let operations = std::mem::take(&mut self.operations)
+            .into_iter()
+            .map(|op| op.translate(&collector))
+            .collect::<SmallVec<[Stmt; 8]>>();
+
+        let renumbering = Renumbering::new(collector.0.into_inner());
+
+        self.operations = operations
+            .into_iter()
+            .map(|op| op.translate(&renumbering))
+            .collect();
+    }
+}
+
+#[derive(Default)]
+struct TemporaryCollector(RefCell<Vec<(u64, usize)>>);
+
+impl TranslateIR<Location, BitVec, Var> for TemporaryCollector {
+    type TLoc = Location;
+    type TVal = BitVec;
+    type TVar = Var;
+
+    fn translate_loc(&self, loc: Location) -> Location {
+        loc
+    }
+
+    fn translate_val(&self, val: BitVec) -> BitVec {
+        val
+    }
+
+    fn translate_var(&self, var: Var) -> Var {
+        if var.space().is_unique() {
+            self.0.borrow_mut().push((var.offset, var.bits / 8));
+        }
+        var
+    }
+}
+
+impl TranslateIR<Location, BitVec, Var> for Renumbering {
+    type TLoc = Location;
+    type TVal = BitVec;
+    type TVar = Var;
+
+    fn translate_loc(&self, loc: Location) -> Location {
+        loc
+    }
+
+    fn translate_val(&self, val: BitVec) -> BitVec {
+        val
+    }
+
+    fn translate_var(&self, var: Var) -> Var {
+        if var.space().is_unique() {
+            Var {
+                offset: self.offset(var.offset),
+                ..var
+            }
+        } else {
+            var
+        }
+    }
+}
+
+impl ECode {
+    /// Translates lifted p-code into ECode; fails if an operation refers
+    /// to a space not managed by `translator`.
+    pub fn from_pcode(translator: &Translator, pcode: PCode) -> Result<Self, Error> {
         let address = pcode.address;
         let mut operations = SmallVec::with_capacity(pcode.operations.len());
 
         for (i, op) in pcode.operations.into_iter().enumerate() {
-            operations.push(StmtT::from_pcode(translator, op, &address, i));
+            operations.push(StmtT::from_pcode(translator, op, &address, i)?);
         }
 
-        Self {
+        Ok(Self {
             operations,
             address,
             delay_slots: pcode.delay_slots,
             length: pcode.length,
-        }
+        })
     }
 }
 
@@ -3790,12 +3955,12 @@ impl<'ecode, 'trans> fmt::Display for ECodeFormatter<'ecode, 'trans> {
                 write!(
                     f,
                     "{}{}{}.{}{:02}{}: {}{}",
-                    self.fmt.location_start,
+                    self.fmt.start(DisplayTokenKind::Location),
                     self.ecode.address,
-                    self.fmt.location_end,
-                    self.fmt.location_start,
+                    self.fmt.end(DisplayTokenKind::Location),
+                    self.fmt.start(DisplayTokenKind::Location),
                     i,
-                    self.fmt.location_end,
+                    self.fmt.end(DisplayTokenKind::Location),
                     op.display_full(Cow::Borrowed(&*self.fmt)),
                     if i == len - 1 { "" } else { "\n" }
                 )?;
@@ -3805,11 +3970,11 @@ impl<'ecode, 'trans> fmt::Display for ECodeFormatter<'ecode, 'trans> {
             write!(
                 f,
                 "{}{}{}.{}00{}: skip",
-                self.fmt.location_start,
-                self.fmt.location_end,
+                self.fmt.start(DisplayTokenKind::Location),
+                self.fmt.end(DisplayTokenKind::Location),
                 self.ecode.address,
-                self.fmt.location_start,
-                self.fmt.location_end
+                self.fmt.start(DisplayTokenKind::Location),
+                self.fmt.end(DisplayTokenKind::Location)
             )
         }
     }
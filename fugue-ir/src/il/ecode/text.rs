@@ -0,0 +1,855 @@
+//! A textual syntax for ECode that is parsed back without loss, for
+//! IL-level tests, golden files, and hand-written IL (e.g., for hooks and
+//! stubs).
+//!
+//! Unlike the `Display` implementations, which are intended to be read
+//! and abbreviate details such as address space identifiers, the syntax
+//! records every component of the IL. It is a prefix notation:
+//!
+//! ```text
+//! ecode    := (ecode ADDRESS LENGTH DELAY-SLOTS STMT*)
+//! stmt     := (assign VAR EXPR) | (store EXPR EXPR BITS SPACE)
+//!           | (branch TARGET) | (cbranch EXPR TARGET) | (call TARGET EXPR*)
+//!           | (return TARGET) | (skip) | (intrinsic NAME EXPR*)
+//! target   := LOCATION | EXPR
+//! expr     := (OP EXPR) | (OP EXPR EXPR) | (cast EXPR CAST)
+//!           | (load EXPR BITS SPACE) | (ite EXPR EXPR EXPR)
+//!           | (extract EXPR LSB MSB) | (extract-high EXPR BITS)
+//!           | (extract-low EXPR BITS) | (concat EXPR EXPR)
+//!           | (call TARGET BITS EXPR*) | (intrinsic NAME BITS EXPR*)
+//!           | VAL | VAR
+//! cast     := void | bool | (signed BITS) | (unsigned BITS)
+//!           | (float SIZE SIGN-POS FRAC-POS FRAC-SIZE EXP-POS EXP-MAX EXP-SIZE BIAS J-BIT)
+//!           | (pointer CAST BITS) | (function CAST CAST*) | (named NAME BITS)
+//! var      := (var SPACE OFFSET BITS GENERATION)
+//! val      := VALUE:BITS | VALUE:BITSs
+//! location := (location ADDRESS POSITION)
+//! address  := (address SPACE WORD-SIZE HIGHEST OFFSET)
+//! ```
+//!
+//! where `OP` is the lower-case name of a `UnRel`, `BinRel`, `UnOp`, or
+//! `BinOp` (e.g., `nan`, `slt`, `popcount`, `add`), `SPACE` is the bits of
+//! an `AddressSpaceId`, `NAME` is a double-quoted string, and a trailing
+//! `s` marks a signed value. Integers are decimal, or hexadecimal if
+//! prefixed by `0x`; `;` starts a comment extending to the end of the line.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use fugue_bv::BitVec;
+use smallvec::SmallVec;
+use ustr::Ustr;
+
+use crate::address::AddressValue;
+use crate::deserialise::parse::FromStrRadix;
+use crate::float_format::FloatFormat;
+use crate::space::AddressSpaceId;
+
+use super::{
+    BinOp, BinRel, BranchTarget, BranchTargetT, Cast, ECode, Expr, ExprT, Location, Stmt, StmtT,
+    UnOp, UnRel, Var,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+    #[error("unexpected `{token}` at offset {offset}")]
+    Unexpected { token: String, offset: usize },
+    #[error("expected {expected} at offset {offset}")]
+    Expected {
+        expected: &'static str,
+        offset: usize,
+    },
+    #[error("`{form}` expects {arity} operands at offset {offset}")]
+    Arity {
+        form: String,
+        arity: usize,
+        offset: usize,
+    },
+}
+
+/// IL displayed in the textual syntax.
+pub struct Text<'a, T: ?Sized>(&'a T);
+
+pub trait ToText {
+    fn to_text(&self) -> Text<'_, Self> {
+        Text(self)
+    }
+}
+
+impl ToText for ECode {}
+impl ToText for Stmt {}
+impl ToText for Expr {}
+
+impl<'a> fmt::Display for Text<'a, ECode> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ecode = self.0;
+
+        write!(f, "(ecode ")?;
+        write_address(f, &ecode.address)?;
+        write!(f, " {} {}", ecode.length, ecode.delay_slots)?;
+
+        for stmt in ecode.operations.iter() {
+            write!(f, "\n  ")?;
+            write_stmt(f, stmt)?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+impl<'a> fmt::Display for Text<'a, Stmt> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_stmt(f, self.0)
+    }
+}
+
+impl<'a> fmt::Display for Text<'a, Expr> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_expr(f, self.0)
+    }
+}
+
+impl FromStr for ECode {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)?.ecode()
+    }
+}
+
+impl FromStr for Stmt {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)?.stmt()
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)?.expr()
+    }
+}
+
+const UNRELS: &[(UnRel, &str)] = &[(UnRel::NAN, "nan")];
+
+const BINRELS: &[(BinRel, &str)] = &[
+    (BinRel::EQ, "eq"),
+    (BinRel::NEQ, "neq"),
+    (BinRel::LT, "lt"),
+    (BinRel::LE, "le"),
+    (BinRel::SLT, "slt"),
+    (BinRel::SLE, "sle"),
+    (BinRel::SBORROW, "sborrow"),
+    (BinRel::CARRY, "carry"),
+    (BinRel::SCARRY, "scarry"),
+];
+
+const UNOPS: &[(UnOp, &str)] = &[
+    (UnOp::NOT, "not"),
+    (UnOp::NEG, "neg"),
+    (UnOp::ABS, "abs"),
+    (UnOp::SQRT, "sqrt"),
+    (UnOp::CEILING, "ceiling"),
+    (UnOp::FLOOR, "floor"),
+    (UnOp::ROUND, "round"),
+    (UnOp::POPCOUNT, "popcount"),
+];
+
+const BINOPS: &[(BinOp, &str)] = &[
+    (BinOp::AND, "and"),
+    (BinOp::OR, "or"),
+    (BinOp::XOR, "xor"),
+    (BinOp::ADD, "add"),
+    (BinOp::SUB, "sub"),
+    (BinOp::DIV, "div"),
+    (BinOp::SDIV, "sdiv"),
+    (BinOp::MUL, "mul"),
+    (BinOp::REM, "rem"),
+    (BinOp::SREM, "srem"),
+    (BinOp::SHL, "shl"),
+    (BinOp::SAR, "sar"),
+    (BinOp::SHR, "shr"),
+];
+
+fn name_of<T: PartialEq>(table: &[(T, &'static str)], op: &T) -> &'static str {
+    table
+        .iter()
+        .find_map(|(t, name)| if t == op { Some(*name) } else { None })
+        .expect("all operators are named")
+}
+
+fn op_of<T: Copy>(table: &[(T, &'static str)], name: &str) -> Option<T> {
+    table
+        .iter()
+        .find_map(|(t, n)| if *n == name { Some(*t) } else { None })
+}
+
+fn write_name(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in name.chars() {
+        if c == '"' || c == '\\' {
+            write!(f, "\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    write!(f, "\"")
+}
+
+fn write_address(f: &mut fmt::Formatter<'_>, address: &AddressValue) -> fmt::Result {
+    write!(
+        f,
+        "(address {:#x} {} {:#x} {:#x})",
+        address.space().bits(),
+        address.word_size(),
+        address.highest_offset(),
+        address.offset()
+    )
+}
+
+fn write_location(f: &mut fmt::Formatter<'_>, location: &Location) -> fmt::Result {
+    write!(f, "(location ")?;
+    write_address(f, &location.address)?;
+    write!(f, " {})", location.position)
+}
+
+fn write_var(f: &mut fmt::Formatter<'_>, var: &Var) -> fmt::Result {
+    write!(
+        f,
+        "(var {:#x} {:#x} {} {})",
+        var.space.bits(),
+        var.offset,
+        var.bits,
+        var.generation
+    )
+}
+
+fn write_val(f: &mut fmt::Formatter<'_>, val: &BitVec) -> fmt::Result {
+    write!(f, "{:x}", val.clone().unsigned())?;
+    if val.is_signed() {
+        write!(f, "s")?;
+    }
+    Ok(())
+}
+
+fn write_cast(f: &mut fmt::Formatter<'_>, cast: &Cast) -> fmt::Result {
+    match cast {
+        Cast::Void => write!(f, "void"),
+        Cast::Bool => write!(f, "bool"),
+        Cast::Signed(bits) => write!(f, "(signed {})", bits),
+        Cast::Unsigned(bits) => write!(f, "(unsigned {})", bits),
+        Cast::Float(format) => write!(
+            f,
+            "(float {} {} {} {} {} {} {} {} {})",
+            format.size,
+            format.sign_pos,
+            format.frac_pos,
+            format.frac_size,
+            format.exp_pos,
+            format.exp_max,
+            format.exp_size,
+            format.bias,
+            format.j_bit_implied
+        ),
+        Cast::Pointer(cast, bits) => {
+            write!(f, "(pointer ")?;
+            write_cast(f, cast)?;
+            write!(f, " {})", bits)
+        }
+        Cast::Function(ret, args) => {
+            write!(f, "(function ")?;
+            write_cast(f, ret)?;
+            for arg in args.iter() {
+                write!(f, " ")?;
+                write_cast(f, arg)?;
+            }
+            write!(f, ")")
+        }
+        Cast::Named(name, bits) => {
+            write!(f, "(named ")?;
+            write_name(f, name)?;
+            write!(f, " {})", bits)
+        }
+    }
+}
+
+fn write_target(f: &mut fmt::Formatter<'_>, target: &BranchTarget) -> fmt::Result {
+    match target {
+        BranchTargetT::Location(location) => write_location(f, location),
+        BranchTargetT::Computed(expr) => write_expr(f, expr),
+    }
+}
+
+fn write_args<'e, I>(f: &mut fmt::Formatter<'_>, args: I) -> fmt::Result
+where
+    I: IntoIterator<Item = &'e Expr>,
+{
+    for arg in args {
+        write!(f, " ")?;
+        write_expr(f, arg)?;
+    }
+    Ok(())
+}
+
+fn write_expr(f: &mut fmt::Formatter<'_>, expr: &Expr) -> fmt::Result {
+    match expr {
+        ExprT::UnRel(rel, e) => {
+            write!(f, "({} ", name_of(UNRELS, rel))?;
+            write_expr(f, e)?;
+            write!(f, ")")
+        }
+        ExprT::BinRel(rel, l, r) => {
+            write!(f, "({} ", name_of(BINRELS, rel))?;
+            write_expr(f, l)?;
+            write!(f, " ")?;
+            write_expr(f, r)?;
+            write!(f, ")")
+        }
+        ExprT::UnOp(op, e) => {
+            write!(f, "({} ", name_of(UNOPS, op))?;
+            write_expr(f, e)?;
+            write!(f, ")")
+        }
+        ExprT::BinOp(op, l, r) => {
+            write!(f, "({} ", name_of(BINOPS, op))?;
+            write_expr(f, l)?;
+            write!(f, " ")?;
+            write_expr(f, r)?;
+            write!(f, ")")
+        }
+        ExprT::Cast(e, cast) => {
+            write!(f, "(cast ")?;
+            write_expr(f, e)?;
+            write!(f, " ")?;
+            write_cast(f, cast)?;
+            write!(f, ")")
+        }
+        ExprT::Load(e, bits, space) => {
+            write!(f, "(load ")?;
+            write_expr(f, e)?;
+            write!(f, " {} {:#x})", bits, space.bits())
+        }
+        ExprT::IfElse(c, t, e) => {
+            write!(f, "(ite ")?;
+            write_args(f, [&**c, &**t, &**e])?;
+            write!(f, ")")
+        }
+        ExprT::Extract(e, lsb, msb) => {
+            write!(f, "(extract ")?;
+            write_expr(f, e)?;
+            write!(f, " {} {})", lsb, msb)
+        }
+        ExprT::ExtractHigh(e, bits) => {
+            write!(f, "(extract-high ")?;
+            write_expr(f, e)?;
+            write!(f, " {})", bits)
+        }
+        ExprT::ExtractLow(e, bits) => {
+            write!(f, "(extract-low ")?;
+            write_expr(f, e)?;
+            write!(f, " {})", bits)
+        }
+        ExprT::Concat(l, r) => {
+            write!(f, "(concat ")?;
+            write_args(f, [&**l, &**r])?;
+            write!(f, ")")
+        }
+        ExprT::Call(target, args, bits) => {
+            write!(f, "(call ")?;
+            write_target(f, target)?;
+            write!(f, " {}", bits)?;
+            write_args(f, args.iter().map(|arg| &**arg))?;
+            write!(f, ")")
+        }
+        ExprT::Intrinsic(name, args, bits) => {
+            write!(f, "(intrinsic ")?;
+            write_name(f, name)?;
+            write!(f, " {}", bits)?;
+            write_args(f, args.iter().map(|arg| &**arg))?;
+            write!(f, ")")
+        }
+        ExprT::Val(val) => write_val(f, val),
+        ExprT::Var(var) => write_var(f, var),
+    }
+}
+
+fn write_stmt(f: &mut fmt::Formatter<'_>, stmt: &Stmt) -> fmt::Result {
+    match stmt {
+        StmtT::Assign(var, expr) => {
+            write!(f, "(assign ")?;
+            write_var(f, var)?;
+            write_args(f, [expr])?;
+            write!(f, ")")
+        }
+        StmtT::Store(dest, src, bits, space) => {
+            write!(f, "(store")?;
+            write_args(f, [dest, src])?;
+            write!(f, " {} {:#x})", bits, space.bits())
+        }
+        StmtT::Branch(target) => {
+            write!(f, "(branch ")?;
+            write_target(f, target)?;
+            write!(f, ")")
+        }
+        StmtT::CBranch(cond, target) => {
+            write!(f, "(cbranch ")?;
+            write_expr(f, cond)?;
+            write!(f, " ")?;
+            write_target(f, target)?;
+            write!(f, ")")
+        }
+        StmtT::Call(target, args) => {
+            write!(f, "(call ")?;
+            write_target(f, target)?;
+            write_args(f, args.iter())?;
+            write!(f, ")")
+        }
+        StmtT::Return(target) => {
+            write!(f, "(return ")?;
+            write_target(f, target)?;
+            write!(f, ")")
+        }
+        StmtT::Skip => write!(f, "(skip)"),
+        StmtT::Intrinsic(name, args) => {
+            write!(f, "(intrinsic ")?;
+            write_name(f, name)?;
+            write_args(f, args.iter())?;
+            write!(f, ")")
+        }
+    }
+}
+
+enum Sexp<'s> {
+    Atom(&'s str, usize),
+    Name(String, usize),
+    List(Vec<Sexp<'s>>, usize),
+}
+
+fn parse(input: &str) -> Result<Sexp<'_>, ParseError> {
+    let mut reader = Reader { input, offset: 0 };
+    let sexp = reader.sexp()?;
+
+    reader.skip_whitespace();
+    if let Some(c) = reader.peek() {
+        return Err(ParseError::Unexpected {
+            token: c.to_string(),
+            offset: reader.offset,
+        });
+    }
+
+    Ok(sexp)
+}
+
+struct Reader<'s> {
+    input: &'s str,
+    offset: usize,
+}
+
+impl<'s> Reader<'s> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.offset..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == ';' {
+                while !matches!(self.bump(), None | Some('\n')) {}
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn sexp(&mut self) -> Result<Sexp<'s>, ParseError> {
+        self.skip_whitespace();
+
+        let start = self.offset;
+        match self.peek() {
+            None => Err(ParseError::UnexpectedEnd),
+            Some('(') => {
+                self.bump();
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        None => return Err(ParseError::UnexpectedEnd),
+                        Some(')') => {
+                            self.bump();
+                            return Ok(Sexp::List(items, start));
+                        }
+                        Some(_) => items.push(self.sexp()?),
+                    }
+                }
+            }
+            Some(')') => Err(ParseError::Unexpected {
+                token: ")".to_owned(),
+                offset: start,
+            }),
+            Some('"') => {
+                self.bump();
+                let mut name = String::new();
+                loop {
+                    match self.bump() {
+                        None => return Err(ParseError::UnexpectedEnd),
+                        Some('"') => return Ok(Sexp::Name(name, start)),
+                        Some('\\') => name.push(self.bump().ok_or(ParseError::UnexpectedEnd)?),
+                        Some(c) => name.push(c),
+                    }
+                }
+            }
+            Some(_) => {
+                while let Some(c) = self.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '"' | ';') {
+                        break;
+                    }
+                    self.bump();
+                }
+                Ok(Sexp::Atom(&self.input[start..self.offset], start))
+            }
+        }
+    }
+}
+
+impl<'s> Sexp<'s> {
+    fn offset(&self) -> usize {
+        match self {
+            Self::Atom(_, offset) | Self::Name(_, offset) | Self::List(_, offset) => *offset,
+        }
+    }
+
+    fn expected<T>(&self, expected: &'static str) -> Result<T, ParseError> {
+        Err(ParseError::Expected {
+            expected,
+            offset: self.offset(),
+        })
+    }
+
+    /// The name and operands of a form.
+    fn form(&self, expected: &'static str) -> Result<(&'s str, &[Sexp<'s>]), ParseError> {
+        match self {
+            Self::List(items, _) => match items.split_first() {
+                Some((Self::Atom(name, _), operands)) => Ok((name, operands)),
+                _ => self.expected(expected),
+            },
+            _ => self.expected(expected),
+        }
+    }
+
+    fn name(&self) -> Result<Ustr, ParseError> {
+        match self {
+            Self::Name(name, _) => Ok(Ustr::from(name)),
+            _ => self.expected("name"),
+        }
+    }
+
+    fn int<T: FromStrRadix>(&self) -> Result<T, ParseError> {
+        if let Self::Atom(atom, _) = self {
+            let parsed = match atom.strip_prefix("0x") {
+                Some(hex) => T::from_str_base(hex, 16),
+                None => T::from_str_base(atom, 10),
+            };
+            if let Ok(value) = parsed {
+                return Ok(value);
+            }
+        }
+        self.expected("integer")
+    }
+
+    fn bool(&self) -> Result<bool, ParseError> {
+        match self {
+            Self::Atom("true", _) => Ok(true),
+            Self::Atom("false", _) => Ok(false),
+            _ => self.expected("boolean"),
+        }
+    }
+
+    fn space(&self) -> Result<AddressSpaceId, ParseError> {
+        self.int().map(AddressSpaceId::from_bits)
+    }
+
+    fn ecode(&self) -> Result<ECode, ParseError> {
+        match self.form("ecode")? {
+            ("ecode", [address, length, delay_slots, operations @ ..]) => Ok(ECode {
+                address: address.address()?,
+                length: length.int()?,
+                delay_slots: delay_slots.int()?,
+                operations: operations
+                    .iter()
+                    .map(Sexp::stmt)
+                    .collect::<Result<_, _>>()?,
+            }),
+            _ => self.expected("ecode"),
+        }
+    }
+
+    fn address(&self) -> Result<AddressValue, ParseError> {
+        match self.form("address")? {
+            ("address", [space, word_size, highest, offset]) => Ok(AddressValue::from_parts(
+                space.space()?,
+                word_size.int()?,
+                highest.int()?,
+                offset.int()?,
+            )),
+            _ => self.expected("address"),
+        }
+    }
+
+    fn location(&self) -> Result<Location, ParseError> {
+        match self.form("location")? {
+            ("location", [address, position]) => {
+                Ok(Location::new(address.address()?, position.int()?))
+            }
+            _ => self.expected("location"),
+        }
+    }
+
+    fn var(&self) -> Result<Var, ParseError> {
+        match self.form("variable")? {
+            ("var", [space, offset, bits, generation]) => Ok(Var::new(
+                space.space()?,
+                offset.int()?,
+                bits.int()?,
+                generation.int()?,
+            )),
+            _ => self.expected("variable"),
+        }
+    }
+
+    fn val(&self) -> Result<BitVec, ParseError> {
+        if let Self::Atom(atom, _) = self {
+            let (atom, signed) = match atom.strip_suffix('s') {
+                Some(atom) => (atom, true),
+                None => (*atom, false),
+            };
+            if let Ok(val) = BitVec::from_str(atom) {
+                return Ok(if signed { val.signed() } else { val });
+            }
+        }
+        self.expected("value")
+    }
+
+    fn cast(&self) -> Result<Cast, ParseError> {
+        match self {
+            Self::Atom("void", _) => return Ok(Cast::Void),
+            Self::Atom("bool", _) => return Ok(Cast::Bool),
+            _ => (),
+        }
+
+        Ok(match self.form("cast")? {
+            ("signed", [bits]) => Cast::Signed(bits.int()?),
+            ("unsigned", [bits]) => Cast::Unsigned(bits.int()?),
+            (
+                "float",
+                [size, sign_pos, frac_pos, frac_size, exp_pos, exp_max, exp_size, bias, j_bit_implied],
+            ) => Cast::Float(Arc::new(FloatFormat {
+                size: size.int()?,
+                sign_pos: sign_pos.int()?,
+                frac_pos: frac_pos.int()?,
+                frac_size: frac_size.int()?,
+                exp_pos: exp_pos.int()?,
+                exp_max: exp_max.int()?,
+                exp_size: exp_size.int()?,
+                bias: bias.int()?,
+                j_bit_implied: j_bit_implied.bool()?,
+            })),
+            ("pointer", [cast, bits]) => Cast::Pointer(Box::new(cast.cast()?), bits.int()?),
+            ("function", [ret, args @ ..]) => Cast::Function(
+                Box::new(ret.cast()?),
+                args.iter()
+                    .map(|arg| arg.cast().map(Box::new))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ("named", [name, bits]) => Cast::Named(name.name()?, bits.int()?),
+            _ => return self.expected("cast"),
+        })
+    }
+
+    fn target(&self) -> Result<BranchTarget, ParseError> {
+        match self.form("branch target") {
+            Ok(("location", _)) => self.location().map(BranchTargetT::Location),
+            _ => self.expr().map(BranchTargetT::Computed),
+        }
+    }
+
+    fn args<C>(operands: &[Sexp<'s>]) -> Result<C, ParseError>
+    where
+        C: FromIterator<Expr>,
+    {
+        operands.iter().map(Sexp::expr).collect()
+    }
+
+    fn expr(&self) -> Result<Expr, ParseError> {
+        if let Self::Atom(_, _) = self {
+            return self.val().map(ExprT::Val);
+        }
+
+        let (form, operands) = self.form("expression")?;
+        let bx = |sexp: &Sexp<'s>| sexp.expr().map(Box::new);
+
+        let arity = |arity| {
+            Err(ParseError::Arity {
+                form: form.to_owned(),
+                arity,
+                offset: self.offset(),
+            })
+        };
+
+        if let Some(rel) = op_of(UNRELS, form) {
+            return match operands {
+                [e] => Ok(ExprT::UnRel(rel, bx(e)?)),
+                _ => arity(1),
+            };
+        }
+
+        if let Some(rel) = op_of(BINRELS, form) {
+            return match operands {
+                [l, r] => Ok(ExprT::BinRel(rel, bx(l)?, bx(r)?)),
+                _ => arity(2),
+            };
+        }
+
+        if let Some(op) = op_of(UNOPS, form) {
+            return match operands {
+                [e] => Ok(ExprT::UnOp(op, bx(e)?)),
+                _ => arity(1),
+            };
+        }
+
+        if let Some(op) = op_of(BINOPS, form) {
+            return match operands {
+                [l, r] => Ok(ExprT::BinOp(op, bx(l)?, bx(r)?)),
+                _ => arity(2),
+            };
+        }
+
+        match (form, operands) {
+            ("var", _) => self.var().map(ExprT::Var),
+            ("cast", [e, cast]) => Ok(ExprT::Cast(bx(e)?, cast.cast()?)),
+            ("load", [e, bits, space]) => Ok(ExprT::Load(bx(e)?, bits.int()?, space.space()?)),
+            ("ite", [c, t, e]) => Ok(ExprT::IfElse(bx(c)?, bx(t)?, bx(e)?)),
+            ("extract", [e, lsb, msb]) => Ok(ExprT::Extract(bx(e)?, lsb.int()?, msb.int()?)),
+            ("extract-high", [e, bits]) => Ok(ExprT::ExtractHigh(bx(e)?, bits.int()?)),
+            ("extract-low", [e, bits]) => Ok(ExprT::ExtractLow(bx(e)?, bits.int()?)),
+            ("concat", [l, r]) => Ok(ExprT::Concat(bx(l)?, bx(r)?)),
+            ("call", [target, bits, args @ ..]) => Ok(ExprT::Call(
+                Box::new(target.target()?),
+                args.iter().map(bx).collect::<Result<_, _>>()?,
+                bits.int()?,
+            )),
+            ("intrinsic", [name, bits, args @ ..]) => Ok(ExprT::Intrinsic(
+                name.name()?,
+                args.iter().map(bx).collect::<Result<_, _>>()?,
+                bits.int()?,
+            )),
+            ("ite" | "extract" | "load", _) => arity(3),
+            ("cast" | "extract-high" | "extract-low" | "concat", _) => arity(2),
+            _ => self.expected("expression"),
+        }
+    }
+
+    fn stmt(&self) -> Result<Stmt, ParseError> {
+        let (form, operands) = self.form("statement")?;
+        Ok(match (form, operands) {
+            ("assign", [var, expr]) => StmtT::Assign(var.var()?, expr.expr()?),
+            ("store", [dest, src, bits, space]) => {
+                StmtT::Store(dest.expr()?, src.expr()?, bits.int()?, space.space()?)
+            }
+            ("branch", [target]) => StmtT::Branch(target.target()?),
+            ("cbranch", [cond, target]) => StmtT::CBranch(cond.expr()?, target.target()?),
+            ("call", [target, args @ ..]) => {
+                StmtT::Call(target.target()?, Self::args::<SmallVec<_>>(args)?)
+            }
+            ("return", [target]) => StmtT::Return(target.target()?),
+            ("skip", []) => StmtT::Skip,
+            ("intrinsic", [name, args @ ..]) => {
+                StmtT::Intrinsic(name.name()?, Self::args::<SmallVec<_>>(args)?)
+            }
+            _ => return self.expected("statement"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::space::{AddressSpace, Space, SpaceKind};
+    use smallvec::smallvec;
+
+    #[test]
+    fn test_round_trip() -> Result<(), ParseError> {
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 8, 1, 2, None, 0));
+        let address = AddressValue::new(&ram, 0x1000);
+
+        let eax = Var::new(AddressSpaceId::register_id(1), 0, 32, 3);
+        let tmp = Var::new(AddressSpaceId::unique_id(3), 0x80, 32, 0);
+
+        let binary32 = Arc::new(FloatFormat {
+            size: 4,
+            sign_pos: 31,
+            frac_pos: 0,
+            frac_size: 23,
+            exp_pos: 23,
+            exp_max: 255,
+            exp_size: 8,
+            bias: 127,
+            j_bit_implied: true,
+        });
+
+        let ecode = ECode {
+            address,
+            operations: smallvec![
+                StmtT::assign(tmp, Expr::load(eax, 32, &ram)),
+                StmtT::assign(
+                    eax,
+                    Expr::ite(
+                        Expr::int_slt(tmp, BitVec::from_i64(-1, 32)),
+                        Expr::extract(Expr::concat(eax, tmp), 16, 48),
+                        ExprT::Cast(Box::new(tmp.into()), Cast::Float(binary32)),
+                    )
+                ),
+                StmtT::CBranch(
+                    Expr::int_eq(eax, BitVec::zero(32)),
+                    BranchTargetT::Location(Location::new(address, 0))
+                ),
+                StmtT::Intrinsic(Ustr::from("say \"hi\""), smallvec![Expr::from(tmp)]),
+                StmtT::Return(BranchTargetT::Computed(Expr::from(eax))),
+            ],
+            delay_slots: 0,
+            length: 4,
+        };
+
+        let text = ecode.to_text().to_string();
+        assert_eq!(text.parse::<ECode>()?, ecode);
+
+        let stmt = "(assign (var 0x20000001 0 8 0) ; al\n  (add 0xff:8 (var 0x20000001 0 8 0)))"
+            .parse::<Stmt>()?;
+        assert_eq!(
+            stmt.to_text().to_string(),
+            "(assign (var 0x20000001 0x0 8 0) (add 0xff:8 (var 0x20000001 0x0 8 0)))"
+        );
+
+        assert_eq!(
+            "(add 0x1:8)".parse::<Expr>(),
+            Err(ParseError::Arity {
+                form: "add".to_owned(),
+                arity: 2,
+                offset: 0
+            })
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+//! Validation of the bit-sizes of ECode expressions and statements, to
+//! catch lifter and transformation bugs before the IL is evaluated.
+//!
+//! Sizes are checked against those implied by the declared sizes of
+//! variables and values: the operands of binary operations and relations,
+//! and the branches of conditional expressions, must be of equal size;
+//! assignments and stores must be of the size of their destinations;
+//! conditions must be boolean; loads must be of whole bytes; and
+//! extractions must lie within their operands.
+
+use std::fmt;
+
+use crate::il::traits::*;
+
+use fugue_bv::BitVec;
+
+use super::visit::{walk_expr, walk_stmt};
+use super::{Cast, ECode, Expr, ExprT, ExprVisitor, Location, Stmt, StmtT, Var};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum SizeError {
+    #[error("assignment of {expr} bits to variable of {var} bits")]
+    Assign { var: usize, expr: usize },
+    #[error("store of {value} bits with size of {size} bits")]
+    Store { value: usize, size: usize },
+    #[error("operands of {lhs} and {rhs} bits")]
+    Operands { lhs: usize, rhs: usize },
+    #[error("branches of {lhs} and {rhs} bits")]
+    Branches { lhs: usize, rhs: usize },
+    #[error("condition of {0} bits")]
+    Condition(usize),
+    #[error("load of {0} bits")]
+    Load(usize),
+    #[error("extraction of bits {lsb} to {msb} from {bits} bits")]
+    Extract { lsb: usize, msb: usize, bits: usize },
+    #[error("expression of zero bits")]
+    Empty,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A size error, the location of the statement it occurs within, and the
+/// offending expression or statement, as displayed.
+pub struct Violation {
+    location: Location,
+    context: String,
+    error: SizeError,
+}
+
+impl Violation {
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    pub fn error(&self) -> &SizeError {
+        &self.error
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} in `{}`", self.location, self.error, self.context)
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// Validates the sizes of all expressions within `stmt`, which is located
+/// at `location`.
+pub fn validate_stmt(location: &Location, stmt: &Stmt) -> Vec<Violation> {
+    let mut validator = Validator {
+        location,
+        violations: Vec::new(),
+    };
+    validator.visit_stmt(stmt);
+    validator.violations
+}
+
+impl ECode {
+    /// Validates the sizes of all expressions and statements; see
+    /// [`crate::il::ecode::validate`].
+    pub fn validate(&self) -> Result<(), Vec<Violation>> {
+        let violations = self
+            .operations()
+            .iter()
+            .enumerate()
+            .flat_map(|(position, stmt)| {
+                validate_stmt(&Location::new(self.address, position), stmt)
+            })
+            .collect::<Vec<_>>();
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+struct Validator<'a> {
+    location: &'a Location,
+    violations: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    fn report<C: fmt::Display>(&mut self, context: C, error: SizeError) {
+        self.violations.push(Violation {
+            location: self.location.clone(),
+            context: context.to_string(),
+            error,
+        });
+    }
+}
+
+impl<'a, 'ecode> ExprVisitor<'ecode, Location, BitVec, Var> for Validator<'a> {
+    fn visit_stmt(&mut self, stmt: &'ecode Stmt) {
+        walk_stmt(self, stmt);
+
+        match stmt {
+            StmtT::Assign(var, expr) if var.bits() != expr.bits() => {
+                let error = SizeError::Assign {
+                    var: var.bits(),
+                    expr: expr.bits(),
+                };
+                self.report(stmt, error);
+            }
+            StmtT::Store(_, src, size, _) if src.bits() != *size => {
+                let error = SizeError::Store {
+                    value: src.bits(),
+                    size: *size,
+                };
+                self.report(stmt, error);
+            }
+            StmtT::CBranch(cond, _) if cond.bits() != 1 => {
+                self.report(stmt, SizeError::Condition(cond.bits()));
+            }
+            _ => (),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'ecode Expr) {
+        walk_expr(self, expr);
+
+        match expr {
+            ExprT::BinRel(_, l, r) | ExprT::BinOp(_, l, r) if l.bits() != r.bits() => {
+                let error = SizeError::Operands {
+                    lhs: l.bits(),
+                    rhs: r.bits(),
+                };
+                self.report(expr, error);
+            }
+            ExprT::Cast(
+                _,
+                Cast::Signed(0) | Cast::Unsigned(0) | Cast::Pointer(_, 0) | Cast::Named(_, 0),
+            ) => {
+                self.report(expr, SizeError::Empty);
+            }
+            ExprT::Load(_, bits, _) if *bits == 0 || *bits % 8 != 0 => {
+                self.report(expr, SizeError::Load(*bits));
+            }
+            ExprT::IfElse(c, t, f) => {
+                if c.bits() != 1 {
+                    self.report(expr, SizeError::Condition(c.bits()));
+                }
+                if t.bits() != f.bits() {
+                    let error = SizeError::Branches {
+                        lhs: t.bits(),
+                        rhs: f.bits(),
+                    };
+                    self.report(expr, error);
+                }
+            }
+            ExprT::Extract(e, lsb, msb) if lsb >= msb || *msb > e.bits() => {
+                let error = SizeError::Extract {
+                    lsb: *lsb,
+                    msb: *msb,
+                    bits: e.bits(),
+                };
+                self.report(expr, error);
+            }
+            ExprT::ExtractHigh(e, bits) if *bits == 0 || *bits > e.bits() => {
+                let error = SizeError::Extract {
+                    lsb: e.bits().saturating_sub(*bits),
+                    msb: e.bits(),
+                    bits: e.bits(),
+                };
+                self.report(expr, error);
+            }
+            ExprT::ExtractLow(e, bits) if *bits == 0 || *bits > e.bits() => {
+                let error = SizeError::Extract {
+                    lsb: 0,
+                    msb: *bits,
+                    bits: e.bits(),
+                };
+                self.report(expr, error);
+            }
+            ExprT::Val(_) | ExprT::Var(_) if expr.bits() == 0 => {
+                self.report(expr, SizeError::Empty);
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::address::AddressValue;
+    use crate::il::ecode::BinOp;
+    use crate::space::{AddressSpace, AddressSpaceId, Space, SpaceKind};
+    use smallvec::smallvec;
+
+    #[test]
+    fn test_validate() {
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 8, 1, 1, None, 0));
+        let address = AddressValue::new(&ram, 0x1000);
+
+        let eax = Var::new(AddressSpaceId::register_id(1), 0, 32, 0);
+        let al = Var::new(AddressSpaceId::register_id(1), 0, 8, 0);
+
+        let valid = ECode {
+            address,
+            operations: smallvec![
+                StmtT::assign(al, Expr::int_add(al, BitVec::from_u64(1, 8))),
+                StmtT::assign(eax, Expr::int_eq(al, BitVec::zero(32))),
+            ],
+            delay_slots: 0,
+            length: 2,
+        };
+        assert_eq!(valid.validate(), Ok(()));
+
+        let invalid = ECode {
+            address,
+            operations: smallvec![
+                StmtT::Skip,
+                StmtT::Assign(
+                    al,
+                    ExprT::BinOp(
+                        BinOp::ADD,
+                        Box::new(eax.into()),
+                        Box::new(BitVec::from_u64(1, 8).into()),
+                    ),
+                ),
+            ],
+            delay_slots: 0,
+            length: 2,
+        };
+
+        let violations = invalid.validate().unwrap_err();
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].location(), &Location::new(address, 1));
+        assert_eq!(
+            violations[0].error(),
+            &SizeError::Operands { lhs: 32, rhs: 8 }
+        );
+        assert_eq!(
+            violations[1].error(),
+            &SizeError::Assign { var: 8, expr: 32 }
+        );
+    }
+}
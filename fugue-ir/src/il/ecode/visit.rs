@@ -0,0 +1,671 @@
+//! Traversals of ECode statements and expressions.
+//!
+//! Visitors and rewriters override the methods for the nodes they are
+//! interested in; each method defaults to recursing into the node's
+//! children using the corresponding `walk_*` (or `rewrite_*_children`)
+//! function, which an overriding method can call to continue the
+//! traversal, or omit to prune it. Variables assigned by statements are
+//! passed to `visit_def` (or `rewrite_def`), rather than `visit_var`, so
+//! that definitions and uses can be told apart.
+//!
+//! When traversing a sequence of statements, e.g., those of an [`ECode`],
+//! each statement is first passed to `visit_stmt_at` (or
+//! `rewrite_stmt_at`) along with its position in the sequence, which
+//! visitors needing to know where they are can override.
+//!
+//! The `Try` variants of each trait stop at, and return, the first error.
+
+use fugue_bv::BitVec;
+use smallvec::SmallVec;
+
+use super::{BranchTargetT, ECode, ExprT, Location, StmtT, Var};
+
+/// The arguments of an intrinsic call.
+type Args<Loc, Val, Var> = SmallVec<[ExprT<Loc, Val, Var>; 4]>;
+
+pub trait ExprVisitor<'a, Loc: 'a, Val: 'a, Var: 'a> {
+    fn visit_stmt_at(&mut self, _position: usize, stmt: &'a StmtT<Loc, Val, Var>) {
+        self.visit_stmt(stmt)
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a StmtT<Loc, Val, Var>) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_branch_target(&mut self, target: &'a BranchTargetT<Loc, Val, Var>) {
+        walk_branch_target(self, target)
+    }
+
+    fn visit_expr(&mut self, expr: &'a ExprT<Loc, Val, Var>) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_def(&mut self, _var: &'a Var) {}
+
+    fn visit_loc(&mut self, _loc: &'a Loc) {}
+
+    fn visit_val(&mut self, _val: &'a Val) {}
+
+    fn visit_var(&mut self, _var: &'a Var) {}
+}
+
+pub fn walk_stmts<'a, V, Loc, Val, Var>(visitor: &mut V, stmts: &'a [StmtT<Loc, Val, Var>])
+where
+    V: ExprVisitor<'a, Loc, Val, Var> + ?Sized,
+{
+    for (position, stmt) in stmts.iter().enumerate() {
+        visitor.visit_stmt_at(position, stmt);
+    }
+}
+
+pub fn walk_stmt<'a, V, Loc, Val, Var>(visitor: &mut V, stmt: &'a StmtT<Loc, Val, Var>)
+where
+    V: ExprVisitor<'a, Loc, Val, Var> + ?Sized,
+{
+    match stmt {
+        StmtT::Assign(var, expr) => {
+            visitor.visit_def(var);
+            visitor.visit_expr(expr);
+        }
+        StmtT::Store(dest, src, _, _) => {
+            visitor.visit_expr(dest);
+            visitor.visit_expr(src);
+        }
+        StmtT::Branch(target) | StmtT::Return(target) => visitor.visit_branch_target(target),
+        StmtT::CBranch(cond, target) => {
+            visitor.visit_expr(cond);
+            visitor.visit_branch_target(target);
+        }
+        StmtT::Call(target, args) => {
+            visitor.visit_branch_target(target);
+            for arg in args.iter() {
+                visitor.visit_expr(arg);
+            }
+        }
+        StmtT::Intrinsic(_, args) => {
+            for arg in args.iter() {
+                visitor.visit_expr(arg);
+            }
+        }
+        StmtT::Skip => (),
+    }
+}
+
+pub fn walk_branch_target<'a, V, Loc, Val, Var>(
+    visitor: &mut V,
+    target: &'a BranchTargetT<Loc, Val, Var>,
+) where
+    V: ExprVisitor<'a, Loc, Val, Var> + ?Sized,
+{
+    match target {
+        BranchTargetT::Location(loc) => visitor.visit_loc(loc),
+        BranchTargetT::Computed(expr) => visitor.visit_expr(expr),
+    }
+}
+
+pub fn walk_expr<'a, V, Loc, Val, Var>(visitor: &mut V, expr: &'a ExprT<Loc, Val, Var>)
+where
+    V: ExprVisitor<'a, Loc, Val, Var> + ?Sized,
+{
+    match expr {
+        ExprT::UnRel(_, e)
+        | ExprT::UnOp(_, e)
+        | ExprT::Cast(e, _)
+        | ExprT::Load(e, _, _)
+        | ExprT::Extract(e, _, _)
+        | ExprT::ExtractHigh(e, _)
+        | ExprT::ExtractLow(e, _) => visitor.visit_expr(e),
+        ExprT::BinRel(_, l, r) | ExprT::BinOp(_, l, r) | ExprT::Concat(l, r) => {
+            visitor.visit_expr(l);
+            visitor.visit_expr(r);
+        }
+        ExprT::IfElse(c, t, f) => {
+            visitor.visit_expr(c);
+            visitor.visit_expr(t);
+            visitor.visit_expr(f);
+        }
+        ExprT::Call(target, args, _) => {
+            visitor.visit_branch_target(target);
+            for arg in args.iter() {
+                visitor.visit_expr(arg);
+            }
+        }
+        ExprT::Intrinsic(_, args, _) => {
+            for arg in args.iter() {
+                visitor.visit_expr(arg);
+            }
+        }
+        ExprT::Val(val) => visitor.visit_val(val),
+        ExprT::Var(var) => visitor.visit_var(var),
+    }
+}
+
+pub trait TryExprVisitor<'a, Loc: 'a, Val: 'a, Var: 'a> {
+    type Error;
+
+    fn visit_stmt_at(
+        &mut self,
+        _position: usize,
+        stmt: &'a StmtT<Loc, Val, Var>,
+    ) -> Result<(), Self::Error> {
+        self.visit_stmt(stmt)
+    }
+
+    fn visit_stmt(&mut self, stmt: &'a StmtT<Loc, Val, Var>) -> Result<(), Self::Error> {
+        try_walk_stmt(self, stmt)
+    }
+
+    fn visit_branch_target(
+        &mut self,
+        target: &'a BranchTargetT<Loc, Val, Var>,
+    ) -> Result<(), Self::Error> {
+        try_walk_branch_target(self, target)
+    }
+
+    fn visit_expr(&mut self, expr: &'a ExprT<Loc, Val, Var>) -> Result<(), Self::Error> {
+        try_walk_expr(self, expr)
+    }
+
+    fn visit_def(&mut self, _var: &'a Var) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_loc(&mut self, _loc: &'a Loc) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_val(&mut self, _val: &'a Val) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn visit_var(&mut self, _var: &'a Var) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+pub fn try_walk_stmts<'a, V, Loc, Val, Var>(
+    visitor: &mut V,
+    stmts: &'a [StmtT<Loc, Val, Var>],
+) -> Result<(), V::Error>
+where
+    V: TryExprVisitor<'a, Loc, Val, Var> + ?Sized,
+{
+    for (position, stmt) in stmts.iter().enumerate() {
+        visitor.visit_stmt_at(position, stmt)?;
+    }
+    Ok(())
+}
+
+pub fn try_walk_stmt<'a, V, Loc, Val, Var>(
+    visitor: &mut V,
+    stmt: &'a StmtT<Loc, Val, Var>,
+) -> Result<(), V::Error>
+where
+    V: TryExprVisitor<'a, Loc, Val, Var> + ?Sized,
+{
+    match stmt {
+        StmtT::Assign(var, expr) => {
+            visitor.visit_def(var)?;
+            visitor.visit_expr(expr)
+        }
+        StmtT::Store(dest, src, _, _) => {
+            visitor.visit_expr(dest)?;
+            visitor.visit_expr(src)
+        }
+        StmtT::Branch(target) | StmtT::Return(target) => visitor.visit_branch_target(target),
+        StmtT::CBranch(cond, target) => {
+            visitor.visit_expr(cond)?;
+            visitor.visit_branch_target(target)
+        }
+        StmtT::Call(target, args) => {
+            visitor.visit_branch_target(target)?;
+            args.iter().try_for_each(|arg| visitor.visit_expr(arg))
+        }
+        StmtT::Intrinsic(_, args) => args.iter().try_for_each(|arg| visitor.visit_expr(arg)),
+        StmtT::Skip => Ok(()),
+    }
+}
+
+pub fn try_walk_branch_target<'a, V, Loc, Val, Var>(
+    visitor: &mut V,
+    target: &'a BranchTargetT<Loc, Val, Var>,
+) -> Result<(), V::Error>
+where
+    V: TryExprVisitor<'a, Loc, Val, Var> + ?Sized,
+{
+    match target {
+        BranchTargetT::Location(loc) => visitor.visit_loc(loc),
+        BranchTargetT::Computed(expr) => visitor.visit_expr(expr),
+    }
+}
+
+pub fn try_walk_expr<'a, V, Loc, Val, Var>(
+    visitor: &mut V,
+    expr: &'a ExprT<Loc, Val, Var>,
+) -> Result<(), V::Error>
+where
+    V: TryExprVisitor<'a, Loc, Val, Var> + ?Sized,
+{
+    match expr {
+        ExprT::UnRel(_, e)
+        | ExprT::UnOp(_, e)
+        | ExprT::Cast(e, _)
+        | ExprT::Load(e, _, _)
+        | ExprT::Extract(e, _, _)
+        | ExprT::ExtractHigh(e, _)
+        | ExprT::ExtractLow(e, _) => visitor.visit_expr(e),
+        ExprT::BinRel(_, l, r) | ExprT::BinOp(_, l, r) | ExprT::Concat(l, r) => {
+            visitor.visit_expr(l)?;
+            visitor.visit_expr(r)
+        }
+        ExprT::IfElse(c, t, f) => {
+            visitor.visit_expr(c)?;
+            visitor.visit_expr(t)?;
+            visitor.visit_expr(f)
+        }
+        ExprT::Call(target, args, _) => {
+            visitor.visit_branch_target(target)?;
+            args.iter().try_for_each(|arg| visitor.visit_expr(arg))
+        }
+        ExprT::Intrinsic(_, args, _) => args.iter().try_for_each(|arg| visitor.visit_expr(arg)),
+        ExprT::Val(val) => visitor.visit_val(val),
+        ExprT::Var(var) => visitor.visit_var(var),
+    }
+}
+
+pub trait ExprRewriter<Loc, Val, Var> {
+    fn rewrite_stmt_at(
+        &mut self,
+        _position: usize,
+        stmt: StmtT<Loc, Val, Var>,
+    ) -> StmtT<Loc, Val, Var> {
+        self.rewrite_stmt(stmt)
+    }
+
+    fn rewrite_stmt(&mut self, stmt: StmtT<Loc, Val, Var>) -> StmtT<Loc, Val, Var> {
+        rewrite_stmt_children(self, stmt)
+    }
+
+    fn rewrite_branch_target(
+        &mut self,
+        target: BranchTargetT<Loc, Val, Var>,
+    ) -> BranchTargetT<Loc, Val, Var> {
+        rewrite_branch_target_children(self, target)
+    }
+
+    fn rewrite_expr(&mut self, expr: ExprT<Loc, Val, Var>) -> ExprT<Loc, Val, Var> {
+        rewrite_expr_children(self, expr)
+    }
+
+    fn rewrite_def(&mut self, var: Var) -> Var {
+        var
+    }
+
+    fn rewrite_loc(&mut self, loc: Loc) -> Loc {
+        loc
+    }
+
+    fn rewrite_val(&mut self, val: Val) -> Val {
+        val
+    }
+
+    fn rewrite_var(&mut self, var: Var) -> Var {
+        var
+    }
+}
+
+pub fn rewrite_stmts<R, Loc, Val, Var>(rewriter: &mut R, stmts: &mut [StmtT<Loc, Val, Var>])
+where
+    R: ExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    for (position, stmt) in stmts.iter_mut().enumerate() {
+        let taken = std::mem::replace(stmt, StmtT::Skip);
+        *stmt = rewriter.rewrite_stmt_at(position, taken);
+    }
+}
+
+pub fn rewrite_stmt_children<R, Loc, Val, Var>(
+    rewriter: &mut R,
+    stmt: StmtT<Loc, Val, Var>,
+) -> StmtT<Loc, Val, Var>
+where
+    R: ExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    match stmt {
+        StmtT::Assign(var, expr) => {
+            let var = rewriter.rewrite_def(var);
+            StmtT::Assign(var, rewriter.rewrite_expr(expr))
+        }
+        StmtT::Store(dest, src, size, space) => {
+            let dest = rewriter.rewrite_expr(dest);
+            StmtT::Store(dest, rewriter.rewrite_expr(src), size, space)
+        }
+        StmtT::Branch(target) => StmtT::Branch(rewriter.rewrite_branch_target(target)),
+        StmtT::CBranch(cond, target) => {
+            let cond = rewriter.rewrite_expr(cond);
+            StmtT::CBranch(cond, rewriter.rewrite_branch_target(target))
+        }
+        StmtT::Call(target, args) => {
+            let target = rewriter.rewrite_branch_target(target);
+            StmtT::Call(target, rewrite_args(rewriter, args))
+        }
+        StmtT::Return(target) => StmtT::Return(rewriter.rewrite_branch_target(target)),
+        StmtT::Skip => StmtT::Skip,
+        StmtT::Intrinsic(name, args) => StmtT::Intrinsic(name, rewrite_args(rewriter, args)),
+    }
+}
+
+pub fn rewrite_branch_target_children<R, Loc, Val, Var>(
+    rewriter: &mut R,
+    target: BranchTargetT<Loc, Val, Var>,
+) -> BranchTargetT<Loc, Val, Var>
+where
+    R: ExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    match target {
+        BranchTargetT::Location(loc) => BranchTargetT::Location(rewriter.rewrite_loc(loc)),
+        BranchTargetT::Computed(expr) => BranchTargetT::Computed(rewriter.rewrite_expr(expr)),
+    }
+}
+
+pub fn rewrite_expr_children<R, Loc, Val, Var>(
+    rewriter: &mut R,
+    expr: ExprT<Loc, Val, Var>,
+) -> ExprT<Loc, Val, Var>
+where
+    R: ExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    let mut bx = |e: Box<ExprT<Loc, Val, Var>>| Box::new(rewriter.rewrite_expr(*e));
+
+    match expr {
+        ExprT::UnRel(rel, e) => ExprT::UnRel(rel, bx(e)),
+        ExprT::BinRel(rel, l, r) => {
+            let l = bx(l);
+            ExprT::BinRel(rel, l, bx(r))
+        }
+        ExprT::UnOp(op, e) => ExprT::UnOp(op, bx(e)),
+        ExprT::BinOp(op, l, r) => {
+            let l = bx(l);
+            ExprT::BinOp(op, l, bx(r))
+        }
+        ExprT::Cast(e, cast) => ExprT::Cast(bx(e), cast),
+        ExprT::Load(e, size, space) => ExprT::Load(bx(e), size, space),
+        ExprT::IfElse(c, t, f) => {
+            let c = bx(c);
+            let t = bx(t);
+            ExprT::IfElse(c, t, bx(f))
+        }
+        ExprT::Extract(e, lsb, msb) => ExprT::Extract(bx(e), lsb, msb),
+        ExprT::ExtractHigh(e, bits) => ExprT::ExtractHigh(bx(e), bits),
+        ExprT::ExtractLow(e, bits) => ExprT::ExtractLow(bx(e), bits),
+        ExprT::Concat(l, r) => {
+            let l = bx(l);
+            ExprT::Concat(l, bx(r))
+        }
+        ExprT::Call(target, args, bits) => {
+            let target = Box::new(rewriter.rewrite_branch_target(*target));
+            let args = args
+                .into_iter()
+                .map(|arg| Box::new(rewriter.rewrite_expr(*arg)))
+                .collect();
+            ExprT::Call(target, args, bits)
+        }
+        ExprT::Intrinsic(name, args, bits) => {
+            ExprT::Intrinsic(name, args.into_iter().map(bx).collect(), bits)
+        }
+        ExprT::Val(val) => ExprT::Val(rewriter.rewrite_val(val)),
+        ExprT::Var(var) => ExprT::Var(rewriter.rewrite_var(var)),
+    }
+}
+
+fn rewrite_args<R, Loc, Val, Var>(
+    rewriter: &mut R,
+    args: Args<Loc, Val, Var>,
+) -> Args<Loc, Val, Var>
+where
+    R: ExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    args.into_iter()
+        .map(|arg| rewriter.rewrite_expr(arg))
+        .collect()
+}
+
+pub trait TryExprRewriter<Loc, Val, Var> {
+    type Error;
+
+    fn rewrite_stmt_at(
+        &mut self,
+        _position: usize,
+        stmt: StmtT<Loc, Val, Var>,
+    ) -> Result<StmtT<Loc, Val, Var>, Self::Error> {
+        self.rewrite_stmt(stmt)
+    }
+
+    fn rewrite_stmt(
+        &mut self,
+        stmt: StmtT<Loc, Val, Var>,
+    ) -> Result<StmtT<Loc, Val, Var>, Self::Error> {
+        try_rewrite_stmt_children(self, stmt)
+    }
+
+    fn rewrite_branch_target(
+        &mut self,
+        target: BranchTargetT<Loc, Val, Var>,
+    ) -> Result<BranchTargetT<Loc, Val, Var>, Self::Error> {
+        try_rewrite_branch_target_children(self, target)
+    }
+
+    fn rewrite_expr(
+        &mut self,
+        expr: ExprT<Loc, Val, Var>,
+    ) -> Result<ExprT<Loc, Val, Var>, Self::Error> {
+        try_rewrite_expr_children(self, expr)
+    }
+
+    fn rewrite_def(&mut self, var: Var) -> Result<Var, Self::Error> {
+        Ok(var)
+    }
+
+    fn rewrite_loc(&mut self, loc: Loc) -> Result<Loc, Self::Error> {
+        Ok(loc)
+    }
+
+    fn rewrite_val(&mut self, val: Val) -> Result<Val, Self::Error> {
+        Ok(val)
+    }
+
+    fn rewrite_var(&mut self, var: Var) -> Result<Var, Self::Error> {
+        Ok(var)
+    }
+}
+
+/// Rewrites each statement of `stmts` in place; on error, the statements
+/// preceding the failing statement remain rewritten and the failing
+/// statement is replaced by a skip.
+pub fn try_rewrite_stmts<R, Loc, Val, Var>(
+    rewriter: &mut R,
+    stmts: &mut [StmtT<Loc, Val, Var>],
+) -> Result<(), R::Error>
+where
+    R: TryExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    for (position, stmt) in stmts.iter_mut().enumerate() {
+        let taken = std::mem::replace(stmt, StmtT::Skip);
+        *stmt = rewriter.rewrite_stmt_at(position, taken)?;
+    }
+    Ok(())
+}
+
+pub fn try_rewrite_stmt_children<R, Loc, Val, Var>(
+    rewriter: &mut R,
+    stmt: StmtT<Loc, Val, Var>,
+) -> Result<StmtT<Loc, Val, Var>, R::Error>
+where
+    R: TryExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    Ok(match stmt {
+        StmtT::Assign(var, expr) => {
+            let var = rewriter.rewrite_def(var)?;
+            StmtT::Assign(var, rewriter.rewrite_expr(expr)?)
+        }
+        StmtT::Store(dest, src, size, space) => {
+            let dest = rewriter.rewrite_expr(dest)?;
+            StmtT::Store(dest, rewriter.rewrite_expr(src)?, size, space)
+        }
+        StmtT::Branch(target) => StmtT::Branch(rewriter.rewrite_branch_target(target)?),
+        StmtT::CBranch(cond, target) => {
+            let cond = rewriter.rewrite_expr(cond)?;
+            StmtT::CBranch(cond, rewriter.rewrite_branch_target(target)?)
+        }
+        StmtT::Call(target, args) => {
+            let target = rewriter.rewrite_branch_target(target)?;
+            StmtT::Call(target, try_rewrite_args(rewriter, args)?)
+        }
+        StmtT::Return(target) => StmtT::Return(rewriter.rewrite_branch_target(target)?),
+        StmtT::Skip => StmtT::Skip,
+        StmtT::Intrinsic(name, args) => StmtT::Intrinsic(name, try_rewrite_args(rewriter, args)?),
+    })
+}
+
+pub fn try_rewrite_branch_target_children<R, Loc, Val, Var>(
+    rewriter: &mut R,
+    target: BranchTargetT<Loc, Val, Var>,
+) -> Result<BranchTargetT<Loc, Val, Var>, R::Error>
+where
+    R: TryExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    Ok(match target {
+        BranchTargetT::Location(loc) => BranchTargetT::Location(rewriter.rewrite_loc(loc)?),
+        BranchTargetT::Computed(expr) => BranchTargetT::Computed(rewriter.rewrite_expr(expr)?),
+    })
+}
+
+pub fn try_rewrite_expr_children<R, Loc, Val, Var>(
+    rewriter: &mut R,
+    expr: ExprT<Loc, Val, Var>,
+) -> Result<ExprT<Loc, Val, Var>, R::Error>
+where
+    R: TryExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    let mut bx = |e: Box<ExprT<Loc, Val, Var>>| rewriter.rewrite_expr(*e).map(Box::new);
+
+    Ok(match expr {
+        ExprT::UnRel(rel, e) => ExprT::UnRel(rel, bx(e)?),
+        ExprT::BinRel(rel, l, r) => {
+            let l = bx(l)?;
+            ExprT::BinRel(rel, l, bx(r)?)
+        }
+        ExprT::UnOp(op, e) => ExprT::UnOp(op, bx(e)?),
+        ExprT::BinOp(op, l, r) => {
+            let l = bx(l)?;
+            ExprT::BinOp(op, l, bx(r)?)
+        }
+        ExprT::Cast(e, cast) => ExprT::Cast(bx(e)?, cast),
+        ExprT::Load(e, size, space) => ExprT::Load(bx(e)?, size, space),
+        ExprT::IfElse(c, t, f) => {
+            let c = bx(c)?;
+            let t = bx(t)?;
+            ExprT::IfElse(c, t, bx(f)?)
+        }
+        ExprT::Extract(e, lsb, msb) => ExprT::Extract(bx(e)?, lsb, msb),
+        ExprT::ExtractHigh(e, bits) => ExprT::ExtractHigh(bx(e)?, bits),
+        ExprT::ExtractLow(e, bits) => ExprT::ExtractLow(bx(e)?, bits),
+        ExprT::Concat(l, r) => {
+            let l = bx(l)?;
+            ExprT::Concat(l, bx(r)?)
+        }
+        ExprT::Call(target, args, bits) => {
+            let target = Box::new(rewriter.rewrite_branch_target(*target)?);
+            let args = args
+                .into_iter()
+                .map(|arg| rewriter.rewrite_expr(*arg).map(Box::new))
+                .collect::<Result<_, _>>()?;
+            ExprT::Call(target, args, bits)
+        }
+        ExprT::Intrinsic(name, args, bits) => ExprT::Intrinsic(
+            name,
+            args.into_iter().map(bx).collect::<Result<_, _>>()?,
+            bits,
+        ),
+        ExprT::Val(val) => ExprT::Val(rewriter.rewrite_val(val)?),
+        ExprT::Var(var) => ExprT::Var(rewriter.rewrite_var(var)?),
+    })
+}
+
+fn try_rewrite_args<R, Loc, Val, Var>(
+    rewriter: &mut R,
+    args: Args<Loc, Val, Var>,
+) -> Result<Args<Loc, Val, Var>, R::Error>
+where
+    R: TryExprRewriter<Loc, Val, Var> + ?Sized,
+{
+    args.into_iter()
+        .map(|arg| rewriter.rewrite_expr(arg))
+        .collect()
+}
+
+impl ECode {
+    /// Visits each statement, passing its position to `visit_stmt_at`.
+    pub fn visit<'a, V>(&'a self, visitor: &mut V)
+    where
+        V: ExprVisitor<'a, Location, BitVec, Var> + ?Sized,
+    {
+        walk_stmts(visitor, self.operations())
+    }
+
+    /// Rewrites each statement in place, passing its position to
+    /// `rewrite_stmt_at`.
+    pub fn rewrite<R>(&mut self, rewriter: &mut R)
+    where
+        R: ExprRewriter<Location, BitVec, Var> + ?Sized,
+    {
+        rewrite_stmts(rewriter, self.operations_mut())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Stmt = StmtT<(), u64, &'static str>;
+    type Expr = ExprT<(), u64, &'static str>;
+
+    struct Uses(Vec<&'static str>);
+
+    impl<'a> ExprVisitor<'a, (), u64, &'static str> for Uses {
+        fn visit_var(&mut self, var: &'a &'static str) {
+            self.0.push(var);
+        }
+    }
+
+    struct Rename;
+
+    impl TryExprRewriter<(), u64, &'static str> for Rename {
+        type Error = &'static str;
+
+        fn rewrite_var(&mut self, var: &'static str) -> Result<&'static str, Self::Error> {
+            match var {
+                "x" => Ok("y"),
+                "y" => Err(var),
+                _ => Ok(var),
+            }
+        }
+    }
+
+    #[test]
+    fn test_visit_rewrite() {
+        let add = |l, r| Expr::BinOp(crate::il::ecode::BinOp::ADD, Box::new(l), Box::new(r));
+        let stmt = Stmt::Assign("z", add(Expr::Var("x"), add(Expr::Val(1), Expr::Var("w"))));
+
+        let mut uses = Uses(Vec::new());
+        uses.visit_stmt(&stmt);
+        assert_eq!(uses.0, vec!["x", "w"]);
+
+        let renamed = Rename.rewrite_stmt(stmt.clone()).unwrap();
+        assert_eq!(
+            renamed,
+            Stmt::Assign("z", add(Expr::Var("y"), add(Expr::Val(1), Expr::Var("w"))))
+        );
+        assert_eq!(Rename.rewrite_stmt(renamed), Err("y"));
+    }
+}
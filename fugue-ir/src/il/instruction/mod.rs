@@ -2,8 +2,9 @@ use std::fmt;
 use bumpalo::collections::String as BString;
 
 use crate::AddressValue;
+use crate::il::traits::{DisplayToken, DisplayTokenKind};
 
-pub use crate::disassembly::symbol::{Operand, Operands};
+pub use crate::disassembly::symbol::{Operand, Operands, Token, Tokens};
 
 #[derive(Debug, Clone)]
 pub struct Instruction<'z> {
@@ -40,6 +41,85 @@ impl<'z> Instruction<'z> {
     }
 }
 
+#[derive(Debug, Clone)]
+/// An instruction's display output as typed tokens, as produced by
+/// [`Translator::disassemble_tokens`](crate::Translator::disassemble_tokens).
+pub struct InstructionTokens<'z> {
+    pub address: AddressValue,
+    pub mnemonic_tokens: Tokens<'z, 'z>,
+    pub operand_tokens: Tokens<'z, 'z>,
+    pub delay_slots: usize,
+    pub length: usize,
+}
+
+impl<'z> InstructionTokens<'z> {
+    pub fn address(&self) -> AddressValue {
+        self.address
+    }
+
+    pub fn mnemonic_tokens(&self) -> &Tokens<'z, 'z> {
+        &self.mnemonic_tokens
+    }
+
+    pub fn operand_tokens(&self) -> &Tokens<'z, 'z> {
+        &self.operand_tokens
+    }
+
+    pub fn delay_slots(&self) -> usize {
+        self.delay_slots
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// The tokens of the instruction's display output, typed according to
+    /// the decoded operands: registers are variables, addresses are
+    /// locations, and the literal text of the mnemonic is a keyword.
+    pub fn display_tokens(&self) -> Vec<DisplayToken> {
+        let mnemonic = trimmed(&self.mnemonic_tokens, DisplayTokenKind::Keyword);
+        let operands = trimmed(&self.operand_tokens, DisplayTokenKind::Text);
+
+        let mut tokens = vec![
+            DisplayToken::new(DisplayTokenKind::Location, self.address.to_string()),
+            DisplayToken::new(DisplayTokenKind::Text, " "),
+        ];
+        tokens.extend(mnemonic);
+        if !operands.is_empty() {
+            tokens.push(DisplayToken::new(DisplayTokenKind::Text, " "));
+            tokens.extend(operands);
+        }
+        tokens
+    }
+}
+
+// converts decoded tokens to display tokens, with the literal text of the
+// instruction given `literal` kind, and surrounding whitespace removed
+fn trimmed(tokens: &Tokens, literal: DisplayTokenKind) -> Vec<DisplayToken> {
+    let mut tokens = tokens
+        .iter()
+        .map(|token| {
+            let kind = match token {
+                Token::Address(_) => DisplayTokenKind::Location,
+                Token::Register(_) => DisplayTokenKind::Variable,
+                Token::Value(_) => DisplayTokenKind::Value,
+                Token::Symbol(_) => literal,
+            };
+            DisplayToken::new(kind, token.to_string())
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(first) = tokens.first_mut() {
+        *first = DisplayToken::new(first.kind(), first.text().trim_start());
+    }
+    if let Some(last) = tokens.last_mut() {
+        *last = DisplayToken::new(last.kind(), last.text().trim_end());
+    }
+
+    tokens.retain(|token| !token.text().is_empty());
+    tokens
+}
+
 pub struct InstructionFormatter<'insn> {
     insn: &'insn Instruction<'insn>,
 }
@@ -89,3 +169,70 @@ impl<'a, 'z> InstructionFull<'a, 'z> {
         self.length
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::disassembly::IRBuilderArena;
+    use crate::space::{AddressSpace, Space, SpaceKind};
+
+    use super::*;
+
+    #[test]
+    fn test_display_tokens() {
+        let irb = IRBuilderArena::with_capacity(1024);
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 4, 1, 1, None, 0));
+
+        let mut mnemonic_tokens = Tokens::new(&irb);
+        mnemonic_tokens.push(Token::symbol("ldr"));
+
+        // r0, [sp, #-0x10]
+        let mut operand_tokens = Tokens::new(&irb);
+        operand_tokens.push(Token::register("r0"));
+        operand_tokens.push(Token::symbol(", ["));
+        operand_tokens.push(Token::register("sp"));
+        operand_tokens.push(Token::symbol(", #"));
+        operand_tokens.push(-0x10i64);
+        operand_tokens.push(Token::symbol("] "));
+
+        let address = AddressValue::new(&ram, 0x1000);
+
+        let insn = Instruction {
+            address,
+            mnemonic: BString::from_str_in("ldr ", irb.inner()),
+            operands: BString::from_str_in("r0, [sp, #-0x10] ", irb.inner()),
+            delay_slots: 0,
+            length: 4,
+        };
+
+        let tokens = InstructionTokens {
+            address,
+            mnemonic_tokens,
+            operand_tokens,
+            delay_slots: 0,
+            length: 4,
+        }
+        .display_tokens();
+
+        assert_eq!(
+            tokens.iter().map(DisplayToken::text).collect::<String>(),
+            insn.display().to_string()
+        );
+        assert_eq!(
+            tokens.iter().map(DisplayToken::kind).collect::<Vec<_>>(),
+            [
+                DisplayTokenKind::Location,
+                DisplayTokenKind::Text,
+                DisplayTokenKind::Keyword,
+                DisplayTokenKind::Text,
+                DisplayTokenKind::Variable,
+                DisplayTokenKind::Text,
+                DisplayTokenKind::Variable,
+                DisplayTokenKind::Text,
+                DisplayTokenKind::Value,
+                DisplayTokenKind::Text,
+            ]
+        );
+        assert_eq!(tokens[8].text(), "-0x10");
+        assert_eq!(tokens[9].text(), "]");
+    }
+}
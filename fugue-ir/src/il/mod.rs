@@ -1,3 +1,5 @@
+pub mod dataflow;
+
 pub mod ecode;
 pub use ecode::{Location, ECode, ECodeFormatter};
 
@@ -7,4 +9,6 @@ pub use pcode::{PCode, PCodeFormatter};
 pub mod instruction;
 pub use instruction::{Instruction, InstructionFormatter};
 
+pub mod temporaries;
+
 pub mod traits;
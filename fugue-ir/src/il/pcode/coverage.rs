@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::disassembly::{LinearSweep, Opcode, RecoveryPolicy, SweepItem};
+use crate::translator::Translator;
+
+use super::{PCode, PCodeOp};
+
+/// Tallies the p-code operations and user-defined operations (intrinsics)
+/// that appear in a language's lifted output across a corpus, e.g., to
+/// determine which an emulator must implement before running code from
+/// the corpus.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Coverage {
+    instructions: usize,
+    undecoded: usize,
+    operations: BTreeMap<Opcode, usize>,
+    intrinsics: BTreeMap<String, usize>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tallies the operations of a lifted instruction.
+    pub fn add(&mut self, pcode: &PCode) {
+        self.instructions += 1;
+
+        for op in pcode.operations() {
+            if let Some(opcode) = op.opcode() {
+                *self.operations.entry(opcode).or_default() += 1;
+            }
+
+            if let PCodeOp::Intrinsic { name, .. } = op {
+                *self.intrinsics.entry(name.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    /// Lifts `bytes` as a linear sweep from `address`, tallying the
+    /// operations of each instruction; bytes that cannot be lifted are
+    /// counted as undecoded, and the sweep resumes at the next aligned
+    /// address.
+    pub fn scan(&mut self, translator: &Translator, address: u64, bytes: &[u8]) {
+        let mut db = translator.context_database();
+        let items = LinearSweep::new(translator)
+            .policy(RecoveryPolicy::NextAlignment)
+            .pcode(&mut db, translator.address(address), bytes);
+
+        for item in items {
+            match item {
+                SweepItem::Instruction(pcode) => self.add(&pcode),
+                SweepItem::BadInstruction(bad) => self.undecoded += bad.length(),
+            }
+        }
+    }
+
+    /// Combines the tallies of `other`, e.g., of a scan of another part of
+    /// the corpus.
+    pub fn merge(&mut self, other: &Coverage) {
+        self.instructions += other.instructions;
+        self.undecoded += other.undecoded;
+
+        for (opcode, count) in other.operations.iter() {
+            *self.operations.entry(*opcode).or_default() += count;
+        }
+
+        for (name, count) in other.intrinsics.iter() {
+            *self.intrinsics.entry(name.clone()).or_default() += count;
+        }
+    }
+
+    /// The number of instructions lifted.
+    pub fn instructions(&self) -> usize {
+        self.instructions
+    }
+
+    /// The number of bytes that could not be lifted.
+    pub fn undecoded(&self) -> usize {
+        self.undecoded
+    }
+
+    /// The number of occurrences of each opcode.
+    pub fn operations(&self) -> &BTreeMap<Opcode, usize> {
+        &self.operations
+    }
+
+    /// The number of occurrences of each intrinsic, by name.
+    pub fn intrinsics(&self) -> &BTreeMap<String, usize> {
+        &self.intrinsics
+    }
+
+    /// The opcodes that appear, but are not handled according to
+    /// `handled`, e.g., by an evaluator.
+    pub fn unhandled_operations<F>(&self, mut handled: F) -> Vec<Opcode>
+    where
+        F: FnMut(Opcode) -> bool,
+    {
+        self.operations
+            .keys()
+            .copied()
+            .filter(|opcode| !handled(*opcode))
+            .collect()
+    }
+
+    /// The intrinsics that appear, but are not handled according to
+    /// `handled`, e.g., as no evaluator handler is registered for them.
+    pub fn unhandled_intrinsics<F>(&self, mut handled: F) -> Vec<&str>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.intrinsics
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !handled(name))
+            .collect()
+    }
+
+    /// The intrinsics declared by `translator`'s language that do not
+    /// appear.
+    pub fn unseen_intrinsics<'t>(&self, translator: &'t Translator) -> Vec<&'t str> {
+        translator
+            .user_ops()
+            .iter()
+            .map(|name| name.as_str())
+            .filter(|name| !self.intrinsics.contains_key(*name))
+            .collect()
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} instructions lifted, {} bytes undecoded",
+            self.instructions, self.undecoded
+        )?;
+
+        writeln!(f, "\noperations:")?;
+        for (opcode, count) in self.operations.iter() {
+            writeln!(f, "    {:<16} {}", opcode.name(), count)?;
+        }
+
+        if !self.intrinsics.is_empty() {
+            writeln!(f, "\nintrinsics:")?;
+            for (name, count) in self.intrinsics.iter() {
+                writeln!(f, "    {:<16} {}", name, count)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use smallvec::smallvec;
+    use ustr::Ustr;
+
+    use crate::address::AddressValue;
+    use crate::il::pcode::Operand;
+    use crate::space::{AddressSpace, Space, SpaceKind};
+
+    use super::*;
+
+    #[test]
+    fn test_coverage() {
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 4, 1, 1, None, 0));
+        let register = |name: &str| Operand::Register {
+            name: Ustr::from(name),
+            offset: 0,
+            size: 4,
+        };
+
+        // r0 = r0 + 1; coproc_moveto(r0)
+        let pcode = PCode {
+            address: AddressValue::new(&ram, 0x1000),
+            operations: smallvec![
+                PCodeOp::IntAdd {
+                    result: register("r0"),
+                    operands: [register("r0"), Operand::Constant { value: 1, size: 4 }],
+                },
+                PCodeOp::Intrinsic {
+                    name: Ustr::from("coproc_moveto"),
+                    operands: smallvec![register("r0")],
+                    result: None,
+                },
+            ],
+            delay_slots: 0,
+            length: 4,
+        };
+
+        let mut coverage = Coverage::new();
+        coverage.add(&pcode);
+        coverage.add(&PCode::nop(AddressValue::new(&ram, 0x1004), 4));
+
+        assert_eq!(coverage.instructions(), 2);
+        assert_eq!(coverage.undecoded(), 0);
+        assert_eq!(coverage.operations().get(&Opcode::IntAdd), Some(&1));
+        assert_eq!(coverage.intrinsics().get("coproc_moveto"), Some(&1));
+
+        assert_eq!(coverage.unhandled_intrinsics(|_| false), ["coproc_moveto"]);
+        assert!(coverage.unhandled_intrinsics(|name| name == "coproc_moveto").is_empty());
+        assert_eq!(coverage.unhandled_operations(|_| false), [Opcode::CallOther, Opcode::IntAdd]);
+        assert!(coverage.unhandled_operations(|_| true).is_empty());
+
+        let mut merged = coverage.clone();
+        merged.merge(&coverage);
+        assert_eq!(merged.instructions(), 4);
+        assert_eq!(merged.operations().get(&Opcode::IntAdd), Some(&2));
+        assert_eq!(merged.intrinsics().get("coproc_moveto"), Some(&2));
+
+        let text = merged.to_string();
+        assert!(text.starts_with("4 instructions lifted, 0 bytes undecoded"));
+        assert!(text.contains("coproc_moveto"));
+    }
+}
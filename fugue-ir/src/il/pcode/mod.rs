@@ -5,15 +5,25 @@ use crate::address::AddressValue;
 use crate::space::AddressSpaceId;
 use crate::space_manager::SpaceManager;
 
+use crate::il::temporaries::Renumbering;
 use crate::register::RegisterNames;
 
+pub mod coverage;
+pub use coverage::Coverage;
+
 pub mod operand;
 pub use operand::Operand;
 
 pub mod register;
 pub use register::Register;
 
-use smallvec::SmallVec;
+pub mod summary;
+pub use summary::{MemoryAccess, MemoryAccessKind, OperandSummary};
+
+pub mod validate;
+pub use validate::{Anomaly, Diagnostic, Validator};
+
+use smallvec::{smallvec, SmallVec};
 
 use unsafe_unwrap::UnsafeUnwrap;
 
@@ -384,6 +394,9 @@ impl PCodeOp {
             log::trace!("lifting opcode {opcode:?}");
         }
 
+        // SAFETY: the operations are issued by `IRBuilder` from the
+        // specification's templates, and SLEIGH gives each opcode's template
+        // the inputs and output the opcode requires
         unsafe { match opcode {
             Opcode::Copy => PCodeOp::Copy {
                 destination: Operand::from_varnodedata(manager, registers, output.unsafe_unwrap()),
@@ -762,6 +775,164 @@ impl PCodeOp {
     pub fn skip() -> Self {
         PCodeOp::Skip
     }
+
+    /// The opcode the operation was lifted from; `None` for skipped
+    /// operations.
+    pub fn opcode(&self) -> Option<Opcode> {
+        Some(match self {
+            Self::Copy { .. } => Opcode::Copy,
+            Self::Load { .. } => Opcode::Load,
+            Self::Store { .. } => Opcode::Store,
+            Self::Branch { .. } => Opcode::Branch,
+            Self::CBranch { .. } => Opcode::CBranch,
+            Self::IBranch { .. } => Opcode::IBranch,
+            Self::Call { .. } => Opcode::Call,
+            Self::ICall { .. } => Opcode::ICall,
+            Self::Intrinsic { .. } => Opcode::CallOther,
+            Self::Return { .. } => Opcode::Return,
+            Self::IntEq { .. } => Opcode::IntEq,
+            Self::IntNotEq { .. } => Opcode::IntNotEq,
+            Self::IntLess { .. } => Opcode::IntLess,
+            Self::IntLessEq { .. } => Opcode::IntLessEq,
+            Self::IntSLess { .. } => Opcode::IntSLess,
+            Self::IntSLessEq { .. } => Opcode::IntSLessEq,
+            Self::IntZExt { .. } => Opcode::IntZExt,
+            Self::IntSExt { .. } => Opcode::IntSExt,
+            Self::IntAdd { .. } => Opcode::IntAdd,
+            Self::IntSub { .. } => Opcode::IntSub,
+            Self::IntCarry { .. } => Opcode::IntCarry,
+            Self::IntSCarry { .. } => Opcode::IntSCarry,
+            Self::IntSBorrow { .. } => Opcode::IntSBorrow,
+            Self::IntNeg { .. } => Opcode::IntNeg,
+            Self::IntNot { .. } => Opcode::IntNot,
+            Self::IntXor { .. } => Opcode::IntXor,
+            Self::IntAnd { .. } => Opcode::IntAnd,
+            Self::IntOr { .. } => Opcode::IntOr,
+            Self::IntLeftShift { .. } => Opcode::IntLShift,
+            Self::IntRightShift { .. } => Opcode::IntRShift,
+            Self::IntSRightShift { .. } => Opcode::IntSRShift,
+            Self::IntMul { .. } => Opcode::IntMul,
+            Self::IntDiv { .. } => Opcode::IntDiv,
+            Self::IntSDiv { .. } => Opcode::IntSDiv,
+            Self::IntRem { .. } => Opcode::IntRem,
+            Self::IntSRem { .. } => Opcode::IntSRem,
+            Self::BoolNot { .. } => Opcode::BoolNot,
+            Self::BoolXor { .. } => Opcode::BoolXor,
+            Self::BoolAnd { .. } => Opcode::BoolAnd,
+            Self::BoolOr { .. } => Opcode::BoolOr,
+            Self::FloatEq { .. } => Opcode::FloatEq,
+            Self::FloatNotEq { .. } => Opcode::FloatNotEq,
+            Self::FloatLess { .. } => Opcode::FloatLess,
+            Self::FloatLessEq { .. } => Opcode::FloatLessEq,
+            Self::FloatIsNaN { .. } => Opcode::FloatIsNaN,
+            Self::FloatAdd { .. } => Opcode::FloatAdd,
+            Self::FloatDiv { .. } => Opcode::FloatDiv,
+            Self::FloatMul { .. } => Opcode::FloatMul,
+            Self::FloatSub { .. } => Opcode::FloatSub,
+            Self::FloatNeg { .. } => Opcode::FloatNeg,
+            Self::FloatAbs { .. } => Opcode::FloatAbs,
+            Self::FloatSqrt { .. } => Opcode::FloatSqrt,
+            Self::FloatOfInt { .. } => Opcode::FloatOfInt,
+            Self::FloatOfFloat { .. } => Opcode::FloatOfFloat,
+            Self::FloatTruncate { .. } => Opcode::FloatTruncate,
+            Self::FloatCeiling { .. } => Opcode::FloatCeiling,
+            Self::FloatFloor { .. } => Opcode::FloatFloor,
+            Self::FloatRound { .. } => Opcode::FloatRound,
+            Self::Subpiece { .. } => Opcode::Subpiece,
+            Self::PopCount { .. } => Opcode::PopCount,
+            Self::Skip => return None,
+        })
+    }
+
+    /// The operation's operands, including its output (if any).
+    pub fn operands_mut(&mut self) -> SmallVec<[&mut Operand; 4]> {
+        match self {
+            PCodeOp::Copy {
+                source,
+                destination,
+            }
+            | PCodeOp::Load {
+                source,
+                destination,
+                ..
+            }
+            | PCodeOp::Store {
+                source,
+                destination,
+                ..
+            } => smallvec![source, destination],
+            PCodeOp::Branch { destination }
+            | PCodeOp::IBranch { destination }
+            | PCodeOp::Call { destination }
+            | PCodeOp::ICall { destination }
+            | PCodeOp::Return { destination } => smallvec![destination],
+            PCodeOp::CBranch {
+                destination,
+                condition,
+            } => smallvec![destination, condition],
+            PCodeOp::Intrinsic {
+                operands, result, ..
+            } => operands.iter_mut().chain(result.as_mut()).collect(),
+            PCodeOp::IntEq { result, operands }
+            | PCodeOp::IntNotEq { result, operands }
+            | PCodeOp::IntLess { result, operands }
+            | PCodeOp::IntLessEq { result, operands }
+            | PCodeOp::IntSLess { result, operands }
+            | PCodeOp::IntSLessEq { result, operands }
+            | PCodeOp::IntAdd { result, operands }
+            | PCodeOp::IntSub { result, operands }
+            | PCodeOp::IntCarry { result, operands }
+            | PCodeOp::IntSCarry { result, operands }
+            | PCodeOp::IntSBorrow { result, operands }
+            | PCodeOp::IntXor { result, operands }
+            | PCodeOp::IntAnd { result, operands }
+            | PCodeOp::IntOr { result, operands }
+            | PCodeOp::IntLeftShift { result, operands }
+            | PCodeOp::IntRightShift { result, operands }
+            | PCodeOp::IntSRightShift { result, operands }
+            | PCodeOp::IntMul { result, operands }
+            | PCodeOp::IntDiv { result, operands }
+            | PCodeOp::IntSDiv { result, operands }
+            | PCodeOp::IntRem { result, operands }
+            | PCodeOp::IntSRem { result, operands }
+            | PCodeOp::BoolXor { result, operands }
+            | PCodeOp::BoolAnd { result, operands }
+            | PCodeOp::BoolOr { result, operands }
+            | PCodeOp::FloatEq { result, operands }
+            | PCodeOp::FloatNotEq { result, operands }
+            | PCodeOp::FloatLess { result, operands }
+            | PCodeOp::FloatLessEq { result, operands }
+            | PCodeOp::FloatAdd { result, operands }
+            | PCodeOp::FloatDiv { result, operands }
+            | PCodeOp::FloatMul { result, operands }
+            | PCodeOp::FloatSub { result, operands } => {
+                let [lhs, rhs] = operands;
+                smallvec![lhs, rhs, result]
+            }
+            PCodeOp::IntZExt { result, operand }
+            | PCodeOp::IntSExt { result, operand }
+            | PCodeOp::IntNeg { result, operand }
+            | PCodeOp::IntNot { result, operand }
+            | PCodeOp::BoolNot { result, operand }
+            | PCodeOp::FloatIsNaN { result, operand }
+            | PCodeOp::FloatNeg { result, operand }
+            | PCodeOp::FloatAbs { result, operand }
+            | PCodeOp::FloatSqrt { result, operand }
+            | PCodeOp::FloatOfInt { result, operand }
+            | PCodeOp::FloatOfFloat { result, operand }
+            | PCodeOp::FloatTruncate { result, operand }
+            | PCodeOp::FloatCeiling { result, operand }
+            | PCodeOp::FloatFloor { result, operand }
+            | PCodeOp::FloatRound { result, operand }
+            | PCodeOp::PopCount { result, operand } => smallvec![operand, result],
+            PCodeOp::Subpiece {
+                result,
+                operand,
+                amount,
+            } => smallvec![operand, amount, result],
+            PCodeOp::Skip => SmallVec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -799,6 +970,39 @@ impl PCode {
         self.length
     }
 
+    /// The registers, memory, and immediates used by this instruction.
+    pub fn summary(&self) -> OperandSummary {
+        OperandSummary::from_pcode(self)
+    }
+
+    /// Renumbers the instruction's temporaries so that they no longer
+    /// depend upon its address; see [`crate::il::temporaries`].
+    pub fn renumber_temporaries(&mut self) {
+        let accesses = self
+            .operations
+            .iter_mut()
+            .flat_map(PCodeOp::operands_mut)
+            .filter_map(|operand| match operand {
+                Operand::Variable {
+                    offset,
+                    size,
+                    space,
+                } if space.is_unique() => Some((*offset, *size)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let renumbering = Renumbering::new(accesses);
+
+        for operand in self.operations.iter_mut().flat_map(PCodeOp::operands_mut) {
+            if let Operand::Variable { offset, space, .. } = operand {
+                if space.is_unique() {
+                    *offset = renumbering.offset(*offset);
+                }
+            }
+        }
+    }
+
     pub fn display<'pcode>(&'pcode self) -> PCodeFormatter<'pcode> {
         PCodeFormatter { pcode: self }
     }
@@ -67,12 +67,14 @@ impl<'z> Operand {
                 value: offset,
                 size,
             }
-        } else if space_id.is_register() {
-            // register
-            let name = registers.unchecked_get(offset, size).clone();
-
+        } else if let Some(name) = space_id
+            .is_register()
+            .then(|| registers.get(offset, size))
+            .flatten()
+        {
+            // register; unnamed register varnodes fall through to variables
             Operand::Register {
-                name,
+                name: name.clone(),
                 offset,
                 size,
             }
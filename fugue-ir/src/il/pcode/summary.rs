@@ -0,0 +1,361 @@
+use crate::space::AddressSpaceId;
+
+use super::{Operand, PCode, PCodeOp, Register};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub enum MemoryAccessKind {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+/// A memory operand of an instruction.
+pub struct MemoryAccess {
+    address: Operand,
+    size: usize,
+    space: Option<AddressSpaceId>,
+    kind: MemoryAccessKind,
+}
+
+impl MemoryAccess {
+    /// For direct accesses, the accessed `Operand::Address`; otherwise, the
+    /// operand holding the address, e.g., a register.
+    pub fn address(&self) -> &Operand {
+        &self.address
+    }
+
+    /// The accessed address, if it is known without evaluating any
+    /// registers or temporaries.
+    pub fn constant_address(&self) -> Option<u64> {
+        match self.address {
+            Operand::Address { value, .. } => Some(value.offset()),
+            Operand::Constant { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes accessed.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The space accessed by a load or store; `None` for direct accesses,
+    /// which are always to the default space.
+    pub fn space(&self) -> Option<AddressSpaceId> {
+        self.space
+    }
+
+    pub fn kind(&self) -> MemoryAccessKind {
+        self.kind
+    }
+
+    pub fn is_read(&self) -> bool {
+        matches!(self.kind, MemoryAccessKind::Read)
+    }
+
+    pub fn is_write(&self) -> bool {
+        matches!(self.kind, MemoryAccessKind::Write)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+/// The registers, memory, and immediate values used by an instruction,
+/// each listed once in the order first encountered in its operations.
+/// Temporaries (i.e., operands in the unique space) are omitted.
+///
+/// A register is listed as read if any operation reads it, even if an
+/// earlier operation of the same instruction has written to it.
+pub struct OperandSummary {
+    registers_read: Vec<Register>,
+    registers_written: Vec<Register>,
+    memory: Vec<MemoryAccess>,
+    immediates: Vec<(u64, usize)>,
+}
+
+impl OperandSummary {
+    pub fn registers_read(&self) -> &[Register] {
+        &self.registers_read
+    }
+
+    pub fn registers_written(&self) -> &[Register] {
+        &self.registers_written
+    }
+
+    pub fn memory(&self) -> &[MemoryAccess] {
+        &self.memory
+    }
+
+    pub fn memory_reads(&self) -> impl Iterator<Item = &MemoryAccess> {
+        self.memory.iter().filter(|m| m.is_read())
+    }
+
+    pub fn memory_writes(&self) -> impl Iterator<Item = &MemoryAccess> {
+        self.memory.iter().filter(|m| m.is_write())
+    }
+
+    /// Constant operands as pairs of value and size in bytes; constants
+    /// used as p-code relative branch targets are excluded.
+    pub fn immediates(&self) -> &[(u64, usize)] {
+        &self.immediates
+    }
+
+    pub fn reads_register(&self, name: &str) -> bool {
+        self.registers_read.iter().any(|r| r.name() == name)
+    }
+
+    pub fn writes_register(&self, name: &str) -> bool {
+        self.registers_written.iter().any(|r| r.name() == name)
+    }
+
+    pub fn reads_memory(&self) -> bool {
+        self.memory_reads().next().is_some()
+    }
+
+    pub fn writes_memory(&self) -> bool {
+        self.memory_writes().next().is_some()
+    }
+
+    pub fn from_pcode(pcode: &PCode) -> Self {
+        let mut summary = Self::default();
+        for op in pcode.operations() {
+            summary.add_operation(op);
+        }
+        summary
+    }
+
+    fn add_operation(&mut self, op: &PCodeOp) {
+        match op {
+            PCodeOp::Copy {
+                source,
+                destination,
+            } => {
+                self.read(source);
+                self.write(destination);
+            }
+            PCodeOp::Load {
+                source,
+                destination,
+                space,
+            } => {
+                self.read(source);
+                self.access(source, destination.size(), *space, MemoryAccessKind::Read);
+                self.write(destination);
+            }
+            PCodeOp::Store {
+                source,
+                destination,
+                space,
+            } => {
+                self.read(source);
+                self.read(destination);
+                self.access(destination, source.size(), *space, MemoryAccessKind::Write);
+            }
+            PCodeOp::Branch { .. } | PCodeOp::Call { .. } | PCodeOp::Skip => (),
+            PCodeOp::CBranch { condition, .. } => {
+                self.read(condition);
+            }
+            PCodeOp::IBranch { destination }
+            | PCodeOp::ICall { destination }
+            | PCodeOp::Return { destination } => {
+                self.read(destination);
+            }
+            PCodeOp::Intrinsic {
+                operands, result, ..
+            } => {
+                for operand in operands.iter() {
+                    self.read(operand);
+                }
+                if let Some(result) = result {
+                    self.write(result);
+                }
+            }
+            PCodeOp::IntEq { result, operands }
+            | PCodeOp::IntNotEq { result, operands }
+            | PCodeOp::IntLess { result, operands }
+            | PCodeOp::IntLessEq { result, operands }
+            | PCodeOp::IntSLess { result, operands }
+            | PCodeOp::IntSLessEq { result, operands }
+            | PCodeOp::IntAdd { result, operands }
+            | PCodeOp::IntSub { result, operands }
+            | PCodeOp::IntCarry { result, operands }
+            | PCodeOp::IntSCarry { result, operands }
+            | PCodeOp::IntSBorrow { result, operands }
+            | PCodeOp::IntXor { result, operands }
+            | PCodeOp::IntAnd { result, operands }
+            | PCodeOp::IntOr { result, operands }
+            | PCodeOp::IntLeftShift { result, operands }
+            | PCodeOp::IntRightShift { result, operands }
+            | PCodeOp::IntSRightShift { result, operands }
+            | PCodeOp::IntMul { result, operands }
+            | PCodeOp::IntDiv { result, operands }
+            | PCodeOp::IntSDiv { result, operands }
+            | PCodeOp::IntRem { result, operands }
+            | PCodeOp::IntSRem { result, operands }
+            | PCodeOp::BoolXor { result, operands }
+            | PCodeOp::BoolAnd { result, operands }
+            | PCodeOp::BoolOr { result, operands }
+            | PCodeOp::FloatEq { result, operands }
+            | PCodeOp::FloatNotEq { result, operands }
+            | PCodeOp::FloatLess { result, operands }
+            | PCodeOp::FloatLessEq { result, operands }
+            | PCodeOp::FloatAdd { result, operands }
+            | PCodeOp::FloatDiv { result, operands }
+            | PCodeOp::FloatMul { result, operands }
+            | PCodeOp::FloatSub { result, operands } => {
+                self.read(&operands[0]);
+                self.read(&operands[1]);
+                self.write(result);
+            }
+            PCodeOp::IntZExt { result, operand }
+            | PCodeOp::IntSExt { result, operand }
+            | PCodeOp::IntNeg { result, operand }
+            | PCodeOp::IntNot { result, operand }
+            | PCodeOp::BoolNot { result, operand }
+            | PCodeOp::FloatIsNaN { result, operand }
+            | PCodeOp::FloatNeg { result, operand }
+            | PCodeOp::FloatAbs { result, operand }
+            | PCodeOp::FloatSqrt { result, operand }
+            | PCodeOp::FloatOfInt { result, operand }
+            | PCodeOp::FloatOfFloat { result, operand }
+            | PCodeOp::FloatTruncate { result, operand }
+            | PCodeOp::FloatCeiling { result, operand }
+            | PCodeOp::FloatFloor { result, operand }
+            | PCodeOp::FloatRound { result, operand }
+            | PCodeOp::PopCount { result, operand }
+            | PCodeOp::Subpiece {
+                result, operand, ..
+            } => {
+                self.read(operand);
+                self.write(result);
+            }
+        }
+    }
+
+    fn read(&mut self, operand: &Operand) {
+        match operand {
+            Operand::Address { size, .. } => {
+                self.direct(operand, *size, MemoryAccessKind::Read);
+            }
+            Operand::Constant { value, size } => {
+                if !self.immediates.contains(&(*value, *size)) {
+                    self.immediates.push((*value, *size));
+                }
+            }
+            Operand::Register { .. } => {
+                let register = operand.register().unwrap();
+                if !self.registers_read.contains(&register) {
+                    self.registers_read.push(register);
+                }
+            }
+            Operand::Variable { .. } => (),
+        }
+    }
+
+    fn write(&mut self, operand: &Operand) {
+        match operand {
+            Operand::Address { size, .. } => {
+                self.direct(operand, *size, MemoryAccessKind::Write);
+            }
+            Operand::Register { .. } => {
+                let register = operand.register().unwrap();
+                if !self.registers_written.contains(&register) {
+                    self.registers_written.push(register);
+                }
+            }
+            Operand::Constant { .. } | Operand::Variable { .. } => (),
+        }
+    }
+
+    fn direct(&mut self, operand: &Operand, size: usize, kind: MemoryAccessKind) {
+        self.push_access(MemoryAccess {
+            address: operand.clone(),
+            size,
+            space: None,
+            kind,
+        });
+    }
+
+    fn access(
+        &mut self,
+        address: &Operand,
+        size: usize,
+        space: AddressSpaceId,
+        kind: MemoryAccessKind,
+    ) {
+        self.push_access(MemoryAccess {
+            address: address.clone(),
+            size,
+            space: Some(space),
+            kind,
+        });
+    }
+
+    fn push_access(&mut self, access: MemoryAccess) {
+        if !self.memory.contains(&access) {
+            self.memory.push(access);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::address::AddressValue;
+    use crate::space::AddressSpace;
+
+    #[test]
+    fn test_summarise_push() {
+        let ram = AddressSpace::constant("ram", 1);
+        let ram_id = AddressSpaceId::default_id(1);
+        let rsp = Operand::Register {
+            name: "RSP".into(),
+            offset: 0x20,
+            size: 8,
+        };
+        let rbp = Operand::Register {
+            name: "RBP".into(),
+            offset: 0x28,
+            size: 8,
+        };
+
+        // push rbp
+        let pcode = PCode {
+            address: AddressValue::new(&ram, 0x1000),
+            operations: [
+                PCodeOp::IntSub {
+                    result: rsp.clone(),
+                    operands: [rsp.clone(), Operand::Constant { value: 8, size: 8 }],
+                },
+                PCodeOp::Store {
+                    source: rbp.clone(),
+                    destination: rsp.clone(),
+                    space: ram_id,
+                },
+            ]
+            .into_iter()
+            .collect(),
+            delay_slots: 0,
+            length: 1,
+        };
+
+        let summary = pcode.summary();
+
+        assert_eq!(
+            summary.registers_read(),
+            &[rsp.register().unwrap(), rbp.register().unwrap()]
+        );
+        assert_eq!(summary.registers_written(), &[rsp.register().unwrap()]);
+        assert_eq!(summary.immediates(), &[(8, 8)]);
+        assert!(summary.writes_register("RSP") && !summary.writes_register("RBP"));
+        assert!(!summary.reads_memory());
+
+        let store = summary.memory_writes().next().unwrap();
+        assert_eq!(store.address(), &rsp);
+        assert_eq!(store.size(), 8);
+        assert_eq!(store.space(), Some(ram_id));
+        assert_eq!(store.constant_address(), None);
+    }
+}
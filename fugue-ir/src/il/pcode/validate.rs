@@ -0,0 +1,377 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::address::AddressValue;
+use crate::disassembly::{ContextDatabase, IRBuilderArena};
+use crate::error::Error;
+use crate::translator::Translator;
+
+use super::{Operand, PCode, PCodeOp};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub enum Anomaly {
+    /// A branch or call to an address outside of the plausible ranges.
+    ImplausibleTarget { operation: usize, target: u64 },
+    /// A branch or call to an address not aligned to the translator's
+    /// instruction alignment.
+    MisalignedTarget {
+        operation: usize,
+        target: u64,
+        alignment: usize,
+    },
+    /// A p-code relative branch to a position outside of the instruction's
+    /// operations.
+    RelativeTargetOutOfRange { operation: usize, offset: i64 },
+    /// An instruction with no length.
+    ZeroLength,
+    /// An instruction longer than the bytes it was lifted from.
+    LengthExceedsInput { length: usize, available: usize },
+    /// The lifted length differs from the length of the decoded
+    /// instruction (and its delay slots).
+    LengthMismatch { lifted: usize, decoded: usize },
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ImplausibleTarget { operation, target } => {
+                write!(
+                    f,
+                    "operation {} targets implausible address {:#x}",
+                    operation, target
+                )
+            }
+            Self::MisalignedTarget {
+                operation,
+                target,
+                alignment,
+            } => write!(
+                f,
+                "operation {} targets {:#x}, which is not aligned to {} bytes",
+                operation, target, alignment
+            ),
+            Self::RelativeTargetOutOfRange { operation, offset } => write!(
+                f,
+                "operation {} branches {} operations outside of the instruction",
+                operation, offset
+            ),
+            Self::ZeroLength => write!(f, "instruction has zero length"),
+            Self::LengthExceedsInput { length, available } => write!(
+                f,
+                "instruction length {} exceeds the {} bytes available",
+                length, available
+            ),
+            Self::LengthMismatch { lifted, decoded } => write!(
+                f,
+                "lifted length {} differs from decoded length {}",
+                lifted, decoded
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Diagnostic {
+    address: AddressValue,
+    anomaly: Anomaly,
+}
+
+impl Diagnostic {
+    /// The address of the instruction the anomaly was found in.
+    pub fn address(&self) -> AddressValue {
+        self.address
+    }
+
+    pub fn anomaly(&self) -> &Anomaly {
+        &self.anomaly
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.address, self.anomaly)
+    }
+}
+
+/// Checks lifted instructions for anomalies indicative of lifter bugs,
+/// e.g., branches to implausible addresses, or relative branches outside
+/// of the instruction's operations.
+///
+/// By default, any address within the instruction's address space is a
+/// plausible target; use `Validator::range` to restrict them, e.g., to the
+/// mapped segments of a binary.
+pub struct Validator<'a> {
+    translator: &'a Translator,
+    ranges: Vec<Range<u64>>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(translator: &'a Translator) -> Self {
+        Self {
+            translator,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Adds `range` to the plausible targets of branches and calls.
+    pub fn range(mut self, range: Range<u64>) -> Self {
+        self.ranges.push(range);
+        self
+    }
+
+    pub fn ranges<I>(mut self, ranges: I) -> Self
+    where
+        I: IntoIterator<Item = Range<u64>>,
+    {
+        self.ranges.extend(ranges);
+        self
+    }
+
+    /// Checks the targets of `pcode`'s direct branches and calls, and its
+    /// relative branches.
+    pub fn validate(&self, pcode: &PCode) -> Vec<Diagnostic> {
+        let mut anomalies = Vec::new();
+
+        if pcode.length() == 0 {
+            anomalies.push(Anomaly::ZeroLength);
+        }
+
+        let alignment = self.translator.alignment().max(1) as u64;
+        let highest = pcode.address.highest_offset();
+
+        for (operation, op) in pcode.operations().iter().enumerate() {
+            let destination = match op {
+                PCodeOp::Branch { destination }
+                | PCodeOp::CBranch { destination, .. }
+                | PCodeOp::Call { destination } => destination,
+                _ => continue,
+            };
+
+            match destination {
+                Operand::Address { value, .. } => {
+                    let target = value.offset();
+                    if target > highest || !self.is_plausible(target) {
+                        anomalies.push(Anomaly::ImplausibleTarget { operation, target });
+                    } else if target % alignment != 0 {
+                        anomalies.push(Anomaly::MisalignedTarget {
+                            operation,
+                            target,
+                            alignment: alignment as usize,
+                        });
+                    }
+                }
+                Operand::Constant { value, size } if !matches!(op, PCodeOp::Call { .. }) => {
+                    let offset = sign_extend(*value, *size);
+                    let position = operation as i64 + offset;
+                    if position < 0 || position > pcode.operations().len() as i64 {
+                        anomalies.push(Anomaly::RelativeTargetOutOfRange { operation, offset });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        anomalies
+            .into_iter()
+            .map(|anomaly| Diagnostic {
+                address: pcode.address(),
+                anomaly,
+            })
+            .collect()
+    }
+
+    /// Lifts the instruction at `address`, additionally checking its
+    /// length against that of the decoded instruction and the available
+    /// bytes.
+    pub fn lift_pcode(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+    ) -> Result<(PCode, Vec<Diagnostic>), Error> {
+        let irb = IRBuilderArena::with_capacity(1024);
+        let insn = self
+            .translator
+            .disassemble(db, &irb, address, bytes)?;
+        let pcode = self.translator.lift_pcode(db, address, bytes)?;
+
+        let mut diagnostics = self.validate(&pcode);
+
+        let lifted = pcode.length();
+        let decoded = insn.length();
+
+        let mismatch = if insn.delay_slots() == 0 {
+            lifted != decoded
+        } else {
+            lifted < decoded + insn.delay_slots()
+        };
+
+        if mismatch {
+            diagnostics.push(Diagnostic {
+                address: pcode.address(),
+                anomaly: Anomaly::LengthMismatch { lifted, decoded },
+            });
+        }
+
+        if lifted > bytes.len() {
+            diagnostics.push(Diagnostic {
+                address: pcode.address(),
+                anomaly: Anomaly::LengthExceedsInput {
+                    length: lifted,
+                    available: bytes.len(),
+                },
+            });
+        }
+
+        Ok((pcode, diagnostics))
+    }
+
+    fn is_plausible(&self, target: u64) -> bool {
+        self.ranges.is_empty() || self.ranges.iter().any(|range| range.contains(&target))
+    }
+}
+
+fn sign_extend(value: u64, size: usize) -> i64 {
+    if size == 0 || size >= 8 {
+        value as i64
+    } else {
+        let shift = 64 - 8 * size as u32;
+        ((value << shift) as i64) >> shift
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_arch::ArchitectureDef;
+    use fugue_bytes::Endian;
+    use smallvec::smallvec;
+
+    use super::*;
+    use crate::address::Address;
+
+    #[test]
+    fn test_validate() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("tiny", Endian::Little, 32, "default"),
+            &Default::default(),
+            "./data/test/tiny.sla",
+        )?;
+
+        let validator = Validator::new(&translator).range(0x1000..0x2000);
+
+        let address = translator.address(0x1000u64);
+        let target = |offset: u64| Operand::Address {
+            value: Address::from(offset),
+            size: 4,
+        };
+
+        let pcode = PCode {
+            address,
+            operations: smallvec![
+                PCodeOp::CBranch {
+                    destination: Operand::Constant { value: 2, size: 4 },
+                    condition: Operand::Constant { value: 1, size: 1 },
+                },
+                PCodeOp::Call {
+                    destination: target(0x1001),
+                },
+                PCodeOp::Branch {
+                    destination: target(0x1800),
+                },
+            ],
+            delay_slots: 0,
+            length: 0,
+        };
+
+        let anomalies = validator
+            .validate(&pcode)
+            .into_iter()
+            .map(|diagnostic| diagnostic.anomaly().clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            anomalies,
+            [
+                Anomaly::ZeroLength,
+                Anomaly::MisalignedTarget {
+                    operation: 1,
+                    target: 0x1001,
+                    alignment: 2,
+                },
+            ]
+        );
+
+        // nop
+        let mut db = translator.context_database();
+        let (pcode, diagnostics) = validator.lift_pcode(&mut db, address, &[0x00, 0x00])?;
+        assert_eq!(pcode.length(), 2);
+        assert!(diagnostics.is_empty());
+
+        assert!(validator.lift_pcode(&mut db, address, &[0xff, 0xff]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "requires processor specifications"]
+    fn test_validate_branches() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("ARM", Endian::Little, 32, "v7"),
+            &Default::default(),
+            "./data/processors/ARM/ARM8_le.sla",
+        )?;
+
+        let validator = Validator::new(&translator).range(0x1000..0x2000);
+
+        let address = translator.address(0x1000u64);
+        let constant = |value| Operand::Constant { value, size: 4 };
+
+        let pcode = PCode {
+            address,
+            operations: smallvec![
+                PCodeOp::Branch {
+                    destination: constant(1),
+                },
+                PCodeOp::Branch {
+                    destination: constant(0xffff_fffe),
+                },
+                PCodeOp::Branch {
+                    destination: Operand::Address {
+                        value: Address::from(0x3000u64),
+                        size: 4,
+                    },
+                },
+            ],
+            delay_slots: 0,
+            length: 4,
+        };
+
+        let diagnostics = validator.validate(&pcode);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].anomaly(),
+            &Anomaly::RelativeTargetOutOfRange {
+                operation: 1,
+                offset: -2
+            }
+        );
+        assert_eq!(
+            diagnostics[1].anomaly(),
+            &Anomaly::ImplausibleTarget {
+                operation: 2,
+                target: 0x3000
+            }
+        );
+
+        // b 0x1008
+        let mut db = translator.context_database();
+        let (_, diagnostics) = validator.lift_pcode(&mut db, address, &[0x00, 0x00, 0x00, 0xea])?;
+        assert!(diagnostics.is_empty());
+
+        Ok(())
+    }
+}
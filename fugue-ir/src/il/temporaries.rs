@@ -0,0 +1,113 @@
+//! Deterministic renumbering of temporaries (i.e., varnodes in the unique
+//! space).
+//!
+//! The offsets of temporaries allocated while lifting are salted with the
+//! address of the instruction they are allocated for, hence two lifts of
+//! the same bytes at different addresses yield IL that differs only in
+//! the offsets of its temporaries. Renumbering replaces these offsets with
+//! ones that depend only upon the order in which temporaries are first
+//! used, so that such lifts produce identical IL.
+
+/// The alignment of each renumbered temporary.
+const ALIGNMENT: u64 = 0x10;
+
+/// A mapping from the offsets of temporaries to their renumbered offsets.
+///
+/// Overlapping accesses (e.g., reading the low bytes of a wider temporary)
+/// are mapped to the same renumbered temporary, preserving their relative
+/// offsets. Renumbered temporaries are allocated from offset zero, in
+/// order of first use.
+#[derive(Debug, Clone, Default)]
+pub struct Renumbering {
+    // (original start, original end, renumbered start)
+    ranges: Vec<(u64, u64, u64)>,
+}
+
+impl Renumbering {
+    /// Builds a renumbering from the offsets and sizes, in bytes, of each
+    /// access to a temporary, in the order they occur.
+    pub fn new<I>(accesses: I) -> Self
+    where
+        I: IntoIterator<Item = (u64, usize)>,
+    {
+        // (start, end, first use)
+        let mut ranges = Vec::<(u64, u64, usize)>::new();
+
+        for (index, (offset, size)) in accesses.into_iter().enumerate() {
+            let mut start = offset;
+            let mut end = offset.saturating_add(size.max(1) as u64);
+            let mut first = index;
+
+            // merging may cause the range to overlap others; repeat until
+            // it does not
+            loop {
+                let count = ranges.len();
+                ranges.retain(|&(s, e, f)| {
+                    if s < end && start < e {
+                        start = start.min(s);
+                        end = end.max(e);
+                        first = first.min(f);
+                        false
+                    } else {
+                        true
+                    }
+                });
+
+                if ranges.len() == count {
+                    break;
+                }
+            }
+
+            ranges.push((start, end, first));
+        }
+
+        ranges.sort_by_key(|&(_, _, first)| first);
+
+        let mut next = 0;
+        let ranges = ranges
+            .into_iter()
+            .map(|(start, end, _)| {
+                let renumbered = next;
+                next = (renumbered + (end - start) + ALIGNMENT - 1) & !(ALIGNMENT - 1);
+                (start, end, renumbered)
+            })
+            .collect();
+
+        Self { ranges }
+    }
+
+    /// The renumbered offset of `offset`; offsets not covered by any
+    /// access are returned unchanged.
+    pub fn offset(&self, offset: u64) -> u64 {
+        self.ranges
+            .iter()
+            .find(|&&(start, end, _)| start <= offset && offset < end)
+            .map(|&(start, _, renumbered)| renumbered + (offset - start))
+            .unwrap_or(offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_renumber_overlapping() {
+        let renumbering = Renumbering::new([(0x1a80, 4), (0x1a00, 8), (0x1a04, 4), (0x1a80, 4)]);
+
+        assert_eq!(renumbering.offset(0x1a80), 0);
+        assert_eq!(renumbering.offset(0x1a00), 0x10);
+        assert_eq!(renumbering.offset(0x1a04), 0x14);
+        assert_eq!(renumbering.offset(0x2000), 0x2000);
+
+        // the same accesses, salted differently
+        let salted = Renumbering::new([(0x3a80, 4), (0x3a00, 8), (0x3a04, 4), (0x3a80, 4)]);
+
+        for offset in [0x80, 0x00, 0x04] {
+            assert_eq!(
+                renumbering.offset(0x1a00 + offset),
+                salted.offset(0x3a00 + offset)
+            );
+        }
+    }
+}
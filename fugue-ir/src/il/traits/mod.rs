@@ -1,9 +1,12 @@
+use crate::address::AddressValue;
 use crate::space::AddressSpaceId;
 use crate::translator::Translator;
 use fugue_bv::BitVec;
 
 use std::borrow::Cow;
-use std::fmt;
+use std::cell::{Cell, RefCell};
+use std::fmt::{self, Write as _};
+use std::rc::Rc;
 
 pub trait BitSize {
     fn bits(&self) -> usize;
@@ -26,9 +29,29 @@ pub trait TranslateIR<Loc, Val, Var> {
     fn translate_var(&self, var: Var) -> Self::TVar;
 }
 
+/// Resolves addresses to symbols for display, e.g., using a database of
+/// the functions within a program.
+pub trait SymbolResolver {
+    /// The name of the symbol containing `address` and the offset of
+    /// `address` from its start.
+    fn resolve(&self, address: &AddressValue) -> Option<(String, u64)>;
+}
+
+impl<F> SymbolResolver for F
+where
+    F: Fn(&AddressValue) -> Option<(String, u64)>,
+{
+    fn resolve(&self, address: &AddressValue) -> Option<(String, u64)> {
+        self(address)
+    }
+}
+
 #[derive(Clone)]
 pub struct TranslatorFormatter<'t> {
     pub translator: Option<&'t Translator>,
+    /// Displays locations relative to the symbols they resolve to, e.g.,
+    /// `memcpy+0x12`, rather than as raw addresses.
+    pub symbols: Option<&'t dyn SymbolResolver>,
     pub branch_start: &'t str,
     pub branch_end: &'t str,
     pub keyword_start: &'t str,
@@ -41,12 +64,16 @@ pub struct TranslatorFormatter<'t> {
     pub value_end: &'t str,
     pub variable_start: &'t str,
     pub variable_end: &'t str,
+    /// Collects the tokens of the output, in addition to writing it; see
+    /// [`TranslatorDisplay::display_tokens`].
+    pub tokens: Option<Rc<DisplayTokenSink>>,
 }
 
 impl<'t> Default for TranslatorFormatter<'t> {
     fn default() -> Self {
         Self {
             translator: None,
+            symbols: None,
             branch_start: "",
             branch_end: "",
             keyword_start: "",
@@ -59,6 +86,7 @@ impl<'t> Default for TranslatorFormatter<'t> {
             value_end: "",
             variable_start: "",
             variable_end: "",
+            tokens: None,
         }
     }
 }
@@ -81,6 +109,159 @@ impl<'t> From<Option<&'t Translator>> for TranslatorFormatter<'t> {
     }
 }
 
+impl<'t> TranslatorFormatter<'t> {
+    pub fn symbols(self, symbols: &'t dyn SymbolResolver) -> Self {
+        Self {
+            symbols: Some(symbols),
+            ..self
+        }
+    }
+
+    /// The delimiter that opens a span of `kind`.
+    pub fn start(&self, kind: DisplayTokenKind) -> SpanDelimiter<'_> {
+        SpanDelimiter {
+            fmt: self,
+            kind,
+            start: true,
+        }
+    }
+
+    /// The delimiter that closes a span of `kind`.
+    pub fn end(&self, kind: DisplayTokenKind) -> SpanDelimiter<'_> {
+        SpanDelimiter {
+            fmt: self,
+            kind,
+            start: false,
+        }
+    }
+}
+
+/// Opens or closes a span of formatted output; it displays as the
+/// formatter's delimiter for the kind of span, and records the span's
+/// bounds in the formatter's token sink, if any.
+pub struct SpanDelimiter<'f> {
+    fmt: &'f TranslatorFormatter<'f>,
+    kind: DisplayTokenKind,
+    start: bool,
+}
+
+impl<'f> fmt::Display for SpanDelimiter<'f> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (start, end) = match self.kind {
+            DisplayTokenKind::Text => ("", ""),
+            DisplayTokenKind::Branch => (self.fmt.branch_start, self.fmt.branch_end),
+            DisplayTokenKind::Keyword => (self.fmt.keyword_start, self.fmt.keyword_end),
+            DisplayTokenKind::Location => (self.fmt.location_start, self.fmt.location_end),
+            DisplayTokenKind::Type => (self.fmt.type_start, self.fmt.type_end),
+            DisplayTokenKind::Value => (self.fmt.value_start, self.fmt.value_end),
+            DisplayTokenKind::Variable => (self.fmt.variable_start, self.fmt.variable_end),
+        };
+
+        if let Some(ref tokens) = self.fmt.tokens {
+            if self.start {
+                tokens.enter(self.kind);
+            } else {
+                tokens.exit();
+            }
+        }
+
+        f.write_str(if self.start { start } else { end })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum DisplayTokenKind {
+    Text,
+    Branch,
+    Keyword,
+    Location,
+    Type,
+    Value,
+    Variable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+/// A span of displayed output and the kind of entity it represents; the
+/// concatenation of a sequence of tokens is the plain display output.
+pub struct DisplayToken {
+    kind: DisplayTokenKind,
+    text: String,
+}
+
+impl DisplayToken {
+    pub fn new<S: Into<String>>(kind: DisplayTokenKind, text: S) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+        }
+    }
+
+    pub fn kind(&self) -> DisplayTokenKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+#[derive(Debug, Default)]
+/// Collects the tokens of output written using a [`TranslatorFormatter`]
+/// that refers to it; where spans are nested, text is attributed to the
+/// innermost span.
+pub struct DisplayTokenSink {
+    tokens: RefCell<Vec<DisplayToken>>,
+    kinds: RefCell<Vec<DisplayTokenKind>>,
+    // set at span bounds, so adjacent spans of the same kind are kept apart
+    split: Cell<bool>,
+}
+
+impl DisplayTokenSink {
+    fn enter(&self, kind: DisplayTokenKind) {
+        self.kinds.borrow_mut().push(kind);
+        self.split.set(true);
+    }
+
+    fn exit(&self) {
+        self.kinds.borrow_mut().pop();
+        self.split.set(true);
+    }
+
+    fn push_str(&self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        let kind = self
+            .kinds
+            .borrow()
+            .last()
+            .copied()
+            .unwrap_or(DisplayTokenKind::Text);
+
+        let mut tokens = self.tokens.borrow_mut();
+        match tokens.last_mut() {
+            Some(last) if !self.split.get() && last.kind == kind => last.text.push_str(text),
+            _ => tokens.push(DisplayToken::new(kind, text)),
+        }
+
+        self.split.set(false);
+    }
+
+    /// Takes the tokens collected so far.
+    pub fn take(&self) -> Vec<DisplayToken> {
+        self.split.set(true);
+        std::mem::take(&mut *self.tokens.borrow_mut())
+    }
+}
+
+impl fmt::Write for &'_ DisplayTokenSink {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        self.push_str(text);
+        Ok(())
+    }
+}
+
 pub trait TranslatorDisplay<'v, 't> {
     type Target: fmt::Display;
 
@@ -89,6 +270,21 @@ pub trait TranslatorDisplay<'v, 't> {
     }
 
     fn display_full(&'v self, display: Cow<'t, TranslatorFormatter<'t>>) -> Self::Target;
+
+    /// Formats `self` as a sequence of tokens rather than a string, so that
+    /// frontends can apply their own highlighting to each kind of span.
+    fn display_tokens(&'v self, translator: Option<&'t Translator>) -> Vec<DisplayToken> {
+        let sink = Rc::new(DisplayTokenSink::default());
+        let fmt = TranslatorFormatter {
+            translator,
+            tokens: Some(sink.clone()),
+            ..Default::default()
+        };
+
+        // writes to a sink cannot fail
+        let _ = write!(&*sink, "{}", self.display_full(Cow::Owned(fmt)));
+        sink.take()
+    }
 }
 
 impl BitSize for BitVec {
@@ -112,12 +308,12 @@ impl<'v, 't> fmt::Display for BitVecFormatter<'v, 't> {
         write!(
             f,
             "{}{:#x}{}:{}{}{}",
-            self.fmt.value_start,
+            self.fmt.start(DisplayTokenKind::Value),
             &*v,
-            self.fmt.value_end,
-            self.fmt.value_start,
+            self.fmt.end(DisplayTokenKind::Value),
+            self.fmt.start(DisplayTokenKind::Value),
             self.bv.bits(),
-            self.fmt.value_end
+            self.fmt.end(DisplayTokenKind::Value)
         )
     }
 }
@@ -135,3 +331,79 @@ impl<'v, 't> TranslatorDisplay<'v, 't> for BitVec {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bitvec_tokens() {
+        let tokens = BitVec::from_u64(0x10, 32).display_tokens(None);
+        assert_eq!(
+            tokens,
+            vec![
+                DisplayToken::new(DisplayTokenKind::Value, "0x10"),
+                DisplayToken::new(DisplayTokenKind::Text, ":"),
+                DisplayToken::new(DisplayTokenKind::Value, "32"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_statement_tokens() {
+        use crate::il::ecode::{BinOp, BranchTarget, Expr, Location, Stmt};
+        use crate::space::{AddressSpace, Space, SpaceKind};
+
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 4, 1, 1, None, 0));
+
+        let sum = Expr::BinOp(
+            BinOp::ADD,
+            Box::new(Expr::Val(BitVec::from_u64(1, 8))),
+            Box::new(Expr::Val(BitVec::from_u64(2, 8))),
+        );
+        let branch = Stmt::Branch(BranchTarget::location(Location::new(
+            AddressValue::new(&ram, 0x10),
+            0,
+        )));
+
+        let tokens = sum.display_tokens(None);
+        assert_eq!(
+            tokens.iter().map(DisplayToken::text).collect::<String>(),
+            sum.display_with(None).to_string()
+        );
+        assert_eq!(tokens[4], DisplayToken::new(DisplayTokenKind::Keyword, "+"));
+        assert_eq!(tokens[6], DisplayToken::new(DisplayTokenKind::Value, "0x2"));
+
+        // the location is attributed to the innermost span
+        assert_eq!(
+            branch.display_tokens(None),
+            vec![
+                DisplayToken::new(DisplayTokenKind::Keyword, "goto"),
+                DisplayToken::new(DisplayTokenKind::Text, " "),
+                DisplayToken::new(DisplayTokenKind::Branch, "0x10.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_symbolised_targets() {
+        use crate::il::ecode::{BranchTarget, Location};
+        use crate::space::{AddressSpace, Space, SpaceKind};
+
+        let ram = AddressSpace::Space(Space::new(SpaceKind::Processor, "ram", 4, 1, 1, None, 0));
+        let resolver = |address: &AddressValue| {
+            let offset = address.offset().checked_sub(0x1000)?;
+            Some(("memcpy".to_owned(), offset))
+        };
+
+        let fmt = TranslatorFormatter::default().symbols(&resolver);
+        let target = |offset| {
+            let target = BranchTarget::location(Location::new(AddressValue::new(&ram, offset), 0));
+            target.display_full(Cow::Borrowed(&fmt)).to_string()
+        };
+
+        assert_eq!(target(0x1012), "memcpy+0x12");
+        assert_eq!(target(0x1000), "memcpy");
+        assert_eq!(target(0x10), "0x10.0");
+    }
+}
@@ -187,6 +187,10 @@ impl<'a> LanguageBuilder<'a> {
             &self.language.sla_file,
         )?;
 
+        for (name, alias) in self.language.processor_spec.register_aliases() {
+            translator.add_register_alias(name, alias, false);
+        }
+
         if apply_context {
             self.apply_context(&mut translator)
         }
@@ -239,6 +243,62 @@ impl LanguageDB {
             .map(|language| LanguageBuilder { language })
     }
 
+    /// Finds languages for the same processor as `def` (compared without
+    /// regard to case), ranked by how closely they match it: a matching
+    /// endian is preferred over matching bits, which is preferred over a
+    /// matching variant. Where the variant does not match, the processor's
+    /// default variant is preferred.
+    pub fn find<'a>(&'a self, def: &ArchitectureDef) -> Vec<LanguageBuilder<'a>> {
+        let mut candidates = self
+            .db
+            .iter()
+            .filter(|(cdef, _)| cdef.processor().eq_ignore_ascii_case(def.processor()))
+            .map(|(cdef, language)| {
+                let score = (cdef.endian() == def.endian()) as u8 * 8
+                    + (cdef.bits() == def.bits()) as u8 * 4
+                    + cdef.variant().eq_ignore_ascii_case(def.variant()) as u8 * 2
+                    + (cdef.variant() == "default") as u8;
+                (score, cdef, language)
+            })
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|(sa, da, _), (sb, db, _)| sb.cmp(sa).then_with(|| da.cmp(db)));
+        candidates
+            .into_iter()
+            .map(|(_, _, language)| LanguageBuilder { language })
+            .collect()
+    }
+
+    /// Finds languages for `name`, which may be a language identifier, a
+    /// common name for an architecture, or a target triple; see
+    /// [`fugue_arch::resolve`]. Candidates are ranked as by
+    /// [`LanguageDB::find`].
+    pub fn resolve<'a, S: AsRef<str>>(
+        &'a self,
+        name: S,
+    ) -> Result<Vec<LanguageBuilder<'a>>, ArchDefParseError> {
+        let def = fugue_arch::resolve(name.as_ref())?;
+        Ok(self.find(&def))
+    }
+
+    /// Proposes languages for the binary image `bytes` based on its ELF,
+    /// PE, or Mach-O headers; see [`fugue_arch::detect`]. Candidates are
+    /// ordered from most to least likely.
+    pub fn detect<'a>(&'a self, bytes: &[u8]) -> Vec<LanguageBuilder<'a>> {
+        let mut candidates = Vec::<LanguageBuilder<'a>>::new();
+        for def in fugue_arch::detect(bytes) {
+            for builder in self.find(&def) {
+                if !candidates
+                    .iter()
+                    .any(|c| std::ptr::eq(c.language, builder.language))
+                {
+                    candidates.push(builder);
+                }
+            }
+        }
+        candidates
+    }
+
     pub fn definitions<'a>(&'a self) -> impl Iterator<Item = &'a ArchitectureDef> {
         self.db.keys()
     }
@@ -409,3 +469,88 @@ impl LanguageDB {
             })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fixture() -> Result<LanguageDB, Box<dyn std::error::Error>> {
+        let processor_spec =
+            PSpec::from_str(r#"<processor_spec><programcounter register="pc"/></processor_spec>"#)?;
+
+        let db = [
+            "x86:LE:32:default",
+            "x86:LE:64:default",
+            "ARM:LE:32:v7",
+            "ARM:LE:32:v8",
+            "ARM:BE:32:v8",
+            "AARCH64:LE:64:v8A",
+            "AARCH64:LE:32:ilp32",
+            "PowerPC:BE:32:default",
+        ]
+        .into_iter()
+        .map(|id| {
+            let architecture = id.parse::<ArchitectureDef>()?;
+            let language = Language {
+                id: id.to_owned(),
+                architecture: architecture.clone(),
+                version: "1.0".to_owned(),
+                sla_file: PathBuf::from(format!("{}.sla", id)),
+                processor_spec: processor_spec.clone(),
+                compiler_specs: Map::default(),
+            };
+            Ok((architecture, language))
+        })
+        .collect::<Result<_, ArchDefParseError>>()?;
+
+        Ok(LanguageDB { db })
+    }
+
+    fn ids(builders: Vec<LanguageBuilder<'_>>) -> Vec<&str> {
+        builders.into_iter().map(|b| b.language().id()).collect()
+    }
+
+    #[test]
+    fn test_find() -> Result<(), Box<dyn std::error::Error>> {
+        let db = fixture()?;
+
+        let found = ids(db.find(&"arm:LE:32:v7".parse()?));
+        assert_eq!(found, ["ARM:LE:32:v7", "ARM:LE:32:v8", "ARM:BE:32:v8"]);
+
+        let found = ids(db.find(&"AARCH64:LE:32:ilp32".parse()?));
+        assert_eq!(found, ["AARCH64:LE:32:ilp32", "AARCH64:LE:64:v8A"]);
+
+        assert!(db.find(&"MIPS:BE:32:default".parse()?).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect() -> Result<(), Box<dyn std::error::Error>> {
+        let db = fixture()?;
+
+        // ELF, x86-64
+        let mut elf = vec![0u8; 64];
+        elf[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        elf[18..20].copy_from_slice(&62u16.to_le_bytes());
+        assert_eq!(ids(db.detect(&elf)), ["x86:LE:64:default", "x86:LE:32:default"]);
+
+        // PE, ARM
+        let mut pe = vec![0u8; 0x80];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+        pe[0x44..0x46].copy_from_slice(&0x01c0u16.to_le_bytes());
+        assert_eq!(ids(db.detect(&pe)), ["ARM:LE:32:v8", "ARM:LE:32:v7", "ARM:BE:32:v8"]);
+
+        // Mach-O, arm64_32
+        let mut macho = vec![0u8; 28];
+        macho[..4].copy_from_slice(&[0xce, 0xfa, 0xed, 0xfe]);
+        macho[4..8].copy_from_slice(&0x0200_000cu32.to_le_bytes());
+        assert_eq!(ids(db.detect(&macho)), ["AARCH64:LE:32:ilp32", "AARCH64:LE:64:v8A"]);
+
+        assert!(db.detect(b"#!/bin/sh").is_empty());
+
+        Ok(())
+    }
+}
@@ -3,22 +3,36 @@ extern crate roxmltree as xml;
 mod bits;
 
 pub mod address;
+pub mod address_map;
+#[cfg(feature = "capstone-display")]
+pub mod capstone_display;
 pub mod compiler;
 pub mod convention;
 pub mod deserialise;
+pub mod digest;
 pub mod disassembly;
 pub mod endian;
 pub mod error;
+pub mod flags;
 pub mod float_format;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
 pub mod il;
 pub mod language;
+#[cfg(feature = "lift-cache")]
+pub mod lift_cache;
 pub mod processor;
+pub mod raw_detect;
 pub mod register;
+pub mod semantics;
 pub mod space;
 pub mod space_manager;
+#[cfg(feature = "spec-cache")]
+pub mod spec_cache;
 pub mod translator;
 
 pub use address::{Address, AddressValue, IntoAddress};
+pub use address_map::AddressMap;
 pub use disassembly::{IRBuilder, VarnodeData};
 pub use il::{PCode, PCodeFormatter};
 pub use language::LanguageDB;
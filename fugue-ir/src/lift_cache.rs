@@ -0,0 +1,360 @@
+//! An on-disk cache of lifted instructions, allowing repeated analyses of
+//! the same code across runs to skip re-lifting.
+//!
+//! Entries are keyed by an instruction's address, the context words in
+//! effect at that address, and a fixed-size window of the bytes starting
+//! at it. Each architecture has its own tree within the store, which
+//! records the crate version and a digest of the translator's
+//! specification used to populate it; opening a tree with a translator
+//! that differs in either clears it.
+
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::address::AddressValue;
+use crate::digest::Digest;
+use crate::disassembly::ContextDatabase;
+use crate::error::Error as LiftError;
+use crate::il::ecode::ECode;
+use crate::il::pcode::PCode;
+use crate::translator::Translator;
+
+/// The default number of bytes covered by each key; instructions longer
+/// than the window (including any delay slots) are not cached.
+pub const DEFAULT_WINDOW: usize = 16;
+
+/// Bumped whenever the encoding of keys or entries changes.
+const FORMAT_VERSION: u32 = 1;
+
+const VERSION_KEY: &[u8] = b"\0version";
+
+const PCODE_TAG: u8 = 1;
+const ECODE_TAG: u8 = 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Store(#[from] sled::Error),
+    #[error(transparent)]
+    Encoding(#[from] bincode::Error),
+}
+
+/// A cache of lifted instructions for a single translator.
+///
+/// Failures to read from or write to the store are logged and treated as
+/// cache misses, hence lifting through the cache only fails when lifting
+/// itself fails.
+pub struct LiftCache {
+    tree: sled::Tree,
+    window: usize,
+}
+
+impl LiftCache {
+    /// Opens (or creates) the store at `path`.
+    pub fn open<P: AsRef<Path>>(path: P, translator: &Translator) -> Result<Self, Error> {
+        let db = sled::open(path)?;
+        Self::from_db(&db, translator)
+    }
+
+    /// Uses the tree for `translator`'s architecture within `db`, clearing
+    /// it if it was populated by a different version of the crate or
+    /// translator.
+    pub fn from_db(db: &sled::Db, translator: &Translator) -> Result<Self, Error> {
+        let tree = db.open_tree(translator.architecture().to_string())?;
+        let version = Self::version(translator);
+
+        if tree.get(VERSION_KEY)?.as_deref() != Some(version.as_bytes()) {
+            tree.clear()?;
+            tree.insert(VERSION_KEY, version.as_bytes())?;
+        }
+
+        Ok(Self {
+            tree,
+            window: DEFAULT_WINDOW,
+        })
+    }
+
+    pub fn window(self, window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            ..self
+        }
+    }
+
+    /// The number of cached instructions.
+    pub fn len(&self) -> usize {
+        self.tree.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached instructions.
+    pub fn invalidate(&self) -> Result<(), Error> {
+        let version = self.tree.get(VERSION_KEY)?;
+        self.tree.clear()?;
+        if let Some(version) = version {
+            self.tree.insert(VERSION_KEY, version)?;
+        }
+        Ok(())
+    }
+
+    /// Writes any pending entries to disk, returning the number of bytes
+    /// written.
+    pub fn flush(&self) -> Result<usize, Error> {
+        Ok(self.tree.flush()?)
+    }
+
+    pub fn lift_pcode(
+        &self,
+        translator: &Translator,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+    ) -> Result<PCode, LiftError> {
+        self.lift_with(
+            PCODE_TAG,
+            translator,
+            db,
+            address,
+            bytes,
+            |t, db, address, bytes| {
+                let pcode = t.lift_pcode(db, address, bytes)?;
+                let length = pcode.length();
+                Ok((pcode, length))
+            },
+        )
+    }
+
+    pub fn lift_ecode(
+        &self,
+        translator: &Translator,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+    ) -> Result<ECode, LiftError> {
+        self.lift_with(
+            ECODE_TAG,
+            translator,
+            db,
+            address,
+            bytes,
+            |t, db, address, bytes| {
+                let ecode = t.lift_ecode(db, address, bytes)?;
+                let length = ecode.length();
+                Ok((ecode, length))
+            },
+        )
+    }
+
+    fn lift_with<T, F>(
+        &self,
+        tag: u8,
+        translator: &Translator,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &[u8],
+        lift: F,
+    ) -> Result<T, LiftError>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce(
+            &Translator,
+            &mut ContextDatabase,
+            AddressValue,
+            &[u8],
+        ) -> Result<(T, usize), LiftError>,
+    {
+        let key = self.key(tag, db, &address, bytes);
+
+        match self.tree.get(&key) {
+            Ok(Some(value)) => match bincode::deserialize(&value) {
+                Ok(lifted) => return Ok(lifted),
+                Err(e) => log::warn!("discarding malformed lift cache entry: {}", e),
+            },
+            Ok(None) => (),
+            Err(e) => log::warn!("lift cache lookup failed: {}", e),
+        }
+
+        let generation = db.generation();
+        let (lifted, length) = lift(translator, db, address, bytes)?;
+
+        // instructions that commit context are not cached, as the commits
+        // would not be replayed when they are served from the cache
+        if length <= self.window && db.generation() == generation {
+            let inserted = bincode::serialize(&lifted)
+                .map_err(Error::from)
+                .and_then(|value| Ok(self.tree.insert(key, value)?));
+
+            if let Err(e) = inserted {
+                log::warn!("lift cache update failed: {}", e);
+            }
+        }
+
+        Ok(lifted)
+    }
+
+    fn key(&self, tag: u8, db: &ContextDatabase, address: &AddressValue, bytes: &[u8]) -> Vec<u8> {
+        let context = db.get_context(address);
+        let window = &bytes[..bytes.len().min(self.window)];
+
+        let mut key = Vec::with_capacity(1 + 8 + 8 + 4 * context.len() + 4 + window.len());
+
+        key.push(tag);
+        key.extend_from_slice(&(address.space().index() as u64).to_be_bytes());
+        key.extend_from_slice(&address.offset().to_be_bytes());
+
+        for word in context.iter() {
+            key.extend_from_slice(&word.to_be_bytes());
+        }
+
+        key.extend_from_slice(&(window.len() as u32).to_be_bytes());
+        key.extend_from_slice(window);
+        key
+    }
+
+    fn version(translator: &Translator) -> String {
+        let mut digest = Digest::default();
+        digest.write_u64(translator.specification_digest());
+
+        let mut files = translator.source_files().iter().collect::<Vec<_>>();
+        files.sort();
+        for (name, id) in files {
+            digest.write(name.as_bytes());
+            digest.write_u64(*id as u64);
+        }
+
+        let mut registers = translator.registers().iter().collect::<Vec<_>>();
+        registers.sort();
+        for ((offset, size), name) in registers {
+            digest.write(name.as_bytes());
+            digest.write_u64(*offset);
+            digest.write_u64(*size as u64);
+        }
+
+        for op in translator.user_ops() {
+            digest.write(op.as_bytes());
+        }
+
+        digest.write_u64(translator.alignment() as u64);
+        digest.write_u64(translator.unique_base());
+        digest.write_u64(translator.register_space_size() as u64);
+
+        format!(
+            "{}/{}/{:016x}",
+            env!("CARGO_PKG_VERSION"),
+            FORMAT_VERSION,
+            digest.finish()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_arch::ArchitectureDef;
+    use fugue_bytes::Endian;
+
+    use super::*;
+
+    #[test]
+    fn test_lift_cache_tiny() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("tiny", Endian::Little, 32, "default"),
+            &Default::default(),
+            "./data/test/tiny.sla",
+        )?;
+
+        let path =
+            std::env::temp_dir().join(format!("fugue-lift-cache-tiny-{}", std::process::id()));
+        let db = sled::open(&path)?;
+
+        let mut context = translator.context_database();
+        let addr = translator.address(0x1000u64);
+
+        let cache = LiftCache::from_db(&db, &translator)?;
+        let generation = context.generation();
+        let pcode = cache.lift_pcode(&translator, &mut context, addr, &[0, 0])?;
+        assert_eq!(pcode.length(), 2);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(context.generation(), generation);
+
+        // served from the cache
+        let cached = cache.lift_pcode(&translator, &mut context, addr, &[0, 0])?;
+        assert_eq!(format!("{:?}", cached), format!("{:?}", pcode));
+        assert_eq!(cache.len(), 1);
+
+        // the same bytes under a different context are keyed separately
+        context.set_variable_default("mode", 1);
+        cache.lift_pcode(&translator, &mut context, addr, &[1, 0])?;
+        assert_eq!(cache.len(), 2);
+
+        // a translator loaded from a different specification clears the
+        // cache, even if its registers and operations are the same
+        let modified = std::fs::read_to_string("./data/test/tiny.sla")?
+            .replacen("<sleigh ", "<sleigh  ", 1);
+        let other = Translator::from_str(
+            "pc",
+            &ArchitectureDef::new("tiny", Endian::Little, 32, "default"),
+            &Default::default(),
+            modified,
+        )?;
+        assert_ne!(other.specification_digest(), translator.specification_digest());
+        assert_eq!(LiftCache::version(&translator), LiftCache::version(&translator.clone()));
+        assert_ne!(LiftCache::version(&other), LiftCache::version(&translator));
+
+        let cache = LiftCache::from_db(&db, &other)?;
+        assert!(cache.is_empty());
+
+        drop(cache);
+        drop(db);
+        std::fs::remove_dir_all(path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "requires processor specifications"]
+    fn test_lift_cache() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("x86", Endian::Little, 64, "default"),
+            &Default::default(),
+            "./data/processors/x86/x86-64.sla",
+        )?;
+
+        let path = std::env::temp_dir().join(format!("fugue-lift-cache-{}", std::process::id()));
+
+        let mut db = translator.context_database();
+        let addr = translator.address(0x1000u64);
+
+        // push rbp; mov rbp, rsp
+        let bytes = [0x55, 0x48, 0x89, 0xe5];
+
+        {
+            let cache = LiftCache::open(&path, &translator)?;
+            let pcode = cache.lift_pcode(&translator, &mut db, addr, &bytes)?;
+            assert_eq!(pcode.length(), 1);
+            assert_eq!(cache.len(), 1);
+            cache.flush()?;
+        }
+
+        let cache = LiftCache::open(&path, &translator)?;
+        assert_eq!(cache.len(), 1);
+
+        let cached = cache.lift_pcode(&translator, &mut db, addr, &bytes)?;
+        let lifted = translator.lift_pcode(&mut db, addr, &bytes)?;
+        assert_eq!(format!("{:?}", cached), format!("{:?}", lifted));
+
+        cache.invalidate()?;
+        assert!(cache.is_empty());
+
+        drop(cache);
+        std::fs::remove_dir_all(path)?;
+
+        Ok(())
+    }
+}
@@ -13,6 +13,8 @@ pub struct Specification {
     program_counter: String,
     context_set: Map<String, u32>,
     tracked_set: Map<String, u32>,
+    #[serde(default)]
+    register_aliases: Vec<(String, String)>,
 }
 
 impl Specification {
@@ -24,6 +26,14 @@ impl Specification {
         self.context_set.iter().map(|(n, v)| (n.as_ref(), *v))
     }
 
+    /// Pairs of register names and their aliases given by the
+    /// specification's `register_data`.
+    pub fn register_aliases(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.register_aliases
+            .iter()
+            .map(|(n, a)| (n.as_ref(), a.as_ref()))
+    }
+
     pub fn from_xml(input: xml::Node) -> Result<Self, DeserialiseError> {
         // Example .pspec file
         // <processor_spec>
@@ -43,6 +53,10 @@ impl Specification {
         //     <set name="spsr" val="0"/>
         //     </tracked_set>
         // </context_data>
+        // <register_data>
+        //     <register name="r13" alias="sp"/>
+        //     ...
+        // </register_data>
 
         // <default_symbols>
         //     <symbol name="Reset" address="ram:0x0" entry="true"/>
@@ -58,6 +72,7 @@ impl Specification {
         let mut program_counter = None;
         let mut context_set = Map::default();
         let mut tracked_set = Map::default();
+        let mut register_aliases = Vec::new();
 
         for child in input.children().filter(xml::Node::is_element) {
             match child.tag_name().name() {
@@ -87,6 +102,14 @@ impl Specification {
                         }
                     }
                 }
+                "register_data" => {
+                    for reg in child.children().filter(xml::Node::is_element) {
+                        if let Some(alias) = reg.attribute("alias") {
+                            register_aliases
+                                .push((reg.attribute_string("name")?, alias.to_owned()));
+                        }
+                    }
+                }
                 _ => (),
             }
         }
@@ -96,6 +119,7 @@ impl Specification {
                 program_counter,
                 context_set,
                 tracked_set,
+                register_aliases,
             })
         } else {
             Err(DeserialiseError::Invariant(
@@ -0,0 +1,292 @@
+//! Heuristic detection of the language of headerless images (e.g., raw
+//! firmware dumps).
+//!
+//! Candidate languages are scored by lifting samples of the image: the
+//! proportion of bytes that lift, how plausible the density of branches
+//! among the lifted instructions is, and how often common function
+//! prologues for the candidate's processor occur within the image.
+
+use fugue_arch::ArchitectureDef;
+use fugue_bytes::Endian;
+
+use crate::disassembly::{LinearSweep, RecoveryPolicy};
+use crate::il::pcode::PCodeOp;
+use crate::language::{LanguageBuilder, LanguageDB};
+
+/// The proportion of instructions that branch in typical code.
+const EXPECTED_BRANCH_DENSITY: f64 = 0.15;
+
+/// The number of prologues per KiB at (or above) which an image is
+/// considered to be certainly code for a candidate.
+const EXPECTED_PROLOGUES_PER_KIB: f64 = 1.0;
+
+const LIFT_WEIGHT: f64 = 0.6;
+const BRANCH_WEIGHT: f64 = 0.2;
+const PROLOGUE_WEIGHT: f64 = 0.2;
+
+#[derive(Debug, Clone)]
+pub struct Candidate<'a> {
+    builder: LanguageBuilder<'a>,
+    lift_rate: f64,
+    branch_density: f64,
+    prologues: usize,
+    score: f64,
+}
+
+impl<'a> Candidate<'a> {
+    pub fn architecture(&self) -> &'a ArchitectureDef {
+        self.builder.language().architecture()
+    }
+
+    pub fn builder(&self) -> &LanguageBuilder<'a> {
+        &self.builder
+    }
+
+    /// The proportion of sampled bytes that were lifted.
+    pub fn lift_rate(&self) -> f64 {
+        self.lift_rate
+    }
+
+    /// The proportion of lifted instructions that branch, call, or return.
+    pub fn branch_density(&self) -> f64 {
+        self.branch_density
+    }
+
+    /// The number of known function prologues found within the image.
+    pub fn prologues(&self) -> usize {
+        self.prologues
+    }
+
+    /// The combined score, between zero and one.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+/// Ranks the languages of a `LanguageDB` by how likely they are to be the
+/// language of an image.
+pub struct RawDetector<'a> {
+    languages: &'a LanguageDB,
+    samples: usize,
+    sample_size: usize,
+}
+
+impl<'a> RawDetector<'a> {
+    pub fn new(languages: &'a LanguageDB) -> Self {
+        Self {
+            languages,
+            samples: 16,
+            sample_size: 256,
+        }
+    }
+
+    /// The number of samples lifted from each image.
+    pub fn samples(self, samples: usize) -> Self {
+        Self {
+            samples: samples.max(1),
+            ..self
+        }
+    }
+
+    /// The size of each sample in bytes.
+    pub fn sample_size(self, sample_size: usize) -> Self {
+        Self {
+            sample_size: sample_size.max(1),
+            ..self
+        }
+    }
+
+    /// Scores each language against `bytes`, returning the candidates from
+    /// most to least likely; languages that cannot be built are skipped.
+    pub fn detect(&self, bytes: &[u8]) -> Vec<Candidate<'a>> {
+        let mut candidates = self
+            .languages
+            .iter()
+            .filter_map(|builder| self.score(builder, bytes))
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.architecture().cmp(b.architecture()))
+        });
+
+        candidates
+    }
+
+    fn score(&self, builder: LanguageBuilder<'a>, bytes: &[u8]) -> Option<Candidate<'a>> {
+        let translator = match builder.build() {
+            Ok(translator) => translator,
+            Err(e) => {
+                log::debug!(
+                    "skipping language {}: {}",
+                    builder.language().architecture(),
+                    e
+                );
+                return None;
+            }
+        };
+
+        let sweep = LinearSweep::new(&translator).policy(RecoveryPolicy::NextAlignment);
+        let alignment = translator.alignment().max(1);
+
+        let mut sampled = 0;
+        let mut lifted = 0;
+        let mut instructions = 0;
+        let mut branches = 0;
+
+        for range in sample_ranges(bytes.len(), self.samples, self.sample_size, alignment) {
+            let mut db = translator.context_database();
+            let address = translator.address(range.start as u64);
+
+            for item in sweep.pcode(&mut db, address, &bytes[range.clone()]) {
+                if let Some(pcode) = item.instruction() {
+                    lifted += pcode.length();
+                    instructions += 1;
+                    branches += pcode.operations().iter().any(is_branch) as usize;
+                }
+            }
+
+            sampled += range.len();
+        }
+
+        let prologues = prologues(builder.language().architecture())
+            .iter()
+            .map(|pattern| count_matches(pattern, bytes, alignment))
+            .sum::<usize>();
+
+        let lift_rate = if sampled == 0 {
+            0.0
+        } else {
+            (lifted as f64 / sampled as f64).min(1.0)
+        };
+
+        let branch_density = if instructions == 0 {
+            0.0
+        } else {
+            branches as f64 / instructions as f64
+        };
+
+        let branch_score = if instructions == 0 {
+            0.0
+        } else {
+            1.0 - ((branch_density - EXPECTED_BRANCH_DENSITY).abs() / EXPECTED_BRANCH_DENSITY)
+                .min(1.0)
+        };
+
+        let kib = (bytes.len() as f64 / 1024.0).max(1.0);
+        let prologue_score = (prologues as f64 / kib / EXPECTED_PROLOGUES_PER_KIB).min(1.0);
+
+        Some(Candidate {
+            builder,
+            lift_rate,
+            branch_density,
+            prologues,
+            score: LIFT_WEIGHT * lift_rate
+                + BRANCH_WEIGHT * branch_score
+                + PROLOGUE_WEIGHT * prologue_score,
+        })
+    }
+}
+
+impl LanguageDB {
+    /// Proposes languages for the headerless image `bytes` using a
+    /// `RawDetector` with its default configuration.
+    pub fn detect_raw<'a>(&'a self, bytes: &[u8]) -> Vec<Candidate<'a>> {
+        RawDetector::new(self).detect(bytes)
+    }
+}
+
+fn is_branch(op: &PCodeOp) -> bool {
+    matches!(
+        op,
+        PCodeOp::Branch { .. }
+            | PCodeOp::CBranch { .. }
+            | PCodeOp::IBranch { .. }
+            | PCodeOp::Call { .. }
+            | PCodeOp::ICall { .. }
+            | PCodeOp::Return { .. }
+    )
+}
+
+/// Evenly spaced, aligned ranges covering at most `samples * size` bytes
+/// of an image of `length` bytes.
+fn sample_ranges(
+    length: usize,
+    samples: usize,
+    size: usize,
+    alignment: usize,
+) -> Vec<std::ops::Range<usize>> {
+    if length <= samples * size {
+        // the whole image as a single range
+        return if length == 0 {
+            Vec::new()
+        } else {
+            std::iter::once(0..length).collect()
+        };
+    }
+
+    let stride = length / samples;
+    (0..samples)
+        .map(|i| {
+            let start = (i * stride) / alignment * alignment;
+            start..(start + size).min(length)
+        })
+        .collect()
+}
+
+/// Common function prologues, as hex bytes with `??` for any byte.
+fn prologues(def: &ArchitectureDef) -> &'static [&'static str] {
+    match (def.processor(), def.endian(), def.bits()) {
+        ("x86", Endian::Little, 32) => &["55 89 e5", "55 8b ec"],
+        ("x86", Endian::Little, 64) => &["55 48 89 e5", "f3 0f 1e fa"],
+        ("ARM", Endian::Little, 32) => &["?? ?? 2d e9", "?? b5"],
+        ("ARM", Endian::Big, 32) => &["e9 2d ?? ??", "b5 ??"],
+        ("AARCH64", Endian::Little, 64) => &["fd 7b ?? a9", "3f 23 03 d5"],
+        ("MIPS", Endian::Little, _) => &["?? ff bd 27"],
+        ("MIPS", Endian::Big, _) => &["27 bd ff ??"],
+        ("PowerPC", Endian::Big, _) => &["94 21 ff ??", "7c 08 02 a6"],
+        _ => &[],
+    }
+}
+
+/// Counts the occurrences of `pattern` at offsets aligned to `alignment`.
+fn count_matches(pattern: &str, bytes: &[u8], alignment: usize) -> usize {
+    let pattern = pattern
+        .split_whitespace()
+        .map(|b| u8::from_str_radix(b, 16).ok())
+        .collect::<Vec<_>>();
+
+    if pattern.is_empty() || pattern.len() > bytes.len() {
+        return 0;
+    }
+
+    (0..=bytes.len() - pattern.len())
+        .step_by(alignment.max(1))
+        .filter(|&offset| {
+            pattern
+                .iter()
+                .zip(&bytes[offset..])
+                .all(|(p, b)| p.map(|p| p == *b).unwrap_or(true))
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_count_prologues() {
+        let bytes = [
+            0x55, 0x48, 0x89, 0xe5, 0xc3, 0x90, 0x90, 0x90, 0x55, 0x48, 0x89, 0xe5,
+        ];
+
+        assert_eq!(count_matches("55 48 89 e5", &bytes, 1), 2);
+        assert_eq!(count_matches("55 ?? 89", &bytes, 1), 2);
+        assert_eq!(count_matches("48 89", &bytes, 4), 0);
+
+        assert_eq!(sample_ranges(100, 4, 64, 4), vec![0..100]);
+        assert_eq!(sample_ranges(1000, 2, 64, 4), vec![0..64, 500..564]);
+    }
+}
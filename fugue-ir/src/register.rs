@@ -6,12 +6,23 @@ use ustr::Ustr;
 
 use crate::space::AddressSpace;
 
+const RISCV_ABI_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2",
+    "s0", "s1", "a0", "a1", "a2", "a3", "a4", "a5",
+    "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7",
+    "s8", "s9", "s10", "s11", "t3", "t4", "t5", "t6",
+];
+
 #[derive(Debug, Clone)]
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct RegisterNames {
     exact: Map<(u64, usize), Ustr>,
     reversed: Map<Ustr, (u64, usize)>,
     overlaps: IntervalMap<u64, Ustr>,
+    #[serde(default)]
+    aliases: Map<Ustr, Ustr>,
+    #[serde(default)]
+    display: Map<Ustr, Ustr>,
     space: Arc<AddressSpace>,
 }
 
@@ -21,6 +32,8 @@ impl RegisterNames {
             exact: Map::default(),
             reversed: Map::default(),
             overlaps: IntervalMap::new(),
+            aliases: Map::default(),
+            display: Map::default(),
             space,
         }
     }
@@ -46,13 +59,105 @@ impl RegisterNames {
             })
     }
 
-    pub fn unchecked_get(&self, offset: u64, size: usize) -> &Ustr {
-        unsafe { self.get(offset, size).unsafe_unwrap() }
+    /// # Safety
+    ///
+    /// A register must be defined that covers `size` bytes at `offset`.
+    pub unsafe fn unchecked_get(&self, offset: u64, size: usize) -> &Ustr {
+        self.get(offset, size).unsafe_unwrap()
     }
 
+    /// Looks up a register by its name or any of its aliases; the name
+    /// returned is always the register's canonical name.
     pub fn get_by_name<N>(&self, name: N) -> Option<(&Ustr, u64, usize)>
     where N: AsRef<str> {
-        self.reversed.get_key_value(&name.as_ref().into()).map(|(k, vv)| (k, vv.0, vv.1))
+        let name = Ustr::from(name.as_ref());
+        let name = self.aliases.get(&name).unwrap_or(&name);
+        self.reversed.get_key_value(name).map(|(k, vv)| (k, vv.0, vv.1))
+    }
+
+    /// The name to use when displaying the register at `offset` of `size`
+    /// bytes; this is its preferred alias, if one is set, otherwise its
+    /// canonical name.
+    pub fn display_name(&self, offset: u64, size: usize) -> Option<&Ustr> {
+        let name = self.get(offset, size)?;
+        Some(self.display.get(name).unwrap_or(name))
+    }
+
+    /// Adds `alias` as an alternative name for the register `name`, which
+    /// may itself be an alias. Returns false, and does nothing, if `name`
+    /// is not a known register or if `alias` is already a register name.
+    pub fn insert_alias<N, A>(&mut self, name: N, alias: A) -> bool
+    where N: AsRef<str>,
+          A: AsRef<str> {
+        let alias = Ustr::from(alias.as_ref());
+        if self.reversed.contains_key(&alias) {
+            return false
+        }
+
+        if let Some((name, _, _)) = self.get_by_name(name) {
+            let name = *name;
+            self.aliases.insert(alias, name);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Adds `alias` as an alternative name for the register `name` and
+    /// uses it in place of the canonical name when displaying the register.
+    pub fn insert_display_alias<N, A>(&mut self, name: N, alias: A) -> bool
+    where N: AsRef<str>,
+          A: AsRef<str> {
+        let alias = alias.as_ref();
+        if !self.insert_alias(name, alias) {
+            return false
+        }
+
+        let alias = Ustr::from(alias);
+        let name = self.aliases[&alias];
+        self.display.insert(name, alias);
+        true
+    }
+
+    /// The aliases of the register with canonical name `name`.
+    pub fn aliases<'a, N>(&'a self, name: N) -> impl Iterator<Item=&'a Ustr> + 'a
+    where N: AsRef<str> {
+        let name = Ustr::from(name.as_ref());
+        self.aliases.iter().filter_map(move |(alias, canonical)| if *canonical == name {
+            Some(alias)
+        } else {
+            None
+        })
+    }
+
+    /// Adds the conventional aliases for registers of `processor`, e.g.,
+    /// `fp` and `lr` for AArch64, and the numbered names for RISC-V
+    /// registers (which SLEIGH names by their ABI names). Aliases for
+    /// registers that are not defined are skipped.
+    pub fn insert_default_aliases<P: AsRef<str>>(&mut self, processor: P) {
+        let processor = processor.as_ref();
+
+        if processor.eq_ignore_ascii_case("RISCV") {
+            for (i, name) in RISCV_ABI_NAMES.iter().enumerate() {
+                self.insert_alias(name, format!("x{}", i));
+            }
+            self.insert_alias("s0", "fp");
+            return
+        }
+
+        let aliases: &[(&str, &str)] = if processor.eq_ignore_ascii_case("AARCH64") {
+            &[("x29", "fp"), ("x30", "lr"), ("w29", "wfp"), ("w30", "wlr")]
+        } else if processor.eq_ignore_ascii_case("ARM") {
+            &[("r11", "fp"), ("r12", "ip"), ("sp", "r13"), ("lr", "r14"), ("pc", "r15")]
+        } else if processor.eq_ignore_ascii_case("MIPS") {
+            &[("s8", "fp")]
+        } else {
+            &[]
+        };
+
+        for (name, alias) in aliases {
+            self.insert_alias(name, alias);
+        }
     }
 
     pub fn register_space(&self) -> &Arc<AddressSpace> {
@@ -63,3 +168,26 @@ impl RegisterNames {
         self.exact.iter()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_register_aliases() {
+        let mut registers = RegisterNames::new(Arc::new(AddressSpace::constant("register", 1)));
+        registers.insert(0x50, 8, "a0".into());
+        registers.insert(0x40, 8, "s0".into());
+        registers.insert_default_aliases("RISCV");
+
+        assert_eq!(registers.get_by_name("x10"), Some((&Ustr::from("a0"), 0x50, 8)));
+        assert_eq!(registers.get_by_name("fp").map(|(n, _, _)| *n), Some("s0".into()));
+        assert!(registers.get_by_name("x11").is_none());
+
+        assert!(!registers.insert_alias("a0", "s0"));
+        assert!(registers.insert_display_alias("x10", "arg0"));
+        assert_eq!(registers.display_name(0x50, 8), Some(&Ustr::from("arg0")));
+        assert_eq!(registers.display_name(0x40, 8), Some(&Ustr::from("s0")));
+        assert_eq!(registers.aliases("a0").count(), 2);
+    }
+}
@@ -0,0 +1,320 @@
+//! Reports of the IL each constructor of a language lifts to.
+//!
+//! A report is built by synthesising a representative encoding for each
+//! constructor of the language's root table from its decision patterns,
+//! and disassembling and lifting it. Reports are deterministic for a given
+//! translator, so comparing the reports of two versions of a translator
+//! highlights changes in their semantics.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use fugue_arch::ArchitectureDef;
+
+use crate::disassembly::{IRBuilderArena, Symbol};
+use crate::translator::Translator;
+
+/// The minimum number of bytes each encoding is padded to, so that
+/// operands decoded from bytes beyond the root constructor's pattern have
+/// bytes to decode.
+const MIN_ENCODING_SIZE: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct ConstructorSemantics {
+    constructor: usize,
+    source: Option<String>,
+    encoding: Vec<u8>,
+    mnemonic: String,
+    operands: String,
+    operations: Vec<String>,
+}
+
+impl ConstructorSemantics {
+    /// The index of the constructor within the root table.
+    pub fn constructor(&self) -> usize {
+        self.constructor
+    }
+
+    /// The location of the constructor's definition, as `file:line`.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// The bytes disassembled and lifted for the constructor.
+    pub fn encoding(&self) -> &[u8] {
+        &self.encoding
+    }
+
+    pub fn mnemonic(&self) -> &str {
+        &self.mnemonic
+    }
+
+    pub fn operands(&self) -> &str {
+        &self.operands
+    }
+
+    /// The lifted p-code operations; temporaries are renumbered (see
+    /// [`crate::il::temporaries`]).
+    pub fn operations(&self) -> &[String] {
+        &self.operations
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+/// A constructor for which no encoding could be lifted, e.g., as it is
+/// only selected in a non-default context.
+pub struct UnliftedConstructor {
+    constructor: usize,
+    source: Option<String>,
+    error: String,
+}
+
+impl UnliftedConstructor {
+    pub fn constructor(&self) -> usize {
+        self.constructor
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// The error raised by the last attempt to lift the constructor.
+    pub fn error(&self) -> &str {
+        &self.error
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct SemanticsReport {
+    architecture: ArchitectureDef,
+    constructors: Vec<ConstructorSemantics>,
+    unlifted: Vec<UnliftedConstructor>,
+}
+
+impl SemanticsReport {
+    pub fn new(translator: &Translator) -> Self {
+        let mut report = Self {
+            architecture: translator.architecture().clone(),
+            constructors: Vec::new(),
+            unlifted: Vec::new(),
+        };
+
+        let symbols = translator.symbol_table();
+        let root = symbols
+            .global_scope()
+            .and_then(|scope| scope.find("instruction", symbols));
+
+        let (constructors, decision_tree) = if let Some(Symbol::Subtable {
+            constructors,
+            decision_tree,
+            ..
+        }) = root
+        {
+            (constructors, decision_tree)
+        } else {
+            return report;
+        };
+
+        let mut patterns = BTreeMap::<usize, Vec<_>>::new();
+        for (id, pattern) in decision_tree.patterns() {
+            patterns.entry(id).or_default().push(pattern);
+        }
+
+        let files = translator
+            .source_files()
+            .iter()
+            .map(|(name, index)| (*index, name.as_str()))
+            .collect::<BTreeMap<_, _>>();
+
+        let irb = IRBuilderArena::with_capacity(4096);
+
+        for (index, constructor) in constructors.iter().enumerate() {
+            let source = files
+                .get(&constructor.source_file_index())
+                .map(|file| format!("{}:{}", file, constructor.line_number()));
+
+            let mut error = String::from("no matching encoding");
+            let mut semantics = None;
+
+            let encodings = patterns
+                .get(&index)
+                .into_iter()
+                .flatten()
+                .filter_map(|pattern| pattern.instruction_encoding());
+
+            for mut encoding in encodings {
+                let size = encoding
+                    .len()
+                    .max(constructor.minimum_length())
+                    .max(MIN_ENCODING_SIZE);
+                encoding.resize(size, 0);
+
+                let mut db = translator.context_database();
+                let address = translator.address(0);
+
+                let lifted = translator
+                    .disassemble(&mut db, &irb, address, &encoding)
+                    .and_then(|insn| {
+                        let pcode = translator.lift_pcode(&mut db, address, &encoding)?;
+                        Ok((insn, pcode))
+                    });
+
+                match lifted {
+                    Ok((insn, mut pcode)) => {
+                        pcode.renumber_temporaries();
+                        encoding.truncate(pcode.length().max(insn.length()));
+
+                        semantics = Some(ConstructorSemantics {
+                            constructor: index,
+                            source: source.clone(),
+                            encoding,
+                            mnemonic: insn.mnemonic().to_owned(),
+                            operands: insn.operands().to_owned(),
+                            operations: pcode
+                                .operations()
+                                .iter()
+                                .map(|op| op.to_string())
+                                .collect(),
+                        });
+                        break;
+                    }
+                    Err(e) => {
+                        error = e.to_string();
+                    }
+                }
+            }
+
+            if let Some(semantics) = semantics {
+                report.constructors.push(semantics);
+            } else {
+                report.unlifted.push(UnliftedConstructor {
+                    constructor: index,
+                    source,
+                    error,
+                });
+            }
+        }
+
+        report
+    }
+
+    pub fn architecture(&self) -> &ArchitectureDef {
+        &self.architecture
+    }
+
+    pub fn constructors(&self) -> &[ConstructorSemantics] {
+        &self.constructors
+    }
+
+    pub fn unlifted(&self) -> &[UnliftedConstructor] {
+        &self.unlifted
+    }
+
+    /// The lifted constructors, grouped by mnemonic (ignoring case).
+    pub fn by_mnemonic(&self) -> BTreeMap<String, Vec<&ConstructorSemantics>> {
+        let mut groups = BTreeMap::<_, Vec<_>>::new();
+        for constructor in self.constructors.iter() {
+            groups
+                .entry(constructor.mnemonic.to_lowercase())
+                .or_default()
+                .push(constructor);
+        }
+        groups
+    }
+}
+
+impl fmt::Display for SemanticsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# {}", self.architecture)?;
+
+        for (mnemonic, constructors) in self.by_mnemonic() {
+            writeln!(f, "\n## {}", mnemonic)?;
+            for constructor in constructors {
+                write!(f, "\n{} {}", constructor.mnemonic, constructor.operands)?;
+                if let Some(ref source) = constructor.source {
+                    write!(f, " ({})", source)?;
+                }
+                writeln!(f)?;
+
+                write!(f, "encoding:")?;
+                for b in constructor.encoding.iter() {
+                    write!(f, " {:02x}", b)?;
+                }
+                writeln!(f)?;
+
+                for operation in constructor.operations.iter() {
+                    writeln!(f, "    {}", operation)?;
+                }
+            }
+        }
+
+        if !self.unlifted.is_empty() {
+            writeln!(f, "\n## unlifted")?;
+            for unlifted in self.unlifted.iter() {
+                write!(f, "\nconstructor {}", unlifted.constructor)?;
+                if let Some(ref source) = unlifted.source {
+                    write!(f, " ({})", source)?;
+                }
+                writeln!(f, ": {}", unlifted.error)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_bytes::Endian;
+
+    use super::*;
+
+    #[test]
+    fn test_semantics_report_tiny() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("tiny", Endian::Little, 32, "default"),
+            &Default::default(),
+            "./data/test/tiny.sla",
+        )?;
+
+        let report = SemanticsReport::new(&translator);
+
+        assert_eq!(report.constructors().len(), 1);
+        let nop = &report.constructors()[0];
+        assert_eq!(nop.constructor(), 0);
+        assert_eq!(nop.mnemonic(), "nop");
+        assert_eq!(nop.encoding(), &[0x00, 0x00]);
+        assert!(nop.operations().is_empty());
+
+        // alt is only decodable under a non-default context
+        assert_eq!(report.unlifted().len(), 1);
+        assert_eq!(report.unlifted()[0].constructor(), 1);
+
+        assert!(report.by_mnemonic().contains_key("nop"));
+        assert!(report.to_string().contains("encoding: 00 00"));
+
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "requires processor specifications"]
+    fn test_semantics_report() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("AARCH64", Endian::Little, 64, "v8A"),
+            &Default::default(),
+            "./data/processors/AARCH64/AARCH64.sla",
+        )?;
+
+        let report = SemanticsReport::new(&translator);
+        assert!(!report.constructors().is_empty());
+        assert!(report.by_mnemonic().contains_key("nop"));
+        assert_eq!(report, SemanticsReport::new(&translator));
+
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use crate::address::AddressValue;
 use crate::deserialise::Error;
+use crate::disassembly::Error as DisassemblyError;
 use crate::disassembly::IRBuilderArena;
 use crate::space::{AddressSpace, AddressSpaceId, Space, SpaceKind, SpaceProperty};
 
@@ -16,22 +17,37 @@ pub struct SpaceManager {
     unique_space: usize,
 }
 
-pub trait FromSpace<'z, T> {
-    fn from_space(t: T, manager: &SpaceManager) -> Self;
-    fn from_space_with(t: T, arena: &'z IRBuilderArena, manager: &SpaceManager) -> Self;
+/// Conversions that resolve the spaces referred to by `T` using a
+/// [`SpaceManager`]; they fail if `T` refers to a space the manager does
+/// not manage.
+pub trait FromSpace<'z, T>: Sized {
+    fn from_space(t: T, manager: &SpaceManager) -> Result<Self, DisassemblyError>;
+    fn from_space_with(
+        t: T,
+        arena: &'z IRBuilderArena,
+        manager: &SpaceManager,
+    ) -> Result<Self, DisassemblyError>;
 }
 
 pub trait IntoSpace<'z, T> {
-    fn into_space(self, manager: &SpaceManager) -> T;
-    fn into_space_with(self, arena: &'z IRBuilderArena, manager: &SpaceManager) -> T;
+    fn into_space(self, manager: &SpaceManager) -> Result<T, DisassemblyError>;
+    fn into_space_with(
+        self,
+        arena: &'z IRBuilderArena,
+        manager: &SpaceManager,
+    ) -> Result<T, DisassemblyError>;
 }
 
 impl<'z, T, U> IntoSpace<'z, T> for U where T: FromSpace<'z, U> {
-    fn into_space(self, manager: &SpaceManager) -> T {
+    fn into_space(self, manager: &SpaceManager) -> Result<T, DisassemblyError> {
         T::from_space(self, manager)
     }
 
-    fn into_space_with(self, arena: &'z IRBuilderArena, manager: &SpaceManager) -> T {
+    fn into_space_with(
+        self,
+        arena: &'z IRBuilderArena,
+        manager: &SpaceManager,
+    ) -> Result<T, DisassemblyError> {
         T::from_space_with(self, arena, manager)
     }
 }
@@ -43,7 +59,7 @@ impl SpaceManager {
     }
 
     pub fn address_size(&self) -> usize {
-        unsafe { self.spaces.get_unchecked(self.default_space) }.address_size()
+        self.spaces[self.default_space].address_size()
     }
 
     pub fn spaces(&self) -> &[Arc<AddressSpace>] {
@@ -55,20 +71,24 @@ impl SpaceManager {
         self.spaces.iter().find_map(|space| if space.name() == name { Some(space.clone()) } else { None })
     }
 
-    pub fn space_by_id(&self, id: AddressSpaceId) -> &AddressSpace {
-        &self.spaces[id.index()]
+    pub fn space_by_id(&self, id: AddressSpaceId) -> Option<&AddressSpace> {
+        self.spaces.get(id.index()).map(|space| &**space)
     }
 
-    pub fn unchecked_space_by_id(&self, id: AddressSpaceId) -> &AddressSpace {
+    /// # Safety
+    ///
+    /// `id` must identify a space managed by this manager.
+    pub unsafe fn unchecked_space_by_id(&self, id: AddressSpaceId) -> &AddressSpace {
+        // SAFETY: the caller guarantees that `id` indexes `self.spaces`
         &* unsafe { self.spaces.get_unchecked(id.index()) }
     }
 
     pub fn constant_space(&self) -> Arc<AddressSpace> {
-        unsafe { self.spaces.get_unchecked(self.constant_space) }.clone()
+        self.spaces[self.constant_space].clone()
     }
 
     pub fn constant_space_ref(&self) -> &AddressSpace {
-        &*unsafe { self.spaces.get_unchecked(self.constant_space) }
+        &self.spaces[self.constant_space]
     }
 
     pub fn constant_space_id(&self) -> AddressSpaceId {
@@ -76,11 +96,11 @@ impl SpaceManager {
     }
 
     pub fn default_space(&self) -> Arc<AddressSpace> {
-        unsafe { self.spaces.get_unchecked(self.default_space) }.clone()
+        self.spaces[self.default_space].clone()
     }
 
     pub fn default_space_ref(&self) -> &AddressSpace {
-        &*unsafe { self.spaces.get_unchecked(self.default_space) }
+        &self.spaces[self.default_space]
     }
 
     pub fn default_space_id(&self) -> AddressSpaceId {
@@ -88,11 +108,11 @@ impl SpaceManager {
     }
 
     pub fn register_space(&self) -> Arc<AddressSpace> {
-        unsafe { self.spaces.get_unchecked(self.register_space) }.clone()
+        self.spaces[self.register_space].clone()
     }
 
     pub fn register_space_ref(&self) -> &AddressSpace {
-        &*unsafe { self.spaces.get_unchecked(self.register_space) }
+        &self.spaces[self.register_space]
     }
 
     pub fn register_space_id(&self) -> AddressSpaceId {
@@ -100,11 +120,11 @@ impl SpaceManager {
     }
 
     pub fn unique_space(&self) -> Arc<AddressSpace> {
-        unsafe { self.spaces.get_unchecked(self.unique_space) }.clone()
+        self.spaces[self.unique_space].clone()
     }
 
     pub fn unique_space_ref(&self) -> &AddressSpace {
-        &*unsafe { self.spaces.get_unchecked(self.unique_space) }
+        &self.spaces[self.unique_space]
     }
 
     pub fn unique_space_id(&self) -> AddressSpaceId {
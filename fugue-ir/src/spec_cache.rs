@@ -0,0 +1,398 @@
+//! An on-disk cache of compiled specifications, allowing large `.sla`
+//! files to be loaded without being parsed each time.
+//!
+//! The first time a specification is loaded through the cache, the
+//! translator built from it is encoded (using `bincode`) to a file within
+//! the cache directory; later loads map that file and decode the
+//! translator from it. The encoding is a plain cache, rather than a format
+//! usable in place: each load still decodes the whole translator, but that
+//! is considerably cheaper than parsing the specification's XML.
+//!
+//! Entries are keyed by the crate version, and the path, program counter,
+//! and architecture used to build them. Each entry also records the size,
+//! modification time, and a digest of the specification it was built
+//! from; the specification is only read and hashed if its size or
+//! modification time differ, and the entry is rebuilt if its digest
+//! differs. Compiler conventions are not cached, and are applied to the
+//! translator after it is loaded.
+
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use ahash::AHashMap as Map;
+use fugue_arch::ArchitectureDef;
+use memmap2::Mmap;
+
+use crate::compiler;
+use crate::digest::Digest;
+use crate::error::Error as LoadError;
+use crate::translator::Translator;
+
+const MAGIC: &[u8; 8] = b"fugueSLA";
+
+/// Bumped whenever the encoding of cached specifications changes.
+const FORMAT_VERSION: u32 = 2;
+
+const HEADER_SIZE: usize = MAGIC.len() + 4 + 8 + 8 + 16 + 8;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Encoding(#[from] bincode::Error),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Identifies the specification a cache entry was built from.
+struct Header {
+    key: u64,
+    length: u64,
+    /// Nanoseconds since the epoch; zero if the platform does not record
+    /// modification times.
+    modified: u128,
+    digest: u64,
+}
+
+impl Header {
+    fn new(key: u64, metadata: &Metadata, digest: u64) -> Self {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_nanos())
+            .unwrap_or(0);
+
+        Self {
+            key,
+            length: metadata.len(),
+            modified,
+            digest,
+        }
+    }
+
+    /// True if the specification described by `other` is unchanged, going
+    /// by its size and modification time alone.
+    fn unchanged(&self, other: &Self) -> bool {
+        self.modified != 0 && self.length == other.length && self.modified == other.modified
+    }
+
+    fn encode(&self) -> [u8; HEADER_SIZE] {
+        let mut header = [0u8; HEADER_SIZE];
+        let (magic, rest) = header.split_at_mut(MAGIC.len());
+        let (version, rest) = rest.split_at_mut(4);
+        let (key, rest) = rest.split_at_mut(8);
+        let (length, rest) = rest.split_at_mut(8);
+        let (modified, digest) = rest.split_at_mut(16);
+
+        magic.copy_from_slice(MAGIC);
+        version.copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        key.copy_from_slice(&self.key.to_le_bytes());
+        length.copy_from_slice(&self.length.to_le_bytes());
+        modified.copy_from_slice(&self.modified.to_le_bytes());
+        digest.copy_from_slice(&self.digest.to_le_bytes());
+
+        header
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let header = bytes.get(..HEADER_SIZE)?;
+        let (magic, rest) = header.split_at(MAGIC.len());
+        let (version, rest) = rest.split_at(4);
+        let (key, rest) = rest.split_at(8);
+        let (length, rest) = rest.split_at(8);
+        let (modified, digest) = rest.split_at(16);
+
+        if magic != MAGIC || version != FORMAT_VERSION.to_le_bytes() {
+            return None;
+        }
+
+        Some(Self {
+            key: u64::from_le_bytes(key.try_into().ok()?),
+            length: u64::from_le_bytes(length.try_into().ok()?),
+            modified: u128::from_le_bytes(modified.try_into().ok()?),
+            digest: u64::from_le_bytes(digest.try_into().ok()?),
+        })
+    }
+}
+
+/// A directory of compiled specifications.
+///
+/// Failures to read from or write to the cache are logged and treated as
+/// cache misses, hence loading through the cache only fails when loading
+/// the specification itself fails.
+pub struct SpecCache {
+    directory: PathBuf,
+}
+
+impl SpecCache {
+    /// Opens (or creates) the cache directory at `directory`.
+    pub fn open<P: AsRef<Path>>(directory: P) -> Result<Self, Error> {
+        let directory = directory.as_ref().to_owned();
+        fs::create_dir_all(&directory)?;
+        Ok(Self { directory })
+    }
+
+    /// Loads a translator for the specification at `path`; see
+    /// [`Translator::from_file`].
+    pub fn translator<PC: AsRef<str>, P: AsRef<Path>>(
+        &self,
+        program_counter: PC,
+        architecture: &ArchitectureDef,
+        compiler_specs: &Map<String, compiler::Specification>,
+        path: P,
+    ) -> Result<Translator, LoadError> {
+        let path = path.as_ref();
+        let parse_error = |error| LoadError::ParseFile {
+            path: path.to_owned(),
+            error,
+        };
+
+        let metadata = fs::metadata(path).map_err(parse_error)?;
+
+        let key = Self::key(path, program_counter.as_ref(), architecture);
+        let cached = self.path(path, key);
+
+        // the specification's contents, if read to validate the entry
+        let mut input = None;
+
+        let mut translator = match self.load(&cached, path, key, &metadata, &mut input) {
+            Ok(Some(translator)) => translator,
+            result => {
+                if let Err(e) = result {
+                    log::warn!("discarding malformed specification cache entry: {}", e);
+                }
+
+                let input = match input {
+                    Some(input) => input,
+                    None => fs::read(path).map_err(parse_error)?,
+                };
+                let header = Header::new(key, &metadata, Self::digest(&input));
+
+                let input = String::from_utf8(input).map_err(|e| {
+                    parse_error(io::Error::new(io::ErrorKind::InvalidData, e))
+                })?;
+
+                let translator =
+                    Translator::from_str(program_counter, architecture, &Map::default(), input)
+                        .map_err(|error| LoadError::DeserialiseFile {
+                            path: path.to_owned(),
+                            error,
+                        })?;
+
+                if let Err(e) = self.store(&cached, &header, &translator) {
+                    log::warn!("specification cache update failed: {}", e);
+                }
+
+                translator
+            }
+        };
+
+        translator
+            .add_compiler_conventions(compiler_specs)
+            .map_err(|error| LoadError::DeserialiseFile {
+                path: path.to_owned(),
+                error,
+            })?;
+
+        Ok(translator)
+    }
+
+    /// Removes all compiled specifications.
+    pub fn invalidate(&self) -> Result<(), Error> {
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.extension().map(|ext| ext == "bin").unwrap_or(false) {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decodes the entry at `cached` if it was built from the
+    /// specification at `source`; if the specification's size or
+    /// modification time have changed, its contents are read into `input`
+    /// and compared with the entry's digest.
+    fn load(
+        &self,
+        cached: &Path,
+        source: &Path,
+        key: u64,
+        metadata: &Metadata,
+        input: &mut Option<Vec<u8>>,
+    ) -> Result<Option<Translator>, Error> {
+        let file = match File::open(cached) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        // SAFETY: entries are replaced by renaming, rather than modified
+        // in place, except for their headers, which are not borrowed from
+        // the mapping
+        let bytes = unsafe { Mmap::map(&file) }?;
+
+        let header = match Header::decode(&bytes) {
+            Some(header) if header.key == key => header,
+            _ => return Ok(None),
+        };
+
+        let current = Header::new(key, metadata, header.digest);
+        if !header.unchanged(&current) {
+            let contents = input.insert(fs::read(source)?);
+            if Self::digest(contents) != header.digest {
+                return Ok(None);
+            }
+
+            // the specification was touched but not changed; record its
+            // metadata so that later loads need not read it
+            OpenOptions::new()
+                .write(true)
+                .open(cached)?
+                .write_all(&current.encode())?;
+        }
+
+        Ok(Some(bincode::deserialize(&bytes[HEADER_SIZE..])?))
+    }
+
+    fn store(&self, cached: &Path, header: &Header, translator: &Translator) -> Result<(), Error> {
+        let mut bytes = header.encode().to_vec();
+        bincode::serialize_into(&mut bytes, translator)?;
+
+        let partial = cached.with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&partial, bytes)?;
+        fs::rename(&partial, cached)?;
+
+        Ok(())
+    }
+
+    fn path(&self, source: &Path, key: u64) -> PathBuf {
+        let stem = source
+            .file_stem()
+            .map(|stem| stem.to_string_lossy())
+            .unwrap_or_default();
+        self.directory.join(format!("{}-{:016x}.bin", stem, key))
+    }
+
+    fn key(source: &Path, program_counter: &str, architecture: &ArchitectureDef) -> u64 {
+        let source = fs::canonicalize(source).unwrap_or_else(|_| source.to_owned());
+        let mut digest = Digest::default();
+
+        digest.write_prefixed(env!("CARGO_PKG_VERSION").as_bytes());
+        digest.write_prefixed(source.to_string_lossy().as_bytes());
+        digest.write_prefixed(program_counter.as_bytes());
+        digest.write_prefixed(architecture.to_string().as_bytes());
+
+        digest.finish()
+    }
+
+    fn digest(input: &[u8]) -> u64 {
+        let mut digest = Digest::default();
+        digest.write(input);
+        digest.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use fugue_bytes::Endian;
+
+    use super::*;
+
+    fn entries(path: &Path) -> Result<Vec<PathBuf>, io::Error> {
+        fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect()
+    }
+
+    fn lift(translator: &Translator) -> Result<String, LoadError> {
+        let mut db = translator.context_database();
+        let addr = translator.address(0x1000u64);
+        let pcode = translator.lift_pcode(&mut db, addr, &[0x00, 0x00])?;
+        Ok(pcode.display().to_string())
+    }
+
+    #[test]
+    fn test_spec_cache() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("fugue-spec-cache-{}", std::process::id()));
+        let cache = SpecCache::open(&path)?;
+
+        let architecture = ArchitectureDef::new("tiny", Endian::Little, 32, "default");
+        let sla = "./data/test/tiny.sla";
+
+        let built = cache.translator("pc", &architecture, &Default::default(), sla)?;
+
+        let cached = entries(&path)?;
+        assert_eq!(cached.len(), 1);
+
+        let loaded = cache.translator("pc", &architecture, &Default::default(), sla)?;
+        assert_eq!(loaded.alignment(), built.alignment());
+        assert_eq!(lift(&loaded)?, lift(&built)?);
+
+        // malformed entries are rebuilt
+        fs::write(&cached[0], &MAGIC[..])?;
+        let loaded = cache.translator("pc", &architecture, &Default::default(), sla)?;
+        assert_eq!(lift(&loaded)?, lift(&built)?);
+
+        let mut truncated = fs::read(&cached[0])?;
+        truncated.truncate(HEADER_SIZE + 16);
+        fs::write(&cached[0], truncated)?;
+        let loaded = cache.translator("pc", &architecture, &Default::default(), sla)?;
+        assert_eq!(lift(&loaded)?, lift(&built)?);
+
+        // a different program counter is cached separately
+        cache.translator("r0", &architecture, &Default::default(), sla)?;
+        assert_eq!(entries(&path)?.len(), 2);
+
+        cache.invalidate()?;
+        assert!(entries(&path)?.is_empty());
+        fs::remove_dir(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spec_cache_validation() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("fugue-spec-valid-{}", std::process::id()));
+        let cache = SpecCache::open(path.join("cache"))?;
+
+        let architecture = ArchitectureDef::new("tiny", Endian::Little, 32, "default");
+        let sla = path.join("tiny.sla");
+        fs::copy("./data/test/tiny.sla", &sla)?;
+
+        let header = |entry: &Path| -> Result<Header, io::Error> {
+            Header::decode(&fs::read(entry)?).ok_or_else(|| io::ErrorKind::InvalidData.into())
+        };
+
+        let built = cache.translator("pc", &architecture, &Default::default(), &sla)?;
+        let entry = entries(&path.join("cache"))?.remove(0);
+        let original = header(&entry)?;
+
+        // touched, but unchanged: the entry is kept, with the new metadata
+        let modified = fs::metadata(&sla)?.modified()? + std::time::Duration::from_secs(60);
+        File::options().write(true).open(&sla)?.set_modified(modified)?;
+
+        let loaded = cache.translator("pc", &architecture, &Default::default(), &sla)?;
+        assert_eq!(lift(&loaded)?, lift(&built)?);
+
+        let touched = header(&entry)?;
+        assert_eq!(touched.digest, original.digest);
+        assert_ne!(touched.modified, original.modified);
+
+        // changed: the entry is rebuilt in place
+        let mut contents = fs::read(&sla)?;
+        contents.push(b'\n');
+        fs::write(&sla, contents)?;
+
+        let loaded = cache.translator("pc", &architecture, &Default::default(), &sla)?;
+        assert_eq!(lift(&loaded)?, lift(&built)?);
+        assert_eq!(entries(&path.join("cache"))?, vec![entry.clone()]);
+        assert_ne!(header(&entry)?.digest, original.digest);
+
+        fs::remove_dir_all(&path)?;
+
+        Ok(())
+    }
+}
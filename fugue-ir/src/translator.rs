@@ -10,7 +10,6 @@ use ahash::AHashMap as Map;
 use fugue_arch::ArchitectureDef;
 use itertools::Itertools;
 
-use unsafe_unwrap::UnsafeUnwrap;
 use ustr::Ustr;
 
 use crate::address::AddressValue;
@@ -19,21 +18,23 @@ use crate::deserialise::parse::XmlExt;
 use crate::deserialise::Error as DeserialiseError;
 
 use crate::disassembly::lift::{FloatFormats, UserOpStr};
-use crate::disassembly::symbol::{FixedHandle, Symbol, SymbolScope, SymbolTable};
+use crate::disassembly::symbol::{Constructor, FixedHandle, Symbol, SymbolScope, SymbolTable};
 use crate::disassembly::walker::InstructionFormatter;
 use crate::disassembly::ContextDatabase;
 use crate::disassembly::Error as DisassemblyError;
 use crate::disassembly::PatternExpression;
+use crate::disassembly::PermissionedBytes;
 use crate::disassembly::VarnodeData;
 use crate::disassembly::{
     IRBuilder, IRBuilderArena, IRBuilderBase, PCodeRaw, ParserContext, ParserState, ParserWalker,
 };
 
 use crate::il::ecode::ECode;
-use crate::il::instruction::{Instruction, InstructionFull};
+use crate::il::instruction::{Instruction, InstructionFull, InstructionTokens};
 use crate::il::pcode::PCode;
 
-use crate::error::Error;
+use crate::digest::Digest;
+use crate::error::{Error, ErrorContext, Phase};
 
 use crate::float_format::FloatFormat;
 
@@ -45,6 +46,16 @@ use crate::space_manager::SpaceManager;
 
 // Translator is used for parsing the processor spec XML and
 // lifting instructions
+//
+// The tables built from the specification are immutable once loaded and
+// shared between clones, and all parser state is held by the
+// `ParserContext` passed to (or created by) each call, so a single loaded
+// translator can be shared between threads, or cheaply cloned to give each
+// thread its own context variable defaults and register aliases. Tables
+// that can be modified after loading (the space manager, register names
+// and compiler conventions) are copied on the first modification of a
+// clone; the context database is not shared, as its defaults are expected
+// to differ between clones.
 #[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub struct Translator {
     alignment: usize,
@@ -53,19 +64,20 @@ pub struct Translator {
     unique_mask: u64,
     maximum_delay: usize,
     section_count: usize,
-    float_formats: FloatFormats,
-    manager: SpaceManager,
-    symbol_table: SymbolTable,
+    float_formats: Arc<FloatFormats>,
+    manager: Arc<SpaceManager>,
+    symbol_table: Arc<SymbolTable>,
     root: Arc<Symbol>,
     global_scope: Arc<SymbolScope>,
     registers: Arc<RegisterNames>,
     registers_size: usize,
     program_counter: VarnodeData,
-    user_ops: Vec<UserOpStr>,
+    user_ops: Arc<Vec<UserOpStr>>,
     context_db: ContextDatabase,
     architecture: ArchitectureDef,
-    compiler_conventions: Map<String, Convention>,
-    source_files: Map<String, usize>,
+    compiler_conventions: Arc<Map<String, Convention>>,
+    source_files: Arc<Map<String, usize>>,
+    specification_digest: u64,
 }
 
 impl Translator {
@@ -81,6 +93,39 @@ impl Translator {
         self.alignment
     }
 
+    /// The length of the shortest instruction, in bytes.
+    pub fn minimum_instruction_length(&self) -> usize {
+        let constructors = if let Symbol::Subtable { constructors, .. } = &*self.root {
+            constructors.as_slice()
+        } else {
+            &[]
+        };
+
+        constructors
+            .iter()
+            .map(Constructor::minimum_length)
+            .filter(|length| *length > 0)
+            .min()
+            .unwrap_or(self.alignment)
+            .max(1)
+    }
+
+    /// The length of the longest instruction, in bytes, if known for the
+    /// processor; SLEIGH specifications do not record it.
+    pub fn maximum_instruction_length(&self) -> Option<usize> {
+        maximum_instruction_length(&self.architecture)
+    }
+
+    /// The maximum number of bytes of delay slot instructions following
+    /// any instruction.
+    pub fn maximum_delay_slot_length(&self) -> usize {
+        self.maximum_delay
+    }
+
+    pub fn has_delay_slots(&self) -> bool {
+        self.maximum_delay > 0
+    }
+
     pub fn unique_base(&self) -> u64 {
         self.unique_base
     }
@@ -101,6 +146,14 @@ impl Translator {
         self.context_db.clone()
     }
 
+    /// The context variables that influenced the decoding of the last
+    /// instruction disassembled or lifted using `context`, and their
+    /// values; `context` must record the context bits read, see
+    /// [`ParserContext::set_track_context`].
+    pub fn decode_context(&self, context: &ParserContext) -> Vec<(&str, u32)> {
+        context.decode_context(&self.context_db)
+    }
+
     pub fn set_variable_default<S: Borrow<str>>(&mut self, name: S, value: u32) {
         let name = name.borrow();
         log::trace!("setting context variable {} to {}", name, value);
@@ -117,7 +170,7 @@ impl Translator {
     }
 
     pub fn manager_mut(&mut self) -> &mut SpaceManager {
-        &mut self.manager
+        Arc::make_mut(&mut self.manager)
     }
 
     pub fn registers(&self) -> &Arc<RegisterNames> {
@@ -132,6 +185,23 @@ impl Translator {
             })
     }
 
+    /// Adds `alias` as an alternative name for the register `name`; if
+    /// `display` is true, the alias is also used when displaying the
+    /// register. See [`RegisterNames::insert_alias`].
+    pub fn add_register_alias<N: AsRef<str>, A: AsRef<str>>(
+        &mut self,
+        name: N,
+        alias: A,
+        display: bool,
+    ) -> bool {
+        let registers = Arc::make_mut(&mut self.registers);
+        if display {
+            registers.insert_display_alias(name, alias)
+        } else {
+            registers.insert_alias(name, alias)
+        }
+    }
+
     pub fn register_space_size(&self) -> usize {
         self.registers_size
     }
@@ -165,6 +235,12 @@ impl Translator {
         &self.source_files
     }
 
+    /// A stable digest of the specification the translator was loaded
+    /// from, e.g., to detect when caches populated using it are stale.
+    pub fn specification_digest(&self) -> u64 {
+        self.specification_digest
+    }
+
     pub fn from_file<PC: AsRef<str>, P: AsRef<Path>>(
         program_counter: PC,
         architecture: &ArchitectureDef,
@@ -216,7 +292,8 @@ impl Translator {
         let registers = Arc::<RegisterNames>::get_mut(&mut self.registers)
             .expect("unique access to RegisterNames");
 
-        let user_ops = &mut self.user_ops;
+        let user_ops = Arc::<Vec<UserOpStr>>::get_mut(&mut self.user_ops)
+            .expect("unique access to user operations");
         let mut pc = None;
         let mut registers_size = 0;
 
@@ -280,6 +357,8 @@ impl Translator {
             }
         }
 
+        registers.insert_default_aliases(self.architecture.processor());
+
         if let Some((pc_offset, pc_size)) = pc {
             self.program_counter.offset = pc_offset;
             self.program_counter.size = pc_size;
@@ -291,10 +370,19 @@ impl Translator {
 
         self.registers_size = registers_size;
 
+        self.add_compiler_conventions(compiler_specs)
+    }
+
+    /// Adds the calling conventions defined by `compiler_specs`, replacing
+    /// any existing conventions of the same names.
+    pub fn add_compiler_conventions(
+        &mut self,
+        compiler_specs: &Map<String, compiler::Specification>,
+    ) -> Result<(), DeserialiseError> {
         for (name, spec) in compiler_specs.iter() {
             let conv = Convention::from_spec(spec, &self.registers, &self.manager)?;
             log::debug!("loaded compiler convention `{}`", name);
-            self.compiler_conventions.insert(name.clone(), conv);
+            Arc::make_mut(&mut self.compiler_conventions).insert(name.clone(), conv);
         }
 
         Ok(())
@@ -388,6 +476,9 @@ impl Translator {
                 .to_owned(),
         );
 
+        let mut digest = Digest::default();
+        digest.write(input.document().input_text()[input.range()].as_bytes());
+
         let mut slf = Self {
             alignment,
             big_endian,
@@ -395,19 +486,20 @@ impl Translator {
             unique_mask,
             maximum_delay,
             section_count,
-            float_formats,
-            manager,
-            symbol_table,
+            float_formats: Arc::new(float_formats),
+            manager: Arc::new(manager),
+            symbol_table: Arc::new(symbol_table),
             root,
             global_scope,
             registers: Arc::new(RegisterNames::new(register_space)),
             registers_size: 0,
             program_counter: program_counter_vnd,
-            user_ops: Vec::new(),
+            user_ops: Arc::default(),
             context_db: ContextDatabase::new(),
             architecture: architecture.clone(),
-            compiler_conventions: Map::default(),
-            source_files,
+            compiler_conventions: Arc::default(),
+            source_files: Arc::new(source_files),
+            specification_digest: digest.finish(),
         };
 
         slf.build_xrefs(program_counter, compiler_specs)?;
@@ -452,6 +544,15 @@ impl Translator {
         }
     }
 
+    fn in_context(&self, error: Error, phase: Phase, address: AddressValue, bytes: &[u8]) -> Error {
+        error.in_context(ErrorContext::new(
+            self.architecture.clone(),
+            address,
+            bytes,
+            phase,
+        ))
+    }
+
     pub fn disassemble_aux<'a, 'az, 'c, T, E, F>(
         &'a self,
         db: &mut ContextDatabase,
@@ -465,31 +566,37 @@ impl Translator {
         F: FnMut(InstructionFormatter<'a, 'c, 'az>, usize, usize) -> Result<T, E>,
         E: From<Error>,
     {
+        let in_context =
+            |error: Error| self.in_context(error, Phase::Disassembly, address.clone(), bytes);
+
         if self.alignment() != 1 {
             if address.offset() % self.alignment() as u64 != 0 {
                 return Err(DisassemblyError::IncorrectAlignment {
                     address: address.offset(),
                     alignment: self.alignment(),
                 })
-                .map_err(Error::from)?;
+                .map_err(|error| in_context(error.into()))?;
             }
         }
 
         context.reinitialise(arena, db, address.clone(), bytes);
         let mut walker = ParserWalker::new(context);
 
-        Translator::resolve(&mut walker, self.root.id(), &self.symbol_table)?;
-        Translator::resolve_handles(&mut walker, &self.manager, &self.symbol_table)?;
+        Translator::resolve(&mut walker, self.root.id(), &self.symbol_table).map_err(in_context)?;
+        Translator::resolve_handles(&mut walker, &self.manager, &self.symbol_table)
+            .map_err(in_context)?;
 
         walker.base_state();
         walker
             .apply_commits(db, &self.manager, &self.symbol_table)
-            .map_err(Error::from)?;
+            .map_err(|error| in_context(error.into()))?;
 
         let delay_slots = walker.delay_slot();
         let length = walker.length();
 
-        let ctor = walker.unchecked_constructor();
+        // SAFETY: `resolve` has resolved a constructor for every construct state,
+        // and the walker is positioned at the root state
+        let ctor = unsafe { walker.unchecked_constructor() };
 
         f(
             InstructionFormatter::new(walker, &self.symbol_table, ctor),
@@ -521,6 +628,42 @@ impl Translator {
         })
     }
 
+    /// Disassembles the instruction at `address` as [`Translator::disassemble`],
+    /// producing its display output as typed tokens rather than strings.
+    pub fn disassemble_tokens<'z>(
+        &self,
+        db: &mut ContextDatabase,
+        builder: &'z IRBuilderArena,
+        address: AddressValue,
+        bytes: &[u8],
+    ) -> Result<InstructionTokens<'z>, Error> {
+        let arena = IRBuilderArena::with_capacity(4096);
+        let mut ctxt = ParserContext::empty(&arena, self.manager());
+        self.disassemble_tokens_with(db, &mut ctxt, &arena, builder, address, bytes)
+    }
+
+    pub fn disassemble_tokens_with<'a, 'az, 'z>(
+        &'a self,
+        db: &mut ContextDatabase,
+        context: &mut ParserContext<'a, 'az>,
+        arena: &'az IRBuilderArena,
+        builder: &'z IRBuilderArena,
+        address: AddressValue,
+        bytes: &[u8],
+    ) -> Result<InstructionTokens<'z>, Error> {
+        self.disassemble_aux(db, context, arena, address, bytes, |fmt, delay_slots, length| {
+            let (mnemonic_tokens, operand_tokens) = fmt.tokens(arena);
+
+            Ok(InstructionTokens {
+                address,
+                mnemonic_tokens: mnemonic_tokens.to_owned_in(builder),
+                operand_tokens: operand_tokens.to_owned_in(builder),
+                delay_slots,
+                length,
+            })
+        })
+    }
+
     pub fn disassemble_full<'a, 'az, 'z>(
         &'a self,
         db: &mut ContextDatabase,
@@ -567,6 +710,19 @@ impl Translator {
         builder: &mut IRBuilderBase<'a, 'z>,
         address: AddressValue,
         bytes: &[u8],
+    ) -> Result<PCodeRaw<'z>, Error> {
+        self.lift_pcode_raw_aux(db, context, arena, builder, address.clone(), bytes)
+            .map_err(|error| self.in_context(error, Phase::Lifting, address, bytes))
+    }
+
+    fn lift_pcode_raw_aux<'a, 'az, 'z>(
+        &'a self,
+        db: &mut ContextDatabase,
+        context: &mut ParserContext<'a, 'az>,
+        arena: &'az IRBuilderArena,
+        builder: &mut IRBuilderBase<'a, 'z>,
+        address: AddressValue,
+        bytes: &[u8],
     ) -> Result<PCodeRaw<'z>, Error> {
         if self.alignment != 1 {
             if address.offset() % self.alignment as u64 != 0 {
@@ -624,7 +780,9 @@ impl Translator {
         }
 
         if let Some(ctor) = walker.constructor()? {
-            let tmpl = ctor.unchecked_template();
+            let tmpl = ctor
+                .template()
+                .ok_or(DisassemblyError::InvalidConstructor)?;
             let mut builder =
                 IRBuilder::new(builder, ParserWalker::new(context), &mut delay_contexts);
             builder.build(tmpl, None, &self.symbol_table)?;
@@ -653,6 +811,18 @@ impl Translator {
         arena: &'az IRBuilderArena,
         address: AddressValue,
         bytes: &[u8],
+    ) -> Result<PCode, Error> {
+        self.lift_pcode_aux(db, context, arena, address.clone(), bytes)
+            .map_err(|error| self.in_context(error, Phase::Lifting, address, bytes))
+    }
+
+    fn lift_pcode_aux<'a, 'az>(
+        &'a self,
+        db: &mut ContextDatabase,
+        context: &mut ParserContext<'a, 'az>,
+        arena: &'az IRBuilderArena,
+        address: AddressValue,
+        bytes: &[u8],
     ) -> Result<PCode, Error> {
         if self.alignment != 1 {
             if address.offset() % self.alignment as u64 != 0 {
@@ -712,7 +882,9 @@ impl Translator {
         }
 
         if let Some(ctor) = walker.constructor()? {
-            let tmpl = ctor.unchecked_template();
+            let tmpl = ctor
+                .template()
+                .ok_or(DisassemblyError::InvalidConstructor)?;
             let mut base = arena.builder(self);
             let mut builder =
                 IRBuilder::new(&mut base, ParserWalker::new(context), &mut delay_contexts);
@@ -742,6 +914,18 @@ impl Translator {
         arena: &'az IRBuilderArena,
         address: AddressValue,
         bytes: &[u8],
+    ) -> Result<ECode, Error> {
+        self.lift_ecode_aux(db, context, arena, address.clone(), bytes)
+            .map_err(|error| self.in_context(error, Phase::Lifting, address, bytes))
+    }
+
+    fn lift_ecode_aux<'a, 'az>(
+        &'a self,
+        db: &mut ContextDatabase,
+        context: &mut ParserContext<'a, 'az>,
+        arena: &'az IRBuilderArena,
+        address: AddressValue,
+        bytes: &[u8],
     ) -> Result<ECode, Error> {
         if self.alignment != 1 {
             if address.offset() % self.alignment as u64 != 0 {
@@ -800,18 +984,90 @@ impl Translator {
         }
 
         if let Some(ctor) = walker.constructor()? {
-            let tmpl = ctor.unchecked_template();
+            let tmpl = ctor
+                .template()
+                .ok_or(DisassemblyError::InvalidConstructor)?;
             let mut base = arena.builder(self);
             let mut builder =
                 IRBuilder::new(&mut base, ParserWalker::new(context), &mut delay_contexts);
             builder.build(tmpl, None, &self.symbol_table)?;
             builder.resolve_relatives()?;
-            Ok(builder.emit_ecode(fall_offset))
+            Ok(builder.emit_ecode(fall_offset)?)
         } else {
             Ok(ECode::nop(address, walker.length()))
         }
     }
 
+    /// Disassembles the instruction at `address` from `bytes`, failing if
+    /// it is not permitted by the spans of `bytes`; on failure, `db` is
+    /// left unchanged.
+    pub fn disassemble_permissioned<'z>(
+        &self,
+        db: &mut ContextDatabase,
+        builder: &'z IRBuilderArena,
+        address: AddressValue,
+        bytes: &PermissionedBytes,
+    ) -> Result<Instruction<'z>, Error> {
+        self.with_permissioned(db, address, bytes, |t, db, address, view| {
+            let insn = t.disassemble(db, builder, address, view)?;
+            let length = insn.length();
+            Ok((insn, length))
+        })
+    }
+
+    /// Lifts the instruction at `address` (and its delay slots) from
+    /// `bytes`, failing if it is not permitted by the spans of `bytes`; on
+    /// failure, `db` is left unchanged.
+    pub fn lift_pcode_permissioned(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &PermissionedBytes,
+    ) -> Result<PCode, Error> {
+        self.with_permissioned(db, address, bytes, |t, db, address, view| {
+            let pcode = t.lift_pcode(db, address, view)?;
+            let length = pcode.length();
+            Ok((pcode, length))
+        })
+    }
+
+    pub fn lift_ecode_permissioned(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &PermissionedBytes,
+    ) -> Result<ECode, Error> {
+        self.with_permissioned(db, address, bytes, |t, db, address, view| {
+            let ecode = t.lift_ecode(db, address, view)?;
+            let length = ecode.length();
+            Ok((ecode, length))
+        })
+    }
+
+    fn with_permissioned<T, F>(
+        &self,
+        db: &mut ContextDatabase,
+        address: AddressValue,
+        bytes: &PermissionedBytes,
+        f: F,
+    ) -> Result<T, Error>
+    where
+        F: FnOnce(&Self, &mut ContextDatabase, AddressValue, &[u8]) -> Result<(T, usize), Error>,
+    {
+        let offset = address.offset();
+        let view = bytes.view(offset)?;
+
+        // the decoder pads its input, so an instruction may be decoded
+        // from bytes beyond the view; commits are only applied once its
+        // extent is checked
+        let mut scratch = db.clone();
+        let (insn, length) = f(self, &mut scratch, address, &view)?;
+        bytes.check(offset, length)?;
+
+        *db = scratch;
+        Ok(insn)
+    }
+
     fn resolve_handles<'b, 'c, 'z>(
         walker: &mut ParserWalker<'b, 'c, 'z>,
         manager: &'b SpaceManager,
@@ -821,16 +1077,21 @@ impl Translator {
         walker.base_state();
 
         while walker.is_state() {
-            let ct = walker.unchecked_constructor();
+            // SAFETY: the loop runs while the walker is positioned at a construct
+            // state, whose constructor `resolve` has resolved
+            let ct = unsafe { walker.unchecked_constructor() };
 
             let nops = ct.operand_count();
             let mut op = walker.operand();
 
             'inner: while op < nops {
-                let operand = symbol_table.unchecked_symbol(ct.operand(op));
-                //.ok_or_else(|| DisassemblyError::InvalidSymbol)?;
+                let operand = symbol_table
+                    .symbol(ct.operand(op))
+                    .ok_or(DisassemblyError::InvalidSymbol)?;
 
-                walker.unchecked_push_operand(op);
+                // SAFETY: `resolve` allocated each operand of the state's constructor,
+                // within the maximum parse depth
+                unsafe { walker.unchecked_push_operand(op) };
 
                 if let Some(tsym) = operand.defining_symbol(symbol_table) {
                     if tsym.is_subtable() {
@@ -840,7 +1101,9 @@ impl Translator {
                         walker.set_parent_handle(h);
                     }
                 } else {
-                    let pexp = unsafe { operand.defining_expression().unsafe_unwrap() };
+                    let pexp = operand
+                        .defining_expression()
+                        .ok_or(DisassemblyError::InvalidPattern)?;
                     let res = pexp.value(walker, symbol_table)?;
                     let const_space = manager.constant_space_ref();
                     if let Some(handle) = walker.parent_handle_mut() {
@@ -856,7 +1119,8 @@ impl Translator {
                         walker.set_parent_handle(handle);
                     }
                 }
-                walker.unchecked_pop_operand();
+                // SAFETY: the walker is positioned at the operand's construct state
+                unsafe { walker.unchecked_pop_operand() };
                 op += 1;
             }
             if op >= nops {
@@ -866,7 +1130,8 @@ impl Translator {
                         walker.set_parent_handle(h);
                     }
                 }
-                walker.unchecked_pop_operand();
+                // SAFETY: the walker is positioned at a construct state
+                unsafe { walker.unchecked_pop_operand() };
             }
         }
 
@@ -885,17 +1150,20 @@ impl Translator {
         ctor.apply_context(walker, symbol_table)?;
 
         while walker.is_state() {
-            let ct = walker.unchecked_constructor();
+            // SAFETY: the loop runs while the walker is positioned at a construct
+            // state, and each state's constructor is set before it is visited
+            let ct = unsafe { walker.unchecked_constructor() };
             let nops = ct.operand_count();
             let mut op = walker.operand();
 
             'inner: while op < nops {
-                let operand = symbol_table.unchecked_symbol(ct.operand(op));
-                //.ok_or_else(|| DisassemblyError::InvalidSymbol)?;
+                let operand = symbol_table
+                    .symbol(ct.operand(op))
+                    .ok_or(DisassemblyError::InvalidSymbol)?;
 
-                let offset = walker.offset(operand.offset_base()) + operand.relative_offset();
+                let offset = walker.offset(operand.offset_base())? + operand.relative_offset();
 
-                walker.unchecked_allocate_operand(op);
+                walker.allocate_operand(op)?;
                 walker.set_offset(offset)?;
 
                 if let Some(tsym) = operand.defining_symbol(symbol_table) {
@@ -906,12 +1174,14 @@ impl Translator {
                     }
                 }
                 walker.set_current_length(operand.minimum_length()?);
-                walker.unchecked_pop_operand();
+                // SAFETY: the walker is positioned at the operand's construct state
+                unsafe { walker.unchecked_pop_operand() };
                 op += 1;
             }
             if op >= nops {
                 walker.calculate_length(ct.minimum_length(), nops); //?;
-                walker.unchecked_pop_operand();
+                // SAFETY: the walker is positioned at a construct state
+                unsafe { walker.unchecked_pop_operand() };
 
                 match ct.template() {
                     Some(templ) if templ.delay_slot() > 0 => {
@@ -928,6 +1198,18 @@ impl Translator {
     }
 }
 
+/// The length of the longest instruction of a processor, in bytes.
+fn maximum_instruction_length(def: &ArchitectureDef) -> Option<usize> {
+    match def.processor() {
+        "x86" => Some(15),
+        "AARCH64" | "ARM" | "MIPS" | "PowerPC" | "RISCV" | "sparc" => Some(4),
+        "6502" | "8051" => Some(3),
+        "TI_MSP430" => Some(6),
+        "TI_MSP430X" => Some(8),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod test {
     use fugue_bytes::Endian;
@@ -984,6 +1266,120 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    #[ignore = "requires processor specifications"]
+    fn test_instruction_metadata() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "RIP",
+            &ArchitectureDef::new("x86", Endian::Little, 64, "default"),
+            &Default::default(),
+            "./data/processors/x86/x86-64.sla",
+        )?;
+
+        assert_eq!(translator.alignment(), 1);
+        assert_eq!(translator.minimum_instruction_length(), 1);
+        assert_eq!(translator.maximum_instruction_length(), Some(15));
+        assert!(!translator.has_delay_slots());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shareable() {
+        fn shareable<T: Send + Sync>() {}
+
+        shareable::<Translator>();
+        shareable::<crate::LanguageDB>();
+    }
+
+    #[test]
+    fn test_clone_shares_tables() -> Result<(), Box<dyn std::error::Error>> {
+        let translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("tiny", Endian::Little, 32, "default"),
+            &Default::default(),
+            "./data/test/tiny.sla",
+        )?;
+
+        let mut clone = translator.clone();
+        assert!(Arc::ptr_eq(&translator.symbol_table, &clone.symbol_table));
+        assert!(Arc::ptr_eq(&translator.float_formats, &clone.float_formats));
+        assert!(Arc::ptr_eq(&translator.manager, &clone.manager));
+        assert!(Arc::ptr_eq(&translator.user_ops, &clone.user_ops));
+        assert!(Arc::ptr_eq(&translator.compiler_conventions, &clone.compiler_conventions));
+        assert!(Arc::ptr_eq(&translator.source_files, &clone.source_files));
+
+        // modifications are not visible to the original
+        clone.manager_mut();
+        assert!(!Arc::ptr_eq(&translator.manager, &clone.manager));
+
+        assert!(clone.add_register_alias("r0", "acc", false));
+        assert!(clone.register_by_name("acc").is_some());
+        assert!(translator.register_by_name("acc").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    #[ignore = "requires processor specifications"]
+    fn test_decode_context() -> Result<(), Box<dyn std::error::Error>> {
+        let mut translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("ARM", Endian::Little, 32, "V8T"),
+            &Default::default(),
+            "./data/processors/ARM/ARM8_le.sla",
+        )?;
+
+        translator.set_variable_default("TMode", 1);
+        translator.set_variable_default("LRset", 0);
+        translator.set_variable_default("spsr", 0);
+
+        let bytes = [0x00, 0xb1];
+
+        let mut db = translator.context_database();
+        let irb = IRBuilderArena::with_capacity(4096);
+        let mut context = ParserContext::empty(&irb, translator.manager());
+        context.set_track_context(true);
+
+        let addr = translator.address(0x1000u64);
+        let _insn = translator.lift_ecode_with(&mut db, &mut context, &irb, addr, &bytes)?;
+
+        assert!(translator.decode_context(&context).contains(&("TMode", 1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_context_tiny() -> Result<(), Box<dyn std::error::Error>> {
+        let mut translator = Translator::from_file(
+            "pc",
+            &ArchitectureDef::new("tiny", Endian::Little, 32, "default"),
+            &Default::default(),
+            "./data/test/tiny.sla",
+        )?;
+        translator.set_variable_default("mode", 1);
+
+        let mut db = translator.context_database();
+        let irb = IRBuilderArena::with_capacity(4096);
+        let mut context = ParserContext::empty(&irb, translator.manager());
+        let addr = translator.address(0x1000u64);
+
+        // not recorded by default
+        translator.disassemble_with(&mut db, &mut context, &irb, &irb, addr, &[0x01, 0x00])?;
+        assert!(translator.decode_context(&context).is_empty());
+
+        context.set_track_context(true);
+
+        // alt is only decoded when mode = 1, whereas nop does not read mode
+        translator.disassemble_with(&mut db, &mut context, &irb, &irb, addr, &[0x01, 0x00])?;
+        assert_eq!(translator.decode_context(&context), [("mode", 1)]);
+
+        translator.disassemble_with(&mut db, &mut context, &irb, &irb, addr, &[0x00, 0x00])?;
+        assert!(translator.decode_context(&context).is_empty());
+
+        Ok(())
+    }
+
     #[test]
     #[ignore = "test arm32 bug #9"]
     fn test_arm32_bug_9() -> Result<(), Box<dyn std::error::Error>> {
@@ -0,0 +1,40 @@
+//! Seeds the `lift` corpus from real binaries:
+//!
+//!     cargo run --example seed -- corpus/lift path/to/binary...
+//!
+//! The processors directory is located as for the `lift` target.
+
+use std::fs;
+use std::path::PathBuf;
+
+use fugue_ir::fuzz::LiftHarness;
+
+const WINDOW: usize = 64;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args_os().skip(1);
+    let corpus = PathBuf::from(args.next().ok_or("missing corpus directory")?);
+
+    let processors =
+        std::env::var("FUGUE_PROCESSORS").unwrap_or_else(|_| "../data/processors".to_owned());
+    let harness = LiftHarness::from_directory(processors)?;
+
+    fs::create_dir_all(&corpus)?;
+
+    for binary in args {
+        let image = fs::read(&binary)?;
+        let seeds = harness.seeds(&image, WINDOW);
+        let stem = PathBuf::from(&binary)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        for (i, seed) in seeds.iter().enumerate() {
+            fs::write(corpus.join(format!("{}-{:06}", stem, i)), seed)?;
+        }
+
+        println!("{}: {} seeds", binary.to_string_lossy(), seeds.len());
+    }
+
+    Ok(())
+}
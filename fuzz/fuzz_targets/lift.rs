@@ -0,0 +1,22 @@
+#![no_main]
+
+//! Decodes and lifts random bytes with each language found beneath the
+//! directory given by `FUGUE_PROCESSORS` (default: `../data/processors`).
+//!
+//! Seed the corpus from real binaries with the `seed` example.
+
+use std::sync::OnceLock;
+
+use fugue_ir::fuzz::LiftHarness;
+use libfuzzer_sys::fuzz_target;
+
+static HARNESS: OnceLock<LiftHarness> = OnceLock::new();
+
+fuzz_target!(|data: &[u8]| {
+    let harness = HARNESS.get_or_init(|| {
+        let path = std::env::var("FUGUE_PROCESSORS")
+            .unwrap_or_else(|_| "../data/processors".to_owned());
+        LiftHarness::from_directory(path).expect("language definitions")
+    });
+    harness.run(data);
+});